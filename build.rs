@@ -0,0 +1,47 @@
+fn main() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+    // With the `ffi` feature, generates the C header of the `ffi` module into `OUT_DIR`. The
+    // copy checked in under `include/` is only rewritten on demand, with
+    // `SUM_CHECK_UPDATE_HEADER=1`, so that builds do not write to the source tree.
+    #[cfg(feature = "ffi")]
+    {
+        println!("cargo:rerun-if-env-changed=SUM_CHECK_UPDATE_HEADER");
+        let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out_dir = std::env::var("OUT_DIR").unwrap();
+        let config = cbindgen::Config {
+            usize_is_size_t: true,
+            ..Default::default()
+        };
+        cbindgen::Builder::new()
+            .with_config(config)
+            .with_src(format!("{crate_dir}/src/ffi.rs"))
+            .with_language(cbindgen::Language::C)
+            .with_include_guard("SUM_CHECK_H")
+            .with_sys_include("stddef.h")
+            .with_sys_include("stdint.h")
+            .with_no_includes()
+            .with_documentation(true)
+            .generate()
+            .expect("cannot generate the C header")
+            .write_to_file(format!("{out_dir}/sum_check.h"));
+        if std::env::var_os("SUM_CHECK_UPDATE_HEADER").is_some_and(|value| value == "1") {
+            std::fs::copy(
+                format!("{out_dir}/sum_check.h"),
+                format!("{crate_dir}/include/sum_check.h"),
+            )
+            .expect("cannot update the C header");
+        }
+    }
+    // With the `server` feature, generates the gRPC service of the `server` module, with the
+    // vendored `protoc`.
+    #[cfg(feature = "server")]
+    {
+        println!("cargo:rerun-if-changed=proto/sum_check.proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("no vendored protoc");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::configure()
+            .build_client(true)
+            .compile_protos(&["proto/sum_check.proto"], &["proto"])
+            .expect("cannot generate the gRPC service");
+    }
+}
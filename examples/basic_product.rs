@@ -0,0 +1,24 @@
+//! Runs the interactive protocol on a product of three multilinear polynomials in three variables.
+//!
+//! cargo run --example basic_product
+
+use sum_check::polynomial::parse_product;
+use sum_check::protocol::{orchestrate_protocol, setup_protocol};
+
+fn main() {
+    // Any product of multilinear polynomials works: the polynomial whose sum is proven is the
+    // product of the factors.
+    let poly = parse_product("(x0*x2 + x1 + x2) * (x0 + x1 + x2) * (x0 + x1 + x2)").unwrap();
+    let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+    println!("Claimed sum over {{0,1}}^{}: {}", num_vars, claimed_sum);
+
+    let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+    for (round, message) in transcript.messages.iter().enumerate() {
+        println!("Round {}: {} evaluations", round, message.len());
+    }
+    if transcript.accept {
+        println!("The verifier accepts the claim.");
+    } else if let Some(err) = transcript.error {
+        println!("The verifier rejects the claim: {}", err);
+    }
+}
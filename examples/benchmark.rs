@@ -0,0 +1,48 @@
+//! Proves and verifies the sum of a product of three multilinear polynomials in 20 variables, and
+//! reports the cost of each phase of the prover and the size of the proof.
+//!
+//! cargo run --release --example benchmark
+
+use std::time::Instant;
+
+use sum_check::polynomial::parse_product;
+use sum_check::protocol::{prove_with_stats, verify};
+
+const NUM_VARS: usize = 20;
+
+fn main() {
+    let factors = [
+        "(x0 + 3*x5*x19 + 2*x11 + 1)",
+        "(x1*x2 + 5*x7 + x13*x17 + 4)",
+        "(7*x3 + x8*x9 + x14 + x18*x19 + 2)",
+    ];
+    let poly = parse_product(&factors.join(" * ")).unwrap();
+    assert_eq!(poly[0].num_vars, NUM_VARS);
+
+    let (proof, stats) = prove_with_stats(&poly);
+    println!("Evaluation on the hypercube: {:?}", stats.evaluation_time);
+    println!(
+        "Claimed sum: {:?}, {} multiplications",
+        stats.claim_sum.time, stats.claim_sum.ops.multiplications
+    );
+    for (round, round_stats) in stats.rounds.iter().enumerate() {
+        println!(
+            "Round {:2}: {:8} entries, message {:?}, fold {:?}",
+            round, round_stats.table_size, round_stats.message.time, round_stats.fold.time
+        );
+    }
+
+    println!(
+        "Proof size: {} bytes, {} bytes compressed",
+        proof.size_in_bytes(),
+        proof.compress().size_in_bytes()
+    );
+
+    let start = Instant::now();
+    let result = verify(&poly, &proof);
+    println!(
+        "Verification: {:?}, {:?}",
+        start.elapsed(),
+        result.map(|_| ())
+    );
+}
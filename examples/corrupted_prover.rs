@@ -0,0 +1,37 @@
+//! Runs the protocol against provers which tamper with one of their messages, or claim a wrong
+//! sum, and shows where the verifier catches them.
+//!
+//! cargo run --example corrupted_prover
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sum_check::field::Field256 as F;
+use sum_check::polynomial::parse_product;
+use sum_check::protocol::RoundMessage;
+use sum_check::testing::adversary::{run_with_adversary, TamperKind, Tampering};
+
+fn main() {
+    let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3) * (x0*x2 + 1)").unwrap();
+    let rng = || StdRng::seed_from_u64(0);
+
+    let mut honest = |_: usize, _: &mut RoundMessage| {};
+    let result = run_with_adversary(&poly, F::from(0), &mut honest, rng());
+    println!("Honest prover: {:?}", result.map(|_| "accepted"));
+
+    let result = run_with_adversary(&poly, F::from(1), &mut honest, rng());
+    println!("Wrong claimed sum: {}", result.unwrap_err());
+
+    let attacks = [
+        Tampering::new(1, TamperKind::WrongConstant(F::from(1))),
+        Tampering::new(2, TamperKind::ExtraEvaluation(F::from(0))),
+        Tampering::new(0, TamperKind::MissingEvaluation),
+        Tampering::new(2, TamperKind::SwapEvaluations(0, 1)),
+    ];
+    for mut attack in attacks {
+        let description = format!("{:?} at round {}", attack.kind, attack.round);
+        match run_with_adversary(&poly, F::from(0), &mut attack, rng()) {
+            Ok(_) => println!("{}: accepted", description),
+            Err(err) => println!("{}: {}", description, err),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+//! Serves the prover over gRPC on port 50051, with the `server` feature.
+//!
+//! ```text
+//! cargo run --example proving_server --features server
+//! ```
+
+use sum_check::server::ProvingService;
+use tonic::transport::Server;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let address = "127.0.0.1:50051".parse()?;
+    println!("Proving service listening on {}", address);
+    Server::builder()
+        .add_service(ProvingService::new().into_server())
+        .serve(address)
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,9 @@
+//! Writes the test vectors of `test-vectors/vectors.json` to the standard output.
+//!
+//! cargo run --example test_vectors > test-vectors/vectors.json
+
+use sum_check::testing::vectors::generate;
+
+fn main() {
+    print!("{}", generate());
+}
@@ -0,0 +1,20 @@
+//! Serves the verification endpoint on port 3000, with the `http` feature.
+//!
+//! ```text
+//! cargo run --example verification_server --features http
+//! curl -X POST localhost:3000/verify -H 'Content-Type: application/json' \
+//!     -d '{"instance": ..., "proof": ...}'
+//! ```
+
+use sum_check::http::router;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:3000").await?;
+    println!(
+        "Verification endpoint listening on {}",
+        listener.local_addr()?
+    );
+    axum::serve(listener, router()).await?;
+    Ok(())
+}
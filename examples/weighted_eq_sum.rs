@@ -0,0 +1,31 @@
+//! Proves the evaluation at a point r of the multilinear extension of the hypercube evaluations
+//! of a product, as the sum of eq(b, r)·poly(b) over the hypercube.
+//!
+//! cargo run --example weighted_eq_sum
+
+use sum_check::field::Field256 as F;
+use sum_check::polynomial::{evaluate_polynomial_on_hypercube, evaluate_table, parse_product};
+use sum_check::protocol::{prove_weighted, verify_weighted};
+
+fn main() {
+    let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3)").unwrap();
+    let r = [F::from(5), F::from(7), F::from(11)];
+
+    let proof = prove_weighted(&poly, &r);
+    // The claimed sum is the evaluation at r of the multilinear extension of the table of the
+    // product, which the verifier would not compute itself.
+    let tables: Vec<_> = poly.iter().map(evaluate_polynomial_on_hypercube).collect();
+    let product: Vec<F> = (0..tables[0].len())
+        .map(|b| tables.iter().map(|table| table[b]).product())
+        .collect();
+    assert_eq!(proof.claimed_sum, evaluate_table(&product, &r));
+    println!("Claimed evaluation at r: {}", proof.claimed_sum);
+
+    match verify_weighted(&poly, &r, &proof) {
+        Ok(point) => println!(
+            "The verifier accepts, with final check at a point of {} coordinates.",
+            point.len()
+        ),
+        Err(err) => println!("The verifier rejects the claim: {}", err),
+    }
+}
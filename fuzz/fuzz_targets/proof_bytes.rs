@@ -0,0 +1,26 @@
+//! Arbitrary bytes as serialized proofs. Decoding must fail or give a proof which the verifier
+//! checks without panicking. The statement of the decoded proof is set to the instance, so that
+//! the bytes reach the rounds instead of being rejected by the instance hash.
+
+#![no_main]
+
+use ark_serialize::CanonicalDeserialize;
+use libfuzzer_sys::fuzz_target;
+use sum_check::polynomial::parse_product;
+use sum_check::protocol::{instance_hash, verify, CompressedProof, Proof, PROTOCOL_VERSION};
+
+fuzz_target!(|data: &[u8]| {
+    let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x2 + 5)").unwrap();
+    if let Ok(proof) = Proof::deserialize_compressed(data) {
+        let _ = verify(&poly, &proof);
+        let proof = Proof {
+            version: PROTOCOL_VERSION,
+            instance_hash: instance_hash(&poly, proof.claimed_sum),
+            ..proof
+        };
+        let _ = verify(&poly, &proof);
+    }
+    if let Ok(proof) = CompressedProof::deserialize_compressed(data) {
+        let _ = verify(&poly, &proof.decompress());
+    }
+});
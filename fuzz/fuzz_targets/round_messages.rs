@@ -0,0 +1,92 @@
+//! Mutations of the round messages of an honest run, fed to the verifier one round at a time in
+//! each message format. The verifier must accept or reject them without panicking, and must accept
+//! the run when nothing is mutated.
+
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use sum_check::field::Field256 as F;
+use sum_check::polynomial::parse_product;
+use sum_check::protocol::{
+    setup_protocol_with_format, MessageFormat, RoundMessage, SumCheckProver, SumCheckVerifier,
+};
+
+#[derive(Debug, Arbitrary)]
+enum Mutation {
+    /// Adds 'delta' to an entry of the message.
+    Add { index: u8, delta: u64 },
+    /// Replaces an entry of the message by the element of the little-endian limbs 'value'.
+    Set { index: u8, value: [u64; 4] },
+    /// Keeps the first 'len' entries of the message.
+    Truncate { len: u8 },
+    /// Appends an entry to the message.
+    Push { value: u64 },
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    format: u8,
+    /// The mutations of the message of each round.
+    mutations: Vec<(u8, Mutation)>,
+    /// Replays the last message after the last round.
+    extra_round: bool,
+}
+
+fn apply(message: &mut Vec<F>, mutation: &Mutation) {
+    let len = message.len();
+    match *mutation {
+        Mutation::Add { index, delta } if len > 0 => {
+            message[usize::from(index) % len] += F::from(delta)
+        }
+        Mutation::Set { index, value } if len > 0 => {
+            let value = value.iter().rev().fold(F::from(0), |acc, &limb| {
+                acc * F::from(1u128 << 64) + F::from(limb)
+            });
+            message[usize::from(index) % len] = value;
+        }
+        Mutation::Truncate { len } => message.truncate(usize::from(len)),
+        Mutation::Push { value } => message.push(F::from(value)),
+        _ => {}
+    }
+}
+
+fuzz_target!(|input: Input| {
+    let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x2 + 5)").unwrap();
+    let format = match input.format % 3 {
+        0 => MessageFormat::Evaluations,
+        1 => MessageFormat::Coefficients,
+        _ => MessageFormat::Compressed,
+    };
+    let (num_vars, claimed_sum, prover_state, verifier_state) =
+        setup_protocol_with_format(&poly, format).unwrap();
+    let mut prover = SumCheckProver::from_state(claimed_sum, prover_state);
+    let mut verifier = SumCheckVerifier::from_state(verifier_state);
+    let mut challenge = None;
+    let mut message = vec![];
+    for round in 0..num_vars + usize::from(input.extra_round) {
+        if round < num_vars {
+            message = prover.next_message(challenge).evaluations;
+        }
+        for (_, mutation) in input
+            .mutations
+            .iter()
+            .filter(|(r, _)| usize::from(*r) == round)
+        {
+            apply(&mut message, mutation);
+        }
+        match verifier.receive_message(RoundMessage {
+            evaluations: message.clone(),
+        }) {
+            Ok(r) => challenge = Some(r),
+            Err(_) => return,
+        }
+    }
+    let (result, _) = verifier.finalize();
+    let honest = input
+        .mutations
+        .iter()
+        .all(|&(round, _)| usize::from(round) >= num_vars)
+        && !input.extra_round;
+    assert!(result.is_ok() || !honest);
+});
@@ -0,0 +1,78 @@
+#ifndef SUM_CHECK_H
+#define SUM_CHECK_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+/**
+ * Version of the bundle layout.
+ */
+#define SUMCHECK_BUNDLE_VERSION 1
+
+/**
+ * The verifier accepts the proof.
+ */
+#define SUMCHECK_ACCEPT 1
+
+/**
+ * The verifier rejects the proof.
+ */
+#define SUMCHECK_REJECT 0
+
+/**
+ * A pointer argument is null.
+ */
+#define SUMCHECK_ERR_NULL -1
+
+/**
+ * The bundle is truncated or has an unknown version.
+ */
+#define SUMCHECK_ERR_BUNDLE -2
+
+/**
+ * The instance is not a valid JSON instance.
+ */
+#define SUMCHECK_ERR_INSTANCE -3
+
+/**
+ * The library panicked.
+ */
+#define SUMCHECK_ERR_PANIC -4
+
+/**
+ * Verifies the bundle of 'len' bytes at 'ptr'. Returns [`SUMCHECK_ACCEPT`] or
+ * [`SUMCHECK_REJECT`], or a negative error code. A proof which cannot be deserialized is
+ * rejected.
+ *
+ * # Safety
+ *
+ * 'ptr' must point to 'len' readable bytes.
+ */
+int32_t sumcheck_verify(const uint8_t *ptr, size_t len);
+
+/**
+ * Proves the instance of 'instance_len' bytes at 'instance', and writes to 'out_ptr' and
+ * 'out_len' a bundle of the instance and the proof, to be released with [`sumcheck_free`].
+ * Returns 0, or a negative error code.
+ *
+ * # Safety
+ *
+ * 'instance' must point to 'instance_len' readable bytes, and 'out_ptr' and 'out_len' must be
+ * valid for writes.
+ */
+int32_t sumcheck_prove(const uint8_t *instance,
+                       size_t instance_len,
+                       uint8_t **out_ptr,
+                       size_t *out_len);
+
+/**
+ * Releases a bundle returned by [`sumcheck_prove`].
+ *
+ * # Safety
+ *
+ * 'ptr' and 'len' must come from a call to [`sumcheck_prove`], and the bundle must not be
+ * released twice.
+ */
+void sumcheck_free(uint8_t *ptr, size_t len);
+
+#endif  /* SUM_CHECK_H */
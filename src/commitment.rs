@@ -0,0 +1,120 @@
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::field::Field256 as F;
+use crate::polynomial::{
+    evaluate_polynomial_on_hypercube, evaluate_table, EvalTable, MLPolynomial,
+};
+
+/// A commitment scheme for multilinear polynomials, which lets the verifier check the
+/// evaluation of a committed polynomial at the random point of the protocol without knowing the
+/// polynomial.
+pub trait MLCommitmentScheme {
+    type Commitment: Clone;
+    /// Data kept by the prover to later open the commitment.
+    type Opening;
+    type Proof;
+
+    fn commit(poly: &MLPolynomial) -> (Self::Commitment, Self::Opening);
+
+    /// Returns the evaluation of the committed polynomial at 'point', along with a proof of it.
+    fn open(opening: &Self::Opening, point: &[F]) -> (F, Self::Proof);
+
+    fn verify(commitment: &Self::Commitment, point: &[F], value: F, proof: &Self::Proof) -> bool;
+}
+
+/// Commitment to the evaluation table of a polynomial by its SHA-256 digest.
+///
+/// An opening proof is the whole table, so this scheme is neither succinct nor hiding. It is the
+/// simplest binding scheme, and serves as a reference for the trait.
+pub struct HashCommitment {}
+
+impl MLCommitmentScheme for HashCommitment {
+    type Commitment = [u8; 32];
+    type Opening = EvalTable;
+    type Proof = EvalTable;
+
+    fn commit(poly: &MLPolynomial) -> (Self::Commitment, Self::Opening) {
+        let table = evaluate_polynomial_on_hypercube(poly);
+        (hash_table(&table), table)
+    }
+
+    fn open(opening: &Self::Opening, point: &[F]) -> (F, Self::Proof) {
+        (evaluate_table(opening, point), opening.clone())
+    }
+
+    fn verify(commitment: &Self::Commitment, point: &[F], value: F, proof: &Self::Proof) -> bool {
+        proof.len() == 1 << point.len()
+            && hash_table(proof) == *commitment
+            && evaluate_table(proof, point) == value
+    }
+}
+
+/// A [`HashCommitment`] to the multilinear extension of an evaluation table, which the
+/// protocols over tables absorb into their transcripts before drawing their challenges.
+pub type TableCommitment = [u8; 32];
+
+impl HashCommitment {
+    /// The commitment to the multilinear extension of 'table', the one [`HashCommitment::commit`]
+    /// gives for a polynomial of that evaluation table.
+    pub fn commit_table(table: &EvalTable) -> TableCommitment {
+        hash_table(table)
+    }
+}
+
+fn hash_table(table: &EvalTable) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    table.serialize_compressed(&mut bytes).unwrap();
+    Sha256::digest(&bytes).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+    use ark_poly::{DenseMVPolynomial, Polynomial};
+    use ark_std::vec;
+
+    fn poly() -> MLPolynomial {
+        SparsePolynomial::from_coefficients_vec(
+            2,
+            vec![
+                (F::from(3), SparseTerm::new(vec![(0, 1)])),
+                (F::from(4), SparseTerm::new(vec![(0, 1), (1, 1)])),
+                (F::from(1), SparseTerm::new(vec![])),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_hash_commitment() {
+        let (commitment, opening) = HashCommitment::commit(&poly());
+        let point = vec![F::from(5), F::from(8)];
+        let (value, proof) = HashCommitment::open(&opening, &point);
+        assert_eq!(value, poly().evaluate(&point));
+        assert!(HashCommitment::verify(&commitment, &point, value, &proof));
+        assert!(!HashCommitment::verify(
+            &commitment,
+            &point,
+            value + F::from(1),
+            &proof
+        ));
+    }
+
+    #[test]
+    fn test_commit_table() {
+        let (commitment, opening) = HashCommitment::commit(&poly());
+        assert_eq!(HashCommitment::commit_table(&opening), commitment);
+    }
+
+    #[test]
+    fn test_hash_commitment_binding() {
+        let (commitment, opening) = HashCommitment::commit(&poly());
+        let point = vec![F::from(5), F::from(8)];
+        let (_, mut proof) = HashCommitment::open(&opening, &point);
+        proof[0] += F::from(1);
+        let value = evaluate_table(&proof, &point);
+        assert!(!HashCommitment::verify(&commitment, &point, value, &proof));
+    }
+}
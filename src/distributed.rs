@@ -0,0 +1,209 @@
+//! Proving across several workers, e.g. the machines of a cluster, for instances too large for a
+//! single one. The round polynomial is a sum over the hypercube, so that the sums over the parts
+//! of a partition of the hypercube add up to it.
+//!
+//! The hypercube is split on its last k variables: worker w holds the entries of the tables at the
+//! points whose last k variables are the bits of w, which are tables on the first n - k variables.
+//! It plays the first n - k rounds on them as a prover of its own, and the coordinator sums the
+//! messages of the workers into those of the protocol. After these rounds, each worker is left
+//! with one value per factor, which the coordinator gathers into tables on the last k variables to
+//! play the last rounds alone.
+//!
+//! The workers only exchange field elements with the coordinator, so that they may run in other
+//! processes. [`DistributedProver`] runs them in this one, each round on as many threads.
+
+use ark_std::{vec, vec::Vec};
+
+use crate::field::Field256 as F;
+use crate::polynomial::{EvalTable, PolynomialDescription};
+use crate::protocol::{Prover, ProverState, RoundMessage, SumCheckProver, Verifier};
+
+/// The part of 'table' held by the worker of index 'worker' out of 2^'log_workers': its entries
+/// at the points whose last 'log_workers' variables are the bits of 'worker'.
+pub fn partition(table: &[F], log_workers: usize, worker: usize) -> EvalTable {
+    assert!(worker < 1 << log_workers, "there are only {} workers", 1 << log_workers);
+    (0..table.len() >> log_workers)
+        .map(|pt| table[pt << log_workers | worker])
+        .collect()
+}
+
+/// Sums the round polynomials of the workers, given by their evaluations at 0, 1, ..., d for
+/// degrees d which may differ: a worker whose part does not depend on some factor in the variable
+/// of the round sends fewer evaluations, which are extended to the others' nodes.
+pub fn aggregate(messages: &[PolynomialDescription]) -> PolynomialDescription {
+    let num_points = messages.iter().map(Vec::len).max().unwrap_or(0);
+    let mut sum = vec![F::from(0); num_points];
+    for message in messages {
+        for (node, entry) in sum.iter_mut().enumerate() {
+            *entry += match message.get(node) {
+                Some(&evaluation) => evaluation,
+                None => Verifier::evaluate_at_random_point(message, F::from(node as u64)),
+            };
+        }
+    }
+    sum
+}
+
+/// A worker of a distributed run, proving the sum over its part of the hypercube.
+pub struct Worker {
+    partial_sum: F,
+    state: Option<ProverState>,
+}
+
+impl Worker {
+    /// The worker holding 'tables', the parts of the tables of the factors on the first
+    /// 'num_vars' variables, as given by [`partition`].
+    pub fn new(num_vars: usize, tables: Vec<EvalTable>) -> Self {
+        let (partial_sum, state) = Prover::claim_sum_from_tables(num_vars, tables);
+        Worker { partial_sum, state: Some(state) }
+    }
+
+    /// The sum of the product over the part of the worker. The claimed sum is the sum of those of
+    /// all the workers.
+    pub fn partial_sum(&self) -> F {
+        self.partial_sum
+    }
+
+    /// Binds the variable of the previous round to 'challenge', if any, and returns the round
+    /// polynomial of the part of the worker, by its evaluations at 0, 1, ..., d.
+    pub fn message(&mut self, challenge: Option<F>) -> PolynomialDescription {
+        let state = self.state.take().expect("the worker has finished");
+        let (message, state) = match challenge {
+            Some(r) => Prover::fold_and_round(state, r),
+            None => Prover::round_phase_1(state),
+        };
+        self.state = Some(state);
+        message
+    }
+
+    /// Binds the variable of the last round to 'challenge', and returns the value of each factor
+    /// on the part of the worker, for the coordinator to play the remaining rounds.
+    pub fn finish(mut self, challenge: F) -> Vec<F> {
+        let state = self.state.take().expect("the worker has finished");
+        Prover::round_phase_2(state, challenge).final_evaluations()
+    }
+}
+
+/// Round-by-round driver of a prover whose first rounds are spread over 2^k workers, with the same
+/// interface as [`SumCheckProver`].
+pub struct DistributedProver {
+    num_vars: usize,
+    log_workers: usize,
+    claimed_sum: F,
+    workers: Vec<Worker>,
+    /// The prover of the last rounds, once the workers have finished.
+    tail: Option<SumCheckProver>,
+    round: usize,
+}
+
+impl DistributedProver {
+    /// Splits the product of the multilinear extensions of 'tables' over 2^'log_workers' workers,
+    /// which must leave each of them at least one round to play.
+    pub fn new(tables: &[EvalTable], log_workers: usize) -> Self {
+        let num_vars = tables[0].len().trailing_zeros() as usize;
+        assert!(log_workers < num_vars, "each worker must play at least one round");
+        let workers: Vec<Worker> = (0..1 << log_workers)
+            .map(|worker| {
+                let parts = tables.iter().map(|table| partition(table, log_workers, worker)).collect();
+                Worker::new(num_vars - log_workers, parts)
+            })
+            .collect();
+        let claimed_sum = workers.iter().map(Worker::partial_sum).sum();
+        DistributedProver { num_vars, log_workers, claimed_sum, workers, tail: None, round: 0 }
+    }
+
+    pub fn claimed_sum(&self) -> F {
+        self.claimed_sum
+    }
+
+    /// Number of rounds of the protocol, i.e. the number of variables of the polynomial.
+    pub fn num_rounds(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Produces the message of the next round, as [`SumCheckProver::next_message`] does.
+    pub fn next_message(&mut self, challenge: Option<F>) -> RoundMessage {
+        assert!(self.round < self.num_vars, "all rounds of the protocol have been played");
+        let distributed_rounds = self.num_vars - self.log_workers;
+        let message = if self.round < distributed_rounds {
+            let messages: Vec<PolynomialDescription> = std::thread::scope(|scope| {
+                let handles: Vec<_> = self
+                    .workers
+                    .iter_mut()
+                    .map(|worker| scope.spawn(move || worker.message(challenge)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("a worker panicked"))
+                    .collect()
+            });
+            RoundMessage { evaluations: aggregate(&messages) }
+        } else if self.round == distributed_rounds {
+            let r = challenge.expect("the prover expects the challenge of the previous round");
+            let values: Vec<Vec<F>> =
+                self.workers.drain(..).map(|worker| worker.finish(r)).collect();
+            let tables = (0..values[0].len())
+                .map(|factor| values.iter().map(|value| value[factor]).collect())
+                .collect();
+            let mut tail = SumCheckProver::from_tables(self.log_workers, tables);
+            let message = tail.next_message(None);
+            self.tail = Some(tail);
+            message
+        } else {
+            self.tail.as_mut().unwrap().next_message(challenge)
+        };
+        self.round += 1;
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::SumCheckVerifier;
+    use crate::polynomial::{evaluate_polynomial_on_hypercube, parse_product};
+    use ark_std::UniformRand;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_distributed_prover() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let tables: Vec<EvalTable> = (0..3)
+            .map(|_| (0..1 << 6).map(|_| F::rand(&mut rng)).collect())
+            .collect();
+        let challenges: Vec<F> = (0..6).map(|_| F::rand(&mut rng)).collect();
+        for log_workers in 0..6 {
+            let mut distributed = DistributedProver::new(&tables, log_workers);
+            let mut prover = SumCheckProver::from_tables(6, tables.clone());
+            assert_eq!(distributed.claimed_sum(), prover.claimed_sum());
+            let mut challenge = None;
+            for &r in &challenges {
+                assert_eq!(distributed.next_message(challenge), prover.next_message(challenge));
+                challenge = Some(r);
+            }
+        }
+    }
+
+    #[test]
+    fn test_distributed_run() {
+        // The first factor does not depend on x2 on the parts where x3 = 0, so that the workers
+        // send round polynomials of different degrees in the round of x2.
+        let poly = parse_product("(x0 + x2*x3 + 1) * (x1 + x2 + 2*x3)").unwrap();
+        let tables: Vec<EvalTable> = poly.iter().map(evaluate_polynomial_on_hypercube).collect();
+        let mut prover = DistributedProver::new(&tables, 1);
+        let mut verifier = SumCheckVerifier::new(&poly, prover.claimed_sum());
+        let mut challenge = None;
+        for _ in 0..prover.num_rounds() {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        assert!(verifier.finalize().0.is_ok());
+    }
+
+    #[test]
+    fn test_aggregate() {
+        // 1 + X and 2X², by their evaluations.
+        let messages = vec![vec![F::from(1), F::from(2)], vec![F::from(0), F::from(2), F::from(8)]];
+        assert_eq!(aggregate(&messages), vec![F::from(1), F::from(4), F::from(11)]);
+    }
+}
@@ -0,0 +1,191 @@
+//! C interface, with the `ffi` feature. The header `include/sum_check.h` is generated from this
+//! module by the build script, which updates it with `SUM_CHECK_UPDATE_HEADER=1`.
+//!
+//! Proofs are exchanged as bundles holding the instance along with the proof:
+//!
+//! ```text
+//! version: u8 | instance_len: u32 (little-endian) | instance (JSON) | proof
+//! ```
+//!
+//! where the instance is in the JSON format of
+//! [`parser::from_json`](crate::polynomial::parser::from_json), and the proof is the compressed
+//! serialization of a [`Proof`].
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::{ptr, slice};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use crate::polynomial::{parser, ProductMLPolynomial};
+use crate::protocol::{self, Proof};
+
+/// Version of the bundle layout.
+pub const SUMCHECK_BUNDLE_VERSION: u8 = 1;
+
+/// The verifier accepts the proof.
+pub const SUMCHECK_ACCEPT: i32 = 1;
+/// The verifier rejects the proof.
+pub const SUMCHECK_REJECT: i32 = 0;
+/// A pointer argument is null.
+pub const SUMCHECK_ERR_NULL: i32 = -1;
+/// The bundle is truncated or has an unknown version.
+pub const SUMCHECK_ERR_BUNDLE: i32 = -2;
+/// The instance is not a valid JSON instance.
+pub const SUMCHECK_ERR_INSTANCE: i32 = -3;
+/// The library panicked.
+pub const SUMCHECK_ERR_PANIC: i32 = -4;
+
+/// Verifies the bundle of 'len' bytes at 'ptr'. Returns [`SUMCHECK_ACCEPT`] or
+/// [`SUMCHECK_REJECT`], or a negative error code. A proof which cannot be deserialized is
+/// rejected.
+///
+/// # Safety
+///
+/// 'ptr' must point to 'len' readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn sumcheck_verify(ptr: *const u8, len: usize) -> i32 {
+    if ptr.is_null() {
+        return SUMCHECK_ERR_NULL;
+    }
+    let bundle = slice::from_raw_parts(ptr, len);
+    let Some((instance, proof)) = split_bundle(bundle) else {
+        return SUMCHECK_ERR_BUNDLE;
+    };
+    guard(|| {
+        let poly = parse_instance(instance)?;
+        Ok(match Proof::deserialize_compressed(proof) {
+            Ok(proof) if protocol::verify(&poly, &proof).is_ok() => SUMCHECK_ACCEPT,
+            _ => SUMCHECK_REJECT,
+        })
+    })
+}
+
+/// Proves the instance of 'instance_len' bytes at 'instance', and writes to 'out_ptr' and
+/// 'out_len' a bundle of the instance and the proof, to be released with [`sumcheck_free`].
+/// Returns 0, or a negative error code.
+///
+/// # Safety
+///
+/// 'instance' must point to 'instance_len' readable bytes, and 'out_ptr' and 'out_len' must be
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn sumcheck_prove(
+    instance: *const u8,
+    instance_len: usize,
+    out_ptr: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    if instance.is_null() || out_ptr.is_null() || out_len.is_null() {
+        return SUMCHECK_ERR_NULL;
+    }
+    let instance = slice::from_raw_parts(instance, instance_len);
+    let Ok(prefix_len) = u32::try_from(instance.len()) else {
+        return SUMCHECK_ERR_INSTANCE;
+    };
+    guard(|| {
+        let poly = parse_instance(instance)?;
+        let mut bundle = vec![SUMCHECK_BUNDLE_VERSION];
+        bundle.extend_from_slice(&prefix_len.to_le_bytes());
+        bundle.extend_from_slice(instance);
+        protocol::prove(&poly)
+            .serialize_compressed(&mut bundle)
+            .expect("serialization into a vector cannot fail");
+        let bundle = Box::into_raw(bundle.into_boxed_slice());
+        *out_len = bundle.len();
+        *out_ptr = bundle as *mut u8;
+        Ok(0)
+    })
+}
+
+/// Releases a bundle returned by [`sumcheck_prove`].
+///
+/// # Safety
+///
+/// 'ptr' and 'len' must come from a call to [`sumcheck_prove`], and the bundle must not be
+/// released twice.
+#[no_mangle]
+pub unsafe extern "C" fn sumcheck_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
+
+/// Splits a bundle into the instance and the proof.
+fn split_bundle(bundle: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&version, rest) = bundle.split_first()?;
+    if version != SUMCHECK_BUNDLE_VERSION || rest.len() < 4 {
+        return None;
+    }
+    let (prefix, rest) = rest.split_at(4);
+    let instance_len = u32::from_le_bytes(prefix.try_into().unwrap()) as usize;
+    (instance_len <= rest.len()).then(|| rest.split_at(instance_len))
+}
+
+fn parse_instance(instance: &[u8]) -> Result<ProductMLPolynomial, i32> {
+    let json = std::str::from_utf8(instance).map_err(|_| SUMCHECK_ERR_INSTANCE)?;
+    parser::from_json(json).map_err(|_| SUMCHECK_ERR_INSTANCE)
+}
+
+/// Runs 'f', turning its errors and panics into error codes, as panics must not cross the C
+/// boundary.
+fn guard(f: impl FnOnce() -> Result<i32, i32>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(code)) | Ok(Err(code)) => code,
+        Err(_) => SUMCHECK_ERR_PANIC,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE: &str = r#"{"num_vars": 2, "factors": [
+        [{"coeff": 1, "vars": [[0, 1]]}, {"coeff": 3}],
+        [{"coeff": 2, "vars": [[1, 1]]}, {"coeff": 1, "vars": [[0, 1]]}]
+    ]}"#;
+
+    fn prove(instance: &[u8]) -> Result<Vec<u8>, i32> {
+        let (mut out_ptr, mut out_len) = (ptr::null_mut(), 0);
+        let code = unsafe {
+            sumcheck_prove(
+                instance.as_ptr(),
+                instance.len(),
+                &mut out_ptr,
+                &mut out_len,
+            )
+        };
+        if code != 0 {
+            return Err(code);
+        }
+        let bundle = unsafe { slice::from_raw_parts(out_ptr, out_len) }.to_vec();
+        unsafe { sumcheck_free(out_ptr, out_len) };
+        Ok(bundle)
+    }
+
+    fn verify(bundle: &[u8]) -> i32 {
+        unsafe { sumcheck_verify(bundle.as_ptr(), bundle.len()) }
+    }
+
+    #[test]
+    fn test_prove_verify() {
+        let mut bundle = prove(INSTANCE.as_bytes()).unwrap();
+        assert_eq!(split_bundle(&bundle).unwrap().0, INSTANCE.as_bytes());
+        assert_eq!(verify(&bundle), SUMCHECK_ACCEPT);
+
+        *bundle.last_mut().unwrap() ^= 1;
+        assert_eq!(verify(&bundle), SUMCHECK_REJECT);
+        bundle[0] = SUMCHECK_BUNDLE_VERSION + 1;
+        assert_eq!(verify(&bundle), SUMCHECK_ERR_BUNDLE);
+        assert_eq!(verify(&bundle[..3]), SUMCHECK_ERR_BUNDLE);
+        assert_eq!(
+            unsafe { sumcheck_verify(ptr::null(), 0) },
+            SUMCHECK_ERR_NULL
+        );
+    }
+
+    #[test]
+    fn test_invalid_instance() {
+        assert_eq!(prove(b"{"), Err(SUMCHECK_ERR_INSTANCE));
+        assert_eq!(prove(&[0xff]), Err(SUMCHECK_ERR_INSTANCE));
+    }
+}
@@ -1,6 +1,20 @@
+// The `MontConfig` derive expands to an impl nested inside a constant, and checks the `asm`
+// feature of `ark-ff` for the moduli of four limbs with a spare bit.
+#![allow(non_local_definitions, unexpected_cfgs)]
+
+pub mod batch;
+pub mod binary;
+
 use ark_ff::{
-    fields::{MontConfig, Fp256, MontBackend},
+    fields::{Field, MontConfig, Fp256, Fp64, MontBackend},
+    Fp2, Fp2Config, Fp4, Fp4Config, MontFp,
 };
+use ark_std::{string::String, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+use thiserror::Error;
+
+pub use binary::BinaryField128;
 
 #[derive(MontConfig)]
 #[modulus="57896044618658097711785492504343953926634992332820282019728792003956564819949"]
@@ -9,9 +23,223 @@ pub struct FieldConfig;
 
 pub type Field256 = Fp256<MontBackend<FieldConfig, 4>>;
 
+/// The scalar field of the BLS12-381 curve, of order
+/// 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001.
+#[derive(MontConfig)]
+#[modulus="52435875175126190479447740508185965837690552500527637822603658699938581184513"]
+#[generator="7"]
+pub struct FieldBls12Config;
+
+pub type FieldBls12 = Fp256<MontBackend<FieldBls12Config, 4>>;
+
+/// The Goldilocks field, of order 2^64 - 2^32 + 1.
+#[derive(MontConfig)]
+#[modulus="18446744069414584321"]
+#[generator="7"]
+pub struct Field64Config;
+
+pub type Field64 = Fp64<MontBackend<Field64Config, 1>>;
+
+/// The BabyBear field, of order 15 * 2^27 + 1.
+#[derive(MontConfig)]
+#[modulus="2013265921"]
+#[generator="31"]
+pub struct Field31Config;
+
+pub type Field31 = Fp64<MontBackend<Field31Config, 1>>;
+
+/// Quadratic extension of the Goldilocks field, by a square root of 7.
+pub struct Field64Ext2Config;
+
+impl Fp2Config for Field64Ext2Config {
+    type Fp = Field64;
+    const NONRESIDUE: Field64 = MontFp!("7");
+    const FROBENIUS_COEFF_FP2_C1: &'static [Field64] = &[MontFp!("1"), MontFp!("-1")];
+}
+
+pub type Field64Ext2 = Fp2<Field64Ext2Config>;
+
+/// Quadratic extension of the BabyBear field, by a square root of 11.
+pub struct Field31Ext2Config;
+
+impl Fp2Config for Field31Ext2Config {
+    type Fp = Field31;
+    const NONRESIDUE: Field31 = MontFp!("11");
+    const FROBENIUS_COEFF_FP2_C1: &'static [Field31] = &[MontFp!("1"), MontFp!("-1")];
+}
+
+pub type Field31Ext2 = Fp2<Field31Ext2Config>;
+
+/// Quartic extension of the BabyBear field, by a fourth root of 11, built as a quadratic extension
+/// of `Field31Ext2`.
+pub struct Field31Ext4Config;
+
+impl Fp4Config for Field31Ext4Config {
+    type Fp2Config = Field31Ext2Config;
+    const NONRESIDUE: Field31Ext2 = Field31Ext2::new(MontFp!("0"), MontFp!("1"));
+    // 11^((p^i - 1) / 4) for i = 0, 1, 2, 3.
+    const FROBENIUS_COEFF_FP4_C1: &'static [Field31] = &[
+        MontFp!("1"),
+        MontFp!("1728404513"),
+        MontFp!("2013265920"),
+        MontFp!("284861408"),
+    ];
+}
+
+pub type Field31Ext4 = Fp4<Field31Ext4Config>;
+
+/// Defines the prime field 'name', an `ark_ff::Fp` of 'limbs' limbs of 64 bits, of modulus
+/// 'modulus' and multiplicative generator 'generator', both given as decimal strings. The
+/// `MontConfig` of the field is `module::Config`, in a module of its own so that the lints raised
+/// by the derive stay in it, and the crate using the macro must depend on `ark-ff`, which the
+/// derive refers to. The field can then be used with
+/// [`prove_in_field`](crate::protocol::prove_in_field). For instance, for the scalar field of
+/// secp256k1:
+///
+/// ```ignore
+/// define_sumcheck_field!(
+///     pub Secp256k1Scalar,
+///     secp256k1_scalar,
+///     modulus = "115792089237316195423570985008687907852837564279074904382605163141518161494337",
+///     generator = "7",
+///     limbs = 4,
+/// );
+/// ```
+#[macro_export]
+macro_rules! define_sumcheck_field {
+    (
+        $(#[$attr:meta])*
+        $vis:vis $name:ident, $module:ident,
+        modulus = $modulus:tt,
+        generator = $generator:tt,
+        limbs = $limbs:tt $(,)?
+    ) => {
+        $(#[$attr])*
+        $vis type $name = ark_ff::Fp<ark_ff::MontBackend<$module::Config, $limbs>, $limbs>;
+
+        #[allow(non_local_definitions, unexpected_cfgs)]
+        $vis mod $module {
+            #[derive(ark_ff::MontConfig)]
+            #[modulus = $modulus]
+            #[generator = $generator]
+            pub struct Config;
+        }
+    };
+}
+
+/// The fields over which instances can be proven when the field is chosen at runtime, by the
+/// identifier that the instance files declare. Goldilocks instances are proven with challenges in
+/// `Field64Ext2`, the Goldilocks field alone being too small for the soundness of the protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum DynField {
+    #[default]
+    Field256,
+    Bls12_381,
+    Goldilocks,
+}
+
+impl DynField {
+    pub const ALL: [DynField; 3] = [
+        DynField::Field256,
+        DynField::Bls12_381,
+        DynField::Goldilocks,
+    ];
+
+    /// The identifier of the field in the instance files.
+    pub fn id(self) -> &'static str {
+        match self {
+            DynField::Field256 => "field256",
+            DynField::Bls12_381 => "bls12-381",
+            DynField::Goldilocks => "goldilocks",
+        }
+    }
+}
+
+impl fmt::Display for DynField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for DynField {
+    type Err = UnknownField;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        DynField::ALL
+            .into_iter()
+            .find(|field| field.id() == id)
+            .ok_or_else(|| UnknownField(id.into()))
+    }
+}
+
+/// A field identifier which is not that of a [`DynField`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unknown field '{0}' (expected field256, bls12-381 or goldilocks)")]
+pub struct UnknownField(pub String);
+
+/// A field whose elements can be combined with the elements of its extension 'E'. The prover's
+/// tables may live in such a field while the verifier's challenges live in 'E'.
+pub trait Subfield<E: Field>: Field {
+    /// Embeds the element in 'E'.
+    fn lift(self) -> E;
+    /// Multiplies an element of 'E' by this one, which is cheaper than lifting it first.
+    fn mul_ext(self, e: E) -> E;
+}
+
+impl<E: Field> Subfield<E> for E {
+    fn lift(self) -> E {
+        self
+    }
+
+    fn mul_ext(self, e: E) -> E {
+        self * e
+    }
+}
+
+macro_rules! impl_subfield {
+    ($base:ty, $ext:ty, $mul_by_fp:ident) => {
+        impl Subfield<$ext> for $base {
+            fn lift(self) -> $ext {
+                <$ext>::from_base_prime_field(self)
+            }
+
+            fn mul_ext(self, mut e: $ext) -> $ext {
+                e.$mul_by_fp(&self);
+                e
+            }
+        }
+    };
+}
+
+impl_subfield!(Field64, Field64Ext2, mul_assign_by_fp);
+impl_subfield!(Field31, Field31Ext2, mul_assign_by_fp);
+impl_subfield!(Field31, Field31Ext4, mul_by_fp);
+
+/// Inverts every nonzero element of 'elements' in place with Montgomery's trick, i.e. a single
+/// inversion and three multiplications per element. The zeros are left as they are.
+pub fn batch_invert<T: Field>(elements: &mut [T]) {
+    let mut prefixes = Vec::with_capacity(elements.len());
+    let mut product = T::ONE;
+    for element in elements.iter().filter(|element| !element.is_zero()) {
+        prefixes.push(product);
+        product *= element;
+    }
+    // A product of nonzero elements is nonzero.
+    let mut inverse = product.inverse().expect("the product is nonzero");
+    let nonzero = elements.iter_mut().rev().filter(|element| !element.is_zero());
+    for (element, prefix) in nonzero.zip(prefixes.into_iter().rev()) {
+        let element_inverse = inverse * prefix;
+        inverse *= *element;
+        *element = element_inverse;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_ff::{FftField, LegendreSymbol, PrimeField};
+    use ark_std::{string::ToString, UniformRand};
+    use rand::thread_rng;
 
     #[test]
     fn test_addition() {
@@ -27,4 +255,95 @@ mod tests {
         let el_2 = Field256::from(6);
         assert_eq!(el_1 - el_2, Field256::from(-3));
     }
+
+    #[test]
+    fn test_batch_invert() {
+        let mut rng = thread_rng();
+        let elements: Vec<Field256> = (0..9)
+            .map(|i| if i % 4 == 1 { Field256::from(0) } else { Field256::rand(&mut rng) })
+            .collect();
+        let mut inverses = elements.clone();
+        batch_invert(&mut inverses);
+        for (element, inverse) in elements.iter().zip(&inverses) {
+            assert_eq!(*inverse, element.inverse().unwrap_or(Field256::from(0)));
+        }
+        batch_invert::<Field256>(&mut []);
+    }
+
+    #[test]
+    fn test_field64() {
+        // 2^64 - 2^32 = -1.
+        let two_32 = Field64::from(1u64 << 32);
+        assert_eq!(two_32 * two_32 - two_32, -Field64::from(1));
+        assert_eq!(Field64::from(u64::MAX), Field64::from((1u64 << 32) - 2));
+        let el = Field64::from(123456789);
+        assert_eq!(el * el.inverse().unwrap(), Field64::from(1));
+    }
+
+    #[test]
+    fn test_field31() {
+        assert_eq!(Field31::from(2013265921u64), Field31::from(0));
+        assert_eq!(Field31::from(2013265920u64) + Field31::from(2), Field31::from(1));
+        let el = Field31::from(987654321);
+        assert_eq!(el * el.inverse().unwrap(), Field31::from(1));
+    }
+
+    #[test]
+    fn test_field_bls12() {
+        // The 2^32-th roots of unity exist.
+        assert_eq!(FieldBls12::TWO_ADICITY, 32);
+        let el = FieldBls12::from(123456789);
+        assert_eq!(el * el.inverse().unwrap(), FieldBls12::from(1));
+        assert_eq!(
+            FieldBls12::from(7).legendre(),
+            LegendreSymbol::QuadraticNonResidue
+        );
+    }
+
+    #[test]
+    fn test_field_ids() {
+        for field in DynField::ALL {
+            assert_eq!(field.id().parse(), Ok(field));
+        }
+        assert_eq!(
+            "bn254".parse::<DynField>(),
+            Err(UnknownField("bn254".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_generators() {
+        // The generators must not be a quadratic residue, or they could not generate the
+        // multiplicative group.
+        assert_eq!(Field64::from(7).legendre(), LegendreSymbol::QuadraticNonResidue);
+        assert_eq!(Field31::from(31).legendre(), LegendreSymbol::QuadraticNonResidue);
+    }
+
+    #[test]
+    fn test_extensions() {
+        // The nonresidues have a square root in the extension.
+        let u = Field64Ext2::new(Field64::from(0), Field64::from(1));
+        assert_eq!(u * u, Subfield::<Field64Ext2>::lift(Field64::from(7)));
+        let v = Field31Ext4::new(Field31Ext2::from(0), Field31Ext2::from(1));
+        assert_eq!(v.pow([4]), Subfield::<Field31Ext4>::lift(Field31::from(11)));
+
+        let mut rng = thread_rng();
+        let e = Field31Ext4::rand(&mut rng);
+        assert_eq!(e * e.inverse().unwrap(), Field31Ext4::ONE);
+        // The Frobenius map is the p-th power.
+        let mut frobenius = e;
+        frobenius.frobenius_map_in_place(1);
+        assert_eq!(frobenius, e.pow(Field31::MODULUS));
+    }
+
+    #[test]
+    fn test_mixed_arithmetic() {
+        let mut rng = thread_rng();
+        let b = Field64::rand(&mut rng);
+        let e = Field64Ext2::rand(&mut rng);
+        assert_eq!(b.mul_ext(e), Subfield::<Field64Ext2>::lift(b) * e);
+        let b = Field31::rand(&mut rng);
+        let e = Field31Ext4::rand(&mut rng);
+        assert_eq!(b.mul_ext(e), Subfield::<Field31Ext4>::lift(b) * e);
+    }
 }
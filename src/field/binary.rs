@@ -0,0 +1,225 @@
+//! The binary field GF(2^128), for sum-checks in the binary-field settings of proof systems such as
+//! Binius. The field has characteristic 2: the integers reduce to 0 and 1, so that the round
+//! polynomials cannot be described by their evaluations at 0, 1, ..., d as over the prime fields,
+//! and addition and subtraction are both the XOR of the coefficients.
+//!
+//! The field is not an `ark_ff::Field`, whose base field must be a prime field of odd order, and
+//! comes with its own sum-check, [`prove_binary`](crate::protocol::prove_binary).
+
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use core::fmt;
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+
+/// An element of GF(2^128) = GF(2)[x] / (x^128 + x^7 + x^2 + x + 1), by its coefficients: bit i
+/// holds the coefficient of x^i.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BinaryField128(pub u128);
+
+impl BinaryField128 {
+    pub const ZERO: Self = BinaryField128(0);
+    pub const ONE: Self = BinaryField128(1);
+
+    /// The element whose coefficients are the bits of 'k'. The nodes 0, 1, ..., d are distinct,
+    /// and the first two are the elements 0 and 1, so that they take the place of the integers
+    /// 0, 1, ..., d as the evaluation points of the round polynomials.
+    pub const fn node(k: usize) -> Self {
+        BinaryField128(k as u128)
+    }
+
+    pub fn square(self) -> Self {
+        self * self
+    }
+
+    pub fn pow(self, mut exp: u128) -> Self {
+        let (mut base, mut result) = (self, Self::ONE);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result *= base;
+            }
+            base = base.square();
+            exp >>= 1;
+        }
+        result
+    }
+
+    /// The inverse, as the power 2^128 - 2 of the element, unless it is zero.
+    pub fn inverse(self) -> Option<Self> {
+        (self != Self::ZERO).then(|| self.pow(u128::MAX - 1))
+    }
+}
+
+/// The product of 'a' and 'b' as polynomials over GF(2), as its low and high 128 coefficients.
+fn carryless_mul(a: u128, b: u128) -> (u128, u128) {
+    let (mut low, mut high) = (0, 0);
+    for i in 0..128 {
+        if (b >> i) & 1 == 1 {
+            low ^= a << i;
+            if i > 0 {
+                high ^= a >> (128 - i);
+            }
+        }
+    }
+    (low, high)
+}
+
+/// Reduces low + high * x^128, with x^128 = x^7 + x^2 + x + 1. The multiple of x^128 in the
+/// reduction of 'high' has degree less than 7, and is reduced along with it.
+fn reduce(low: u128, high: u128) -> u128 {
+    let overflow = (high >> 127) ^ (high >> 126) ^ (high >> 121);
+    let folded = high ^ overflow;
+    low ^ folded ^ (folded << 1) ^ (folded << 2) ^ (folded << 7)
+}
+
+impl Add for BinaryField128 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, other: Self) -> Self {
+        BinaryField128(self.0 ^ other.0)
+    }
+}
+
+impl Sub for BinaryField128 {
+    type Output = Self;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, other: Self) -> Self {
+        self + other
+    }
+}
+
+impl Neg for BinaryField128 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self
+    }
+}
+
+impl Mul for BinaryField128 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let (low, high) = carryless_mul(self.0, other.0);
+        BinaryField128(reduce(low, high))
+    }
+}
+
+impl AddAssign for BinaryField128 {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for BinaryField128 {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for BinaryField128 {
+    fn mul_assign(&mut self, other: Self) {
+        *self = *self * other;
+    }
+}
+
+impl Sum for BinaryField128 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl Product for BinaryField128 {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ONE, Mul::mul)
+    }
+}
+
+impl fmt::Display for BinaryField128 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:032x}", self.0)
+    }
+}
+
+/// An element is serialized as the 16 little-endian bytes of its coefficients.
+impl CanonicalSerialize for BinaryField128 {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        _compress: Compress,
+    ) -> Result<(), SerializationError> {
+        Ok(writer.write_all(&self.0.to_le_bytes())?)
+    }
+
+    fn serialized_size(&self, _compress: Compress) -> usize {
+        16
+    }
+}
+
+impl Valid for BinaryField128 {
+    fn check(&self) -> Result<(), SerializationError> {
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for BinaryField128 {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        _compress: Compress,
+        _validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let mut bytes = [0; 16];
+        reader.read_exact(&mut bytes)?;
+        Ok(BinaryField128(u128::from_le_bytes(bytes)))
+    }
+}
+
+/// The elements are drawn from 128 uniform bits, which are uniform in the field, unlike the bits
+/// of an element of a prime field.
+impl Distribution<BinaryField128> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BinaryField128 {
+        BinaryField128(rng.gen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_reduction() {
+        let x = BinaryField128(2);
+        let x127 = BinaryField128(1 << 127);
+        // x^128 = x^7 + x^2 + x + 1.
+        assert_eq!(x * x127, BinaryField128(0b1000_0111));
+        assert_eq!(x127 * x127, x.pow(254));
+        assert_eq!(BinaryField128::node(3) + BinaryField128::node(1), x);
+    }
+
+    #[test]
+    fn test_field_axioms() {
+        let mut rng = thread_rng();
+        let (a, b, c) = (
+            BinaryField128::rand(&mut rng),
+            BinaryField128::rand(&mut rng),
+            BinaryField128::rand(&mut rng),
+        );
+        assert_eq!(a * (b + c), a * b + a * c);
+        assert_eq!((a * b) * c, a * (b * c));
+        assert_eq!(a * a.inverse().unwrap(), BinaryField128::ONE);
+        assert_eq!(BinaryField128::ZERO.inverse(), None);
+        // The Frobenius map has order 128.
+        let mut frobenius = a;
+        (0..128).for_each(|_| frobenius = frobenius.square());
+        assert_eq!(frobenius, a);
+        assert_eq!(a - b, a + b);
+    }
+}
@@ -0,0 +1,351 @@
+use ark_ff::Field;
+use ark_std::UniformRand;
+use rand::thread_rng;
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{evaluate_table, EvalTable, PolynomialDescription};
+use crate::protocol::{SumCheckError, SumCheckProver, Verifier};
+
+/// Largest degree of the round polynomials in the sum-check of a layer. Rounds over the variables
+/// of c, on which the left input does not depend, have degree 2.
+const LAYER_DEGREE: usize = 3;
+
+/// A gate of a layered arithmetic circuit, given with the indices of its two inputs in the
+/// previous layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gate {
+    Add(usize, usize),
+    Mul(usize, usize),
+}
+
+/// A layered arithmetic circuit. `layers[0]` is the output layer, and the gates of layer `i` read
+/// their inputs from layer `i + 1`, or from the circuit inputs for the last layer. Every layer,
+/// as well as the input, must have a power of two size.
+#[derive(Debug, Clone)]
+pub struct Circuit {
+    layers: Vec<Vec<Gate>>,
+    num_inputs: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GkrError {
+    #[error("layer {layer}: {source}")]
+    SumCheck {
+        layer: usize,
+        #[source]
+        source: SumCheckError,
+    },
+    /// The claimed values of the next layer are inconsistent with the last sum-check round.
+    #[error("layer {layer}: the wiring check after the sum-check failed")]
+    WiringCheckFailed { layer: usize },
+    /// The polynomial restricted to the line through the two points is inconsistent.
+    #[error("layer {layer}: the reduction of two claims to one failed")]
+    LineCheckFailed { layer: usize },
+    /// The last claim does not match the multilinear extension of the inputs.
+    #[error("the final claim does not match the circuit inputs")]
+    InputCheckFailed,
+}
+
+impl Circuit {
+    /// # Panics
+    ///
+    /// Panics if a layer or the input does not have a power of two size, or if a gate reads a
+    /// value outside of the previous layer.
+    pub fn new(layers: Vec<Vec<Gate>>, num_inputs: usize) -> Self {
+        assert!(
+            num_inputs.is_power_of_two(),
+            "the input size must be a power of two"
+        );
+        for (i, layer) in layers.iter().enumerate() {
+            assert!(
+                layer.len().is_power_of_two(),
+                "the size of layer {} must be a power of two",
+                i
+            );
+            let previous = layers.get(i + 1).map_or(num_inputs, Vec::len);
+            assert!(
+                layer.iter().all(|&gate| {
+                    let (a, b) = gate.inputs();
+                    a < previous && b < previous
+                }),
+                "a gate of layer {} reads outside of the previous layer",
+                i
+            );
+        }
+        Circuit { layers, num_inputs }
+    }
+
+    /// Evaluates the circuit, and returns the values of all layers, from the output layer to the
+    /// inputs.
+    pub fn evaluate(&self, inputs: &[F]) -> Vec<EvalTable> {
+        assert_eq!(inputs.len(), self.num_inputs);
+        let mut values = vec![inputs.to_vec()];
+        for layer in self.layers.iter().rev() {
+            let previous = values.last().unwrap();
+            let current = layer
+                .iter()
+                .map(|gate| match *gate {
+                    Gate::Add(a, b) => previous[a] + previous[b],
+                    Gate::Mul(a, b) => previous[a] * previous[b],
+                })
+                .collect();
+            values.push(current);
+        }
+        values.reverse();
+        values
+    }
+
+    fn num_vars(&self, layer: usize) -> usize {
+        let size = self.layers.get(layer).map_or(self.num_inputs, Vec::len);
+        size.trailing_zeros() as usize
+    }
+}
+
+impl Gate {
+    fn inputs(&self) -> (usize, usize) {
+        match *self {
+            Gate::Add(a, b) | Gate::Mul(a, b) => (a, b),
+        }
+    }
+}
+
+/// Runs the GKR protocol for the claim that 'circuit' evaluated on 'inputs' gives
+/// 'claimed_outputs'. The prover knows the values of every layer, while the verifier only uses
+/// the circuit description, the inputs and the claimed outputs.
+///
+/// Each layer is reduced to the next one with a sum-check over the wiring predicates restricted
+/// to the current point, followed by a reduction of the two resulting claims to a single one.
+pub fn orchestrate_gkr(
+    circuit: &Circuit,
+    inputs: &[F],
+    claimed_outputs: &[F],
+) -> Result<(), GkrError> {
+    let values = circuit.evaluate(inputs);
+    let mut rng = thread_rng();
+
+    let mut point: Vec<F> = (0..circuit.num_vars(0))
+        .map(|_| F::rand(&mut rng))
+        .collect();
+    let mut claim = evaluate_table(claimed_outputs, &point);
+
+    for layer in 0..circuit.layers.len() {
+        let next_vars = circuit.num_vars(layer + 1);
+
+        // Sum-check over the 2 * next_vars variables (b, c) of the next layer.
+        let mut prover = layer_prover(&circuit.layers[layer], &point, &values[layer + 1]);
+        let mut running_eval = claim;
+        let mut challenge = None;
+        let mut challenges = Vec::with_capacity(2 * next_vars);
+        for round in 0..2 * next_vars {
+            let message = prover.next_message(challenge).evaluations;
+            check_round(round, running_eval, &message)
+                .map_err(|source| GkrError::SumCheck { layer, source })?;
+            let r = F::rand(&mut rng);
+            running_eval = Verifier::evaluate_at_random_point(&message, r);
+            challenges.push(r);
+            challenge = Some(r);
+        }
+        let (b, c) = challenges.split_at(next_vars);
+
+        // The prover claims the values of the next layer at b and c.
+        let (v_b, v_c) = (
+            evaluate_table(&values[layer + 1], b),
+            evaluate_table(&values[layer + 1], c),
+        );
+        let (add, mul) = evaluate_wiring(&circuit.layers[layer], &point, b, c);
+        if add * (v_b + v_c) + mul * v_b * v_c != running_eval {
+            return Err(GkrError::WiringCheckFailed { layer });
+        }
+
+        // The prover sends the restriction of the next layer to the line through b and c.
+        let line = line_message(&values[layer + 1], b, c);
+        if line.len() != next_vars + 1
+            || Verifier::evaluate_at_random_point(&line, F::ZERO) != v_b
+            || Verifier::evaluate_at_random_point(&line, F::ONE) != v_c
+        {
+            return Err(GkrError::LineCheckFailed { layer });
+        }
+        let r = F::rand(&mut rng);
+        point = line_point(b, c, r);
+        claim = Verifier::evaluate_at_random_point(&line, r);
+    }
+
+    if evaluate_table(inputs, &point) != claim {
+        return Err(GkrError::InputCheckFailed);
+    }
+    Ok(())
+}
+
+/// Checks a round message of a layer sum-check against the running evaluation.
+fn check_round(
+    round: usize,
+    running_eval: F,
+    message: &PolynomialDescription,
+) -> Result<(), SumCheckError> {
+    let intermediate = match Verifier::evaluate_intermediate(message) {
+        Some(intermediate) if message.len() <= LAYER_DEGREE + 1 => intermediate,
+        _ => {
+            return Err(SumCheckError::DegreeMismatch {
+                round,
+                expected: LAYER_DEGREE + 1,
+                got: message.len(),
+            })
+        }
+    };
+    if intermediate != running_eval {
+        return Err(SumCheckError::IntermediateCheckFailed {
+            round,
+            lhs: running_eval,
+            rhs: intermediate,
+        });
+    }
+    Ok(())
+}
+
+/// Prepares the sum-check of a layer at point 'z', over the variables (b, c) of the next layer:
+///
+///   V_i(z) = Σ_{b,c} add(z, b, c) · (V(b) + V(c)) + mul(z, b, c) · V(b) · V(c)
+///
+/// The sum is over the three products add · V(b), add · V(c) and mul · V(b) · V(c).
+fn layer_prover(gates: &[Gate], z: &[F], next_values: &EvalTable) -> SumCheckProver {
+    let next_vars = next_values.len().trailing_zeros() as usize;
+    let size = 1 << (2 * next_vars);
+    let eq_z = eq_table(z);
+
+    let mut add_table = vec![F::ZERO; size];
+    let mut mul_table = vec![F::ZERO; size];
+    for (g, gate) in gates.iter().enumerate() {
+        match *gate {
+            Gate::Add(a, b) => add_table[(a << next_vars) | b] += eq_z[g],
+            Gate::Mul(a, b) => mul_table[(a << next_vars) | b] += eq_z[g],
+        }
+    }
+    let mask = (1 << next_vars) - 1;
+    let left: EvalTable = (0..size).map(|i| next_values[i >> next_vars]).collect();
+    let right: EvalTable = (0..size).map(|i| next_values[i & mask]).collect();
+
+    SumCheckProver::from_sum_of_products_tables(
+        2 * next_vars,
+        vec![
+            vec![add_table.clone(), left.clone()],
+            vec![add_table, right.clone()],
+            vec![mul_table, left, right],
+        ],
+    )
+}
+
+/// Evaluates the multilinear extensions of the wiring predicates at (z, b, c).
+fn evaluate_wiring(gates: &[Gate], z: &[F], b: &[F], c: &[F]) -> (F, F) {
+    let (mut add, mut mul) = (F::ZERO, F::ZERO);
+    for (g, gate) in gates.iter().enumerate() {
+        let (x, y) = gate.inputs();
+        let term = eq_at(z, g) * eq_at(b, x) * eq_at(c, y);
+        match gate {
+            Gate::Add(..) => add += term,
+            Gate::Mul(..) => mul += term,
+        }
+    }
+    (add, mul)
+}
+
+/// Evaluations at 0, 1, ..., n of the multilinear extension of 'table' restricted to the line
+/// going through 'b' (at 0) and 'c' (at 1).
+fn line_message(table: &EvalTable, b: &[F], c: &[F]) -> PolynomialDescription {
+    (0..=b.len())
+        .map(|t| evaluate_table(table, &line_point(b, c, F::from(t as u64))))
+        .collect()
+}
+
+fn line_point(b: &[F], c: &[F], t: F) -> Vec<F> {
+    b.iter()
+        .zip(c.iter())
+        .map(|(&b_j, &c_j)| b_j + t * (c_j - b_j))
+        .collect()
+}
+
+/// Table of eq(z, x) for every x of the hypercube.
+fn eq_table(z: &[F]) -> EvalTable {
+    (0..1 << z.len()).map(|x| eq_at(z, x)).collect()
+}
+
+/// Evaluates eq(z, x) for a point 'z' and the hypercube point of index 'x'.
+fn eq_at(z: &[F], x: usize) -> F {
+    z.iter()
+        .enumerate()
+        .map(|(j, &z_j)| {
+            if (x >> (z.len() - j - 1)) & 1 == 1 {
+                z_j
+            } else {
+                F::ONE - z_j
+            }
+        })
+        .product()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Computes (x0 · x1) + (x2 · x3) and (x0 + x1) · (x2 + x3).
+    fn small_circuit() -> Circuit {
+        Circuit::new(
+            vec![
+                vec![Gate::Add(0, 1), Gate::Mul(2, 3)],
+                vec![
+                    Gate::Mul(0, 1),
+                    Gate::Mul(2, 3),
+                    Gate::Add(0, 1),
+                    Gate::Add(2, 3),
+                ],
+            ],
+            4,
+        )
+    }
+
+    #[test]
+    fn test_evaluate_circuit() {
+        let inputs = vec![F::from(1), F::from(2), F::from(3), F::from(4)];
+        let values = small_circuit().evaluate(&inputs);
+        assert_eq!(values.len(), 3);
+        assert_eq!(values[0], vec![F::from(14), F::from(21)]);
+        assert_eq!(values[2], inputs);
+    }
+
+    #[test]
+    fn test_gkr() {
+        let inputs = vec![F::from(1), F::from(2), F::from(3), F::from(4)];
+        let outputs = vec![F::from(14), F::from(21)];
+        assert_eq!(orchestrate_gkr(&small_circuit(), &inputs, &outputs), Ok(()));
+    }
+
+    #[test]
+    fn test_gkr_single_output() {
+        let circuit = Circuit::new(
+            vec![
+                vec![Gate::Mul(0, 1)],
+                vec![Gate::Add(0, 3), Gate::Mul(1, 2)],
+            ],
+            4,
+        );
+        let inputs = vec![F::from(5), F::from(6), F::from(7), F::from(8)];
+        assert_eq!(orchestrate_gkr(&circuit, &inputs, &[F::from(546)]), Ok(()));
+    }
+
+    #[test]
+    fn test_gkr_wrong_output() {
+        let inputs = vec![F::from(1), F::from(2), F::from(3), F::from(4)];
+        let outputs = vec![F::from(14), F::from(20)];
+        let result = orchestrate_gkr(&small_circuit(), &inputs, &outputs);
+        assert!(matches!(result, Err(GkrError::SumCheck { layer: 0, .. })));
+    }
+
+    #[test]
+    fn test_evaluate_table() {
+        let table = vec![F::from(3), F::from(5), F::from(7), F::from(11)];
+        assert_eq!(evaluate_table(&table, &[F::ONE, F::ZERO]), F::from(7));
+        let z = vec![F::from(4), F::from(9)];
+        let expected: F = table.iter().zip(eq_table(&z)).map(|(&t, e)| t * e).sum();
+        assert_eq!(evaluate_table(&table, &z), expected);
+    }
+}
@@ -0,0 +1,332 @@
+//! Experimental offload of the large tables to the GPU, with the `gpu` feature: the evaluation of
+//! the factors on the hypercube and the folds of the rounds run in compute shaders, through
+//! `wgpu`.
+//!
+//! A [`GpuBackend`] falls back to the CPU when no adapter is available, for tables on fewer than
+//! [`GpuConfig::min_vars`] variables, whose transfers would cost more than they save, and for
+//! tables larger than the storage buffers of the device. The elements are exchanged in Montgomery
+//! form, the shaders implementing the arithmetic of [`Field256`](crate::field::Field256) on limbs
+//! of 32 bits.
+
+use ark_ff::{BigInt, MontConfig};
+use ark_poly::DenseMVPolynomial;
+use wgpu::util::DeviceExt;
+
+use crate::field::{Field256 as F, FieldConfig};
+use crate::polynomial::{evaluate_polynomial_on_hypercube, EvalTable, MLPolynomial};
+use crate::protocol::fold_in_place;
+
+const SHADER: &str = include_str!("gpu/sum_check.wgsl");
+
+/// Threads per workgroup of the shaders.
+const WORKGROUP_SIZE: u32 = 64;
+/// Largest number of workgroups in a dimension of a dispatch.
+const MAX_WORKGROUPS: u32 = 65535;
+
+/// When to offload a table to the GPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuConfig {
+    /// Tables on fewer variables stay on the CPU.
+    pub min_vars: usize,
+}
+
+impl Default for GpuConfig {
+    fn default() -> Self {
+        GpuConfig { min_vars: 16 }
+    }
+}
+
+/// Evaluates and folds tables on the GPU when one is available and the tables are large enough,
+/// and on the CPU otherwise.
+pub struct GpuBackend {
+    pub config: GpuConfig,
+    context: Option<GpuContext>,
+}
+
+impl GpuBackend {
+    /// Looks for a GPU adapter, and falls back to the CPU if there is none.
+    pub fn new(config: GpuConfig) -> Self {
+        GpuBackend {
+            config,
+            context: GpuContext::new(),
+        }
+    }
+
+    /// A backend which always runs on the CPU.
+    pub fn cpu(config: GpuConfig) -> Self {
+        GpuBackend {
+            config,
+            context: None,
+        }
+    }
+
+    /// Whether a GPU adapter was found.
+    pub fn has_gpu(&self) -> bool {
+        self.context.is_some()
+    }
+
+    /// The context to use for a table on 'num_vars' variables, if it goes to the GPU.
+    fn offload(&self, num_vars: usize) -> Option<&GpuContext> {
+        self.context
+            .as_ref()
+            .filter(|context| num_vars >= self.config.min_vars && context.fits(num_vars))
+    }
+
+    /// Same as [`evaluate_polynomial_on_hypercube`].
+    pub fn evaluate_on_hypercube(&self, poly: &MLPolynomial) -> EvalTable {
+        match self.offload(poly.num_vars) {
+            Some(context) => context.evaluate(poly),
+            None => evaluate_polynomial_on_hypercube(poly),
+        }
+    }
+
+    /// Binds the first of the 'num_vars' + 1 variables of 'table' to 'r', overwriting its first
+    /// half and dropping the second one.
+    pub fn fold_in_place(&self, num_vars: usize, r: F, table: &mut Vec<F>) {
+        match self.offload(num_vars + 1) {
+            Some(context) => context.fold(num_vars, r, table),
+            None => fold_in_place(num_vars, r, table),
+        }
+    }
+}
+
+struct GpuContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    fold: wgpu::ComputePipeline,
+    evaluate: wgpu::ComputePipeline,
+    /// Largest size in bytes of a storage buffer.
+    max_binding_size: u64,
+}
+
+impl GpuContext {
+    fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let limits = adapter.limits();
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("sum-check"),
+                required_features: wgpu::Features::empty(),
+                required_limits: limits.clone(),
+                memory_hints: wgpu::MemoryHints::Performance,
+            },
+            None,
+        ))
+        .ok()?;
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("sum-check"),
+            source: wgpu::ShaderSource::Wgsl(shader_source().into()),
+        });
+        let pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: None,
+                module: &module,
+                entry_point,
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let (fold, evaluate) = (pipeline("fold"), pipeline("evaluate"));
+        Some(GpuContext {
+            device,
+            queue,
+            fold,
+            evaluate,
+            max_binding_size: u64::from(limits.max_storage_buffer_binding_size)
+                .min(limits.max_buffer_size),
+        })
+    }
+
+    /// Whether a table on 'num_vars' variables fits in a storage buffer, with indices on 32 bits.
+    fn fits(&self, num_vars: usize) -> bool {
+        num_vars < 32 && (32u64 << num_vars) <= self.max_binding_size
+    }
+
+    fn evaluate(&self, poly: &MLPolynomial) -> EvalTable {
+        let num_vars = poly.num_vars;
+        let mut terms = Vec::with_capacity(9 * poly.terms().len());
+        for (coefficient, term) in poly.terms() {
+            let mask = term
+                .iter()
+                .fold(0u32, |mask, &(var, _)| mask | 1 << (num_vars - var - 1));
+            terms.push(mask);
+            terms.extend(to_words(coefficient));
+        }
+        let len = 1 << num_vars;
+        let table = vec![0u32; 8 * len];
+        self.run(
+            &self.evaluate,
+            &table,
+            &terms,
+            len as u32,
+            poly.terms().len() as u32,
+        )
+    }
+
+    fn fold(&self, num_vars: usize, r: F, table: &mut Vec<F>) {
+        let words: Vec<u32> = table.iter().flat_map(to_words).collect();
+        let len = 1 << num_vars;
+        *table = self.run(&self.fold, &words, &to_words(&r), len as u32, 0);
+    }
+
+    /// Runs 'pipeline' on 'len' threads over 'table', and returns the first 'len' entries of the
+    /// table afterwards.
+    fn run(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        table: &[u32],
+        input: &[u32],
+        len: u32,
+        num_terms: u32,
+    ) -> Vec<F> {
+        let device = &self.device;
+        let storage = |contents: &[u32], usage| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                // Bindings may not be empty.
+                contents: as_bytes(if contents.is_empty() { &[0] } else { contents }),
+                usage,
+            })
+        };
+        let table_buffer = storage(
+            table,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        );
+        let input_buffer = storage(input, wgpu::BufferUsages::STORAGE);
+        let params_buffer = storage(&[len, num_terms, 0, 0], wgpu::BufferUsages::UNIFORM);
+        let output_size = 32 * u64::from(len);
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: output_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: table_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: input_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: params_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            let workgroups = len.div_ceil(WORKGROUP_SIZE).max(1);
+            let x = workgroups.min(MAX_WORKGROUPS);
+            pass.dispatch_workgroups(x, workgroups.div_ceil(x), 1);
+        }
+        encoder.copy_buffer_to_buffer(&table_buffer, 0, &staging, 0, output_size);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::Maintain::Wait);
+        let bytes = slice.get_mapped_range();
+        let output = bytes
+            .chunks_exact(32)
+            .map(|chunk| {
+                let limbs = core::array::from_fn(|i| {
+                    u64::from_le_bytes(chunk[8 * i..8 * i + 8].try_into().unwrap())
+                });
+                F::new_unchecked(BigInt(limbs))
+            })
+            .collect();
+        drop(bytes);
+        staging.unmap();
+        output
+    }
+}
+
+/// The shader, preceded by the constants of the field.
+fn shader_source() -> String {
+    let words: Vec<String> = FieldConfig::MODULUS
+        .0
+        .iter()
+        .flat_map(|&limb| [limb as u32, (limb >> 32) as u32])
+        .map(|word| format!("{word}u"))
+        .collect();
+    format!(
+        "var<private> P: array<u32, 8> = array<u32, 8>({});\nconst INV: u32 = {}u;\n{}",
+        words.join(", "),
+        FieldConfig::INV as u32,
+        SHADER
+    )
+}
+
+/// The Montgomery representation of 'element', as little-endian words.
+fn to_words(element: &F) -> [u32; 8] {
+    let limbs = element.0 .0;
+    core::array::from_fn(|i| (limbs[i / 2] >> (32 * (i % 2))) as u32)
+}
+
+fn as_bytes(words: &[u32]) -> &[u8] {
+    // SAFETY: the bytes of a slice of u32 are initialized, and u8 has no alignment requirement.
+    unsafe { core::slice::from_raw_parts(words.as_ptr().cast(), core::mem::size_of_val(words)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use ark_std::UniformRand;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// A backend offloading every table, on the GPU if there is one.
+    fn backend() -> GpuBackend {
+        GpuBackend::new(GpuConfig { min_vars: 0 })
+    }
+
+    #[test]
+    fn test_words() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let element = F::rand(&mut rng);
+        let words = to_words(&element);
+        let limbs =
+            core::array::from_fn(|i| u64::from(words[2 * i]) | u64::from(words[2 * i + 1]) << 32);
+        assert_eq!(F::new_unchecked(BigInt(limbs)), element);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let backend = backend();
+        let poly = parse_product("(3*x0*x2 + x1 + 7*x3*x4*x0 + 5) * (x4)").unwrap();
+        for factor in &poly {
+            assert_eq!(
+                backend.evaluate_on_hypercube(factor),
+                evaluate_polynomial_on_hypercube(factor)
+            );
+        }
+    }
+
+    #[test]
+    fn test_fold() {
+        let backend = backend();
+        let mut rng = StdRng::seed_from_u64(1);
+        for num_vars in [0, 3, 7] {
+            let table: EvalTable = (0..2 << num_vars).map(|_| F::rand(&mut rng)).collect();
+            let r = F::rand(&mut rng);
+            let (mut gpu, mut cpu) = (table.clone(), table);
+            backend.fold_in_place(num_vars, r, &mut gpu);
+            fold_in_place(num_vars, r, &mut cpu);
+            assert_eq!(gpu, cpu);
+        }
+    }
+}
@@ -0,0 +1,206 @@
+// Arithmetic in the proof field on 8 little-endian limbs of 32 bits, in Montgomery form. The
+// modulus P and INV = -1/P mod 2^32 are prepended by the host. The functions copy their array
+// arguments to variables, as arguments may only be indexed by constants.
+
+struct Params {
+    // Number of entries to compute.
+    len: u32,
+    // Number of terms of the polynomial, for `evaluate`.
+    num_terms: u32,
+}
+
+@group(0) @binding(0) var<storage, read_write> table: array<u32>;
+@group(0) @binding(1) var<storage, read> input: array<u32>;
+@group(0) @binding(2) var<uniform> params: Params;
+
+// The product a·b as its low and high words, from products of 16-bit halves.
+fn mul_wide(a: u32, b: u32) -> vec2<u32> {
+    let a0 = a & 0xffffu;
+    let a1 = a >> 16u;
+    let b0 = b & 0xffffu;
+    let b1 = b >> 16u;
+    let p00 = a0 * b0;
+    let mid = a0 * b1 + (p00 >> 16u);
+    let mid2 = (mid & 0xffffu) + a1 * b0;
+    let lo = (mid2 << 16u) | (p00 & 0xffffu);
+    let hi = a1 * b1 + (mid >> 16u) + (mid2 >> 16u);
+    return vec2<u32>(lo, hi);
+}
+
+// a + b + carry, with the carry out.
+fn adc(a: u32, b: u32, carry: u32) -> vec2<u32> {
+    let s = a + b;
+    let s2 = s + carry;
+    return vec2<u32>(s2, select(0u, 1u, s < a) + select(0u, 1u, s2 < s));
+}
+
+// a - b - borrow, with the borrow out.
+fn sbb(a: u32, b: u32, borrow: u32) -> vec2<u32> {
+    let d = a - b;
+    let d2 = d - borrow;
+    return vec2<u32>(d2, select(0u, 1u, a < b) + select(0u, 1u, d < borrow));
+}
+
+// t + a·b + carry, with the high word as the carry out.
+fn mac(t: u32, a: u32, b: u32, carry: u32) -> vec2<u32> {
+    let w = mul_wide(a, b);
+    let s = adc(w.x, t, 0u);
+    let s2 = adc(s.x, carry, 0u);
+    return vec2<u32>(s2.x, w.y + s.y + s2.y);
+}
+
+fn geq_modulus(a_in: array<u32, 8>) -> bool {
+    var a = a_in;
+    for (var i = 7; i >= 0; i--) {
+        if (a[i] != P[i]) {
+            return a[i] > P[i];
+        }
+    }
+    return true;
+}
+
+fn sub_modulus(a_in: array<u32, 8>) -> array<u32, 8> {
+    var a = a_in;
+    var r = a;
+    var borrow = 0u;
+    for (var i = 0u; i < 8u; i++) {
+        let d = sbb(a[i], P[i], borrow);
+        r[i] = d.x;
+        borrow = d.y;
+    }
+    return r;
+}
+
+fn add_mod(a_in: array<u32, 8>, b_in: array<u32, 8>) -> array<u32, 8> {
+    var a = a_in;
+    var b = b_in;
+    // The modulus is below 2^255, so that the sum does not overflow.
+    var r = a;
+    var carry = 0u;
+    for (var i = 0u; i < 8u; i++) {
+        let s = adc(a[i], b[i], carry);
+        r[i] = s.x;
+        carry = s.y;
+    }
+    if (geq_modulus(r)) {
+        r = sub_modulus(r);
+    }
+    return r;
+}
+
+fn sub_mod(a_in: array<u32, 8>, b_in: array<u32, 8>) -> array<u32, 8> {
+    var a = a_in;
+    var b = b_in;
+    var r = a;
+    var borrow = 0u;
+    for (var i = 0u; i < 8u; i++) {
+        let d = sbb(a[i], b[i], borrow);
+        r[i] = d.x;
+        borrow = d.y;
+    }
+    if (borrow != 0u) {
+        var carry = 0u;
+        for (var i = 0u; i < 8u; i++) {
+            let s = adc(r[i], P[i], carry);
+            r[i] = s.x;
+            carry = s.y;
+        }
+    }
+    return r;
+}
+
+// Montgomery multiplication, with the coarsely integrated operand scanning method.
+fn mul_mod(a_in: array<u32, 8>, b_in: array<u32, 8>) -> array<u32, 8> {
+    var a = a_in;
+    var b = b_in;
+    var t: array<u32, 10>;
+    for (var i = 0u; i < 8u; i++) {
+        var carry = 0u;
+        for (var j = 0u; j < 8u; j++) {
+            let s = mac(t[j], a[j], b[i], carry);
+            t[j] = s.x;
+            carry = s.y;
+        }
+        let s = adc(t[8], carry, 0u);
+        t[8] = s.x;
+        t[9] = s.y;
+
+        let m = t[0] * INV;
+        carry = mac(t[0], m, P[0], 0u).y;
+        for (var j = 1u; j < 8u; j++) {
+            let s = mac(t[j], m, P[j], carry);
+            t[j - 1u] = s.x;
+            carry = s.y;
+        }
+        let s2 = adc(t[8], carry, 0u);
+        t[7] = s2.x;
+        t[8] = t[9] + s2.y;
+    }
+    var r: array<u32, 8>;
+    for (var i = 0u; i < 8u; i++) {
+        r[i] = t[i];
+    }
+    if (t[8] != 0u || geq_modulus(r)) {
+        r = sub_modulus(r);
+    }
+    return r;
+}
+
+fn load_table(index: u32) -> array<u32, 8> {
+    var r: array<u32, 8>;
+    for (var i = 0u; i < 8u; i++) {
+        r[i] = table[8u * index + i];
+    }
+    return r;
+}
+
+fn store_table(index: u32, value_in: array<u32, 8>) {
+    var value = value_in;
+    for (var i = 0u; i < 8u; i++) {
+        table[8u * index + i] = value[i];
+    }
+}
+
+fn load_input(offset: u32) -> array<u32, 8> {
+    var r: array<u32, 8>;
+    for (var i = 0u; i < 8u; i++) {
+        r[i] = input[offset + i];
+    }
+    return r;
+}
+
+fn thread_index(id: vec3<u32>, groups: vec3<u32>) -> u32 {
+    return id.y * groups.x * 64u + id.x;
+}
+
+// Binds the first variable of the table to the challenge in 'input': the entry at i, in the low
+// half, becomes a + r·(b - a) with b the entry at i + len.
+@compute @workgroup_size(64)
+fn fold(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = thread_index(id, groups);
+    if (i >= params.len) {
+        return;
+    }
+    let a = load_table(i);
+    let b = load_table(i + params.len);
+    store_table(i, add_mod(a, mul_mod(sub_mod(b, a), load_input(0u))));
+}
+
+// Evaluates a multilinear polynomial at the point i of the hypercube. Each term of 'input' is the
+// mask of its variables, in the bit order of the indices, followed by its coefficient: the
+// polynomial is the sum of the coefficients of the terms whose variables are all set.
+@compute @workgroup_size(64)
+fn evaluate(@builtin(global_invocation_id) id: vec3<u32>, @builtin(num_workgroups) groups: vec3<u32>) {
+    let i = thread_index(id, groups);
+    if (i >= params.len) {
+        return;
+    }
+    var sum: array<u32, 8>;
+    for (var term = 0u; term < params.num_terms; term++) {
+        let mask = input[9u * term];
+        if ((i & mask) == mask) {
+            sum = add_mod(sum, load_input(9u * term + 1u));
+        }
+    }
+    store_table(i, sum);
+}
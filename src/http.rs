@@ -0,0 +1,175 @@
+//! A REST endpoint verifying proofs, with the `http` feature, for services which check incoming
+//! proofs without linking this crate. `POST /verify` takes a JSON body with the instance, in the
+//! format of [`parser::from_json`] and over the field that it declares, and the proof, in the JSON
+//! encoding of [`DynProof::to_json`]:
+//!
+//! ```json
+//! {"instance": {"num_vars": 2, "factors": [...]}, "proof": {"version": 2, "modulus": "0x…", ...}}
+//! ```
+//!
+//! and answers `{"valid": true}`, or `{"valid": false, "error": "..."}` with the reason for
+//! rejecting the proof. An instance or a proof which cannot be read is answered with the status
+//! 400 and the reason in `error`, as is an instance of more than [`MAX_NUM_VARS`] variables or a
+//! proof with another number of rounds than the instance has variables.
+
+use axum::http::StatusCode;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+
+#[cfg(doc)]
+use crate::polynomial::parser;
+use crate::protocol::{DynInstance, DynProof};
+
+#[derive(Debug, Deserialize)]
+pub struct VerifyRequest {
+    pub instance: serde_json::Value,
+    pub proof: serde_json::Value,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct VerifyResponse {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The largest number of variables of the instances which the endpoint verifies, far beyond those
+/// of any instance which a prover could evaluate on the hypercube.
+pub const MAX_NUM_VARS: usize = 64;
+
+/// The routes of the endpoint, to be served by [`axum::serve`].
+pub fn router() -> Router {
+    Router::new().route("/verify", post(verify_proof))
+}
+
+/// Verifies the proof of the request for its instance.
+pub async fn verify_proof(
+    Json(request): Json<VerifyRequest>,
+) -> (StatusCode, Json<VerifyResponse>) {
+    let invalid = |error: String| {
+        let response = VerifyResponse {
+            valid: false,
+            error: Some(error),
+        };
+        (StatusCode::BAD_REQUEST, Json(response))
+    };
+    // The shape of the request is checked on the JSON values, before anything is allocated for
+    // the instance or the verifier.
+    if let Some(num_vars) = request.instance.get("num_vars").and_then(|n| n.as_u64()) {
+        if num_vars > MAX_NUM_VARS as u64 {
+            return invalid(format!(
+                "the instance has {} variables, more than {}",
+                num_vars, MAX_NUM_VARS
+            ));
+        }
+        let rounds = request.proof.get("rounds").and_then(|r| r.as_array());
+        if let Some(rounds) = rounds.filter(|rounds| rounds.len() as u64 != num_vars) {
+            return invalid(format!(
+                "the proof has {} rounds for {} variables",
+                rounds.len(),
+                num_vars
+            ));
+        }
+    }
+    let instance = match DynInstance::from_json(&request.instance.to_string()) {
+        Ok(instance) if instance.num_vars().is_some() => instance,
+        Ok(_) => return invalid("the factors must have the same variables".to_string()),
+        Err(err) => return invalid(format!("invalid instance: {}", err)),
+    };
+    let proof = match DynProof::from_json(instance.field(), &request.proof.to_string()) {
+        Ok(proof) => proof,
+        Err(err) => return invalid(format!("invalid proof: {}", err)),
+    };
+    let result = instance.verify(&proof);
+    let response = VerifyResponse {
+        valid: result.is_ok(),
+        error: result.err().map(|err| err.to_string()),
+    };
+    (StatusCode::OK, Json(response))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parser;
+    use crate::protocol::prove;
+
+    const INSTANCE: &str = r#"{"num_vars": 2, "factors": [
+        [{"coeff": 1, "vars": [[0, 1]]}, {"coeff": 3}],
+        [{"coeff": 2, "vars": [[1, 1]]}, {"coeff": 1, "vars": [[0, 1]]}]
+    ]}"#;
+
+    fn request(instance: &str, proof: &str) -> Json<VerifyRequest> {
+        Json(VerifyRequest {
+            instance: serde_json::from_str(instance).unwrap(),
+            proof: serde_json::from_str(proof).unwrap(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_verify_endpoint() {
+        let proof = prove(&parser::from_json(INSTANCE).unwrap());
+        let (status, Json(response)) = verify_proof(request(INSTANCE, &proof.to_json())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            response,
+            VerifyResponse {
+                valid: true,
+                error: None
+            }
+        );
+        assert_eq!(
+            serde_json::to_string(&response).unwrap(),
+            r#"{"valid":true}"#
+        );
+
+        let mut tampered = proof.clone();
+        tampered.claimed_sum += crate::field::Field256::from(1);
+        let (status, Json(response)) = verify_proof(request(INSTANCE, &tampered.to_json())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(!response.valid);
+        assert_eq!(response.error.unwrap(), "the proof is for another instance");
+
+        let (status, Json(response)) = verify_proof(request(INSTANCE, "{}")).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.error.unwrap().starts_with("invalid proof"));
+        let (status, _) = verify_proof(request("[]", &proof.to_json())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_verify_shape() {
+        let proof = prove(&parser::from_json(INSTANCE).unwrap()).to_json();
+        let huge = INSTANCE.replace("\"num_vars\": 2", "\"num_vars\": 18446744073709551615");
+        let (status, Json(response)) = verify_proof(request(&huge, &proof)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.error.unwrap(),
+            "the instance has 18446744073709551615 variables, more than 64"
+        );
+
+        let wider = INSTANCE.replace("\"num_vars\": 2", "\"num_vars\": 3");
+        let (status, Json(response)) = verify_proof(request(&wider, &proof)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(
+            response.error.unwrap(),
+            "the proof has 2 rounds for 3 variables"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_verify_over_goldilocks() {
+        let instance = INSTANCE.replacen('{', r#"{"field": "goldilocks", "#, 1);
+        let proof = DynInstance::from_json(&instance).unwrap().prove();
+        let (status, Json(response)) = verify_proof(request(&instance, &proof.to_json())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(response.valid);
+
+        // A proof over Field256 is not read as a proof over the field of the instance.
+        let other = prove(&parser::from_json(INSTANCE).unwrap());
+        let (status, Json(response)) = verify_proof(request(&instance, &other.to_json())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(response.error.unwrap().starts_with("invalid proof"));
+    }
+}
@@ -0,0 +1,46 @@
+//! Implementation of the sum-check protocol, for polynomials that are products of multilinears.
+//!
+//! Without the default `std` feature, the crate is `no_std` and only needs `alloc`: the prover and
+//! the verifier then draw their challenges from a caller-provided generator, such as the
+//! Fiat–Shamir [`Transcript`](protocol::Transcript), and the pieces relying on the operating system
+//! (thread-local randomness, timings, security estimates, file parsing) are left out.
+//!
+//! Without the default `prover` feature, only the verifiers are compiled: the evaluation tables,
+//! the provers of the protocols and the drivers running both sides are left out.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// Enters a `tracing` span until the end of the enclosing scope, when the `trace` feature is
+/// enabled. Otherwise, the fields are not even evaluated.
+macro_rules! trace_span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "trace")]
+        let _span = tracing::info_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+pub mod commitment;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub mod distributed;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod field;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub mod gkr;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod polynomial;
+pub mod protocol;
+pub mod protocols;
+#[cfg(feature = "std")]
+pub mod security;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "prover")]
+pub mod testing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
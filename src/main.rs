@@ -1,47 +1,116 @@
-use crate::field::Field256 as F;
-use crate::polynomial::ProductMLPolynomial;
-use crate::protocol::*;
-use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
-use ark_poly::DenseMVPolynomial;
-
-mod field;
-mod polynomial;
-mod protocol;
-
-fn main() {
-    // The protocol works any time 'poly' is a list of multilinear polynomials. The polynomial used
-    // is the product of these polynomials.
-    let poly: ProductMLPolynomial = vec![
-        SparsePolynomial::from_coefficients_vec(
-            3,
-            Vec::from([
-                (F::from(1), SparseTerm::new(vec![(0, 1), (2, 1)])),
-                (F::from(1), SparseTerm::new(vec![(1, 1)])),
-                (F::from(1), SparseTerm::new(vec![(2, 1)])),
-            ]),
-        ),
-        SparsePolynomial::from_coefficients_vec(
-            3,
-            Vec::from([
-                (F::from(1), SparseTerm::new(vec![(0, 1)])),
-                (F::from(1), SparseTerm::new(vec![(1, 1)])),
-                (F::from(1), SparseTerm::new(vec![(2, 1)])),
-            ]),
-        ),
-        SparsePolynomial::from_coefficients_vec(
-            3,
-            Vec::from([
-                (F::from(1), SparseTerm::new(vec![(0, 1)])),
-                (F::from(1), SparseTerm::new(vec![(1, 1)])),
-                (F::from(1), SparseTerm::new(vec![(2, 1)])),
-            ]),
-        ),
-    ];
-    let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
-    let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
-    if transcript.accept {
-        println!("The verifier accepts the claim.");
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use sum_check::protocol::*;
+
+#[derive(Parser)]
+#[command(about = "Prove and verify sum-check claims for products of multilinear polynomials")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Prove the sum over the hypercube of the polynomial described in INPUT, over the field that
+    /// INPUT declares. The proof is written in JSON if OUTPUT has the `.json` extension.
+    Prove {
+        input: PathBuf,
+        #[arg(short, long, default_value = "proof.bin")]
+        output: PathBuf,
+        /// Report the progress of the prover on stderr.
+        #[arg(long)]
+        progress: bool,
+    },
+    /// Verify PROOF for the polynomial described in INPUT.
+    Verify { input: PathBuf, proof: PathBuf },
+}
+
+fn main() -> ExitCode {
+    // With the `trace` feature, the spans of the protocol are logged with their durations.
+    #[cfg(feature = "trace")]
+    tracing_subscriber::fmt()
+        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+    let result = match Cli::parse().command {
+        Command::Prove {
+            input,
+            output,
+            progress,
+        } => prove_command(&input, &output, progress),
+        Command::Verify { input, proof } => verify_command(&input, &proof),
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn prove_command(input: &Path, output: &Path, progress: bool) -> Result<bool, String> {
+    let instance = read_instance(input)?;
+    if instance.num_vars().is_none() {
+        return Err(format!(
+            "{}: the factors must have the same variables",
+            input.display()
+        ));
+    }
+    let proof = match &instance {
+        // The progress is reported by the prover of `Field256`, the other fields being meant
+        // for smaller instances.
+        DynInstance::Field256(poly) if progress => {
+            let proof = prove_with_progress(poly, |step| eprint!("\r\x1b[K{}", step));
+            eprintln!();
+            DynProof::Field256(proof)
+        }
+        instance => instance.prove(),
+    };
+    let bytes = if is_json(output) {
+        proof.to_json().into_bytes()
+    } else {
+        proof.to_bytes()
+    };
+    fs::write(output, bytes).map_err(|e| format!("{}: {}", output.display(), e))?;
+    println!("Claimed sum: {}", proof.claimed_sum());
+    println!("Proof written to {}", output.display());
+    Ok(true)
+}
+
+fn verify_command(input: &Path, proof: &Path) -> Result<bool, String> {
+    let instance = read_instance(input)?;
+    let bytes = fs::read(proof).map_err(|e| format!("{}: {}", proof.display(), e))?;
+    let proof = if is_json(proof) {
+        let json = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+        DynProof::from_json(instance.field(), &json).map_err(|e| e.to_string())?
     } else {
-        println!("The verifier rejects the claim.");
+        DynProof::from_bytes(instance.field(), &bytes).map_err(|e| e.to_string())?
+    };
+    match instance.verify(&proof) {
+        Ok(()) => {
+            println!(
+                "The verifier accepts the claim that the sum is {}.",
+                proof.claimed_sum()
+            );
+            Ok(true)
+        }
+        Err(err) => {
+            println!("The verifier rejects the claim: {}", err);
+            Ok(false)
+        }
     }
 }
+
+/// Proofs are written in JSON to `.json` files, and serialized in binary otherwise.
+fn is_json(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("json")
+}
+
+fn read_instance(path: &Path) -> Result<DynInstance, String> {
+    DynInstance::from_file(path).map_err(|e| format!("{}: {}", path.display(), e))
+}
@@ -3,9 +3,24 @@ use ark_poly::{
     multivariate::{SparsePolynomial, SparseTerm},
     DenseMVPolynomial, Polynomial,
 };
-use std::ops::Mul;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::collections::{BTreeMap, BTreeSet};
+use ark_std::{vec, vec::Vec, Zero};
+use core::convert::Infallible;
+use core::ops::Mul;
 
 use crate::field::Field256 as F;
+#[cfg(feature = "prover")]
+use crate::protocol::{CancellationToken, SumCheckError};
+
+pub mod expression;
+pub mod hypercube;
+pub mod mle;
+#[cfg(feature = "parser")]
+pub mod parser;
+
+pub use expression::parse_product;
+pub use mle::{pad_to_num_vars, pad_to_power_of_two, MLE};
 
 /// Type for a multilinear polynomial.
 pub type MLPolynomial = SparsePolynomial<F, SparseTerm>;
@@ -13,17 +28,105 @@ pub type MLPolynomial = SparsePolynomial<F, SparseTerm>;
 /// Type for a product of multilinear polynomials.
 pub type ProductMLPolynomial = Vec<MLPolynomial>;
 
+/// Type for a sum of products of multilinear polynomials.
+pub type SumOfProducts = Vec<ProductMLPolynomial>;
+
 /// 'Enough' evaluation points of a univariate polynomial for perfect Lagrange interpolation.
 pub type PolynomialDescription = Vec<F>;
 
 /// Type for the evaluation table of a polynomial.
 pub type EvalTable = Vec<F>;
 
+/// The evaluation table of a polynomial which vanishes on most of the hypercube, by its nonzero
+/// entries only, in the order of [`EvalTable`].
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SparseEvalTable {
+    num_vars: usize,
+    entries: BTreeMap<usize, F>,
+}
+
+impl SparseEvalTable {
+    pub fn from_table(table: &[F]) -> Self {
+        assert!(
+            table.len().is_power_of_two(),
+            "the table must have one entry per point of a hypercube"
+        );
+        SparseEvalTable {
+            num_vars: table.len().trailing_zeros() as usize,
+            entries: table
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| !entry.is_zero())
+                .map(|(pt, &entry)| (pt, entry))
+                .collect(),
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Number of nonzero entries.
+    pub fn num_nonzero(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, pt: usize) -> F {
+        self.entries.get(&pt).copied().unwrap_or(F::ZERO)
+    }
+
+    /// The nonzero entries, by increasing point.
+    pub fn entries(&self) -> impl Iterator<Item = (usize, F)> + '_ {
+        self.entries.iter().map(|(&pt, &entry)| (pt, entry))
+    }
+
+    pub fn to_table(&self) -> EvalTable {
+        let mut table = vec![F::ZERO; 1 << self.num_vars];
+        self.entries().for_each(|(pt, entry)| table[pt] = entry);
+        table
+    }
+
+    /// The points pt of the lower half of the table such that the entry at pt or at pt + half is
+    /// nonzero, in increasing order. The multilinear extension vanishes on the lines through the
+    /// other pairs of points along the first variable.
+    pub fn lines(&self) -> Vec<usize> {
+        let half = 1 << (self.num_vars - 1);
+        let lines: BTreeSet<usize> = self.entries.keys().map(|pt| pt & (half - 1)).collect();
+        lines.into_iter().collect()
+    }
+
+    /// Whether the multilinear extension depends on each of the variables.
+    pub fn support(&self) -> Vec<bool> {
+        (0..self.num_vars)
+            .map(|var| {
+                let stride = 1 << (self.num_vars - var - 1);
+                self.entries()
+                    .any(|(pt, entry)| self.get(pt ^ stride) != entry)
+            })
+            .collect()
+    }
+
+    /// Binds the first variable to 'r', in O(k log k) operations for k nonzero entries.
+    pub fn fold(&mut self, r: F) {
+        let half = 1 << (self.num_vars - 1);
+        self.entries = self
+            .lines()
+            .into_iter()
+            .map(|pt| {
+                let (a0, a1) = (self.get(pt), self.get(pt + half));
+                (pt, a0 + (a1 - a0) * r)
+            })
+            .filter(|(_, entry)| !entry.is_zero())
+            .collect();
+        self.num_vars -= 1;
+    }
+}
+
 /// Evaluates a ProductMLPolynomial at 'point'
 pub fn evaluate_mvml_polynomial(mvml_polynomial: ProductMLPolynomial, point: &Vec<F>) -> F {
     mvml_polynomial
         .iter()
-        .map(|ml_polynomial| ml_polynomial.evaluate(&point))
+        .map(|ml_polynomial| ml_polynomial.evaluate(point))
         .fold(F::ONE, F::mul)
 }
 
@@ -34,26 +137,427 @@ pub fn get_num_vars(multilinears: &ProductMLPolynomial) -> Option<usize> {
         [head, tail @ ..] => tail
             .iter()
             .all(|x| x.num_vars == head.num_vars)
-            .then(|| head.num_vars),
+            .then_some(head.num_vars),
+        [] => None,
+    }
+}
+
+/// Evaluates a SumOfProducts at 'point'.
+pub fn evaluate_sum_of_products(poly: &SumOfProducts, point: &Vec<F>) -> F {
+    poly.iter()
+        .map(|product| product.iter().map(|p| p.evaluate(point)).fold(F::ONE, F::mul))
+        .sum()
+}
+
+/// Same as `get_num_vars`, for all the factors of a SumOfProducts.
+pub fn get_sum_num_vars(poly: &SumOfProducts) -> Option<usize> {
+    let num_vars: Option<Vec<usize>> = poly.iter().map(get_num_vars).collect();
+    match num_vars?.as_slice() {
+        [head, tail @ ..] => tail.iter().all(|n| n == head).then_some(*head),
         [] => None,
     }
 }
 
+/// Degree of 'p' in the variable 'var', i.e. the largest power of 'var' in its terms.
+pub fn variable_degree<G: Field>(p: &SparsePolynomial<G, SparseTerm>, var: usize) -> usize {
+    p.terms()
+        .iter()
+        .flat_map(|(_, term)| term.iter())
+        .filter(|&&(v, _)| v == var)
+        .map(|&(_, power)| power)
+        .max()
+        .unwrap_or(0)
+}
+
+/// A product of polynomials with the degree of each factor in each variable, computed once at
+/// setup. The factors need not be multilinear: the round polynomial of the round of a variable
+/// has degree the sum of the degrees of the factors in it, so that its description takes exactly
+/// that many points plus one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProductInstance {
+    poly: ProductMLPolynomial,
+    /// The degree of each factor in each variable.
+    degrees: Vec<Vec<usize>>,
+}
+
+impl ProductInstance {
+    /// Computes the degrees of the factors of 'poly', which must all have the same number of
+    /// variables.
+    pub fn new(poly: ProductMLPolynomial) -> Self {
+        let num_vars = get_num_vars(&poly).expect("the factors must have the same variables");
+        let degrees = poly
+            .iter()
+            .map(|p| (0..num_vars).map(|var| variable_degree(p, var)).collect())
+            .collect();
+        ProductInstance { poly, degrees }
+    }
+
+    pub fn poly(&self) -> &ProductMLPolynomial {
+        &self.poly
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.poly[0].num_vars
+    }
+
+    /// Degree of the factor of index 'factor' in the variable 'var'.
+    pub fn degree(&self, factor: usize, var: usize) -> usize {
+        self.degrees[factor][var]
+    }
+
+    /// Degree of the product in the variable 'var', which bounds the round polynomial of its
+    /// round.
+    pub fn round_degree(&self, var: usize) -> usize {
+        self.degrees.iter().map(|degrees| degrees[var]).sum()
+    }
+
+    /// The degree of the product in each variable, in order.
+    pub fn round_degrees(&self) -> Vec<usize> {
+        (0..self.num_vars()).map(|var| self.round_degree(var)).collect()
+    }
+
+    /// Whether each factor has degree at most one in each variable.
+    pub fn is_multilinear(&self) -> bool {
+        self.degrees.iter().flatten().all(|&degree| degree <= 1)
+    }
+
+    /// Evaluates the product at 'point'. The powers of each coordinate are computed once, up to
+    /// the largest degree of a factor in its variable, and the value of a term is shared by all
+    /// the factors which have it.
+    pub fn evaluate(&self, point: &[F]) -> F {
+        assert_eq!(point.len(), self.num_vars(), "the point must have one coordinate per variable");
+        let powers: Vec<Vec<F>> = point
+            .iter()
+            .enumerate()
+            .map(|(var, &x)| {
+                let degree = self.degrees.iter().map(|degrees| degrees[var]).max().unwrap_or(0);
+                core::iter::successors(Some(F::ONE), |power| Some(*power * x))
+                    .take(degree + 1)
+                    .collect()
+            })
+            .collect();
+        let mut terms: BTreeMap<&SparseTerm, F> = BTreeMap::new();
+        let mut product = F::ONE;
+        for p in &self.poly {
+            let mut value = F::ZERO;
+            for (coefficient, term) in p.terms() {
+                let term_value = *terms
+                    .entry(term)
+                    .or_insert_with(|| term.iter().map(|&(var, power)| powers[var][power]).product());
+                value += *coefficient * term_value;
+            }
+            product *= value;
+        }
+        product
+    }
+
+    /// Same as `evaluate`, at each of 'points'.
+    pub fn evaluate_many(&self, points: &[Vec<F>]) -> Vec<F> {
+        points.iter().map(|point| self.evaluate(point)).collect()
+    }
+}
+
 /// Obtain the evaluation table on the binary hypercube for a multilinear polynomial.
 pub fn evaluate_polynomial_on_hypercube(p: &MLPolynomial) -> EvalTable {
+    mle_table_from_sparse(p)
+}
+
+/// The evaluation table of 'p' on the hypercube, straight from its list of coefficients: each
+/// coefficient is put at the index of its term, and the table expanded variable by variable, in
+/// O(n 2^n) operations rather than an evaluation of every term at each point.
+pub fn mle_table_from_sparse(p: &MLPolynomial) -> EvalTable {
+    evaluate_on_hypercube(p)
+}
+
+/// Number of points of the hypercube evaluated between two reports of
+/// `evaluate_polynomial_on_hypercube_with_progress`.
+const PROGRESS_STEP: usize = 1 << 12;
+
+/// Same as `evaluate_polynomial_on_hypercube`, calling 'report' with the number of points
+/// evaluated so far every few thousand points, and once all of them are.
+pub fn evaluate_polynomial_on_hypercube_with_progress(
+    p: &MLPolynomial,
+    mut report: impl FnMut(usize),
+) -> EvalTable {
+    let Ok(table) = evaluate_in_blocks(p, |points| {
+        if let Some(points) = points {
+            report(points);
+        }
+        Ok::<(), Infallible>(())
+    });
+    table
+}
+
+/// Same as `evaluate_polynomial_on_hypercube`, failing with [`SumCheckError::Cancelled`] once
+/// 'token' is cancelled. The token is checked every few thousand additions, so that a cancelled
+/// evaluation stops shortly after, however many variables 'p' has.
+#[cfg(feature = "prover")]
+pub fn evaluate_polynomial_on_hypercube_cancellable(
+    p: &MLPolynomial,
+    token: &CancellationToken,
+) -> Result<EvalTable, SumCheckError> {
+    evaluate_in_blocks(p, |_| token.check())
+}
+
+/// The evaluation of 'p' on the hypercube, a few thousand entries at a time. 'step' is called
+/// with None after each block of the expansion along the first variables, and with the number of
+/// points evaluated so far after each block of points, whose entries are then final. The
+/// evaluation stops at the first failure of 'step'.
+pub(crate) fn evaluate_in_blocks<E>(
+    p: &MLPolynomial,
+    mut step: impl FnMut(Option<usize>) -> Result<(), E>,
+) -> Result<EvalTable, E> {
+    let num_vars = p.num_vars();
+    let mut table = coefficient_table(p);
+    // The expansion along the first variables mixes entries far apart, and that along the last
+    // ones only entries of the same block of points, which is final once done.
+    let low = num_vars.min(PROGRESS_STEP.trailing_zeros() as usize);
+    for bit in low..num_vars {
+        let stride = 1 << bit;
+        for block in table.chunks_mut(2 * stride) {
+            let (lower, upper) = block.split_at_mut(stride);
+            for (upper, lower) in upper.chunks_mut(PROGRESS_STEP).zip(lower.chunks(PROGRESS_STEP)) {
+                for (u, l) in upper.iter_mut().zip(lower.iter()) {
+                    *u += l;
+                }
+                step(None)?;
+            }
+        }
+    }
+    let mut evaluated = 0;
+    for block in table.chunks_mut(PROGRESS_STEP) {
+        expand_coefficients(block, 0..low);
+        evaluated += block.len();
+        if evaluated < 1 << num_vars {
+            step(Some(evaluated))?;
+        }
+    }
+    step(Some(table.len()))?;
+    Ok(table)
+}
+
+/// Same as `evaluate_polynomial_on_hypercube`, for a polynomial over any field.
+pub fn evaluate_on_hypercube<G: Field>(p: &SparsePolynomial<G, SparseTerm>) -> Vec<G> {
+    let mut table = coefficient_table(p);
+    expand_coefficients(&mut table, 0..p.num_vars());
+    table
+}
+
+/// The coefficients of 'p', each at the point of the hypercube which is 1 exactly on the
+/// variables of its term. On the hypercube x^k = x, so that a term of higher degree counts as the
+/// product of its variables.
+fn coefficient_table<G: Field>(p: &SparsePolynomial<G, SparseTerm>) -> Vec<G> {
     let num_vars = p.num_vars();
-    (0..(1 << num_vars) as usize)
-        .map(|n| usize_to_binary_vector(n, num_vars))
-        .map(|binary| p.evaluate(&binary))
-        .collect::<Vec<F>>()
+    let mut table = vec![G::ZERO; 1 << num_vars];
+    for (coefficient, term) in p.terms() {
+        table[term_index(term, num_vars)] += coefficient;
+    }
+    table
+}
+
+/// The point of the hypercube which is 1 exactly on the variables of 'term'.
+fn term_index(term: &SparseTerm, num_vars: usize) -> usize {
+    term.iter()
+        .fold(0, |pt, &(var, _)| pt | 1 << (num_vars - var - 1))
+}
+
+/// Adds, for each of 'bits', the entry at each point with the bit unset to the entry at the point
+/// with the bit set. Over all the bits, this turns the coefficients of a multilinear polynomial
+/// into its evaluation table, in O(n 2^n) operations: the inverse of the Möbius transform of
+/// [`MLE::to_polynomial`].
+fn expand_coefficients<G: Field>(table: &mut [G], bits: core::ops::Range<usize>) {
+    for bit in bits {
+        let stride = 1 << bit;
+        for block in table.chunks_mut(2 * stride) {
+            let (lower, upper) = block.split_at_mut(stride);
+            for (u, l) in upper.iter_mut().zip(lower.iter()) {
+                *u += l;
+            }
+        }
+    }
+}
+
+/// Number of the last variables spanned by a block of `sum_over_hypercube`, so that a block of
+/// each factor holds a few thousand entries.
+const SUM_BLOCK_VARS: usize = 12;
+
+/// The sum of 'instance' over the hypercube, e.g. to check a claimed sum without running the
+/// protocol. The hypercube is split into blocks on which the first variables are fixed, and the
+/// table of each factor is built one block at a time, from the coefficients of the terms on
+/// those variables, so that the memory does not grow with the number of variables. With the
+/// `std` feature, the blocks are summed on all the available threads. The arithmetic is that of
+/// the field, hence exact, and the sum does not depend on the split nor on the number of threads.
+pub fn sum_over_hypercube(instance: &ProductInstance) -> F {
+    let num_vars = instance.num_vars();
+    let low_vars = num_vars.min(SUM_BLOCK_VARS);
+    let num_blocks = 1usize << (num_vars - low_vars);
+    #[cfg(feature = "std")]
+    {
+        let num_threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(num_blocks);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..num_threads)
+                .map(|thread| {
+                    scope.spawn(move || {
+                        (thread..num_blocks)
+                            .step_by(num_threads)
+                            .map(|block| block_sum(instance, block, low_vars))
+                            .sum::<F>()
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("a summing thread panicked"))
+                .sum()
+        })
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        (0..num_blocks)
+            .map(|block| block_sum(instance, block, low_vars))
+            .sum()
+    }
+}
+
+/// The sum of 'instance' over the points whose first variables are the bits of 'block', and
+/// whose last 'low_vars' variables run over the hypercube.
+fn block_sum(instance: &ProductInstance, block: usize, low_vars: usize) -> F {
+    let num_vars = instance.num_vars();
+    let tables: Vec<EvalTable> = instance
+        .poly()
+        .iter()
+        .map(|p| {
+            // On the hypercube x^k = x, and a term is the product of its variables: those of the
+            // first ones are constant on the block, 1 if all their bits are set and 0 otherwise.
+            let mut table = vec![F::ZERO; 1 << low_vars];
+            for (coefficient, term) in p.terms() {
+                let pt = term_index(term, num_vars);
+                let high = pt >> low_vars;
+                if high & block == high {
+                    table[pt & ((1 << low_vars) - 1)] += coefficient;
+                }
+            }
+            expand_coefficients(&mut table, 0..low_vars);
+            table
+        })
+        .collect();
+    (0..1 << low_vars)
+        .map(|pt| tables.iter().map(|table| table[pt]).product::<F>())
+        .sum()
+}
+
+/// Evaluates the multilinear extension of an evaluation table at 'point', by binding the
+/// variables one after the other.
+pub fn evaluate_table<E: Field>(table: &[E], point: &[E]) -> E {
+    assert_eq!(table.len(), 1 << point.len());
+    let mut table = table.to_vec();
+    for &r in point {
+        let half = table.len() / 2;
+        for pt in 0..half {
+            table[pt] = table[pt] + r * (table[pt + half] - table[pt]);
+        }
+        table.truncate(half);
+    }
+    table[0]
+}
+
+/// Fixes the variable 'var' of the multilinear extension of 'table' to 'value'. The other
+/// variables keep their order.
+pub fn fix_variable(table: &[F], var: usize, value: F) -> EvalTable {
+    let num_vars = table.len().trailing_zeros() as usize;
+    let stride = 1 << (num_vars - var - 1);
+    (0..table.len() / 2)
+        .map(|pt| {
+            let pt0 = (pt / stride) * 2 * stride + pt % stride;
+            let pt1 = pt0 + stride;
+            table[pt0] + value * (table[pt1] - table[pt0])
+        })
+        .collect()
+}
+
+/// Evaluation table of the multilinear polynomial eq(x, 'point'), which is 1 when x = 'point' on
+/// the hypercube and 0 elsewhere. It is built variable by variable, in O(2^n) operations.
+pub fn eq_extension(point: &[F]) -> EvalTable {
+    let mut table = vec![F::ONE];
+    for &r in point {
+        table = table.iter().flat_map(|&t| [t - t * r, t * r]).collect();
+    }
+    table
+}
+
+/// Evaluation table of the selector of the points of the hypercube whose entry of 'mask' is set,
+/// in the order of the evaluation tables. The length of 'mask' must be a power of two.
+pub fn selector_from_bitmask(mask: &[bool]) -> EvalTable {
+    assert!(
+        mask.len().is_power_of_two(),
+        "the mask must cover a hypercube"
+    );
+    mask.iter().map(|&set| F::from(set)).collect()
+}
+
+/// Evaluation table, on 'num_vars' variables, of the selector of the points of the hypercube at
+/// 'indices'.
+pub fn selector_from_indices(num_vars: usize, indices: &[usize]) -> EvalTable {
+    let mut table = vec![F::ZERO; 1 << num_vars];
+    for &index in indices {
+        table[index] = F::ONE;
+    }
+    table
+}
+
+/// Evaluates eq(x, y) = Π (x_i y_i + (1 - x_i)(1 - y_i)) at any two points.
+pub fn eq_eval(x: &[F], y: &[F]) -> F {
+    assert_eq!(x.len(), y.len());
+    x.iter()
+        .zip(y)
+        .map(|(&x_i, &y_i)| x_i * y_i + (F::ONE - x_i) * (F::ONE - y_i))
+        .product()
+}
+
+/// Monomial coefficients, from the constant one up, of the univariate polynomial of degree d given
+/// by its evaluations at 0, 1, ..., d. The Newton form on these points is computed by finite
+/// differences, then expanded.
+pub fn evaluations_to_coefficients<E: Field>(evaluations: &[E]) -> Vec<E> {
+    let degree = evaluations.len().saturating_sub(1);
+    let mut newton = evaluations.to_vec();
+    for k in 1..=degree {
+        for i in (k..=degree).rev() {
+            newton[i] = (newton[i] - newton[i - 1]) / E::from(k as u64);
+        }
+    }
+    // Horner on the Newton basis x (x - 1) ... (x - k + 1).
+    let mut coefficients = Vec::with_capacity(newton.len());
+    for k in (0..newton.len()).rev() {
+        coefficients.insert(0, E::ZERO);
+        for i in 0..coefficients.len() - 1 {
+            let next = coefficients[i + 1];
+            coefficients[i] -= E::from(k as u64) * next;
+        }
+        coefficients[0] += newton[k];
+    }
+    coefficients
+}
+
+/// Evaluates at 'x' the univariate polynomial given by its monomial coefficients, by Horner's rule.
+pub fn evaluate_coefficients<E: Field>(coefficients: &[E], x: E) -> E {
+    coefficients
+        .iter()
+        .rev()
+        .fold(E::ZERO, |acc, &c| acc * x + c)
 }
 
-fn usize_to_binary_vector(n: usize, num_vars: usize) -> Vec<F> {
-    let mut result = Vec::with_capacity(64);
-    for i in (0..64).rev() {
-        result.push(if (n & (1 << i)) != 0 { F::ONE } else { F::ZERO });
+/// Builds a point on all variables from the values of the fixed variables, and the point on the
+/// remaining ones.
+pub fn merge_point(fixed: &[(usize, F)], point: &[F]) -> Vec<F> {
+    let mut fixed = fixed.to_vec();
+    fixed.sort_by_key(|&(var, _)| var);
+    let mut merged = point.to_vec();
+    for (var, value) in fixed {
+        merged.insert(var, value);
     }
-    result.split_off(64 - num_vars)
+    merged
 }
 
 #[cfg(test)]
@@ -66,6 +570,34 @@ mod tests {
     use ark_std::UniformRand;
     use rand::thread_rng;
 
+    fn usize_to_binary_vector(n: usize, num_vars: usize) -> Vec<F> {
+        hypercube::index_to_point(n, num_vars, hypercube::BitOrder::MsbFirst)
+    }
+
+    #[test]
+    fn test_product_instance() {
+        let instance = ProductInstance::new(vec![
+            SparsePolynomial::from_coefficients_vec(
+                2,
+                vec![
+                    (F::from(2), SparseTerm::new(vec![(0, 2), (1, 1)])),
+                    (F::from(1), SparseTerm::new(vec![(0, 1)])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(2, vec![(F::from(3), SparseTerm::new(vec![(0, 1)]))]),
+        ]);
+        assert_eq!((instance.degree(0, 0), instance.degree(0, 1), instance.degree(1, 1)), (2, 1, 0));
+        assert_eq!(instance.round_degrees(), vec![3, 1]);
+        assert!(!instance.is_multilinear());
+
+        let mut rng = thread_rng();
+        let points: Vec<Vec<F>> = (0..3).map(|_| vec![F::rand(&mut rng), F::rand(&mut rng)]).collect();
+        let values = instance.evaluate_many(&points);
+        for (point, value) in points.iter().zip(values) {
+            assert_eq!(value, evaluate_mvml_polynomial(instance.poly().clone(), point));
+        }
+    }
+
     #[test]
     fn test_polynomial_equality() {
         let poly1 = SparsePolynomial::from_coefficients_vec(
@@ -151,4 +683,206 @@ mod tests {
         assert_eq!(some_point, usize_to_binary_vector(point, 3));
         assert_eq!(*value_from_map, value_from_poly)
     }
+
+    #[test]
+    fn test_evaluation_progress() {
+        let poly = SparsePolynomial::from_coefficients_vec(
+            13,
+            vec![
+                (F::from(3), SparseTerm::new(vec![(0, 1), (12, 1)])),
+                (F::from(1), SparseTerm::new(vec![])),
+            ],
+        );
+        let mut reports = Vec::new();
+        let table =
+            evaluate_polynomial_on_hypercube_with_progress(&poly, |points| reports.push(points));
+        assert_eq!(table, evaluate_polynomial_on_hypercube(&poly));
+        assert_eq!(reports, vec![4096, 8192]);
+        assert_eq!(evaluate_on_hypercube(&poly), table);
+        assert_eq!(mle_table_from_sparse(&poly), table);
+    }
+
+    #[cfg(feature = "prover")]
+    #[test]
+    fn test_cancelled_evaluation() {
+        let poly = SparsePolynomial::from_coefficients_vec(
+            14,
+            vec![(F::from(3), SparseTerm::new(vec![(0, 1), (13, 1)]))],
+        );
+        let token = CancellationToken::new();
+        assert_eq!(
+            evaluate_polynomial_on_hypercube_cancellable(&poly, &token).unwrap(),
+            evaluate_polynomial_on_hypercube(&poly)
+        );
+        token.cancel();
+        assert_eq!(
+            evaluate_polynomial_on_hypercube_cancellable(&poly, &token),
+            Err(SumCheckError::Cancelled)
+        );
+        // The evaluation stops within the expansion along the first variables, which takes 4
+        // blocks of 2^12 additions on 14 variables, before any point is final.
+        let mut steps = Vec::new();
+        let result = evaluate_in_blocks(&poly, |points| {
+            steps.push(points);
+            if steps.len() == 2 { Err(()) } else { Ok(()) }
+        });
+        assert_eq!(result, Err(()));
+        assert_eq!(steps, vec![None, None]);
+    }
+
+    #[test]
+    fn test_hypercube_table_matches_evaluation() {
+        let poly = SparsePolynomial::from_coefficients_vec(
+            4,
+            vec![
+                (F::from(2), SparseTerm::new(vec![(0, 3), (3, 1)])),
+                (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                (F::from(4), SparseTerm::new(vec![(0, 1), (2, 2)])),
+                (F::from(1), SparseTerm::new(vec![(1, 1), (2, 1), (3, 1)])),
+                (F::from(5), SparseTerm::new(vec![])),
+            ],
+        );
+        let table = evaluate_polynomial_on_hypercube(&poly);
+        for (pt, &entry) in table.iter().enumerate() {
+            assert_eq!(entry, poly.evaluate(&usize_to_binary_vector(pt, 4)));
+        }
+    }
+
+    #[test]
+    fn test_sum_over_hypercube() {
+        let instance = ProductInstance::new(vec![
+            SparsePolynomial::from_coefficients_vec(
+                14,
+                vec![
+                    (F::from(3), SparseTerm::new(vec![(0, 1), (13, 1)])),
+                    (F::from(2), SparseTerm::new(vec![(1, 2), (5, 1)])),
+                    (F::from(1), SparseTerm::new(vec![])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                14,
+                vec![
+                    (F::from(5), SparseTerm::new(vec![(1, 1)])),
+                    (F::from(7), SparseTerm::new(vec![(12, 1)])),
+                ],
+            ),
+        ]);
+        let tables: Vec<EvalTable> = instance
+            .poly()
+            .iter()
+            .map(evaluate_polynomial_on_hypercube)
+            .collect();
+        let expected: F = (0..1 << 14).map(|pt| tables[0][pt] * tables[1][pt]).sum();
+        assert_eq!(sum_over_hypercube(&instance), expected);
+
+        let instance = ProductInstance::new(parse_product("(x0 + 2) * x1").unwrap());
+        assert_eq!(sum_over_hypercube(&instance), F::from(2 + 3));
+    }
+
+    #[test]
+    fn test_fix_variable() {
+        let poly = SparsePolynomial::from_coefficients_vec(
+            3,
+            vec![
+                (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                (F::from(1), SparseTerm::new(vec![(1, 1), (2, 1)])),
+                (F::from(5), SparseTerm::new(vec![])),
+            ],
+        );
+        let table = evaluate_polynomial_on_hypercube(&poly);
+        let mut rng = thread_rng();
+        let value = F::rand(&mut rng);
+        let point = vec![F::rand(&mut rng), F::rand(&mut rng)];
+        for var in 0..3 {
+            let fixed = fix_variable(&table, var, value);
+            assert_eq!(fixed.len(), 4);
+            assert_eq!(
+                evaluate_table(&fixed, &point),
+                poly.evaluate(&merge_point(&[(var, value)], &point))
+            );
+        }
+    }
+
+    #[test]
+    fn test_evaluate_table() {
+        let poly = SparsePolynomial::from_coefficients_vec(
+            3,
+            vec![
+                (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                (F::from(1), SparseTerm::new(vec![(1, 1), (2, 1)])),
+                (F::from(5), SparseTerm::new(vec![])),
+            ],
+        );
+        let table = evaluate_polynomial_on_hypercube(&poly);
+        let mut rng = thread_rng();
+        let point = vec![F::rand(&mut rng), F::rand(&mut rng), F::rand(&mut rng)];
+        assert_eq!(evaluate_table(&table, &point), poly.evaluate(&point));
+    }
+
+    #[test]
+    fn test_sum_of_products() {
+        let poly = vec![parse_product("x0 * (x1 + 2)").unwrap(), parse_product("3*x0*x1").unwrap()];
+        assert_eq!(get_sum_num_vars(&poly), Some(2));
+        let point = vec![F::from(2), F::from(5)];
+        assert_eq!(evaluate_sum_of_products(&poly, &point), F::from(14 + 30));
+        let poly = vec![poly[0].clone(), parse_product("x2").unwrap()];
+        assert_eq!(get_sum_num_vars(&poly), None);
+    }
+
+    #[test]
+    fn test_sparse_eval_table() {
+        let mut table = vec![F::ZERO; 16];
+        table[1] = F::from(3);
+        table[9] = F::from(5);
+        table[12] = F::from(7);
+        let mut sparse = SparseEvalTable::from_table(&table);
+        assert_eq!(sparse.num_nonzero(), 3);
+        assert_eq!(sparse.to_table(), table);
+        assert_eq!(sparse.lines(), vec![1, 4]);
+        assert_eq!(sparse.support(), vec![true, true, true, true]);
+        for r in [F::from(2), F::from(9), F::from(4)] {
+            let num_vars = sparse.num_vars();
+            sparse.fold(r);
+            table = fix_variable(&table, 0, r);
+            assert_eq!(sparse.num_vars(), num_vars - 1);
+            assert_eq!(sparse.to_table(), table);
+        }
+        // Entries which fold to zero are dropped.
+        let mut sparse = SparseEvalTable::from_table(&[F::ONE, F::from(2), F::ONE, F::ONE]);
+        sparse.fold(F::from(2));
+        assert_eq!(sparse.entries().collect::<Vec<_>>(), vec![(0, F::ONE)]);
+    }
+
+    #[test]
+    fn test_eq_extension() {
+        let point = vec![F::from(3), F::from(5), F::from(9)];
+        let table = eq_extension(&point);
+        for (pt, &entry) in table.iter().enumerate() {
+            assert_eq!(entry, eq_eval(&usize_to_binary_vector(pt, 3), &point));
+        }
+        // The multilinear extension of the table is eq(., point) itself.
+        let other = vec![F::from(2), F::from(7), F::from(4)];
+        assert_eq!(evaluate_table(&table, &other), eq_eval(&other, &point));
+        assert_eq!(eq_extension(&[]), vec![F::ONE]);
+    }
+
+    #[test]
+    fn test_evaluations_to_coefficients() {
+        // 4 - 3x + 2x^3
+        let coefficients = vec![F::from(4), -F::from(3), F::from(0), F::from(2)];
+        let evaluations: Vec<F> = (0..4u64)
+            .map(|x| evaluate_coefficients(&coefficients, F::from(x)))
+            .collect();
+        assert_eq!(
+            evaluations,
+            vec![F::from(4), F::from(3), F::from(14), F::from(49)]
+        );
+        assert_eq!(evaluations_to_coefficients(&evaluations), coefficients);
+        assert_eq!(
+            evaluations_to_coefficients(&[F::from(5), F::from(5)]),
+            vec![F::from(5), F::from(0)]
+        );
+    }
 }
@@ -0,0 +1,316 @@
+//! Recursive-descent parser for products of multilinear polynomials written as expressions, such
+//! as `"(x0*x2 + x1 + x2) * (x0 + x1 + x2)"`.
+//!
+//! The grammar is the usual one for arithmetic expressions over variables `x0, x1, ...` and
+//! non-negative integer constants:
+//!
+//! ```text
+//! sum     := product (('+' | '-') product)*
+//! product := unary ('*' unary)*
+//! unary   := '-' unary | atom
+//! atom    := number | 'x' index | '(' sum ')'
+//! ```
+//!
+//! When the whole expression is a product, each of its operands becomes a factor of the
+//! [`ProductMLPolynomial`]. Otherwise the expression is a single factor. Every factor is expanded,
+//! and must be multilinear.
+
+use core::str::FromStr;
+
+use ark_ff::Field;
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+use ark_poly::DenseMVPolynomial;
+use ark_std::{string::String, vec, vec::Vec};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{MLPolynomial, ProductMLPolynomial};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ExpressionError {
+    #[error("unexpected '{found}' at position {position}")]
+    Unexpected { position: usize, found: char },
+    #[error("unexpected end of expression")]
+    UnexpectedEnd,
+    #[error("variable x{var} is out of range ({num_vars} variables)")]
+    VariableOutOfRange { var: usize, num_vars: usize },
+    #[error("factor {factor} is not multilinear in x{var}")]
+    NotMultilinear { factor: usize, var: usize },
+}
+
+/// An expanded polynomial, as a list of terms.
+type Terms = Vec<(F, Vec<(usize, usize)>)>;
+
+/// Parses 'input' into a product of multilinear polynomials, whose number of variables is one
+/// more than the largest variable index appearing in the expression.
+pub fn parse_product(input: &str) -> Result<ProductMLPolynomial, ExpressionError> {
+    parse(input, None)
+}
+
+/// Same as [`parse_product`], for polynomials on 'num_vars' variables.
+pub fn parse_product_with_num_vars(
+    input: &str,
+    num_vars: usize,
+) -> Result<ProductMLPolynomial, ExpressionError> {
+    parse(input, Some(num_vars))
+}
+
+fn parse(input: &str, num_vars: Option<usize>) -> Result<ProductMLPolynomial, ExpressionError> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        position: 0,
+        max_var: None,
+    };
+    let factors = parser.parse_top_level()?;
+    let num_vars = match (num_vars, parser.max_var) {
+        (Some(num_vars), Some(var)) if var >= num_vars => {
+            return Err(ExpressionError::VariableOutOfRange { var, num_vars })
+        }
+        (Some(num_vars), _) => num_vars,
+        (None, max_var) => max_var.map_or(0, |var| var + 1),
+    };
+    factors
+        .into_iter()
+        .enumerate()
+        .map(|(factor, terms)| to_multilinear(factor, num_vars, terms))
+        .collect()
+}
+
+fn to_multilinear(
+    factor: usize,
+    num_vars: usize,
+    terms: Terms,
+) -> Result<MLPolynomial, ExpressionError> {
+    let terms: Vec<(F, SparseTerm)> = terms
+        .into_iter()
+        .map(|(coeff, vars)| (coeff, SparseTerm::new(vars)))
+        .collect();
+    let poly = SparsePolynomial::from_coefficients_vec(num_vars, terms);
+    for (_, term) in &poly.terms {
+        if let Some(&(var, _)) = term.iter().find(|&&(_, degree)| degree > 1) {
+            return Err(ExpressionError::NotMultilinear { factor, var });
+        }
+    }
+    Ok(poly)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    max_var: Option<usize>,
+}
+
+impl Parser {
+    fn parse_top_level(&mut self) -> Result<Vec<Terms>, ExpressionError> {
+        let mut factors = vec![self.parse_unary()?];
+        while self.eat('*') {
+            factors.push(self.parse_unary()?);
+        }
+        if matches!(self.peek(), Some('+') | Some('-')) {
+            // The expression is a sum: it forms a single factor.
+            let product = factors.into_iter().reduce(|a, b| multiply(&a, &b)).unwrap();
+            let sum = self.parse_sum_continuation(product)?;
+            factors = vec![sum];
+        }
+        self.expect_end()?;
+        Ok(factors)
+    }
+
+    fn parse_sum(&mut self) -> Result<Terms, ExpressionError> {
+        let first = self.parse_product()?;
+        self.parse_sum_continuation(first)
+    }
+
+    fn parse_sum_continuation(&mut self, mut sum: Terms) -> Result<Terms, ExpressionError> {
+        loop {
+            if self.eat('+') {
+                sum.extend(self.parse_product()?);
+            } else if self.eat('-') {
+                sum.extend(negate(self.parse_product()?));
+            } else {
+                return Ok(sum);
+            }
+        }
+    }
+
+    fn parse_product(&mut self) -> Result<Terms, ExpressionError> {
+        let mut product = self.parse_unary()?;
+        while self.eat('*') {
+            product = multiply(&product, &self.parse_unary()?);
+        }
+        Ok(product)
+    }
+
+    fn parse_unary(&mut self) -> Result<Terms, ExpressionError> {
+        if self.eat('-') {
+            return Ok(negate(self.parse_unary()?));
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Result<Terms, ExpressionError> {
+        match self.peek() {
+            Some('(') => {
+                self.position += 1;
+                let sum = self.parse_sum()?;
+                if !self.eat(')') {
+                    return Err(self.unexpected());
+                }
+                Ok(sum)
+            }
+            Some('x') => {
+                self.position += 1;
+                let var = self.parse_integer()?;
+                let var = usize::from_str(&var).map_err(|_| self.unexpected())?;
+                self.max_var = self.max_var.max(Some(var));
+                Ok(vec![(F::ONE, vec![(var, 1)])])
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let number = self.parse_integer()?;
+                let value = F::from_str(&number).map_err(|_| self.unexpected())?;
+                Ok(vec![(value, vec![])])
+            }
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn parse_integer(&mut self) -> Result<String, ExpressionError> {
+        let start = self.position;
+        while self.position < self.chars.len() && self.chars[self.position].is_ascii_digit() {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.unexpected());
+        }
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    /// Next non-whitespace character, without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+            self.position += 1;
+        }
+        self.chars.get(self.position).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_end(&mut self) -> Result<(), ExpressionError> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.unexpected()),
+        }
+    }
+
+    fn unexpected(&self) -> ExpressionError {
+        match self.chars.get(self.position) {
+            Some(&found) => ExpressionError::Unexpected {
+                position: self.position,
+                found,
+            },
+            None => ExpressionError::UnexpectedEnd,
+        }
+    }
+}
+
+fn negate(terms: Terms) -> Terms {
+    terms
+        .into_iter()
+        .map(|(coeff, vars)| (-coeff, vars))
+        .collect()
+}
+
+fn multiply(a: &Terms, b: &Terms) -> Terms {
+    let mut product = Vec::with_capacity(a.len() * b.len());
+    for (coeff_a, vars_a) in a {
+        for (coeff_b, vars_b) in b {
+            let vars = vars_a.iter().chain(vars_b.iter()).copied().collect();
+            product.push((*coeff_a * coeff_b, vars));
+        }
+    }
+    product
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_product() {
+        let poly = parse_product("(x0*x2 + x1 + x2) * (x0 + x1 + x2)").unwrap();
+        let expected = vec![
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                    (F::from(1), SparseTerm::new(vec![(2, 1)])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(0, 1)])),
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                    (F::from(1), SparseTerm::new(vec![(2, 1)])),
+                ],
+            ),
+        ];
+        assert_eq!(poly, expected);
+    }
+
+    #[test]
+    fn test_single_factor() {
+        let poly = parse_product("2*x0 + 7 * x0 * x2 - (x1 - 1) * x2 + 5").unwrap();
+        let expected = SparsePolynomial::from_coefficients_vec(
+            3,
+            vec![
+                (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                (-F::from(1), SparseTerm::new(vec![(1, 1), (2, 1)])),
+                (F::from(1), SparseTerm::new(vec![(2, 1)])),
+                (F::from(5), SparseTerm::new(vec![])),
+            ],
+        );
+        assert_eq!(poly, vec![expected]);
+    }
+
+    #[test]
+    fn test_num_vars() {
+        let poly = parse_product_with_num_vars("x0 * (x1 + 3)", 4).unwrap();
+        assert_eq!(poly.len(), 2);
+        assert!(poly.iter().all(|p| p.num_vars == 4));
+        assert_eq!(
+            parse_product_with_num_vars("x0 * x4", 4),
+            Err(ExpressionError::VariableOutOfRange {
+                var: 4,
+                num_vars: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            parse_product("(x0 + x1"),
+            Err(ExpressionError::UnexpectedEnd)
+        );
+        assert_eq!(
+            parse_product("x0 + y1"),
+            Err(ExpressionError::Unexpected {
+                position: 5,
+                found: 'y'
+            })
+        );
+        assert_eq!(
+            parse_product("(x0 + x1) * (x1 * x1 + 2)"),
+            Err(ExpressionError::NotMultilinear { factor: 1, var: 1 })
+        );
+    }
+}
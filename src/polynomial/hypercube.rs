@@ -0,0 +1,171 @@
+//! Indexing of the points of the boolean hypercube {0, 1}^n. The evaluation tables of the crate
+//! ([`EvalTable`](crate::polynomial::EvalTable)) put the first variable on the most significant
+//! bit of the index: the entry at index 1 is at (0, ..., 0, 1). Some other systems put it on the
+//! least significant bit instead, and [`BitOrder`] converts between the two, so that protocols
+//! built on top of the tables (eq tables, wirings of circuits) agree on which point an index is.
+
+use ark_ff::Field;
+use ark_std::vec::Vec;
+
+/// Which bit of an index holds the first variable of a point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// The first variable on the most significant bit, the order of the evaluation tables.
+    #[default]
+    MsbFirst,
+    /// The first variable on the least significant bit.
+    LsbFirst,
+}
+
+impl BitOrder {
+    /// Position, from the least significant one, of the bit of the variable 'var' in an index on
+    /// 'num_vars' variables.
+    pub fn bit(self, var: usize, num_vars: usize) -> usize {
+        assert!(var < num_vars, "variable {var} out of {num_vars}");
+        match self {
+            BitOrder::MsbFirst => num_vars - var - 1,
+            BitOrder::LsbFirst => var,
+        }
+    }
+}
+
+/// Whether the variable 'var' is 1 at the point of index 'index' on 'num_vars' variables.
+pub fn bit_of(index: usize, var: usize, num_vars: usize, order: BitOrder) -> bool {
+    let bit = order.bit(var, num_vars);
+    bit < usize::BITS as usize && index >> bit & 1 == 1
+}
+
+/// The point of index 'index' on 'num_vars' variables, as booleans. The index must be a point of
+/// the hypercube.
+pub fn index_to_bits(index: usize, num_vars: usize, order: BitOrder) -> Vec<bool> {
+    assert!(
+        num_vars >= usize::BITS as usize || index >> num_vars == 0,
+        "index {index} out of a hypercube on {num_vars} variables"
+    );
+    (0..num_vars)
+        .map(|var| bit_of(index, var, num_vars, order))
+        .collect()
+}
+
+/// The index of the point 'bits'. There must be fewer variables than bits in an index.
+pub fn bits_to_index(bits: &[bool], order: BitOrder) -> usize {
+    assert!(
+        bits.len() <= usize::BITS as usize,
+        "{} variables do not fit in an index",
+        bits.len()
+    );
+    bits.iter()
+        .enumerate()
+        .filter(|(_, &set)| set)
+        .fold(0, |index, (var, _)| index | 1 << order.bit(var, bits.len()))
+}
+
+/// The point of index 'index' on 'num_vars' variables, with coordinates in any field.
+pub fn index_to_point<G: Field>(index: usize, num_vars: usize, order: BitOrder) -> Vec<G> {
+    index_to_bits(index, num_vars, order)
+        .into_iter()
+        .map(G::from)
+        .collect()
+}
+
+/// The index of 'point', or None if one of its coordinates is neither 0 nor 1.
+pub fn point_to_index<G: Field>(point: &[G], order: BitOrder) -> Option<usize> {
+    let bits = point_to_bits(point)?;
+    Some(bits_to_index(&bits, order))
+}
+
+/// The coordinates of 'point' as booleans, or None if one of them is neither 0 nor 1.
+pub fn point_to_bits<G: Field>(point: &[G]) -> Option<Vec<bool>> {
+    point
+        .iter()
+        .map(|x| {
+            if x.is_zero() {
+                Some(false)
+            } else if x.is_one() {
+                Some(true)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The indices of the points of the hypercube on 'num_vars' variables, each with its point as
+/// booleans, in increasing order of index.
+pub fn bits(num_vars: usize, order: BitOrder) -> impl Iterator<Item = (usize, Vec<bool>)> {
+    (0..1usize << num_vars).map(move |index| (index, index_to_bits(index, num_vars, order)))
+}
+
+/// Same as [`bits`], with the points in any field.
+pub fn points<G: Field>(num_vars: usize, order: BitOrder) -> impl Iterator<Item = Vec<G>> {
+    (0..1usize << num_vars).map(move |index| index_to_point(index, num_vars, order))
+}
+
+/// The index on 'num_vars' variables of the same point as 'index' in the other bit order.
+pub fn reverse_index(index: usize, num_vars: usize) -> usize {
+    if num_vars == 0 {
+        return 0;
+    }
+    index.reverse_bits() >> (usize::BITS as usize - num_vars)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Field256 as F;
+    use ark_std::vec;
+
+    #[test]
+    fn test_index_to_point() {
+        let point: Vec<F> = index_to_point(4, 5, BitOrder::MsbFirst);
+        assert_eq!(point, vec![F::ZERO, F::ZERO, F::ONE, F::ZERO, F::ZERO]);
+        let point: Vec<F> = index_to_point(53, 6, BitOrder::MsbFirst);
+        assert_eq!(
+            point,
+            vec![F::ONE, F::ONE, F::ZERO, F::ONE, F::ZERO, F::ONE]
+        );
+        assert_eq!(
+            index_to_bits(53, 6, BitOrder::LsbFirst),
+            vec![true, false, true, false, true, true]
+        );
+        // The variables beyond the bits of an index are 0.
+        let bits = index_to_bits(1023, 70, BitOrder::MsbFirst);
+        assert_eq!(bits.len(), 70);
+        assert_eq!(bits.iter().filter(|&&set| set).count(), 10);
+        assert!(bits[60..].iter().all(|&set| set));
+        assert_eq!(index_to_bits(0, 0, BitOrder::MsbFirst), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_round_trips() {
+        for order in [BitOrder::MsbFirst, BitOrder::LsbFirst] {
+            for (index, bits) in bits(4, order) {
+                assert_eq!(bits_to_index(&bits, order), index);
+                let point: Vec<F> = index_to_point(index, 4, order);
+                assert_eq!(point_to_index(&point, order), Some(index));
+                assert_eq!(point_to_bits(&point), Some(bits.clone()));
+                assert_eq!(bit_of(index, 1, 4, order), bits[1]);
+            }
+            assert_eq!(points::<F>(3, order).count(), 8);
+        }
+        assert_eq!(point_to_index(&[F::ONE, F::from(2)], BitOrder::MsbFirst), None);
+    }
+
+    #[test]
+    fn test_reverse_index() {
+        for index in 0..16 {
+            let bits = index_to_bits(index, 4, BitOrder::MsbFirst);
+            assert_eq!(
+                bits_to_index(&bits, BitOrder::LsbFirst),
+                reverse_index(index, 4)
+            );
+        }
+        assert_eq!(reverse_index(0, 0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 8 out of a hypercube on 3 variables")]
+    fn test_index_out_of_range() {
+        index_to_bits(8, 3, BitOrder::MsbFirst);
+    }
+}
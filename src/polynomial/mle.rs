@@ -0,0 +1,179 @@
+//! Multilinear extensions of data tables, for statements about datasets such as "the sum of this
+//! column is X". A column of n values is padded with zeros to the next power of two 2^k, which does
+//! not change its sum, and becomes the evaluation table of a multilinear polynomial on k variables,
+//! in the order of [`EvalTable`]: the first value at (0, ..., 0), the second at (0, ..., 0, 1), and
+//! so on.
+
+use ark_poly::{
+    multivariate::{SparseTerm, Term},
+    DenseMVPolynomial,
+};
+use ark_std::{vec, vec::Vec, Zero};
+
+use crate::field::Field256 as F;
+use crate::polynomial::{evaluate_table, EvalTable, MLPolynomial};
+
+/// The multilinear extension of a table of values on the hypercube.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MLE {
+    num_vars: usize,
+    table: EvalTable,
+}
+
+impl MLE {
+    /// The multilinear extension of 'table', whose length must be a power of two.
+    pub fn from_table(table: EvalTable) -> Self {
+        assert!(
+            table.len().is_power_of_two(),
+            "the table must have one entry per point of a hypercube"
+        );
+        MLE {
+            num_vars: table.len().trailing_zeros() as usize,
+            table,
+        }
+    }
+
+    /// The multilinear extension of 'values', padded with zeros to the next power of two.
+    pub fn from_values(values: Vec<F>) -> Self {
+        Self::from_table(pad_to_power_of_two(values))
+    }
+
+    /// Same as [`MLE::from_values`], for a column of integers.
+    pub fn from_u64_slice(values: &[u64]) -> Self {
+        Self::from_values(values.iter().map(|&v| F::from(v)).collect())
+    }
+
+    /// Same as [`MLE::from_values`], with each byte of 'bytes' as a value, e.g. for the contents
+    /// of a file. Wider values are read with [`MLE::from_u64_slice`].
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self::from_values(bytes.iter().map(|&b| F::from(b)).collect())
+    }
+
+    /// Same as [`MLE::from_values`], padding to 'num_vars' variables, e.g. to give several
+    /// columns of different lengths the same variables.
+    pub fn from_values_with_num_vars(values: Vec<F>, num_vars: usize) -> Self {
+        Self::from_table(pad_to_num_vars(values, num_vars))
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    pub fn table(&self) -> &EvalTable {
+        &self.table
+    }
+
+    pub fn into_table(self) -> EvalTable {
+        self.table
+    }
+
+    /// The sum of the table over the hypercube, which is the sum of the values before padding.
+    pub fn sum(&self) -> F {
+        self.table.iter().sum()
+    }
+
+    pub fn evaluate(&self, point: &[F]) -> F {
+        evaluate_table(&self.table, point)
+    }
+
+    /// The multilinear extension in the monomial basis, e.g. as a factor for
+    /// [`prove`](crate::protocol::prove). The coefficient of the product of the variables of a set
+    /// S is the alternating sum of the entries at the subsets of S, computed by a Möbius transform
+    /// in O(k 2^k) operations.
+    pub fn to_polynomial(&self) -> MLPolynomial {
+        let mut coefficients = self.table.clone();
+        for bit in 0..self.num_vars {
+            for pt in 0..coefficients.len() {
+                if pt >> bit & 1 == 1 {
+                    let lower = coefficients[pt ^ (1 << bit)];
+                    coefficients[pt] -= lower;
+                }
+            }
+        }
+        let terms = coefficients
+            .into_iter()
+            .enumerate()
+            .filter(|(_, c)| !c.is_zero())
+            .map(|(pt, c)| {
+                let vars = (0..self.num_vars)
+                    .filter(|var| pt >> (self.num_vars - var - 1) & 1 == 1)
+                    .map(|var| (var, 1))
+                    .collect();
+                (c, SparseTerm::new(vars))
+            })
+            .collect();
+        MLPolynomial::from_coefficients_vec(self.num_vars, terms)
+    }
+}
+
+impl From<MLE> for EvalTable {
+    fn from(mle: MLE) -> Self {
+        mle.table
+    }
+}
+
+impl Default for MLE {
+    fn default() -> Self {
+        MLE::from_table(vec![F::zero()])
+    }
+}
+
+/// Pads 'values' with zeros to the next power of two, and an empty column to a single zero.
+pub fn pad_to_power_of_two<G: Zero + Clone>(mut values: Vec<G>) -> Vec<G> {
+    values.resize(values.len().next_power_of_two(), G::zero());
+    values
+}
+
+/// Pads 'values' with zeros to 2^'num_vars' entries. There must not be more values than that.
+pub fn pad_to_num_vars<G: Zero + Clone>(mut values: Vec<G>, num_vars: usize) -> Vec<G> {
+    assert!(
+        values.len() <= 1 << num_vars,
+        "{} values do not fit on {} variables",
+        values.len(),
+        num_vars
+    );
+    values.resize(1 << num_vars, G::zero());
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial::evaluate_polynomial_on_hypercube;
+    use ark_poly::Polynomial;
+
+    #[test]
+    fn test_mle_from_data() {
+        let mle = MLE::from_u64_slice(&[3, 1, 4, 1, 5]);
+        assert_eq!(mle.num_vars(), 3);
+        assert_eq!(
+            mle.table()[4..],
+            [F::from(5), F::zero(), F::zero(), F::zero()]
+        );
+        assert_eq!(mle.sum(), F::from(14));
+        assert_eq!(
+            MLE::from_bytes(b"abcd"),
+            MLE::from_u64_slice(&[97, 98, 99, 100])
+        );
+        assert_eq!(MLE::from_u64_slice(&[]), MLE::default());
+        assert_eq!(
+            MLE::from_values_with_num_vars(vec![F::from(2)], 2).into_table(),
+            vec![F::from(2), F::zero(), F::zero(), F::zero()]
+        );
+    }
+
+    #[test]
+    fn test_mle_to_polynomial() {
+        let mle = MLE::from_u64_slice(&[3, 1, 4, 1, 5, 9, 2, 6]);
+        let poly = mle.to_polynomial();
+        assert_eq!(&evaluate_polynomial_on_hypercube(&poly), mle.table());
+        let point = vec![F::from(7), F::from(11), F::from(13)];
+        assert_eq!(poly.evaluate(&point), mle.evaluate(&point));
+    }
+
+    #[test]
+    #[should_panic(expected = "5 values do not fit on 2 variables")]
+    fn test_pad_to_num_vars() {
+        pad_to_num_vars(vec![F::zero(); 5], 2);
+    }
+}
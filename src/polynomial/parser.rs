@@ -0,0 +1,327 @@
+//! Reads a [`ProductMLPolynomial`] from a JSON or TOML description: a number of variables and a
+//! list of factors, each factor being a list of terms with a coefficient and a list of
+//! `(variable, degree)` pairs. For instance, in JSON:
+//!
+//! ```json
+//! {
+//!   "num_vars": 3,
+//!   "factors": [
+//!     [{"coeff": 1, "vars": [[0, 1], [2, 1]]}, {"coeff": "7", "vars": [[1, 1]]}],
+//!     [{"coeff": -2, "vars": [[0, 1]]}, {"coeff": 5, "vars": []}]
+//!   ]
+//! }
+//! ```
+//!
+//! Coefficients are given either as integers or as decimal strings, for elements too large to be
+//! represented as integers. An instance over another field than `Field256` declares it by its
+//! [`DynField`] identifier, as in `"field": "goldilocks"`, and is read with a [`Description`].
+
+use std::path::Path;
+
+use ark_ff::PrimeField;
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+use ark_poly::DenseMVPolynomial;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::field::{DynField, UnknownField};
+use crate::polynomial::expression::{parse_product, ExpressionError};
+use crate::polynomial::ProductMLPolynomial;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("cannot read the instance: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid syntax: {0}")]
+    Syntax(String),
+    #[error("unsupported file extension (expected .json, .toml or .poly)")]
+    UnknownFormat,
+    #[error("invalid expression: {0}")]
+    Expression(#[from] ExpressionError),
+    #[error("the instance has no factors")]
+    NoFactors,
+    #[error(transparent)]
+    UnknownField(#[from] UnknownField),
+    #[error("the instance is over the field {0}")]
+    UnexpectedField(DynField),
+    #[error("factor {factor}, term {term}: invalid coefficient '{coeff}'")]
+    InvalidCoefficient {
+        factor: usize,
+        term: usize,
+        coeff: String,
+    },
+    #[error("factor {factor}, term {term}: variable {var} is out of range ({num_vars} variables)")]
+    VariableOutOfRange {
+        factor: usize,
+        term: usize,
+        var: usize,
+        num_vars: usize,
+    },
+    #[error("factor {factor}, term {term}: variable {var} appears more than once")]
+    DuplicateVariable {
+        factor: usize,
+        term: usize,
+        var: usize,
+    },
+    #[error("factor {factor}, term {term}: variable {var} has degree {degree}, the factor is not multilinear")]
+    NotMultilinear {
+        factor: usize,
+        term: usize,
+        var: usize,
+        degree: usize,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct InstanceDescription {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    field: Option<String>,
+    num_vars: usize,
+    factors: Vec<Vec<TermDescription>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TermDescription {
+    coeff: Coefficient,
+    #[serde(default)]
+    vars: Vec<(usize, usize)>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Coefficient {
+    Integer(i64),
+    Decimal(String),
+}
+
+/// An instance as described in a JSON or TOML document, before its coefficients are read in the
+/// field that it declares.
+pub struct Description(InstanceDescription);
+
+impl Description {
+    pub fn from_json(input: &str) -> Result<Self, ParseError> {
+        let description =
+            serde_json::from_str(input).map_err(|e| ParseError::Syntax(e.to_string()))?;
+        Ok(Description(description))
+    }
+
+    pub fn from_toml(input: &str) -> Result<Self, ParseError> {
+        let description = toml::from_str(input).map_err(|e| ParseError::Syntax(e.to_string()))?;
+        Ok(Description(description))
+    }
+
+    /// The field of the instance, `Field256` unless the description declares another one.
+    pub fn field(&self) -> Result<DynField, ParseError> {
+        match &self.0.field {
+            Some(id) => Ok(id.parse()?),
+            None => Ok(DynField::Field256),
+        }
+    }
+
+    /// The factors, with the coefficients read in 'G', which the caller chooses from
+    /// [`Description::field`].
+    pub fn build<G: PrimeField>(self) -> Result<Vec<SparsePolynomial<G, SparseTerm>>, ParseError> {
+        build(self.0)
+    }
+
+    /// Same as `build`, for an instance over `Field256`.
+    fn build_field256(self) -> Result<ProductMLPolynomial, ParseError> {
+        match self.field()? {
+            DynField::Field256 => self.build(),
+            field => Err(ParseError::UnexpectedField(field)),
+        }
+    }
+}
+
+pub fn from_json(input: &str) -> Result<ProductMLPolynomial, ParseError> {
+    Description::from_json(input)?.build_field256()
+}
+
+/// The JSON description of 'poly', with the coefficients as decimal strings, which `from_json`
+/// reads back, or `Description::build` for another field than `Field256`.
+pub fn to_json<G: PrimeField>(poly: &[SparsePolynomial<G, SparseTerm>]) -> String {
+    let description = InstanceDescription {
+        field: None,
+        num_vars: poly.first().map_or(0, |p| p.num_vars),
+        factors: poly
+            .iter()
+            .map(|p| {
+                p.terms
+                    .iter()
+                    .map(|(coeff, term)| TermDescription {
+                        coeff: Coefficient::Decimal(coeff.to_string()),
+                        vars: term.to_vec(),
+                    })
+                    .collect()
+            })
+            .collect(),
+    };
+    serde_json::to_string(&description).unwrap()
+}
+
+pub fn from_toml(input: &str) -> Result<ProductMLPolynomial, ParseError> {
+    Description::from_toml(input)?.build_field256()
+}
+
+/// Reads an instance from a `.json` or `.toml` file, or from a `.poly` file holding an
+/// expression in the syntax of [`parse_product`](crate::polynomial::parse_product).
+pub fn from_file(path: &Path) -> Result<ProductMLPolynomial, ParseError> {
+    let contents = std::fs::read_to_string(path)?;
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => from_json(&contents),
+        Some("toml") => from_toml(&contents),
+        Some("poly") => Ok(parse_product(&contents)?),
+        _ => Err(ParseError::UnknownFormat),
+    }
+}
+
+fn build<G: PrimeField>(
+    description: InstanceDescription,
+) -> Result<Vec<SparsePolynomial<G, SparseTerm>>, ParseError> {
+    if description.factors.is_empty() {
+        return Err(ParseError::NoFactors);
+    }
+    let num_vars = description.num_vars;
+    let mut poly = Vec::with_capacity(description.factors.len());
+    for (factor, terms) in description.factors.into_iter().enumerate() {
+        let mut coefficients = Vec::with_capacity(terms.len());
+        for (term, description) in terms.into_iter().enumerate() {
+            let coeff = match description.coeff {
+                Coefficient::Integer(value) if value < 0 => -G::from(value.unsigned_abs()),
+                Coefficient::Integer(value) => G::from(value as u64),
+                Coefficient::Decimal(value) => {
+                    G::from_str(&value).map_err(|_| ParseError::InvalidCoefficient {
+                        factor,
+                        term,
+                        coeff: value,
+                    })?
+                }
+            };
+            for (i, &(var, degree)) in description.vars.iter().enumerate() {
+                if var >= num_vars {
+                    return Err(ParseError::VariableOutOfRange {
+                        factor,
+                        term,
+                        var,
+                        num_vars,
+                    });
+                }
+                if description.vars[..i].iter().any(|&(v, _)| v == var) {
+                    return Err(ParseError::DuplicateVariable { factor, term, var });
+                }
+                if degree > 1 {
+                    return Err(ParseError::NotMultilinear {
+                        factor,
+                        term,
+                        var,
+                        degree,
+                    });
+                }
+            }
+            coefficients.push((coeff, SparseTerm::new(description.vars)));
+        }
+        poly.push(SparsePolynomial::from_coefficients_vec(
+            num_vars,
+            coefficients,
+        ));
+    }
+    Ok(poly)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::Field256 as F;
+
+    #[test]
+    fn test_json() {
+        let poly = from_json(
+            r#"{"num_vars": 3, "factors": [
+                [{"coeff": 1, "vars": [[0, 1], [2, 1]]}, {"coeff": "7", "vars": [[1, 1]]}],
+                [{"coeff": -2, "vars": [[0, 1]]}, {"coeff": 5}]
+            ]}"#,
+        )
+        .unwrap();
+        let expected = vec![
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                    (F::from(7), SparseTerm::new(vec![(1, 1)])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (-F::from(2), SparseTerm::new(vec![(0, 1)])),
+                    (F::from(5), SparseTerm::new(vec![])),
+                ],
+            ),
+        ];
+        assert_eq!(poly, expected);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let poly = parse_product("(x0*x2 - 7*x1) * (x1 + 5)").unwrap();
+        assert_eq!(from_json(&to_json(&poly)).unwrap(), poly);
+    }
+
+    #[test]
+    fn test_toml() {
+        let poly = from_toml(
+            r#"
+            num_vars = 2
+            factors = [
+                [{ coeff = 3, vars = [[0, 1]] }, { coeff = "4", vars = [[0, 1], [1, 1]] }],
+            ]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(poly.len(), 1);
+        assert_eq!(poly[0].terms.len(), 2);
+    }
+
+    #[test]
+    fn test_validation_errors() {
+        let err = from_json(r#"{"num_vars": 2, "factors": [[{"coeff": 1, "vars": [[2, 1]]}]]}"#);
+        assert!(matches!(
+            err,
+            Err(ParseError::VariableOutOfRange {
+                factor: 0,
+                term: 0,
+                var: 2,
+                num_vars: 2
+            })
+        ));
+
+        let err = from_json(
+            r#"{"num_vars": 2, "factors": [[{"coeff": 1}], [{"coeff": 1}, {"coeff": 1, "vars": [[1, 3]]}]]}"#,
+        );
+        assert!(matches!(
+            err,
+            Err(ParseError::NotMultilinear {
+                factor: 1,
+                term: 1,
+                var: 1,
+                degree: 3
+            })
+        ));
+
+        let err =
+            from_json(r#"{"num_vars": 2, "factors": [[{"coeff": 1, "vars": [[1, 1], [1, 1]]}]]}"#);
+        assert!(matches!(
+            err,
+            Err(ParseError::DuplicateVariable { var: 1, .. })
+        ));
+
+        let err = from_json(r#"{"num_vars": 2, "factors": [[{"coeff": "x"}]]}"#);
+        assert!(matches!(err, Err(ParseError::InvalidCoefficient { .. })));
+
+        let err = from_json(r#"{"num_vars": 2, "factors": []}"#);
+        assert!(matches!(err, Err(ParseError::NoFactors)));
+
+        let err = from_json(r#"{"num_vars": 2"#);
+        assert!(matches!(err, Err(ParseError::Syntax(_))));
+    }
+}
@@ -0,0 +1,401 @@
+//! Evaluation tables behind a trait, so that the prover can run on tables which do not fit in
+//! memory: tables in memory, in memory-mapped files (with the `mmap` feature), or evaluated from
+//! the polynomial on demand.
+
+use ark_ff::Field;
+use ark_poly::{DenseMVPolynomial, Polynomial};
+use ark_std::{vec, vec::Vec, UniformRand};
+
+use crate::field::{batch, Field256 as F};
+use crate::polynomial::{EvalTable, MLPolynomial, ProductMLPolynomial};
+use crate::protocol::fiat_shamir::Transcript;
+use crate::protocol::proof::{absorb_statement, Proof, PROTOCOL_LABEL, PROTOCOL_VERSION};
+use crate::protocol::prover::{fold_in_place, multiply_by_line};
+use crate::protocol::statement::instance_hash;
+
+/// The evaluation table of a multilinear polynomial on the hypercube, whose variables are bound
+/// to the challenges one after the other, from the first one.
+pub trait EvalBackend {
+    /// Number of variables left.
+    fn num_vars(&self) -> usize;
+    /// The entry at 'i', in the order of [`EvalTable`].
+    fn get(&self, i: usize) -> F;
+    /// Binds the first variable left to 'r'.
+    fn fold(&mut self, r: F);
+}
+
+impl EvalBackend for EvalTable {
+    fn num_vars(&self) -> usize {
+        self.len().trailing_zeros() as usize
+    }
+
+    fn get(&self, i: usize) -> F {
+        self[i]
+    }
+
+    fn fold(&mut self, r: F) {
+        let num_vars = EvalBackend::num_vars(self);
+        fold_in_place(num_vars - 1, r, self);
+    }
+}
+
+/// A table which is never stored: its entries are evaluated from the polynomial when read, at the
+/// cost of an evaluation of the polynomial per read.
+#[derive(Debug, Clone)]
+pub struct LazyTable {
+    poly: MLPolynomial,
+    /// The values bound to the first variables.
+    fixed: Vec<F>,
+}
+
+impl LazyTable {
+    pub fn new(poly: MLPolynomial) -> Self {
+        LazyTable {
+            poly,
+            fixed: Vec::new(),
+        }
+    }
+}
+
+impl EvalBackend for LazyTable {
+    fn num_vars(&self) -> usize {
+        self.poly.num_vars() - self.fixed.len()
+    }
+
+    fn get(&self, i: usize) -> F {
+        let num_vars = EvalBackend::num_vars(self);
+        let mut point = self.fixed.clone();
+        point.extend(
+            (0..num_vars)
+                .rev()
+                .map(|bit| F::from((i >> bit & 1) as u64)),
+        );
+        self.poly.evaluate(&point)
+    }
+
+    fn fold(&mut self, r: F) {
+        self.fixed.push(r);
+    }
+}
+
+#[cfg(feature = "mmap")]
+pub use mmap::{prove_out_of_core, MmapTable, ProverConfig};
+
+#[cfg(feature = "mmap")]
+mod mmap {
+    use std::fs::{self, OpenOptions};
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use ark_ff::BigInt;
+    use ark_poly::DenseMVPolynomial;
+    use memmap2::MmapMut;
+
+    use super::{prove_with_backend, EvalBackend};
+    use crate::field::Field256 as F;
+    use crate::polynomial::{MLPolynomial, ProductMLPolynomial};
+    use crate::protocol::proof::Proof;
+
+    /// Size in bytes of an entry, stored as the little-endian limbs of its Montgomery form.
+    const ENTRY_SIZE: usize = 32;
+
+    /// Distinguishes the scratch files of the tables of a process.
+    static SCRATCH_FILES: AtomicUsize = AtomicUsize::new(0);
+
+    /// Where and how the prover keeps the tables which do not fit in memory.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct ProverConfig {
+        /// Directory of the files backing the tables, removed once the proof is done.
+        pub scratch_dir: PathBuf,
+        /// The tables are written and folded by pages of 2^'page_vars' entries, the pages done
+        /// with being written back to the disk so that they can leave memory.
+        pub page_vars: usize,
+    }
+
+    impl Default for ProverConfig {
+        fn default() -> Self {
+            ProverConfig {
+                scratch_dir: std::env::temp_dir(),
+                page_vars: 16,
+            }
+        }
+    }
+
+    /// A table in a memory-mapped file, which the operating system pages in and out as needed. The
+    /// table is written and folded page after page, and the file keeps its size.
+    pub struct MmapTable {
+        map: MmapMut,
+        num_vars: usize,
+        page_vars: usize,
+        /// The scratch file to remove with the table, if the table created it.
+        scratch: Option<PathBuf>,
+    }
+
+    impl MmapTable {
+        /// Writes the 2^'num_vars' 'entries' to the file at 'path', which is created or
+        /// truncated.
+        pub fn create(
+            path: &Path,
+            num_vars: usize,
+            entries: impl IntoIterator<Item = F>,
+        ) -> io::Result<Self> {
+            let mut table = Self::allocate(path, num_vars, ProverConfig::default().page_vars)?;
+            let mut count = 0;
+            for (i, entry) in entries.into_iter().take(1 << num_vars).enumerate() {
+                table.set(i, entry);
+                count += 1;
+                if (i + 1) % table.page_len() == 0 {
+                    table.release_page(i / table.page_len());
+                }
+            }
+            if count != 1 << num_vars {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "the table has fewer entries than the hypercube",
+                ));
+            }
+            Ok(table)
+        }
+
+        /// Evaluates 'poly' on the hypercube into a file of the scratch directory of 'config',
+        /// removed when the table is dropped. The evaluation runs in place, one pass per variable,
+        /// so that the table never has to fit in memory.
+        pub fn from_polynomial(poly: &MLPolynomial, config: &ProverConfig) -> io::Result<Self> {
+            let num_vars = poly.num_vars();
+            let path = config.scratch_dir.join(format!(
+                "sum-check-{}-{}.table",
+                std::process::id(),
+                SCRATCH_FILES.fetch_add(1, Ordering::Relaxed)
+            ));
+            let mut table = Self::allocate(&path, num_vars, config.page_vars)?;
+            table.scratch = Some(path);
+            // The coefficient of a monomial goes to the point with exactly its variables, and the
+            // passes add every point to the points above it.
+            for (coeff, term) in poly.terms() {
+                let point = term
+                    .iter()
+                    .fold(0, |point, &(var, _)| point | 1 << (num_vars - 1 - var));
+                table.set(point, table.get(point) + coeff);
+            }
+            for bit in (0..num_vars).map(|var| 1 << var) {
+                for page in 0..table.num_pages() {
+                    for i in table.page_range(page) {
+                        if i & bit != 0 {
+                            table.set(i, table.get(i) + table.get(i ^ bit));
+                        }
+                    }
+                    table.release_page(page);
+                }
+            }
+            Ok(table)
+        }
+
+        fn allocate(path: &Path, num_vars: usize, page_vars: usize) -> io::Result<Self> {
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)?;
+            file.set_len((ENTRY_SIZE << num_vars) as u64)?;
+            // SAFETY: the file was just created for the table, and is not shared.
+            let map = unsafe { MmapMut::map_mut(&file)? };
+            Ok(MmapTable {
+                map,
+                num_vars,
+                page_vars,
+                scratch: None,
+            })
+        }
+
+        fn set(&mut self, i: usize, entry: F) {
+            let bytes = &mut self.map[ENTRY_SIZE * i..ENTRY_SIZE * (i + 1)];
+            for (chunk, limb) in bytes.chunks_exact_mut(8).zip(entry.0 .0) {
+                chunk.copy_from_slice(&limb.to_le_bytes());
+            }
+        }
+
+        fn page_len(&self) -> usize {
+            1 << self.page_vars.min(self.num_vars)
+        }
+
+        fn num_pages(&self) -> usize {
+            (1 << self.num_vars) / self.page_len()
+        }
+
+        fn page_range(&self, page: usize) -> core::ops::Range<usize> {
+            page * self.page_len()..(page + 1) * self.page_len()
+        }
+
+        /// Starts writing the page back to the file, so that the operating system can drop it
+        /// from memory once it is clean. A failure only delays the write to the next flush.
+        fn release_page(&self, page: usize) {
+            let range = self.page_range(page);
+            let _ = self
+                .map
+                .flush_async_range(ENTRY_SIZE * range.start, ENTRY_SIZE * range.len());
+        }
+    }
+
+    impl EvalBackend for MmapTable {
+        fn num_vars(&self) -> usize {
+            self.num_vars
+        }
+
+        fn get(&self, i: usize) -> F {
+            let bytes = &self.map[ENTRY_SIZE * i..ENTRY_SIZE * (i + 1)];
+            let limbs = core::array::from_fn(|j| {
+                u64::from_le_bytes(bytes[8 * j..8 * j + 8].try_into().unwrap())
+            });
+            F::new_unchecked(BigInt(limbs))
+        }
+
+        /// Folds the pages of the lower half one after the other, each with the matching page of
+        /// the upper half.
+        fn fold(&mut self, r: F) {
+            self.num_vars -= 1;
+            let half = 1 << self.num_vars;
+            for page in 0..self.num_pages() {
+                for i in self.page_range(page) {
+                    let (a0, a1) = (self.get(i), self.get(i + half));
+                    self.set(i, a0 + (a1 - a0) * r);
+                }
+                self.release_page(page);
+            }
+        }
+    }
+
+    impl Drop for MmapTable {
+        fn drop(&mut self) {
+            if let Some(path) = &self.scratch {
+                let _ = fs::remove_file(path);
+            }
+        }
+    }
+
+    /// Same as [`prove`](crate::protocol::prove), with the tables of the factors in files of the
+    /// scratch directory of 'config' rather than in memory.
+    pub fn prove_out_of_core(
+        poly: &ProductMLPolynomial,
+        config: &ProverConfig,
+    ) -> io::Result<Proof> {
+        let tables = poly
+            .iter()
+            .map(|factor| MmapTable::from_polynomial(factor, config))
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(prove_with_backend(poly, tables))
+    }
+}
+
+/// Same as [`prove`](crate::protocol::prove), for the product of 'tables', the tables of the
+/// factors of 'poly'. The round polynomials are sent by d + 1 evaluations, d being the number of
+/// tables which depend on the variable of the round, as found along the pass of the round.
+pub fn prove_with_backend<B: EvalBackend>(poly: &ProductMLPolynomial, mut tables: Vec<B>) -> Proof {
+    let num_vars = tables[0].num_vars();
+    assert!(
+        tables.iter().all(|table| table.num_vars() == num_vars),
+        "the tables must have the same number of variables"
+    );
+    let product = |tables: &[B], pt: usize| tables.iter().map(|t| t.get(pt)).product::<F>();
+    let claimed_sum = (0..1 << num_vars).map(|pt| product(&tables, pt)).sum();
+    let instance_hash = instance_hash(poly, claimed_sum);
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    absorb_statement(&mut transcript, &instance_hash, claimed_sum);
+
+    let num_points = tables.len() + 1;
+    let mut points = vec![F::ZERO; num_points];
+    let mut round_messages = Vec::with_capacity(num_vars);
+    for round in 0..num_vars {
+        let half = 1 << (num_vars - round - 1);
+        let mut evaluations = vec![F::ZERO; num_points];
+        let mut active = vec![false; tables.len()];
+        for pt in 0..half {
+            points.fill(F::ONE);
+            for (table, active) in tables.iter().zip(&mut active) {
+                let (a0, a1) = (table.get(pt), table.get(pt + half));
+                *active |= a0 != a1;
+                multiply_by_line(&mut points, a0, a1);
+            }
+            batch::add_assign(&mut evaluations, &points);
+        }
+        // The tables which do not depend on the variable only scale the round polynomial.
+        let degree = active.iter().filter(|&&active| active).count();
+        evaluations.truncate(degree.max(1) + 1);
+        transcript.append_field_elements(b"round", &evaluations);
+        let challenge = F::rand(&mut transcript);
+        round_messages.push(evaluations);
+        if round + 1 < num_vars {
+            tables.iter_mut().for_each(|table| table.fold(challenge));
+        }
+    }
+    Proof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+        round_commitments: Vec::new(),
+        auxiliary_evaluations: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial::{evaluate_polynomial_on_hypercube, parse_product};
+    use crate::protocol::{prove, verify};
+
+    #[test]
+    fn test_backends() {
+        // The second factor does not depend on x2, whose round has degree 1.
+        let poly = parse_product("(x0 + 2*x1*x2 + x3) * (x0*x3 + x1 + 3)").unwrap();
+        let tables: Vec<EvalTable> = poly.iter().map(evaluate_polynomial_on_hypercube).collect();
+        let proof = prove(&poly);
+        assert_eq!(prove_with_backend(&poly, tables.clone()), proof);
+        let lazy = poly.iter().cloned().map(LazyTable::new).collect();
+        assert_eq!(prove_with_backend::<LazyTable>(&poly, lazy), proof);
+        assert!(verify(&poly, &proof).is_ok());
+
+        #[cfg(feature = "mmap")]
+        {
+            let dir = std::env::temp_dir();
+            let mapped = tables
+                .iter()
+                .enumerate()
+                .map(|(i, table)| {
+                    let path = dir.join(format!("sum-check-{}-{}.table", std::process::id(), i));
+                    let mapped = MmapTable::create(&path, 4, table.iter().copied()).unwrap();
+                    std::fs::remove_file(path).unwrap();
+                    mapped
+                })
+                .collect();
+            assert_eq!(prove_with_backend::<MmapTable>(&poly, mapped), proof);
+
+            // Pages smaller than the tables, so that the evaluation and the folds span pages.
+            let config = ProverConfig {
+                page_vars: 2,
+                ..ProverConfig::default()
+            };
+            for (factor, table) in poly.iter().zip(&tables) {
+                let mapped = MmapTable::from_polynomial(factor, &config).unwrap();
+                assert!((0..table.len()).all(|i| mapped.get(i) == table[i]));
+            }
+            assert_eq!(prove_out_of_core(&poly, &config).unwrap(), proof);
+        }
+    }
+
+    #[test]
+    fn test_lazy_table() {
+        let poly = parse_product("(3*x0*x2 + x1 + 5)").unwrap().remove(0);
+        let mut table = evaluate_polynomial_on_hypercube(&poly);
+        let mut lazy = LazyTable::new(poly);
+        for r in [F::from(7), F::from(11)] {
+            table.fold(r);
+            lazy.fold(r);
+            assert_eq!(EvalBackend::num_vars(&lazy), EvalBackend::num_vars(&table));
+            for (i, entry) in table.iter().enumerate() {
+                assert_eq!(lazy.get(i), *entry);
+            }
+        }
+    }
+}
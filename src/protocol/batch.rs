@@ -0,0 +1,509 @@
+//! Batched proofs of independent instances on the same number of variables, which share one
+//! challenge per round.
+//!
+//! The transcript absorbs the statements of all the instances, then draws α, which combines the
+//! instances into the single random linear combination Σ_i α^i · g_i: its round polynomials are
+//! the same combinations of the round polynomials of the instances, and its sum that of the
+//! claimed sums. The prover still sends the round polynomial of each instance, but the verifier
+//! only checks and interpolates their combination, and draws one challenge per round for all the
+//! instances. A false claim survives the combination with probability at most k/|F| for k
+//! instances.
+//!
+//! An [`AggregatedProof`] goes one step further: the prover only sends the combination of the
+//! round polynomials, and the challenges are derived from it, so that the proof of k instances
+//! takes one message per round, as that of a single instance. Proofs produced apart cannot be
+//! merged afterwards, since the challenges of each depend on its own messages: the instances are
+//! proven together by [`prove_aggregated`].
+
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+};
+use ark_std::{vec, vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{
+    evaluate_mvml_polynomial, get_num_vars, PolynomialDescription, ProductMLPolynomial,
+};
+use crate::protocol::fiat_shamir::Transcript;
+use crate::protocol::proof::{absorb_statement, check_statement, PROTOCOL_LABEL, PROTOCOL_VERSION};
+use crate::protocol::statement::{instance_hash, read_hashes, InstanceHash};
+#[cfg(feature = "prover")]
+use crate::protocol::SumCheckProver;
+use crate::protocol::{InterpolationContext, Proof, SumCheckError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum BatchError {
+    #[error("expected {expected} proofs, one per instance, got {got}")]
+    CountMismatch { expected: usize, got: usize },
+    /// The proof of an instance is of another version, for another statement, or of the wrong
+    /// shape.
+    #[error("instance {index}: {source}")]
+    Instance {
+        index: usize,
+        #[source]
+        source: SumCheckError,
+    },
+    /// The combination of the round polynomials fails a check of the sum-check.
+    #[error("batch: {0}")]
+    SumCheck(#[from] SumCheckError),
+}
+
+/// Proves the sums of 'instances', which must have the same number of variables, with shared
+/// challenges. The proofs only verify together, with [`verify_batch`].
+#[cfg(feature = "prover")]
+pub fn prove_batch(instances: &[ProductMLPolynomial]) -> Vec<Proof> {
+    let num_vars = common_num_vars(instances).expect("the instances must have the same variables");
+    let mut provers: Vec<SumCheckProver> = instances.iter().map(SumCheckProver::new).collect();
+    let mut proofs: Vec<Proof> = instances
+        .iter()
+        .zip(&provers)
+        .map(|(poly, prover)| Proof {
+            version: PROTOCOL_VERSION,
+            claimed_sum: prover.claimed_sum(),
+            instance_hash: instance_hash(poly, prover.claimed_sum()),
+            round_messages: Vec::with_capacity(num_vars),
+            round_commitments: Vec::new(),
+            auxiliary_evaluations: Vec::new(),
+        })
+        .collect();
+    let mut transcript = batch_transcript(&proofs);
+    // α only weighs the checks of the verifier.
+    F::rand(&mut transcript);
+    let mut challenge = None;
+    for _ in 0..num_vars {
+        for (prover, proof) in provers.iter_mut().zip(&mut proofs) {
+            let message = prover.next_message(challenge).evaluations;
+            transcript.append_field_elements(b"round", &message);
+            proof.round_messages.push(message);
+        }
+        challenge = Some(F::rand(&mut transcript));
+    }
+    proofs
+}
+
+/// Verifies proofs produced by [`prove_batch`] for 'instances', and returns the random point
+/// shared by their final checks.
+pub fn verify_batch(
+    instances: &[ProductMLPolynomial],
+    proofs: &[Proof],
+) -> Result<Vec<F>, BatchError> {
+    if proofs.len() != instances.len() {
+        return Err(BatchError::CountMismatch {
+            expected: instances.len(),
+            got: proofs.len(),
+        });
+    }
+    let num_vars = instances.first().and_then(get_num_vars).unwrap_or(0);
+    for (index, (poly, proof)) in instances.iter().zip(proofs).enumerate() {
+        let instance_error = |source| BatchError::Instance { index, source };
+        check_statement(poly, proof).map_err(instance_error)?;
+        let vars = get_num_vars(poly).unwrap_or(0);
+        if vars != num_vars || proof.round_messages.len() != num_vars {
+            return Err(instance_error(SumCheckError::VariableCountMismatch {
+                expected: num_vars,
+                got: proof.round_messages.len().min(vars),
+            }));
+        }
+        // The round polynomials have degree at most the number of factors.
+        let expected = poly.len().max(1) + 1;
+        if let Some((round, message)) = proof
+            .round_messages
+            .iter()
+            .enumerate()
+            .find(|(_, message)| message.len() < 2 || message.len() > expected)
+        {
+            return Err(instance_error(SumCheckError::DegreeMismatch {
+                round,
+                expected,
+                got: message.len(),
+            }));
+        }
+    }
+
+    let mut transcript = batch_transcript(proofs);
+    let alpha = F::rand(&mut transcript);
+    let powers = combination_powers(alpha, proofs.len());
+    let degree = instances.iter().map(|poly| poly.len().max(1)).max();
+    let interpolation = InterpolationContext::new(degree.unwrap_or(1));
+    let mut running_eval: F = proofs
+        .iter()
+        .zip(&powers)
+        .map(|(proof, &power)| power * proof.claimed_sum)
+        .sum();
+    let mut point = Vec::with_capacity(num_vars);
+    for round in 0..num_vars {
+        let messages: Vec<_> = proofs
+            .iter()
+            .map(|proof| &proof.round_messages[round])
+            .collect();
+        let len = messages
+            .iter()
+            .map(|message| message.len())
+            .max()
+            .unwrap_or(2);
+        let combined = combine(&messages, &powers, len, &interpolation);
+        let intermediate = combined[0] + combined[1];
+        if intermediate != running_eval {
+            return Err(SumCheckError::IntermediateCheckFailed {
+                round,
+                lhs: running_eval,
+                rhs: intermediate,
+            }
+            .into());
+        }
+        for message in messages {
+            transcript.append_field_elements(b"round", message);
+        }
+        let r = F::rand(&mut transcript);
+        running_eval = interpolation.evaluate(&combined, r);
+        point.push(r);
+    }
+
+    let combined: F = instances
+        .iter()
+        .zip(&powers)
+        .map(|(poly, &power)| power * evaluate_mvml_polynomial(poly.clone(), &point))
+        .sum();
+    if combined != running_eval {
+        return Err(SumCheckError::FinalCheckFailed.into());
+    }
+    Ok(point)
+}
+
+/// Σ_i α^i · p_i for the round polynomials p_i of 'messages' and the powers α^i of 'powers', by
+/// its evaluations at 0, 1, ..., 'len' - 1.
+fn combine(
+    messages: &[&PolynomialDescription],
+    powers: &[F],
+    len: usize,
+    interpolation: &InterpolationContext,
+) -> PolynomialDescription {
+    let mut combined = vec![F::from(0); len];
+    for (message, &power) in messages.iter().zip(powers) {
+        for (node, value) in combined.iter_mut().enumerate() {
+            let evaluation = match message.get(node) {
+                Some(&evaluation) => evaluation,
+                None => interpolation.evaluate(message, F::from(node as u64)),
+            };
+            *value += power * evaluation;
+        }
+    }
+    combined
+}
+
+/// The proof of several instances on the same number of variables, by the combination
+/// Σ_i α^i · p_i of their round polynomials in each round. See [`prove_aggregated`].
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize)]
+pub struct AggregatedProof {
+    pub version: u8,
+    /// The claimed sum of each instance.
+    pub claimed_sums: Vec<F>,
+    /// The [`instance_hash`] of each instance and its claimed sum.
+    pub instance_hashes: Vec<InstanceHash>,
+    /// The combination of the round polynomials of the instances, by its evaluations at 0, 1,
+    /// ..., d for the largest degree d of a round polynomial.
+    pub round_messages: Vec<PolynomialDescription>,
+}
+
+impl Valid for AggregatedProof {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.claimed_sums.check()?;
+        self.round_messages.check()
+    }
+}
+
+impl CanonicalDeserialize for AggregatedProof {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(AggregatedProof {
+            version: u8::deserialize_with_mode(&mut reader, compress, validate)?,
+            claimed_sums: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            instance_hashes: read_hashes(&mut reader)?,
+            round_messages: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
+}
+
+impl AggregatedProof {
+    pub fn num_instances(&self) -> usize {
+        self.claimed_sums.len()
+    }
+
+    /// Size of the serialization of the proof.
+    pub fn size_in_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+}
+
+/// Proves the sums of 'instances', which must have the same number of variables, in a single
+/// [`AggregatedProof`]. The transcript absorbs the statements of the instances and draws α, then
+/// each round absorbs the combination of the round polynomials and draws the challenge shared by
+/// all the instances.
+#[cfg(feature = "prover")]
+pub fn prove_aggregated(instances: &[ProductMLPolynomial]) -> AggregatedProof {
+    let num_vars = common_num_vars(instances).expect("the instances must have the same variables");
+    let mut provers: Vec<SumCheckProver> = instances.iter().map(SumCheckProver::new).collect();
+    let claimed_sums: Vec<F> = provers.iter().map(SumCheckProver::claimed_sum).collect();
+    let instance_hashes: Vec<InstanceHash> = instances
+        .iter()
+        .zip(&claimed_sums)
+        .map(|(poly, &claimed_sum)| instance_hash(poly, claimed_sum))
+        .collect();
+    let mut transcript = aggregated_transcript(&instance_hashes, &claimed_sums);
+    let powers = combination_powers(F::rand(&mut transcript), instances.len());
+    let degree = instances.iter().map(|poly| poly.len().max(1)).max().unwrap_or(1);
+    let interpolation = InterpolationContext::new(degree);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    let mut challenge = None;
+    for _ in 0..num_vars {
+        let messages: Vec<PolynomialDescription> = provers
+            .iter_mut()
+            .map(|prover| prover.next_message(challenge).evaluations)
+            .collect();
+        let len = messages.iter().map(Vec::len).max().unwrap_or(2);
+        let messages: Vec<&PolynomialDescription> = messages.iter().collect();
+        let combined = combine(&messages, &powers, len, &interpolation);
+        transcript.append_field_elements(b"round", &combined);
+        challenge = Some(F::rand(&mut transcript));
+        round_messages.push(combined);
+    }
+    AggregatedProof {
+        version: PROTOCOL_VERSION,
+        claimed_sums,
+        instance_hashes,
+        round_messages,
+    }
+}
+
+/// Verifies a proof produced by [`prove_aggregated`] for 'instances', and returns the random point
+/// shared by their final checks. A false claim survives the combination with probability at most
+/// k/|F| for k instances, on top of the soundness error of a single sum-check.
+pub fn verify_aggregated(
+    instances: &[ProductMLPolynomial],
+    proof: &AggregatedProof,
+) -> Result<Vec<F>, BatchError> {
+    if proof.version != PROTOCOL_VERSION {
+        return Err(SumCheckError::UnsupportedVersion { version: proof.version }.into());
+    }
+    if proof.claimed_sums.len() != instances.len() || proof.instance_hashes.len() != instances.len() {
+        return Err(BatchError::CountMismatch {
+            expected: instances.len(),
+            got: proof.claimed_sums.len(),
+        });
+    }
+    let num_vars = instances.first().and_then(get_num_vars).unwrap_or(0);
+    for (index, poly) in instances.iter().enumerate() {
+        let instance_error = |source| BatchError::Instance { index, source };
+        if proof.instance_hashes[index] != instance_hash(poly, proof.claimed_sums[index]) {
+            return Err(instance_error(SumCheckError::InstanceMismatch));
+        }
+        let vars = get_num_vars(poly).unwrap_or(0);
+        if vars != num_vars {
+            return Err(instance_error(SumCheckError::VariableCountMismatch { expected: num_vars, got: vars }));
+        }
+    }
+    if proof.round_messages.len() != num_vars {
+        return Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: proof.round_messages.len(),
+        }
+        .into());
+    }
+
+    let mut transcript = aggregated_transcript(&proof.instance_hashes, &proof.claimed_sums);
+    let powers = combination_powers(F::rand(&mut transcript), instances.len());
+    // The round polynomials have degree at most the largest number of factors.
+    let degree = instances.iter().map(|poly| poly.len().max(1)).max().unwrap_or(1);
+    let interpolation = InterpolationContext::new(degree);
+    let mut running_eval: F = proof
+        .claimed_sums
+        .iter()
+        .zip(&powers)
+        .map(|(&claimed_sum, &power)| power * claimed_sum)
+        .sum();
+    let mut point = Vec::with_capacity(num_vars);
+    for (round, message) in proof.round_messages.iter().enumerate() {
+        if message.len() < 2 || message.len() > degree + 1 {
+            return Err(SumCheckError::DegreeMismatch {
+                round,
+                expected: degree + 1,
+                got: message.len(),
+            }
+            .into());
+        }
+        let intermediate = message[0] + message[1];
+        if intermediate != running_eval {
+            return Err(SumCheckError::IntermediateCheckFailed {
+                round,
+                lhs: running_eval,
+                rhs: intermediate,
+            }
+            .into());
+        }
+        transcript.append_field_elements(b"round", message);
+        let r = F::rand(&mut transcript);
+        running_eval = interpolation.evaluate(message, r);
+        point.push(r);
+    }
+
+    let combined: F = instances
+        .iter()
+        .zip(&powers)
+        .map(|(poly, &power)| power * evaluate_mvml_polynomial(poly.clone(), &point))
+        .sum();
+    if combined != running_eval {
+        return Err(SumCheckError::FinalCheckFailed.into());
+    }
+    Ok(point)
+}
+
+/// 1, α, α², ..., the weights of the 'len' instances in the combination.
+fn combination_powers(alpha: F, len: usize) -> Vec<F> {
+    core::iter::successors(Some(F::from(1)), |p| Some(*p * alpha))
+        .take(len)
+        .collect()
+}
+
+/// The transcript of an aggregated proof after the statements of all the instances, under a label
+/// of its own so that its challenges differ from those of a batch.
+fn aggregated_transcript(instance_hashes: &[InstanceHash], claimed_sums: &[F]) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"aggregate", &[F::from(claimed_sums.len() as u64)]);
+    for (hash, &claimed_sum) in instance_hashes.iter().zip(claimed_sums) {
+        absorb_statement(&mut transcript, hash, claimed_sum);
+    }
+    transcript
+}
+
+/// The number of variables of the instances, if they all have the same.
+#[cfg(feature = "prover")]
+fn common_num_vars(instances: &[ProductMLPolynomial]) -> Option<usize> {
+    let num_vars = get_num_vars(instances.first()?)?;
+    instances
+        .iter()
+        .all(|poly| get_num_vars(poly) == Some(num_vars))
+        .then_some(num_vars)
+}
+
+/// The transcript after the statements of all the instances.
+fn batch_transcript(proofs: &[Proof]) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"batch", &[F::from(proofs.len() as u64)]);
+    for proof in proofs {
+        absorb_statement(&mut transcript, &proof.instance_hash, proof.claimed_sum);
+    }
+    transcript
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use crate::protocol::verify;
+
+    fn instances() -> Vec<ProductMLPolynomial> {
+        vec![
+            parse_product("x0 * (x1 + 2) * (3*x2 + 1)").unwrap(),
+            parse_product("(x0 + x2) * (x1 + 5)").unwrap(),
+            parse_product("7*x1*x2 + x0").unwrap(),
+        ]
+    }
+
+    #[test]
+    fn test_batch() {
+        let instances = instances();
+        let proofs = prove_batch(&instances);
+        let point = verify_batch(&instances, &proofs).unwrap();
+        assert_eq!(point.len(), 3);
+        for (poly, proof) in instances.iter().zip(&proofs) {
+            assert_eq!(proof.claimed_sum, crate::protocol::prove(poly).claimed_sum);
+            // The proofs only verify as a batch.
+            assert!(verify(poly, proof).is_err());
+        }
+    }
+
+    #[test]
+    fn test_aggregated_proof() {
+        let instances = instances();
+        let proof = prove_aggregated(&instances);
+        assert_eq!(proof.num_instances(), 3);
+        assert_eq!(proof.round_messages.len(), 3);
+        let point = verify_aggregated(&instances, &proof).unwrap();
+        assert_eq!(point.len(), 3);
+        for (poly, &claimed_sum) in instances.iter().zip(&proof.claimed_sums) {
+            assert_eq!(claimed_sum, crate::protocol::prove(poly).claimed_sum);
+        }
+        // One message per round for all the instances.
+        assert!(proof.size_in_bytes() < prove_batch(&instances).iter().map(Proof::size_in_bytes).sum());
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(AggregatedProof::deserialize_compressed(&bytes[..]).unwrap(), proof);
+        assert!(AggregatedProof::deserialize_compressed(&bytes[..40]).is_err());
+
+        let mut wrong = proof.clone();
+        wrong.claimed_sums[1] += F::from(1);
+        assert!(matches!(
+            verify_aggregated(&instances, &wrong),
+            Err(BatchError::Instance { index: 1, source: SumCheckError::InstanceMismatch })
+        ));
+        let mut wrong = proof.clone();
+        wrong.round_messages[1][0] += F::from(1);
+        assert!(matches!(
+            verify_aggregated(&instances, &wrong),
+            Err(BatchError::SumCheck(SumCheckError::IntermediateCheckFailed { round: 1, .. }))
+        ));
+        let mut wrong = proof;
+        wrong.round_messages[2][0] += F::from(1);
+        wrong.round_messages[2][1] -= F::from(1);
+        assert_eq!(
+            verify_aggregated(&instances, &wrong),
+            Err(BatchError::SumCheck(SumCheckError::FinalCheckFailed))
+        );
+    }
+
+    #[test]
+    fn test_wrong_batch() {
+        let instances = instances();
+        let proofs = prove_batch(&instances);
+        assert_eq!(
+            verify_batch(&instances, &proofs[..2]),
+            Err(BatchError::CountMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+
+        let mut swapped = proofs.clone();
+        swapped.swap(0, 1);
+        assert!(matches!(
+            verify_batch(&instances, &swapped),
+            Err(BatchError::Instance {
+                index: 0,
+                source: SumCheckError::InstanceMismatch
+            })
+        ));
+
+        let mut wrong = proofs.clone();
+        wrong[1].round_messages[0][0] += F::from(1);
+        assert!(matches!(
+            verify_batch(&instances, &wrong),
+            Err(BatchError::SumCheck(
+                SumCheckError::IntermediateCheckFailed { round: 0, .. }
+            ))
+        ));
+
+        let mut wrong = proofs;
+        // Keeps p(0) + p(1), so that only the final check fails.
+        wrong[2].round_messages[2][0] += F::from(1);
+        wrong[2].round_messages[2][1] -= F::from(1);
+        assert_eq!(
+            verify_batch(&instances, &wrong),
+            Err(BatchError::SumCheck(SumCheckError::FinalCheckFailed))
+        );
+    }
+}
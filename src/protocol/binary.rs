@@ -0,0 +1,296 @@
+//! Non-interactive sum-check over the binary field [`BinaryField128`], for a product of
+//! multilinear polynomials given by their evaluation tables on the hypercube, in the big-endian
+//! order of [`prove`](crate::protocol::prove).
+//!
+//! The integers 0, 1, ..., d are not distinct in a field of characteristic 2, so that the round
+//! polynomials of degree d are sent as their evaluations at the nodes
+//! [`BinaryField128::node`]`(0..=d)` instead, of which the first two are still 0 and 1, and are
+//! interpolated with [`interpolate_binary`]. The challenges are 128 bits squeezed from the SHA-256
+//! transcript, which absorbs the version, the [`binary_instance_hash`] of the statement and the
+//! claimed sum before the rounds.
+
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+};
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+
+use crate::field::BinaryField128;
+use crate::protocol::fiat_shamir::Transcript;
+use crate::protocol::proof::{PROTOCOL_LABEL, PROTOCOL_VERSION};
+use crate::protocol::statement::{binary_instance_hash, read_hash, InstanceHash};
+use crate::protocol::SumCheckError;
+
+/// A non-interactive proof for an instance over [`BinaryField128`].
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize)]
+pub struct BinaryProof {
+    pub version: u8,
+    pub claimed_sum: BinaryField128,
+    pub instance_hash: InstanceHash,
+    pub round_messages: Vec<Vec<BinaryField128>>,
+}
+
+// The instance hash is read with `read_hash`, which fails on a truncated input.
+impl Valid for BinaryProof {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.claimed_sum.check()?;
+        self.round_messages.check()
+    }
+}
+
+impl CanonicalDeserialize for BinaryProof {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(BinaryProof {
+            version: u8::deserialize_with_mode(&mut reader, compress, validate)?,
+            claimed_sum: BinaryField128::deserialize_with_mode(&mut reader, compress, validate)?,
+            instance_hash: read_hash(&mut reader)?,
+            round_messages: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
+}
+
+/// Same as [`prove`](crate::protocol::prove), for the product of the multilinear extensions of
+/// 'tables' over [`BinaryField128`]. The tables must have the same length, a power of two.
+#[cfg(feature = "prover")]
+pub fn prove_binary(mut tables: Vec<Vec<BinaryField128>>) -> BinaryProof {
+    let num_vars = common_num_vars(&tables).unwrap();
+    let claimed_sum = (0..1 << num_vars)
+        .map(|i| tables.iter().map(|t| t[i]).product::<BinaryField128>())
+        .sum();
+    let instance_hash = binary_instance_hash(&tables, claimed_sum);
+    let mut transcript = statement_transcript(&instance_hash, claimed_sum);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let message = round_message(&tables);
+        absorb(&mut transcript, b"round", &message);
+        let challenge = BinaryField128::rand(&mut transcript);
+        tables.iter_mut().for_each(|t| fold(t, challenge));
+        round_messages.push(message);
+    }
+    BinaryProof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+    }
+}
+
+/// Verifies a proof produced by [`prove_binary`] against 'tables', and returns the random point of
+/// the rounds.
+pub fn verify_binary(
+    tables: &[Vec<BinaryField128>],
+    proof: &BinaryProof,
+) -> Result<Vec<BinaryField128>, SumCheckError<BinaryField128>> {
+    if proof.version != PROTOCOL_VERSION {
+        return Err(SumCheckError::UnsupportedVersion {
+            version: proof.version,
+        });
+    }
+    if proof.instance_hash != binary_instance_hash(tables, proof.claimed_sum) {
+        return Err(SumCheckError::InstanceMismatch);
+    }
+    let num_vars = common_num_vars(tables).ok_or(SumCheckError::InstanceMismatch)?;
+    if proof.round_messages.len() != num_vars {
+        return Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: proof.round_messages.len(),
+        });
+    }
+    let mut transcript = statement_transcript(&proof.instance_hash, proof.claimed_sum);
+    let mut running = proof.claimed_sum;
+    let mut point = Vec::with_capacity(num_vars);
+    for (round, message) in proof.round_messages.iter().enumerate() {
+        if message.len() != tables.len() + 1 {
+            return Err(SumCheckError::DegreeMismatch {
+                round,
+                expected: tables.len() + 1,
+                got: message.len(),
+            });
+        }
+        let rhs = message[0] + message[1];
+        if rhs != running {
+            return Err(SumCheckError::IntermediateCheckFailed {
+                round,
+                lhs: running,
+                rhs,
+            });
+        }
+        absorb(&mut transcript, b"round", message);
+        let challenge = BinaryField128::rand(&mut transcript);
+        running = interpolate_binary(message, challenge);
+        point.push(challenge);
+    }
+    let product: BinaryField128 = tables
+        .iter()
+        .map(|t| evaluate_multilinear(t, &point))
+        .product();
+    if product != running {
+        return Err(SumCheckError::FinalCheckFailed);
+    }
+    Ok(point)
+}
+
+/// The value at 'r' of the polynomial of degree less than the number of 'evaluations', given by
+/// its evaluations at the nodes [`BinaryField128::node`]`(0..)`, by the Lagrange formula.
+pub fn interpolate_binary(evaluations: &[BinaryField128], r: BinaryField128) -> BinaryField128 {
+    let nodes: Vec<_> = (0..evaluations.len()).map(BinaryField128::node).collect();
+    let mut result = BinaryField128::ZERO;
+    for (k, (&x_k, &y_k)) in nodes.iter().zip(evaluations).enumerate() {
+        let (mut numerator, mut denominator) = (y_k, BinaryField128::ONE);
+        for (j, &x_j) in nodes.iter().enumerate() {
+            if j != k {
+                numerator *= r - x_j;
+                denominator *= x_k - x_j;
+            }
+        }
+        // The nodes are distinct, so that the denominator is never zero.
+        result += numerator * denominator.inverse().unwrap();
+    }
+    result
+}
+
+/// The evaluations at the nodes 0, 1, ..., d of the round polynomial, in the first variable left,
+/// of the product of the d tables.
+#[cfg(feature = "prover")]
+fn round_message(tables: &[Vec<BinaryField128>]) -> Vec<BinaryField128> {
+    let half = tables[0].len() / 2;
+    (0..=tables.len())
+        .map(BinaryField128::node)
+        .map(|x| {
+            (0..half)
+                .map(|i| {
+                    tables
+                        .iter()
+                        .map(|t| t[i] + (t[i + half] - t[i]) * x)
+                        .product::<BinaryField128>()
+                })
+                .sum()
+        })
+        .collect()
+}
+
+/// Fixes the first variable of the multilinear extension of 'table' to 'r'.
+fn fold(table: &mut Vec<BinaryField128>, r: BinaryField128) {
+    let half = table.len() / 2;
+    for i in 0..half {
+        let (low, high) = (table[i], table[i + half]);
+        table[i] = low + (high - low) * r;
+    }
+    table.truncate(half);
+}
+
+/// The multilinear extension of 'table' at 'point'.
+fn evaluate_multilinear(table: &[BinaryField128], point: &[BinaryField128]) -> BinaryField128 {
+    let mut table = table.to_vec();
+    point.iter().for_each(|&r| fold(&mut table, r));
+    table[0]
+}
+
+/// The number of variables of the tables, if they all have the same length, a power of two.
+fn common_num_vars(tables: &[Vec<BinaryField128>]) -> Option<usize> {
+    let len = tables.first()?.len();
+    (len.is_power_of_two() && tables.iter().all(|t| t.len() == len))
+        .then_some(len.trailing_zeros() as usize)
+}
+
+/// The transcript of a proof, after the version and the statement.
+fn statement_transcript(instance_hash: &InstanceHash, claimed_sum: BinaryField128) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_message(b"version", &[PROTOCOL_VERSION]);
+    transcript.append_message(b"instance", instance_hash);
+    absorb(&mut transcript, b"claimed_sum", &[claimed_sum]);
+    transcript
+}
+
+fn absorb(transcript: &mut Transcript, label: &[u8], elements: &[BinaryField128]) {
+    let mut bytes = Vec::new();
+    elements.serialize_compressed(&mut bytes).unwrap();
+    transcript.append_message(label, &bytes);
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    fn random_tables(num_vars: usize, degree: usize) -> Vec<Vec<BinaryField128>> {
+        let mut rng = thread_rng();
+        (0..degree)
+            .map(|_| {
+                (0..1 << num_vars)
+                    .map(|_| BinaryField128::rand(&mut rng))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_binary_sum_check() {
+        let tables = random_tables(5, 3);
+        let proof = prove_binary(tables.clone());
+        let sum = (0..32)
+            .map(|i| tables.iter().map(|t| t[i]).product::<BinaryField128>())
+            .sum::<BinaryField128>();
+        assert_eq!(proof.claimed_sum, sum);
+        assert!(proof.round_messages.iter().all(|m| m.len() == 4));
+        let point = verify_binary(&tables, &proof).unwrap();
+        assert_eq!(point.len(), 5);
+
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            BinaryProof::deserialize_compressed(&bytes[..]).unwrap(),
+            proof
+        );
+    }
+
+    #[test]
+    fn test_tampered_binary_proof() {
+        let tables = random_tables(4, 2);
+        let proof = prove_binary(tables.clone());
+
+        let mut tampered = proof.clone();
+        tampered.round_messages[3][2] += BinaryField128::ONE;
+        assert_eq!(
+            verify_binary(&tables, &tampered),
+            Err(SumCheckError::FinalCheckFailed)
+        );
+
+        let mut tampered = proof.clone();
+        tampered.round_messages[0][0] += BinaryField128::ONE;
+        assert!(matches!(
+            verify_binary(&tables, &tampered),
+            Err(SumCheckError::IntermediateCheckFailed { round: 0, .. })
+        ));
+
+        let mut other = tables.clone();
+        other[0][3] += BinaryField128::ONE;
+        assert_eq!(
+            verify_binary(&other, &proof),
+            Err(SumCheckError::InstanceMismatch)
+        );
+    }
+
+    #[test]
+    fn test_interpolate_binary() {
+        let mut rng = thread_rng();
+        let coefficients: Vec<_> = (0..4).map(|_| BinaryField128::rand(&mut rng)).collect();
+        let evaluate = |x: BinaryField128| {
+            coefficients
+                .iter()
+                .rev()
+                .fold(BinaryField128::ZERO, |acc, &c| acc * x + c)
+        };
+        let evaluations: Vec<_> = (0..4).map(|k| evaluate(BinaryField128::node(k))).collect();
+        let r = BinaryField128::rand(&mut rng);
+        assert_eq!(interpolate_binary(&evaluations, r), evaluate(r));
+        assert_eq!(
+            interpolate_binary(&evaluations, BinaryField128::node(2)),
+            evaluations[2]
+        );
+    }
+}
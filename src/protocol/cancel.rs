@@ -0,0 +1,62 @@
+//! Cooperative cancellation of long runs of the protocol.
+
+use alloc::sync::Arc;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::protocol::SumCheckError;
+
+/// Aborts a run of [`orchestrate_protocol_cancellable`] or [`prove_cancellable`] from another
+/// thread. The clones of a token share its state, so that cancelling one cancels them all. With
+/// the `std` feature, a token may also expire at a deadline.
+///
+/// The token is checked every few thousand points while the factors are evaluated on the
+/// hypercube and while their product is summed, and between the rounds, so that a run stops
+/// shortly after its token is cancelled, however many variables the instance has.
+///
+/// [`orchestrate_protocol_cancellable`]: crate::protocol::orchestrate_protocol_cancellable
+/// [`prove_cancellable`]: crate::protocol::prove_cancellable
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    #[cfg(feature = "std")]
+    deadline: Option<std::time::Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        CancellationToken::default()
+    }
+
+    /// A token which also expires once 'timeout' has elapsed.
+    #[cfg(feature = "std")]
+    pub fn with_timeout(timeout: core::time::Duration) -> Self {
+        CancellationToken {
+            deadline: Some(std::time::Instant::now() + timeout),
+            ..CancellationToken::default()
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        #[cfg(feature = "std")]
+        if self
+            .deadline
+            .is_some_and(|deadline| std::time::Instant::now() >= deadline)
+        {
+            return true;
+        }
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Fails with [`SumCheckError::Cancelled`] once the token is cancelled.
+    pub(crate) fn check(&self) -> Result<(), SumCheckError> {
+        if self.is_cancelled() {
+            Err(SumCheckError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! Runs of the interactive protocol between a prover and a verifier on different machines, with the
+//! `async` feature. The two sides exchange frames of bytes over an [`AsyncChannel`], e.g. the
+//! messages of a websocket or the bodies of HTTP requests, without blocking a thread while waiting
+//! for the other side. The futures do not depend on a runtime, so that they run on tokio as on any
+//! other executor.
+//!
+//! The prover sends the claimed sum, then the message of each round, and the verifier answers each
+//! message but the last with its challenge. Field elements and messages are framed in their
+//! canonical compressed serialization, as in a [`Proof`](crate::protocol::Proof). A verifier which
+//! rejects a round stops answering.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+use core::future::Future;
+use rand::{CryptoRng, RngCore};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{get_num_vars, PolynomialDescription, ProductMLPolynomial};
+#[cfg(feature = "prover")]
+use crate::protocol::SumCheckProver;
+use crate::protocol::{MessageFormat, ProtocolTranscript, RoundMessage, SumCheckVerifier};
+
+/// A bidirectional transport of frames of bytes, each sent frame being received whole and in
+/// order by the other side.
+pub trait AsyncChannel {
+    type Error;
+
+    fn send(&mut self, frame: Vec<u8>) -> impl Future<Output = Result<(), Self::Error>> + Send;
+
+    fn receive(&mut self) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send;
+}
+
+/// Reasons for which a remote run of the protocol could not complete. The verifier rejecting the
+/// prover's claim is not one of them, but the outcome of the run.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ChannelError<E> {
+    #[error("transport error: {0}")]
+    Transport(E),
+    /// The other side sent a frame which is not the serialization of the expected value.
+    #[error("invalid {0} frame")]
+    InvalidFrame(&'static str),
+}
+
+/// Verifies the claim of the remote prover at the other end of 'channel' that 'poly' sums to the
+/// claimed sum it sends, drawing the challenges from 'rng'. Returns the record of the run, which
+/// the verifier accepted or rejected.
+pub async fn orchestrate_async<C: AsyncChannel, R: RngCore + CryptoRng>(
+    poly: &ProductMLPolynomial,
+    channel: &mut C,
+    rng: R,
+) -> Result<ProtocolTranscript, ChannelError<C::Error>> {
+    let claimed_sum: F = receive(channel, "claimed sum").await?;
+    let mut verifier = SumCheckVerifier::with_rng(poly, claimed_sum, rng);
+    let mut transcript = ProtocolTranscript::new(claimed_sum, MessageFormat::Evaluations);
+    let num_vars = get_num_vars(poly).unwrap();
+    for round in 0..num_vars {
+        let message: PolynomialDescription = receive(channel, "round message").await?;
+        transcript.messages.push(message.clone());
+        let r = match verifier.receive_message(RoundMessage {
+            evaluations: message,
+        }) {
+            Ok(r) => r,
+            Err(err) => return Ok(transcript.reject(err)),
+        };
+        transcript.challenges.push(r);
+        if round + 1 < num_vars {
+            send(channel, &r).await?;
+        }
+    }
+    let final_evaluation = verifier.running_eval();
+    let (result, point) = verifier.finalize();
+    Ok(transcript.conclude(result, point, final_evaluation))
+}
+
+/// Plays 'prover' against the remote verifier at the other end of 'channel', until the message of
+/// the last round is sent. If the verifier rejects a round, it stops answering, and the transport
+/// fails or waits as it does for a silent peer.
+#[cfg(feature = "prover")]
+pub async fn serve_prover_async<C: AsyncChannel>(
+    mut prover: SumCheckProver,
+    channel: &mut C,
+) -> Result<(), ChannelError<C::Error>> {
+    send(channel, &prover.claimed_sum()).await?;
+    let mut challenge = None;
+    for round in 0..prover.num_rounds() {
+        if round > 0 {
+            challenge = Some(receive(channel, "challenge").await?);
+        }
+        send(channel, &prover.next_message(challenge).evaluations).await?;
+    }
+    Ok(())
+}
+
+async fn send<C: AsyncChannel, T: CanonicalSerialize>(
+    channel: &mut C,
+    value: &T,
+) -> Result<(), ChannelError<C::Error>> {
+    let mut frame = Vec::with_capacity(value.compressed_size());
+    value.serialize_compressed(&mut frame).unwrap();
+    channel.send(frame).await.map_err(ChannelError::Transport)
+}
+
+async fn receive<C: AsyncChannel, T: CanonicalDeserialize>(
+    channel: &mut C,
+    what: &'static str,
+) -> Result<T, ChannelError<C::Error>> {
+    let frame = channel.receive().await.map_err(ChannelError::Transport)?;
+    T::deserialize_compressed(&frame[..]).map_err(|_| ChannelError::InvalidFrame(what))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use crate::protocol::SumCheckError;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+    use std::collections::VecDeque;
+    use std::pin::pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Waker};
+
+    type Queue = Arc<Mutex<VecDeque<Vec<u8>>>>;
+
+    /// One end of an in-memory channel.
+    struct Pipe {
+        inbox: Queue,
+        outbox: Queue,
+    }
+
+    fn pipe() -> (Pipe, Pipe) {
+        let (a, b) = (Queue::default(), Queue::default());
+        let end = |inbox: &Queue, outbox: &Queue| Pipe {
+            inbox: inbox.clone(),
+            outbox: outbox.clone(),
+        };
+        (end(&a, &b), end(&b, &a))
+    }
+
+    impl AsyncChannel for Pipe {
+        type Error = &'static str;
+
+        fn send(&mut self, frame: Vec<u8>) -> impl Future<Output = Result<(), Self::Error>> + Send {
+            self.outbox.lock().unwrap().push_back(frame);
+            core::future::ready(Ok(()))
+        }
+
+        fn receive(&mut self) -> impl Future<Output = Result<Vec<u8>, Self::Error>> + Send {
+            let inbox = self.inbox.clone();
+            core::future::poll_fn(move |_| match inbox.lock().unwrap().pop_front() {
+                Some(frame) => Poll::Ready(Ok(frame)),
+                None => Poll::Pending,
+            })
+        }
+    }
+
+    /// Polls both futures in turn until 'verifier' completes, and returns the output of 'prover'
+    /// if it completed too.
+    fn run<V: Future, P: Future>(verifier: V, prover: P) -> (V::Output, Option<P::Output>) {
+        let mut cx = Context::from_waker(Waker::noop());
+        let (mut verifier, mut prover) = (pin!(verifier), pin!(prover));
+        let mut proved = None;
+        loop {
+            if proved.is_none() {
+                if let Poll::Ready(output) = prover.as_mut().poll(&mut cx) {
+                    proved = Some(output);
+                }
+            }
+            if let Poll::Ready(output) = verifier.as_mut().poll(&mut cx) {
+                return (output, proved);
+            }
+        }
+    }
+
+    #[test]
+    fn test_remote_protocol() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)").unwrap();
+        let (mut verifier_end, mut prover_end) = pipe();
+        let rng = ChaCha20Rng::from_seed([1; 32]);
+        let (transcript, proved) = run(
+            orchestrate_async(&poly, &mut verifier_end, rng),
+            serve_prover_async(SumCheckProver::new(&poly), &mut prover_end),
+        );
+        let transcript = transcript.unwrap();
+        assert!(transcript.accept);
+        assert_eq!(proved, Some(Ok(())));
+        assert_eq!(transcript.messages.len(), 3);
+        assert_eq!(transcript.point, transcript.challenges);
+        assert!(verifier_end.inbox.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remote_rejection() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        // The prover of another polynomial is caught at the final check.
+        let other = parse_product("(x0 + 2*x1) * (x1 + 4)").unwrap();
+        let (mut verifier_end, mut prover_end) = pipe();
+        let rng = ChaCha20Rng::from_seed([1; 32]);
+        let (transcript, proved) = run(
+            orchestrate_async(&poly, &mut verifier_end, rng),
+            serve_prover_async(SumCheckProver::new(&other), &mut prover_end),
+        );
+        assert_eq!(
+            transcript.unwrap().error,
+            Some(SumCheckError::FinalCheckFailed)
+        );
+        assert_eq!(proved, Some(Ok(())));
+
+        // A message of the wrong degree is rejected in the first round, and the prover then waits
+        // for a challenge which never comes.
+        let other = parse_product("(x0 + 2*x1) * (x1 + 3) * (x0 + 1)").unwrap();
+        let (mut verifier_end, mut prover_end) = pipe();
+        let rng = ChaCha20Rng::from_seed([1; 32]);
+        let (transcript, proved) = run(
+            orchestrate_async(&poly, &mut verifier_end, rng),
+            serve_prover_async(SumCheckProver::new(&other), &mut prover_end),
+        );
+        assert!(matches!(
+            transcript.unwrap().error,
+            Some(SumCheckError::DegreeMismatch { round: 0, .. })
+        ));
+        assert_eq!(proved, None);
+
+        let (mut verifier_end, mut prover_end) = pipe();
+        let rng = ChaCha20Rng::from_seed([1; 32]);
+        let (transcript, _) = run(
+            orchestrate_async(&poly, &mut verifier_end, rng),
+            prover_end.send(vec![1, 2, 3]),
+        );
+        assert_eq!(
+            transcript.err(),
+            Some(ChannelError::InvalidFrame("claimed sum"))
+        );
+    }
+}
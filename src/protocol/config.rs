@@ -0,0 +1,280 @@
+//! A protocol configured once with [`SumCheck::builder`], whose [`SumCheck::prove`] and
+//! [`SumCheck::verify`] then stand for the entry points which each fix one of the modes: the
+//! Fiat–Shamir transcript, the hash chain of the round messages, the auxiliary evaluations of the
+//! round polynomials and the constant-time verifier.
+
+use ark_std::vec::Vec;
+
+use crate::field::Field256 as F;
+use crate::polynomial::ProductMLPolynomial;
+#[cfg(feature = "prover")]
+use crate::protocol::hash_chain::round_hash_chain;
+#[cfg(feature = "prover")]
+use crate::protocol::proof::prove_with_auxiliary_node_in;
+#[cfg(feature = "merlin")]
+use crate::protocol::merlin_transcript::MerlinTranscript;
+#[cfg(feature = "poseidon")]
+use crate::protocol::poseidon_transcript::PoseidonTranscript;
+use crate::protocol::proof::{verify_in_mode, Proof, PROTOCOL_LABEL};
+use crate::protocol::{SumCheckError, Transcript};
+
+/// The transcript from which the challenges of a [`SumCheck`] are derived.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FiatShamir {
+    /// The SHA-256 [`Transcript`] of [`prove`](crate::protocol::prove).
+    #[default]
+    Sha256,
+    /// A [`merlin::Transcript`] started for the proof alone, as by
+    /// [`prove_with_merlin`](crate::protocol::prove_with_merlin) from a new transcript.
+    #[cfg(feature = "merlin")]
+    Merlin,
+    /// A Poseidon sponge, cheap to verify in a circuit.
+    #[cfg(feature = "poseidon")]
+    Poseidon,
+}
+
+/// Builder of a [`SumCheck`], every option of which defaults to the mode of
+/// [`prove`](crate::protocol::prove) and [`verify`](crate::protocol::verify).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SumCheckBuilder {
+    protocol: SumCheck,
+}
+
+impl SumCheckBuilder {
+    /// Derives the challenges from 'fiat_shamir'.
+    pub fn fiat_shamir(mut self, fiat_shamir: FiatShamir) -> Self {
+        self.protocol.fiat_shamir = fiat_shamir;
+        self
+    }
+
+    /// Whether the proofs carry the hash chain of their round messages, as those of
+    /// [`prove_with_round_commitments`](crate::protocol::prove_with_round_commitments), and the
+    /// verifier requires it, so that a proof stripped of its chain is rejected.
+    pub fn round_commitments(mut self, round_commitments: bool) -> Self {
+        self.protocol.round_commitments = round_commitments;
+        self
+    }
+
+    /// Whether the proofs carry the evaluation of each round polynomial at an auxiliary node, as
+    /// those of [`prove_with_auxiliary_node`](crate::protocol::prove_with_auxiliary_node), and the
+    /// verifier requires them. It catches a faulty prover whose messages understate the degree of
+    /// its round polynomials, but adds nothing to the soundness against a malicious one, for a
+    /// field element per round and a fold of the tables per round for the prover.
+    pub fn auxiliary_node(mut self, auxiliary_node: bool) -> Self {
+        self.protocol.auxiliary_node = auxiliary_node;
+        self
+    }
+
+    /// Whether the verifier runs in constant-time mode, as in
+    /// [`verify_constant_time`](crate::protocol::verify_constant_time).
+    pub fn constant_time(mut self, constant_time: bool) -> Self {
+        self.protocol.constant_time = constant_time;
+        self
+    }
+
+    pub fn build(self) -> SumCheck {
+        self.protocol
+    }
+}
+
+/// A configuration of the non-interactive protocol. The prover and the verifier must use the same
+/// transcript, whereas the other options only concern one side.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SumCheck {
+    fiat_shamir: FiatShamir,
+    round_commitments: bool,
+    auxiliary_node: bool,
+    constant_time: bool,
+}
+
+impl SumCheck {
+    pub fn builder() -> SumCheckBuilder {
+        SumCheckBuilder::default()
+    }
+
+    pub fn fiat_shamir(&self) -> FiatShamir {
+        self.fiat_shamir
+    }
+
+    pub fn round_commitments(&self) -> bool {
+        self.round_commitments
+    }
+
+    pub fn auxiliary_node(&self) -> bool {
+        self.auxiliary_node
+    }
+
+    pub fn constant_time(&self) -> bool {
+        self.constant_time
+    }
+
+    /// Proves the sum of 'poly' over the hypercube.
+    #[cfg(feature = "prover")]
+    pub fn prove(&self, poly: &ProductMLPolynomial) -> Proof {
+        if self.auxiliary_node {
+            return self.finish(self.prove_with_auxiliary_node(poly));
+        }
+        let proof = match self.fiat_shamir {
+            FiatShamir::Sha256 => crate::protocol::prove(poly),
+            #[cfg(feature = "merlin")]
+            FiatShamir::Merlin => crate::protocol::prove_with_merlin(
+                poly,
+                &mut merlin::Transcript::new(PROTOCOL_LABEL),
+            ),
+            #[cfg(feature = "poseidon")]
+            FiatShamir::Poseidon => crate::protocol::prove_with_poseidon(poly),
+        };
+        self.finish(proof)
+    }
+
+    /// Same as `prove` with auxiliary evaluations, before the round commitments.
+    #[cfg(feature = "prover")]
+    fn prove_with_auxiliary_node(&self, poly: &ProductMLPolynomial) -> Proof {
+        match self.fiat_shamir {
+            FiatShamir::Sha256 => {
+                prove_with_auxiliary_node_in(poly, Transcript::new(PROTOCOL_LABEL))
+            }
+            #[cfg(feature = "merlin")]
+            FiatShamir::Merlin => {
+                let mut transcript = merlin::Transcript::new(PROTOCOL_LABEL);
+                transcript.append_message(b"dom-sep", PROTOCOL_LABEL);
+                prove_with_auxiliary_node_in(poly, MerlinTranscript::new(&mut transcript))
+            }
+            #[cfg(feature = "poseidon")]
+            FiatShamir::Poseidon => {
+                prove_with_auxiliary_node_in(poly, PoseidonTranscript::new(PROTOCOL_LABEL))
+            }
+        }
+    }
+
+    /// Adds the hash chain of the round messages to 'proof' if it is configured.
+    #[cfg(feature = "prover")]
+    fn finish(&self, mut proof: Proof) -> Proof {
+        if self.round_commitments {
+            proof.round_commitments = round_hash_chain(proof.claimed_sum, &proof.round_messages);
+        }
+        proof
+    }
+
+    /// Verifies a proof produced by `prove` with the same transcript, and returns the random point
+    /// of the final check. The hash chain of the round messages and the auxiliary evaluations are
+    /// checked whenever the proof carries them, and each is required if configured.
+    pub fn verify(
+        &self,
+        poly: &ProductMLPolynomial,
+        proof: &Proof,
+    ) -> Result<Vec<F>, SumCheckError> {
+        if self.auxiliary_node && proof.auxiliary_evaluations.len() != proof.round_messages.len() {
+            return Err(SumCheckError::AuxiliaryCountMismatch {
+                expected: proof.round_messages.len(),
+                got: proof.auxiliary_evaluations.len(),
+            });
+        }
+        if self.round_commitments
+            && proof.round_commitments.is_empty()
+            && !proof.round_messages.is_empty()
+        {
+            // The polynomial of the first round has no link of the chain to be checked against.
+            return Err(SumCheckError::RoundCommitmentMismatch { round: 0 });
+        }
+        match self.fiat_shamir {
+            FiatShamir::Sha256 => verify_in_mode(
+                poly,
+                proof,
+                Transcript::new(PROTOCOL_LABEL),
+                self.constant_time,
+            ),
+            #[cfg(feature = "merlin")]
+            FiatShamir::Merlin => {
+                let mut transcript = merlin::Transcript::new(PROTOCOL_LABEL);
+                // The domain separation of `verify_with_merlin`.
+                transcript.append_message(b"dom-sep", PROTOCOL_LABEL);
+                verify_in_mode(
+                    poly,
+                    proof,
+                    MerlinTranscript::new(&mut transcript),
+                    self.constant_time,
+                )
+            }
+            #[cfg(feature = "poseidon")]
+            FiatShamir::Poseidon => verify_in_mode(
+                poly,
+                proof,
+                PoseidonTranscript::new(PROTOCOL_LABEL),
+                self.constant_time,
+            ),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use crate::protocol::{prove, verify};
+
+    #[test]
+    fn test_sum_check_config() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + x2 + 3)").unwrap();
+        let default = SumCheck::builder().build();
+        assert_eq!(default, SumCheck::default());
+        let proof = default.prove(&poly);
+        assert_eq!(proof, prove(&poly));
+        assert_eq!(default.verify(&poly, &proof), verify(&poly, &proof));
+
+        let protocol = SumCheck::builder()
+            .round_commitments(true)
+            .constant_time(true)
+            .build();
+        let proof = protocol.prove(&poly);
+        assert_eq!(proof.round_commitments.len(), 3);
+        assert!(protocol.verify(&poly, &proof).is_ok());
+        let mut tampered = proof.clone();
+        tampered.round_messages[0][0] += F::from(1);
+        assert!(protocol.verify(&poly, &tampered).is_err());
+
+        // A proof stripped of its chain passes the default verifier, not the one requiring it.
+        let mut stripped = proof;
+        stripped.round_commitments.clear();
+        assert!(SumCheck::default().verify(&poly, &stripped).is_ok());
+        assert_eq!(
+            protocol.verify(&poly, &stripped),
+            Err(SumCheckError::RoundCommitmentMismatch { round: 0 })
+        );
+    }
+
+    #[test]
+    fn test_auxiliary_node_config() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + x2 + 3)").unwrap();
+        let protocol = SumCheck::builder()
+            .auxiliary_node(true)
+            .round_commitments(true)
+            .build();
+        let proof = protocol.prove(&poly);
+        assert_eq!(proof.auxiliary_evaluations.len(), 3);
+        assert_eq!(proof.round_commitments.len(), 3);
+        assert!(protocol.verify(&poly, &proof).is_ok());
+        // The default verifier checks them whenever the proof carries them.
+        assert!(SumCheck::default().verify(&poly, &proof).is_ok());
+        assert_eq!(
+            protocol.verify(&poly, &prove(&poly)),
+            Err(SumCheckError::AuxiliaryCountMismatch {
+                expected: 3,
+                got: 0
+            })
+        );
+    }
+
+    #[cfg(feature = "poseidon")]
+    #[test]
+    fn test_poseidon_config() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let protocol = SumCheck::builder()
+            .fiat_shamir(FiatShamir::Poseidon)
+            .build();
+        let proof = protocol.prove(&poly);
+        assert!(protocol.verify(&poly, &proof).is_ok());
+        // The challenges of the other transcripts differ.
+        assert!(SumCheck::default().verify(&poly, &proof).is_err());
+    }
+}
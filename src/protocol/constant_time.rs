@@ -0,0 +1,133 @@
+//! Constant-time mode of the verifier, for deployments where the timing of the verifier could leak
+//! its challenges before they are sent. A failed check of a round does not end the run: its outcome
+//! is folded into [`DeferredChecks`] without branching on field elements, the verifier keeps
+//! answering with challenges, and rejects only once every round has been played.
+//!
+//! The comparisons work on the Montgomery limbs of the elements, which are canonical, with masks
+//! instead of branches. The arithmetic itself is as constant-time as that of `ark-ff`. The shape
+//! of the messages, i.e. their number and lengths, is public and still rejected at once.
+
+use core::hint::black_box;
+
+use ark_ff::BigInt;
+
+use crate::field::Field256 as F;
+use crate::protocol::SumCheckError;
+
+/// All ones if 'a' and 'b' are equal, and zero otherwise.
+pub(crate) fn eq_mask(a: &F, b: &F) -> u64 {
+    let (a, b) = (&a.0 .0, &b.0 .0);
+    let diff = black_box(a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)));
+    // The top bit of diff | -diff is set if and only if diff is nonzero.
+    ((diff | diff.wrapping_neg()) >> 63).wrapping_sub(1)
+}
+
+/// 'a' if 'mask' is all ones, and 'b' if it is zero.
+fn select(mask: u64, a: F, b: F) -> F {
+    let mut limbs = [0; 4];
+    for (limb, (x, y)) in limbs.iter_mut().zip(a.0 .0.iter().zip(b.0 .0)) {
+        *limb = (x & mask) | (y & !mask);
+    }
+    F::new_unchecked(BigInt::new(limbs))
+}
+
+/// The outcome of the checks of the rounds played so far in constant-time mode, and the first
+/// failed one, revealed by the final check.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeferredChecks {
+    /// All ones once a check has failed, zero before.
+    failed: u64,
+    round: u64,
+    lhs: F,
+    rhs: F,
+    /// All ones once a check of an auxiliary evaluation has failed, zero before.
+    auxiliary_failed: u64,
+    auxiliary_round: u64,
+}
+
+impl DeferredChecks {
+    /// Records the check of 'round' that the running evaluation 'lhs' equals p(0) + p(1) = 'rhs'.
+    pub(crate) fn record(&mut self, round: usize, lhs: F, rhs: F) {
+        let mismatch = !eq_mask(&lhs, &rhs);
+        let first = mismatch & !self.failed;
+        self.round = (round as u64 & first) | (self.round & !first);
+        self.lhs = select(first, lhs, self.lhs);
+        self.rhs = select(first, rhs, self.rhs);
+        self.failed |= mismatch;
+    }
+
+    /// Records the check of 'round' that the message evaluates to 'evaluation' at the auxiliary
+    /// node, where the prover claims 'claimed'.
+    pub(crate) fn record_auxiliary(&mut self, round: usize, evaluation: F, claimed: F) {
+        let mismatch = !eq_mask(&evaluation, &claimed);
+        let first = mismatch & !self.auxiliary_failed;
+        self.auxiliary_round = (round as u64 & first) | (self.auxiliary_round & !first);
+        self.auxiliary_failed |= mismatch;
+    }
+
+    /// The decision on the rounds, and on the final check that the polynomial evaluates to
+    /// 'evaluation' where the prover's messages claim 'claimed', if given. The first failed check
+    /// of the rounds is reported, a round being checked before its auxiliary evaluation, and
+    /// before the final check.
+    pub(crate) fn decide(&self, final_check: Option<(F, F)>) -> Result<(), SumCheckError> {
+        let final_failed =
+            final_check.map_or(0, |(evaluation, claimed)| !eq_mask(&evaluation, &claimed));
+        let round = (self.failed != 0).then_some(self.round);
+        let auxiliary_round = (self.auxiliary_failed != 0).then_some(self.auxiliary_round);
+        match (round, auxiliary_round) {
+            (Some(round), Some(auxiliary_round)) if auxiliary_round < round => {
+                Err(SumCheckError::AuxiliaryCheckFailed {
+                    round: auxiliary_round as usize,
+                })
+            }
+            (Some(round), _) => Err(SumCheckError::IntermediateCheckFailed {
+                round: round as usize,
+                lhs: self.lhs,
+                rhs: self.rhs,
+            }),
+            (None, Some(auxiliary_round)) => Err(SumCheckError::AuxiliaryCheckFailed {
+                round: auxiliary_round as usize,
+            }),
+            (None, None) if final_failed != 0 => Err(SumCheckError::FinalCheckFailed),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deferred_checks() {
+        let (a, b) = (F::from(3), -F::from(5));
+        assert_eq!(eq_mask(&a, &a), u64::MAX);
+        assert_eq!(eq_mask(&a, &b), 0);
+        assert_eq!((select(u64::MAX, a, b), select(0, a, b)), (a, b));
+
+        let mut checks = DeferredChecks::default();
+        checks.record(0, a, a);
+        assert_eq!(checks.decide(Some((b, b))), Ok(()));
+        assert_eq!(
+            checks.decide(Some((a, b))),
+            Err(SumCheckError::FinalCheckFailed)
+        );
+        checks.record_auxiliary(1, a, a);
+        checks.record_auxiliary(2, a, b);
+        checks.record_auxiliary(3, b, a);
+        assert_eq!(
+            checks.decide(Some((a, b))),
+            Err(SumCheckError::AuxiliaryCheckFailed { round: 2 })
+        );
+        checks.record(1, a, b);
+        checks.record(2, b, a);
+        assert_eq!(
+            checks.decide(Some((a, a))),
+            Err(SumCheckError::IntermediateCheckFailed {
+                round: 1,
+                lhs: a,
+                rhs: b
+            })
+        );
+    }
+}
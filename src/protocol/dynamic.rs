@@ -0,0 +1,353 @@
+//! Instances and proofs over a field chosen at runtime, with the `parser` feature, for the command
+//! line and the services, which read the field of an instance from its description rather than
+//! from their build.
+//!
+//! A [`DynInstance`] holds the factors in the field of the description, and a [`DynProof`] the
+//! proof in the same field. Instances over `Field256` are proven by [`prove`] into a [`Proof`].
+//! Instances over the other fields are proven by [`prove_in_field`](crate::protocol::prove_in_field)
+//! into a [`FieldProof`], whose challenges are in the BLS12-381 scalar field for that field, and in
+//! `Field64Ext2` for Goldilocks, whose size alone would give too little soundness.
+
+use std::path::Path;
+
+use ark_ff::{Field, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::field::{DynField, Field64, Field64Ext2, FieldBls12};
+#[cfg(doc)]
+use crate::polynomial::parser;
+use crate::polynomial::parser::{Description, ParseError};
+use crate::polynomial::{get_num_vars, parse_product, ProductMLPolynomial};
+use crate::protocol::field_proof::{common_num_vars, verify_in_field, FieldProduct, FieldProof};
+use crate::protocol::json::{
+    bytes_to_hex, hex_to_prime, is_modulus_of, parse_bytes, prime_modulus_hex, prime_to_hex,
+};
+#[cfg(feature = "prover")]
+use crate::protocol::{prove, prove_in_field};
+use crate::protocol::{verify, Proof, ProofJsonError, SumCheckError};
+
+/// An instance over one of the fields of [`DynField`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynInstance {
+    Field256(ProductMLPolynomial),
+    Bls12_381(FieldProduct<FieldBls12>),
+    Goldilocks(FieldProduct<Field64>),
+}
+
+/// A proof over one of the fields of [`DynField`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DynProof {
+    Field256(Proof),
+    Bls12_381(FieldProof<FieldBls12, FieldBls12>),
+    Goldilocks(FieldProof<Field64, Field64Ext2>),
+}
+
+/// Reasons for which the verifier rejects a [`DynProof`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DynSumCheckError {
+    #[error(transparent)]
+    Field256(SumCheckError),
+    #[error(transparent)]
+    Bls12_381(SumCheckError<FieldBls12>),
+    #[error(transparent)]
+    Goldilocks(SumCheckError<Field64Ext2>),
+    #[error("the proof is over the field {proof}, the instance over {instance}")]
+    FieldMismatch { instance: DynField, proof: DynField },
+}
+
+impl DynInstance {
+    /// Reads the factors in the field that 'description' declares.
+    pub fn from_description(description: Description) -> Result<Self, ParseError> {
+        Ok(match description.field()? {
+            DynField::Field256 => DynInstance::Field256(description.build()?),
+            DynField::Bls12_381 => DynInstance::Bls12_381(description.build()?),
+            DynField::Goldilocks => DynInstance::Goldilocks(description.build()?),
+        })
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, ParseError> {
+        Self::from_description(Description::from_json(input)?)
+    }
+
+    /// Same as [`parser::from_file`], in the field that the file declares. The expressions of
+    /// `.poly` files are over `Field256`.
+    pub fn from_file(path: &Path) -> Result<Self, ParseError> {
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_description(Description::from_json(&contents)?),
+            Some("toml") => Self::from_description(Description::from_toml(&contents)?),
+            Some("poly") => Ok(DynInstance::Field256(parse_product(&contents)?)),
+            _ => Err(ParseError::UnknownFormat),
+        }
+    }
+
+    pub fn field(&self) -> DynField {
+        match self {
+            DynInstance::Field256(_) => DynField::Field256,
+            DynInstance::Bls12_381(_) => DynField::Bls12_381,
+            DynInstance::Goldilocks(_) => DynField::Goldilocks,
+        }
+    }
+
+    /// The number of variables of the factors, if they all have the same.
+    pub fn num_vars(&self) -> Option<usize> {
+        match self {
+            DynInstance::Field256(poly) => get_num_vars(poly),
+            DynInstance::Bls12_381(poly) => common_num_vars(poly),
+            DynInstance::Goldilocks(poly) => common_num_vars(poly),
+        }
+    }
+
+    /// Proves the sum of the instance over the hypercube. The factors must have the same
+    /// variables.
+    #[cfg(feature = "prover")]
+    pub fn prove(&self) -> DynProof {
+        match self {
+            DynInstance::Field256(poly) => DynProof::Field256(prove(poly)),
+            DynInstance::Bls12_381(poly) => DynProof::Bls12_381(prove_in_field(poly)),
+            DynInstance::Goldilocks(poly) => DynProof::Goldilocks(prove_in_field(poly)),
+        }
+    }
+
+    /// Verifies 'proof' for the instance, which must be over the same field.
+    pub fn verify(&self, proof: &DynProof) -> Result<(), DynSumCheckError> {
+        match (self, proof) {
+            (DynInstance::Field256(poly), DynProof::Field256(proof)) => verify(poly, proof)
+                .map(|_| ())
+                .map_err(DynSumCheckError::Field256),
+            (DynInstance::Bls12_381(poly), DynProof::Bls12_381(proof)) => {
+                verify_in_field(poly, proof)
+                    .map(|_| ())
+                    .map_err(DynSumCheckError::Bls12_381)
+            }
+            (DynInstance::Goldilocks(poly), DynProof::Goldilocks(proof)) => {
+                verify_in_field(poly, proof)
+                    .map(|_| ())
+                    .map_err(DynSumCheckError::Goldilocks)
+            }
+            _ => Err(DynSumCheckError::FieldMismatch {
+                instance: self.field(),
+                proof: proof.field(),
+            }),
+        }
+    }
+}
+
+impl DynProof {
+    pub fn field(&self) -> DynField {
+        match self {
+            DynProof::Field256(_) => DynField::Field256,
+            DynProof::Bls12_381(_) => DynField::Bls12_381,
+            DynProof::Goldilocks(_) => DynField::Goldilocks,
+        }
+    }
+
+    /// The claimed sum, in decimal.
+    pub fn claimed_sum(&self) -> String {
+        match self {
+            DynProof::Field256(proof) => proof.claimed_sum.to_string(),
+            DynProof::Bls12_381(proof) => proof.claimed_sum.to_string(),
+            DynProof::Goldilocks(proof) => proof.claimed_sum.to_string(),
+        }
+    }
+
+    /// The canonical compressed serialization of the proof, which does not include its field.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            DynProof::Field256(proof) => proof.serialize_compressed(&mut bytes),
+            DynProof::Bls12_381(proof) => proof.serialize_compressed(&mut bytes),
+            DynProof::Goldilocks(proof) => proof.serialize_compressed(&mut bytes),
+        }
+        .unwrap();
+        bytes
+    }
+
+    /// Reads a proof over 'field' from the bytes of `to_bytes`.
+    pub fn from_bytes(field: DynField, bytes: &[u8]) -> Result<Self, SerializationError> {
+        Ok(match field {
+            DynField::Field256 => DynProof::Field256(Proof::deserialize_compressed(bytes)?),
+            DynField::Bls12_381 => DynProof::Bls12_381(FieldProof::deserialize_compressed(bytes)?),
+            DynField::Goldilocks => {
+                DynProof::Goldilocks(FieldProof::deserialize_compressed(bytes)?)
+            }
+        })
+    }
+
+    /// The JSON encoding of the proof: that of [`Proof::to_json`] over `Field256`. Over the other
+    /// fields, the encoding has the same keys, after the identifier of the field, and each element
+    /// of the round messages is the array of its coordinates over the prime field.
+    pub fn to_json(&self) -> String {
+        match self {
+            DynProof::Field256(proof) => proof.to_json(),
+            DynProof::Bls12_381(proof) => proof.to_json(DynField::Bls12_381),
+            DynProof::Goldilocks(proof) => proof.to_json(DynField::Goldilocks),
+        }
+    }
+
+    /// Reads a proof over 'field' from its JSON encoding.
+    pub fn from_json(field: DynField, input: &str) -> Result<Self, ProofJsonError> {
+        Ok(match field {
+            DynField::Field256 => DynProof::Field256(Proof::from_json(input)?),
+            DynField::Bls12_381 => DynProof::Bls12_381(FieldProof::from_json(input)?),
+            DynField::Goldilocks => DynProof::Goldilocks(FieldProof::from_json(input)?),
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct FieldProofDescription {
+    version: u8,
+    field: String,
+    modulus: String,
+    claimed_sum: String,
+    instance_hash: String,
+    rounds: Vec<Vec<Vec<String>>>,
+}
+
+impl<B: PrimeField, E: Field<BasePrimeField = B>> FieldProof<B, E> {
+    fn to_json(&self, field: DynField) -> String {
+        let description = FieldProofDescription {
+            version: self.version,
+            field: field.id().to_string(),
+            modulus: prime_modulus_hex::<B>(),
+            claimed_sum: prime_to_hex(self.claimed_sum),
+            instance_hash: bytes_to_hex(&self.instance_hash),
+            rounds: self
+                .round_messages
+                .iter()
+                .map(|message| {
+                    message
+                        .iter()
+                        .map(|e| e.to_base_prime_field_elements().map(prime_to_hex).collect())
+                        .collect()
+                })
+                .collect(),
+        };
+        serde_json::to_string(&description).unwrap()
+    }
+
+    fn from_json(input: &str) -> Result<Self, ProofJsonError> {
+        let description: FieldProofDescription =
+            serde_json::from_str(input).map_err(|e| ProofJsonError::Syntax(e.to_string()))?;
+        if !is_modulus_of::<B>(&description.modulus) {
+            return Err(ProofJsonError::FieldMismatch(description.modulus));
+        }
+        let instance_hash = parse_bytes(&description.instance_hash).ok_or(
+            ProofJsonError::InvalidInstanceHash(description.instance_hash),
+        )?;
+        let round_messages = description
+            .rounds
+            .iter()
+            .map(|message| message.iter().map(|e| hex_to_element(e)).collect())
+            .collect::<Result<_, _>>()?;
+        Ok(FieldProof {
+            version: description.version,
+            claimed_sum: hex_to_prime(&description.claimed_sum)?,
+            instance_hash,
+            round_messages,
+        })
+    }
+}
+
+/// The element of 'E' of the coordinates 'coordinates' over its prime field.
+fn hex_to_element<E: Field>(coordinates: &[String]) -> Result<E, ProofJsonError> {
+    let elements = coordinates
+        .iter()
+        .map(|hex| hex_to_prime(hex))
+        .collect::<Result<Vec<_>, _>>()?;
+    E::from_base_prime_field_elems(&elements)
+        .ok_or_else(|| ProofJsonError::InvalidFieldElement(coordinates.join(",")))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parser;
+
+    fn description(field: &str) -> String {
+        format!(
+            r#"{{"field": "{}", "num_vars": 3, "factors": [
+                [{{"coeff": 1, "vars": [[0, 1], [2, 1]]}}, {{"coeff": -7, "vars": [[1, 1]]}}],
+                [{{"coeff": 2, "vars": [[0, 1]]}}, {{"coeff": 5}}]
+            ]}}"#,
+            field
+        )
+    }
+
+    #[test]
+    fn test_fields() {
+        for field in DynField::ALL {
+            let instance = DynInstance::from_json(&description(field.id())).unwrap();
+            assert_eq!(instance.field(), field);
+            assert_eq!(instance.num_vars(), Some(3));
+            let proof = instance.prove();
+            assert_eq!(proof.field(), field);
+            assert_eq!(instance.verify(&proof), Ok(()));
+            let decoded = DynProof::from_bytes(field, &proof.to_bytes()).unwrap();
+            assert_eq!(decoded, proof);
+            let decoded = DynProof::from_json(field, &proof.to_json()).unwrap();
+            assert_eq!(decoded, proof);
+        }
+    }
+
+    #[test]
+    fn test_field256_instance() {
+        let json = description("field256");
+        let instance = DynInstance::from_json(&json).unwrap();
+        let poly = parser::from_json(&json).unwrap();
+        assert_eq!(instance, DynInstance::Field256(poly.clone()));
+        assert_eq!(instance.prove(), DynProof::Field256(prove(&poly)));
+        // The field is Field256 when the description does not declare one.
+        let undeclared = json.replace(r#""field": "field256", "#, "");
+        assert_eq!(DynInstance::from_json(&undeclared).unwrap(), instance);
+        assert!(matches!(
+            parser::from_json(&description("goldilocks")),
+            Err(ParseError::UnexpectedField(DynField::Goldilocks))
+        ));
+        assert!(matches!(
+            DynInstance::from_json(&description("bn254")),
+            Err(ParseError::UnknownField(_))
+        ));
+    }
+
+    #[test]
+    fn test_rejected_proofs() {
+        let goldilocks = DynInstance::from_json(&description("goldilocks")).unwrap();
+        let bls = DynInstance::from_json(&description("bls12-381")).unwrap();
+        assert_eq!(
+            goldilocks.verify(&bls.prove()),
+            Err(DynSumCheckError::FieldMismatch {
+                instance: DynField::Goldilocks,
+                proof: DynField::Bls12_381
+            })
+        );
+
+        let DynProof::Goldilocks(mut proof) = goldilocks.prove() else {
+            unreachable!()
+        };
+        proof.round_messages[1][0] += Field64Ext2::from(1u64);
+        assert!(matches!(
+            goldilocks.verify(&DynProof::Goldilocks(proof.clone())),
+            Err(DynSumCheckError::Goldilocks(
+                SumCheckError::IntermediateCheckFailed { round: 1, .. }
+            ))
+        ));
+        proof.claimed_sum += Field64::from(1u64);
+        assert_eq!(
+            goldilocks.verify(&DynProof::Goldilocks(proof)),
+            Err(DynSumCheckError::Goldilocks(
+                SumCheckError::InstanceMismatch
+            ))
+        );
+
+        // A proof over another field is not read as one over the field of the instance.
+        let json = bls.prove().to_json();
+        assert!(matches!(
+            DynProof::from_json(DynField::Goldilocks, &json),
+            Err(ProofJsonError::FieldMismatch(_))
+        ));
+    }
+}
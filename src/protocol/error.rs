@@ -0,0 +1,79 @@
+use crate::field::Field256 as F;
+use thiserror::Error;
+
+/// Reasons for which the verifier rejects the prover's claim. The running evaluations live in 'E',
+/// the field of the challenges.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SumCheckError<E = F> {
+    /// The prover sent a round polynomial with the wrong number of evaluation points.
+    #[error("round {round}: expected a polynomial description of {expected} points, got {got}")]
+    DegreeMismatch {
+        round: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// The round polynomial does not agree with the running evaluation, i.e. p(0) + p(1) != lhs.
+    #[error("round {round}: p(0) + p(1) = {rhs} does not match the running evaluation {lhs}")]
+    IntermediateCheckFailed { round: usize, lhs: E, rhs: E },
+    /// The polynomial evaluated at the random point disagrees with the prover's last message.
+    #[error("the polynomial evaluated at the random point does not match the last round")]
+    FinalCheckFailed,
+    /// The opening proof of a committed factor at the random point is invalid.
+    #[error("the opening proof of factor {factor} is invalid")]
+    OpeningCheckFailed { factor: usize },
+    /// A revealed round polynomial does not match the hash chain sent before it.
+    #[error("round {round}: the polynomial does not match its commitment")]
+    RoundCommitmentMismatch { round: usize },
+    /// The number of rounds played differs from the number of variables of the polynomial.
+    #[error("expected {expected} rounds (one per variable), got {got}")]
+    VariableCountMismatch { expected: usize, got: usize },
+    /// The proof is of another version of the protocol than
+    /// [`PROTOCOL_VERSION`](crate::protocol::PROTOCOL_VERSION).
+    #[error("unsupported proof version {version}")]
+    UnsupportedVersion { version: u8 },
+    /// The proof is for another polynomial or another claimed sum than those it is checked
+    /// against.
+    #[error("the proof is for another instance")]
+    InstanceMismatch,
+    /// The proof does not carry one auxiliary evaluation per round.
+    #[error("expected {expected} auxiliary evaluations (one per round), got {got}")]
+    AuxiliaryCountMismatch { expected: usize, got: usize },
+    /// The round polynomial does not take the auxiliary evaluation at the auxiliary node, see
+    /// [`prove_with_auxiliary_node`](crate::protocol::prove_with_auxiliary_node).
+    #[error("round {round}: the polynomial does not match its auxiliary evaluation")]
+    AuxiliaryCheckFailed { round: usize },
+    /// The run was aborted through its [`CancellationToken`](crate::protocol::CancellationToken).
+    #[error("the run was cancelled")]
+    Cancelled,
+}
+
+/// Reasons for which the protocol cannot be set up for an instance.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum SetupError {
+    /// The field is too small for the number of variables and the degree of the instance.
+    #[error("the protocol has {security_bits:.1} bits of security, below the required {threshold_bits}")]
+    InsufficientSecurity {
+        security_bits: f64,
+        threshold_bits: f64,
+    },
+    /// The product has no factor.
+    #[error("the product has no factor")]
+    EmptyProduct,
+    /// A factor does not have the same number of variables as the first one.
+    #[error("factor {factor} has {got} variables, expected {expected}")]
+    VariableCountMismatch {
+        factor: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// A factor has a variable of degree more than one.
+    #[error("factor {factor} has degree {degree} in x{var}, the factors must be multilinear")]
+    NotMultilinear {
+        factor: usize,
+        var: usize,
+        degree: usize,
+    },
+    /// The polynomial does not sum to the sum asserted for it over the hypercube.
+    #[error("the polynomial sums to {actual_sum}, not to the claimed {claimed_sum}")]
+    FalseClaim { claimed_sum: F, actual_sum: F },
+}
@@ -0,0 +1,275 @@
+//! Sum-check over a small field, such as `Field64` or `Field31`, whose size alone gives too little
+//! soundness (see [`security_bits`](crate::security::security_bits)).
+//!
+//! The evaluation tables stay in the base field 'B', while the challenges, the round messages and
+//! the running evaluations live in an extension 'E' of it. Only the first round is computed in 'B':
+//! binding the first variable to a challenge moves the tables to 'E'.
+
+use ark_ff::Field;
+use ark_std::vec::Vec;
+use rand::{CryptoRng, RngCore};
+
+use crate::field::{batch, Subfield};
+use crate::polynomial::evaluate_table;
+use crate::protocol::error::SumCheckError;
+#[cfg(feature = "prover")]
+use crate::protocol::prover::{fold_in_place, reduce_map, round_evaluations};
+use crate::protocol::Verifier;
+
+#[cfg(feature = "prover")]
+pub struct ExtensionProverState<B, E> {
+    last_round: usize,
+    num_vars: usize,
+    /// The tables in the base field, until the first variable is bound.
+    base_maps: Vec<Vec<B>>,
+    maps: Vec<Vec<E>>,
+}
+
+#[cfg(feature = "prover")]
+pub struct ExtensionProver {}
+
+#[cfg(feature = "prover")]
+impl ExtensionProver {
+    /// Sums over the hypercube the product of the multilinear polynomials given by their
+    /// evaluation tables 'maps'. The sum is in the base field.
+    pub fn claim_sum<B: Subfield<E>, E: Field>(
+        num_vars: usize,
+        maps: Vec<Vec<B>>,
+    ) -> (B, ExtensionProverState<B, E>) {
+        let claim = (0..1 << num_vars)
+            .map(|pt| maps.iter().map(|m| m[pt]).product::<B>())
+            .sum();
+        let state = ExtensionProverState {
+            last_round: 0,
+            num_vars,
+            base_maps: maps,
+            maps: Vec::new(),
+        };
+        (claim, state)
+    }
+
+    pub fn round_phase_1<B: Subfield<E>, E: Field>(
+        state: ExtensionProverState<B, E>,
+    ) -> (Vec<E>, ExtensionProverState<B, E>) {
+        let num_vars = state.num_vars - state.last_round - 1;
+        let evaluations = if state.last_round == 0 {
+            let maps: Vec<&[B]> = state.base_maps.iter().map(|map| &map[..]).collect();
+            round_evaluations(num_vars, &maps, &[], maps.len() + 1)
+                .into_iter()
+                .map(B::lift)
+                .collect()
+        } else {
+            let maps: Vec<&[E]> = state.maps.iter().map(|map| &map[..]).collect();
+            round_evaluations(num_vars, &maps, &[], maps.len() + 1)
+        };
+        (evaluations, state)
+    }
+
+    /// Binds the variable of the round to 'r'. The first round moves the tables to 'E', the next
+    /// ones fold them in place.
+    pub fn round_phase_2<B: Subfield<E>, E: Field>(
+        mut state: ExtensionProverState<B, E>,
+        r: E,
+    ) -> ExtensionProverState<B, E> {
+        let num_vars = state.num_vars - state.last_round - 1;
+        if state.last_round == 0 {
+            state.maps = state
+                .base_maps
+                .iter()
+                .map(|map| reduce_map(num_vars, r, map))
+                .collect();
+            state.base_maps = Vec::new();
+        } else {
+            for map in &mut state.maps {
+                fold_in_place(num_vars, r, map);
+            }
+        }
+        state.last_round += 1;
+        state
+    }
+}
+
+/// State of the verifier, which draws its challenges in 'E' from 'rng'.
+pub struct ExtensionVerifierState<B, E, R: RngCore + CryptoRng> {
+    pub last_round: usize,
+    pub num_vars: usize,
+    pub num_polys: usize,
+    /// The evaluation tables of the factors, used for the final check.
+    pub maps: Vec<Vec<B>>,
+    pub running_eval: E,
+    pub randomness: Vec<E>,
+    pub rng: R,
+}
+
+pub struct ExtensionVerifier {}
+
+type RoundResult<B, E, R> = Result<(E, ExtensionVerifierState<B, E, R>), SumCheckError<E>>;
+
+impl ExtensionVerifier {
+    pub fn initialize<B: Subfield<E>, E: Field, R: RngCore + CryptoRng>(
+        num_vars: usize,
+        maps: Vec<Vec<B>>,
+        claimed: B,
+        rng: R,
+    ) -> ExtensionVerifierState<B, E, R> {
+        ExtensionVerifierState {
+            last_round: 0,
+            num_vars,
+            num_polys: maps.len(),
+            maps,
+            running_eval: claimed.lift(),
+            randomness: Vec::new(),
+            rng,
+        }
+    }
+
+    /// Same as [`Verifier::round`], with the message and the challenge in the extension.
+    pub fn round<B: Subfield<E>, E: Field, R: RngCore + CryptoRng>(
+        mut state: ExtensionVerifierState<B, E, R>,
+        mvml_desc: Vec<E>,
+    ) -> RoundResult<B, E, R> {
+        let expected = state.num_polys + 1;
+        let intermediate = match Verifier::evaluate_intermediate(&mvml_desc) {
+            Some(intermediate) if mvml_desc.len() == expected => intermediate,
+            _ => {
+                return Err(SumCheckError::DegreeMismatch {
+                    round: state.last_round,
+                    expected,
+                    got: mvml_desc.len(),
+                })
+            }
+        };
+        if intermediate != state.running_eval {
+            return Err(SumCheckError::IntermediateCheckFailed {
+                round: state.last_round,
+                lhs: state.running_eval,
+                rhs: intermediate,
+            });
+        }
+        let r = E::rand(&mut state.rng);
+        state.randomness.push(r);
+        let new_state = ExtensionVerifierState {
+            last_round: state.last_round + 1,
+            running_eval: Verifier::evaluate_at_random_point(&mvml_desc, r),
+            ..state
+        };
+        Ok((r, new_state))
+    }
+
+    /// Evaluates the factors at the random point, and compares their product with the running
+    /// evaluation. Also returns the random point.
+    pub fn sanity_check<B: Subfield<E>, E: Field, R: RngCore + CryptoRng>(
+        state: ExtensionVerifierState<B, E, R>,
+    ) -> (Result<(), SumCheckError<E>>, Vec<E>) {
+        if state.randomness.len() != state.num_vars {
+            let err = SumCheckError::VariableCountMismatch {
+                expected: state.num_vars,
+                got: state.randomness.len(),
+            };
+            return (Err(err), state.randomness);
+        }
+        let product: E = state
+            .maps
+            .iter()
+            .map(|map| evaluate_base_table(map, &state.randomness))
+            .product();
+        if product != state.running_eval {
+            return (Err(SumCheckError::FinalCheckFailed), state.randomness);
+        }
+        (Ok(()), state.randomness)
+    }
+}
+
+/// Runs the protocol on the product of the multilinear polynomials given by their evaluation
+/// tables 'maps' in 'B', with challenges in 'E' drawn from 'rng'. Returns the random point when
+/// the verifier accepts.
+#[cfg(feature = "prover")]
+pub fn orchestrate_extension_protocol<B, E, R>(
+    num_vars: usize,
+    maps: Vec<Vec<B>>,
+    rng: R,
+) -> Result<Vec<E>, SumCheckError<E>>
+where
+    B: Subfield<E>,
+    E: Field,
+    R: RngCore + CryptoRng,
+{
+    let (claimed_sum, mut prover_state) = ExtensionProver::claim_sum(num_vars, maps.clone());
+    let mut verifier_state = ExtensionVerifier::initialize(num_vars, maps, claimed_sum, rng);
+    for _ in 0..num_vars {
+        let (mvml_desc, state) = ExtensionProver::round_phase_1(prover_state);
+        let (r, state_v) = ExtensionVerifier::round(verifier_state, mvml_desc)?;
+        prover_state = ExtensionProver::round_phase_2(state, r);
+        verifier_state = state_v;
+    }
+    let (result, point) = ExtensionVerifier::sanity_check(verifier_state);
+    result.map(|()| point)
+}
+
+/// Evaluates the multilinear extension of a table in the base field at a point in the extension.
+fn evaluate_base_table<B: Subfield<E>, E: Field>(table: &[B], point: &[E]) -> E {
+    match point.split_first() {
+        Some((&r, rest)) => {
+            let (low, high) = table.split_at(1 << rest.len());
+            evaluate_table(&batch::fold(low, high, r), rest)
+        }
+        None => table[0].lift(),
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::field::{Field31, Field31Ext4, Field64, Field64Ext2};
+    use ark_std::UniformRand;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn random_tables<B: Field>(num_vars: usize, num_polys: usize, rng: &mut StdRng) -> Vec<Vec<B>> {
+        (0..num_polys)
+            .map(|_| (0..1 << num_vars).map(|_| B::rand(rng)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_completeness() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let maps = random_tables::<Field64>(4, 3, &mut rng);
+        let point = orchestrate_extension_protocol::<_, Field64Ext2, _>(4, maps, rng).unwrap();
+        assert_eq!(point.len(), 4);
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let maps = random_tables::<Field31>(5, 2, &mut rng);
+        assert!(orchestrate_extension_protocol::<_, Field31Ext4, _>(5, maps, rng).is_ok());
+    }
+
+    #[test]
+    fn test_evaluate_base_table() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let table = random_tables::<Field31>(3, 1, &mut rng).remove(0);
+        let point: Vec<Field31Ext4> = (0..3).map(|_| Field31Ext4::rand(&mut rng)).collect();
+        let lifted: Vec<Field31Ext4> = table.iter().map(|&b| b.lift()).collect();
+        assert_eq!(
+            evaluate_base_table(&table, &point),
+            evaluate_table(&lifted, &point)
+        );
+    }
+
+    #[test]
+    fn test_wrong_claim() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let maps = random_tables::<Field64>(3, 2, &mut rng);
+        let (claimed_sum, prover_state) = ExtensionProver::claim_sum(3, maps.clone());
+        let verifier_state = ExtensionVerifier::initialize::<_, Field64Ext2, _>(
+            3,
+            maps,
+            claimed_sum + Field64::ONE,
+            rng,
+        );
+        let (mvml_desc, _) = ExtensionProver::round_phase_1(prover_state);
+        assert!(matches!(
+            ExtensionVerifier::round(verifier_state, mvml_desc),
+            Err(SumCheckError::IntermediateCheckFailed { round: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,114 @@
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use rand::{CryptoRng, Error, RngCore};
+use sha2::{Digest, Sha256};
+
+use crate::field::Field256 as F;
+
+/// A SHA-256 based Fiat–Shamir transcript.
+///
+/// The prover's messages are appended to the transcript, and the verifier's challenges are then
+/// squeezed out of it through its `RngCore` implementation, so that the transcript can be used as
+/// the randomness source of the verifier.
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha256,
+}
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Transcript {
+            hasher: Sha256::new(),
+        };
+        transcript.append_message(b"domain", label);
+        transcript
+    }
+
+    /// Appends a labelled message. The length of the message is absorbed along with it, so that
+    /// the encoding of consecutive messages is unambiguous.
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((message.len() as u64).to_le_bytes());
+        self.hasher.update(message);
+    }
+
+    pub fn append_field_elements(&mut self, label: &[u8], elements: &[F]) {
+        let mut bytes = Vec::new();
+        elements.serialize_compressed(&mut bytes).unwrap();
+        self.append_message(label, &bytes);
+    }
+}
+
+/// A Fiat–Shamir transcript absorbing the prover's messages, from which the verifier's challenges
+/// are squeezed.
+pub trait FiatShamirTranscript: RngCore + CryptoRng {
+    fn append_field_elements(&mut self, label: &'static [u8], elements: &[F]);
+}
+
+impl FiatShamirTranscript for Transcript {
+    fn append_field_elements(&mut self, label: &'static [u8], elements: &[F]) {
+        Transcript::append_field_elements(self, label, elements)
+    }
+}
+
+impl RngCore for Transcript {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Squeezes bytes out of the transcript. The squeezed bytes are absorbed back, so that two
+    /// consecutive squeezes give different outputs.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(32) {
+            let mut squeeze = self.hasher.clone();
+            squeeze.update(b"squeeze");
+            let digest = squeeze.finalize();
+            self.hasher.update(digest);
+            chunk.copy_from_slice(&digest[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Transcript {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_challenges_depend_on_messages() {
+        let mut t1 = Transcript::new(b"test");
+        let mut t2 = Transcript::new(b"test");
+        t1.append_field_elements(b"message", &[F::from(1), F::from(2)]);
+        t2.append_field_elements(b"message", &[F::from(1), F::from(2)]);
+        let c1 = F::rand(&mut t1);
+        assert_eq!(c1, F::rand(&mut t2));
+        assert_ne!(c1, F::rand(&mut t1));
+
+        let mut t3 = Transcript::new(b"test");
+        t3.append_field_elements(b"message", &[F::from(1), F::from(3)]);
+        assert_ne!(c1, F::rand(&mut t3));
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        let mut t1 = Transcript::new(b"domain one");
+        let mut t2 = Transcript::new(b"domain two");
+        assert_ne!(F::rand(&mut t1), F::rand(&mut t2));
+    }
+}
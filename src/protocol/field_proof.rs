@@ -0,0 +1,143 @@
+//! Non-interactive sum-check over any prime field, such as one defined with
+//! [`define_sumcheck_field!`](crate::define_sumcheck_field), of any number of limbs.
+//!
+//! The evaluation tables are in the field 'B' of the instance, and the challenges and the round
+//! messages in an extension 'E' of it, which may be 'B' itself when it is large enough (see
+//! [`security_bits`](crate::security::security_bits)). The challenges are drawn from the SHA-256
+//! transcript of [`prove`](crate::protocol::prove), which absorbs the version, the
+//! [`instance_hash`] of the statement, whose modulus identifies the field, and the claimed sum
+//! before the rounds.
+
+use ark_ff::{Field, PrimeField};
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+};
+use ark_std::vec::Vec;
+
+use crate::field::Subfield;
+use crate::polynomial::evaluate_on_hypercube;
+use crate::protocol::fiat_shamir::Transcript;
+use crate::protocol::proof::{checked_num_vars, PROTOCOL_LABEL, PROTOCOL_VERSION};
+use crate::protocol::statement::{instance_hash, read_hash, InstanceHash};
+#[cfg(feature = "prover")]
+use crate::protocol::ExtensionProver;
+use crate::protocol::{ExtensionVerifier, SumCheckError};
+
+/// A product of multilinear polynomials over the field 'G'.
+pub type FieldProduct<G> = Vec<SparsePolynomial<G, SparseTerm>>;
+
+/// A non-interactive proof for an instance over the prime field 'B', with the challenges and the
+/// round messages in its extension 'E'.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize)]
+pub struct FieldProof<B: PrimeField, E: Field> {
+    pub version: u8,
+    pub claimed_sum: B,
+    pub instance_hash: InstanceHash,
+    pub round_messages: Vec<Vec<E>>,
+}
+
+// Not derived, to read the instance hash with `read_hash`, as for `Proof`.
+impl<B: PrimeField, E: Field> Valid for FieldProof<B, E> {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.claimed_sum.check()?;
+        self.round_messages.check()
+    }
+}
+
+impl<B: PrimeField, E: Field> CanonicalDeserialize for FieldProof<B, E> {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        Ok(FieldProof {
+            version: u8::deserialize_with_mode(&mut reader, compress, validate)?,
+            claimed_sum: B::deserialize_with_mode(&mut reader, compress, validate)?,
+            instance_hash: read_hash(&mut reader)?,
+            round_messages: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+        })
+    }
+}
+
+/// Same as [`prove`](crate::protocol::prove), for a product over 'B' with the challenges in 'E'.
+/// The factors must have the same variables.
+#[cfg(feature = "prover")]
+pub fn prove_in_field<B, E>(poly: &[SparsePolynomial<B, SparseTerm>]) -> FieldProof<B, E>
+where
+    B: PrimeField + Subfield<E>,
+    E: Field,
+{
+    let num_vars = common_num_vars(poly).unwrap();
+    let tables = poly.iter().map(evaluate_on_hypercube).collect();
+    let (claimed_sum, mut state) = ExtensionProver::claim_sum::<B, E>(num_vars, tables);
+    let instance_hash = instance_hash(poly, claimed_sum);
+    let mut transcript = statement_transcript(&instance_hash, claimed_sum);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let (message, next) = ExtensionProver::round_phase_1(state);
+        absorb(&mut transcript, b"round", &message);
+        state = ExtensionProver::round_phase_2(next, E::rand(&mut transcript));
+        round_messages.push(message);
+    }
+    FieldProof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+    }
+}
+
+/// Verifies a proof produced by [`prove_in_field`], and returns the random point of the rounds.
+pub fn verify_in_field<B, E>(
+    poly: &[SparsePolynomial<B, SparseTerm>],
+    proof: &FieldProof<B, E>,
+) -> Result<Vec<E>, SumCheckError<E>>
+where
+    B: PrimeField + Subfield<E>,
+    E: Field,
+{
+    if proof.version != PROTOCOL_VERSION {
+        return Err(SumCheckError::UnsupportedVersion {
+            version: proof.version,
+        });
+    }
+    if proof.instance_hash != instance_hash(poly, proof.claimed_sum) {
+        return Err(SumCheckError::InstanceMismatch);
+    }
+    let num_vars = checked_num_vars(poly)?;
+    let tables = poly.iter().map(evaluate_on_hypercube).collect();
+    let transcript = statement_transcript(&proof.instance_hash, proof.claimed_sum);
+    let mut state =
+        ExtensionVerifier::initialize::<B, E, _>(num_vars, tables, proof.claimed_sum, transcript);
+    for message in &proof.round_messages {
+        absorb(&mut state.rng, b"round", message);
+        state = ExtensionVerifier::round(state, message.clone())?.1;
+    }
+    let (result, point) = ExtensionVerifier::sanity_check(state);
+    result.map(|()| point)
+}
+
+/// The number of variables of the factors, if they all have the same.
+#[cfg(any(feature = "prover", feature = "parser"))]
+pub(crate) fn common_num_vars<G: Field>(poly: &[SparsePolynomial<G, SparseTerm>]) -> Option<usize> {
+    let num_vars = poly.first()?.num_vars;
+    poly.iter()
+        .all(|p| p.num_vars == num_vars)
+        .then_some(num_vars)
+}
+
+/// The transcript of a proof, after the version and the statement.
+fn statement_transcript<B: PrimeField>(instance_hash: &InstanceHash, claimed_sum: B) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_message(b"version", &[PROTOCOL_VERSION]);
+    transcript.append_message(b"instance", instance_hash);
+    absorb(&mut transcript, b"claimed_sum", &[claimed_sum]);
+    transcript
+}
+
+fn absorb<G: CanonicalSerialize>(transcript: &mut Transcript, label: &[u8], elements: &[G]) {
+    let mut bytes = Vec::new();
+    elements.serialize_compressed(&mut bytes).unwrap();
+    transcript.append_message(label, &bytes);
+}
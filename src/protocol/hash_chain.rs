@@ -0,0 +1,156 @@
+//! Commitments to the round polynomials, for messages revealed one at a time.
+//!
+//! The prover sends the hash chain of its round polynomials before revealing them, the link of
+//! round i being H(link of round i - 1 || polynomial of round i), starting from a hash of the
+//! claimed sum. A verifier receiving the polynomials over an unreliable transport then checks each
+//! of them against its link as it arrives, so that a polynomial substituted after the fact is
+//! caught at its round.
+
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::field::Field256 as F;
+use crate::polynomial::PolynomialDescription;
+use crate::protocol::SumCheckError;
+
+/// A link of the hash chain.
+pub type RoundDigest = [u8; 32];
+
+/// Domain separator of the hash chain.
+const CHAIN_LABEL: &[u8] = b"sum-check-round-chain";
+
+fn chain_start(claimed_sum: F) -> RoundDigest {
+    let mut hasher = Sha256::new();
+    hasher.update(CHAIN_LABEL);
+    absorb(&mut hasher, &[claimed_sum]);
+    hasher.finalize().into()
+}
+
+fn chain_link(previous: &RoundDigest, message: &[F]) -> RoundDigest {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    absorb(&mut hasher, message);
+    hasher.finalize().into()
+}
+
+fn absorb(hasher: &mut Sha256, elements: &[F]) {
+    let mut bytes = Vec::new();
+    elements.serialize_compressed(&mut bytes).unwrap();
+    hasher.update(bytes);
+}
+
+/// Computes the hash chain of the round polynomials 'messages' of a proof of 'claimed_sum', with
+/// one link per round.
+pub fn round_hash_chain(claimed_sum: F, messages: &[PolynomialDescription]) -> Vec<RoundDigest> {
+    let mut previous = chain_start(claimed_sum);
+    messages
+        .iter()
+        .map(|message| {
+            previous = chain_link(&previous, message);
+            previous
+        })
+        .collect()
+}
+
+/// Checks round polynomials, revealed in order, against the hash chain sent beforehand.
+#[derive(Debug, Clone)]
+pub struct RoundRevealer {
+    chain: Vec<RoundDigest>,
+    previous: RoundDigest,
+    round: usize,
+}
+
+impl RoundRevealer {
+    pub fn new(claimed_sum: F, chain: Vec<RoundDigest>) -> Self {
+        RoundRevealer {
+            chain,
+            previous: chain_start(claimed_sum),
+            round: 0,
+        }
+    }
+
+    /// The round of the next polynomial to reveal.
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    /// Checks the polynomial of the next round against its link of the chain. A rejected
+    /// polynomial leaves the revealer at the same round.
+    pub fn reveal(&mut self, message: &[F]) -> Result<(), SumCheckError> {
+        let round = self.round;
+        let link = chain_link(&self.previous, message);
+        if self.chain.get(round) != Some(&link) {
+            return Err(SumCheckError::RoundCommitmentMismatch { round });
+        }
+        self.previous = link;
+        self.round += 1;
+        Ok(())
+    }
+
+    /// Checks that all the committed polynomials were revealed.
+    pub fn finish(&self) -> Result<(), SumCheckError> {
+        if self.round != self.chain.len() {
+            return Err(SumCheckError::VariableCountMismatch {
+                expected: self.chain.len(),
+                got: self.round,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<PolynomialDescription> {
+        [[1, 2, 3], [4, 5, 6], [7, 8, 9]]
+            .map(|m| m.map(|v: u64| F::from(v)).to_vec())
+            .to_vec()
+    }
+
+    #[test]
+    fn test_reveal() {
+        let chain = round_hash_chain(F::from(3), &messages());
+        assert_eq!(chain.len(), 3);
+        let mut revealer = RoundRevealer::new(F::from(3), chain.clone());
+        for message in messages() {
+            assert_eq!(revealer.reveal(&message), Ok(()));
+        }
+        assert_eq!(revealer.finish(), Ok(()));
+
+        // The chain binds the claimed sum.
+        let mut revealer = RoundRevealer::new(F::from(4), chain);
+        assert_eq!(
+            revealer.reveal(&messages()[0]),
+            Err(SumCheckError::RoundCommitmentMismatch { round: 0 })
+        );
+    }
+
+    #[test]
+    fn test_tampered_reveal() {
+        let chain = round_hash_chain(F::from(3), &messages());
+        let mut revealer = RoundRevealer::new(F::from(3), chain);
+        revealer.reveal(&messages()[0]).unwrap();
+        let mut tampered = messages()[1].clone();
+        tampered[2] += F::from(1);
+        assert_eq!(
+            revealer.reveal(&tampered),
+            Err(SumCheckError::RoundCommitmentMismatch { round: 1 })
+        );
+        // Out of order.
+        assert!(revealer.reveal(&messages()[2]).is_err());
+        assert_eq!(revealer.round(), 1);
+        assert_eq!(
+            revealer.finish(),
+            Err(SumCheckError::VariableCountMismatch {
+                expected: 3,
+                got: 1
+            })
+        );
+        revealer.reveal(&messages()[1]).unwrap();
+        revealer.reveal(&messages()[2]).unwrap();
+        assert!(revealer.reveal(&messages()[2]).is_err());
+    }
+}
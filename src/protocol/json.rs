@@ -0,0 +1,303 @@
+//! Canonical JSON encoding of a [`Proof`], for verifiers written in other languages, with the
+//! `parser` feature. Field elements are written as `0x`-prefixed big-endian hexadecimal strings of
+//! 64 lowercase digits, the round messages as arrays of field elements, and the hash of the
+//! instance and the digests of the round commitments as strings of 64 hexadecimal digits. For
+//! instance:
+//!
+//! ```json
+//! {
+//!   "version": 2,
+//!   "modulus": "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed",
+//!   "claimed_sum": "0x00000000000000000000000000000000000000000000000000000000000000fc",
+//!   "instance_hash": "3f1c…",
+//!   "rounds": [["0x…", "0x…", "0x…"], ["0x…", "0x…"]],
+//!   "round_commitments": []
+//! }
+//! ```
+//!
+//! The keys are written in this order, without whitespace. The version is the
+//! [`PROTOCOL_VERSION`](crate::protocol::PROTOCOL_VERSION) of the proof, which the verifier checks.
+//! The modulus identifies the field, and a proof for another field is rejected. When reading a
+//! proof, the hexadecimal digits may be in either case and the leading zeros may be omitted, but
+//! the elements must be smaller than the modulus.
+
+use ark_ff::PrimeField;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::protocol::{Proof, RoundDigest};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProofJsonError {
+    #[error("invalid syntax: {0}")]
+    Syntax(String),
+    #[error("the proof is over the field of modulus {0}")]
+    FieldMismatch(String),
+    #[error("invalid field element '{0}'")]
+    InvalidFieldElement(String),
+    #[error("invalid instance hash '{0}'")]
+    InvalidInstanceHash(String),
+    #[error("invalid round commitment '{0}'")]
+    InvalidCommitment(String),
+}
+
+#[derive(Serialize, Deserialize)]
+struct ProofDescription {
+    version: u8,
+    modulus: String,
+    claimed_sum: String,
+    instance_hash: String,
+    rounds: Vec<Vec<String>>,
+    #[serde(default)]
+    round_commitments: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    auxiliary_evaluations: Vec<String>,
+}
+
+impl Proof {
+    /// The canonical JSON encoding of the proof.
+    pub fn to_json(&self) -> String {
+        let description = ProofDescription {
+            version: self.version,
+            modulus: modulus_hex(),
+            claimed_sum: field_to_hex(self.claimed_sum),
+            instance_hash: bytes_to_hex(&self.instance_hash),
+            rounds: self
+                .round_messages
+                .iter()
+                .map(|message| message.iter().copied().map(field_to_hex).collect())
+                .collect(),
+            round_commitments: self
+                .round_commitments
+                .iter()
+                .map(|digest| bytes_to_hex(digest))
+                .collect(),
+            auxiliary_evaluations: self
+                .auxiliary_evaluations
+                .iter()
+                .copied()
+                .map(field_to_hex)
+                .collect(),
+        };
+        serde_json::to_string(&description).unwrap()
+    }
+
+    /// Reads a proof from its JSON encoding.
+    pub fn from_json(input: &str) -> Result<Proof, ProofJsonError> {
+        let description: ProofDescription =
+            serde_json::from_str(input).map_err(|e| ProofJsonError::Syntax(e.to_string()))?;
+        if !is_modulus_of::<F>(&description.modulus) {
+            return Err(ProofJsonError::FieldMismatch(description.modulus));
+        }
+        let instance_hash = parse_bytes(&description.instance_hash).ok_or(
+            ProofJsonError::InvalidInstanceHash(description.instance_hash),
+        )?;
+        let round_messages = description
+            .rounds
+            .iter()
+            .map(|message| message.iter().map(|e| hex_to_field(e)).collect())
+            .collect::<Result<_, _>>()?;
+        let round_commitments = description
+            .round_commitments
+            .iter()
+            .map(|digest| hex_to_digest(digest))
+            .collect::<Result<_, _>>()?;
+        Ok(Proof {
+            version: description.version,
+            claimed_sum: hex_to_field(&description.claimed_sum)?,
+            instance_hash,
+            round_messages,
+            round_commitments,
+            auxiliary_evaluations: description
+                .auxiliary_evaluations
+                .iter()
+                .map(|e| hex_to_field(e))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// The modulus of the field, as written in the documents.
+pub(crate) fn modulus_hex() -> String {
+    prime_modulus_hex::<F>()
+}
+
+/// Same as `modulus_hex`, for any prime field.
+pub(crate) fn prime_modulus_hex<P: PrimeField>() -> String {
+    limbs_to_hex(P::MODULUS.as_ref())
+}
+
+pub(crate) fn field_to_hex(element: F) -> String {
+    prime_to_hex(element)
+}
+
+/// Same as `field_to_hex`, for any prime field, with 16 digits per limb of the field.
+pub(crate) fn prime_to_hex<P: PrimeField>(element: P) -> String {
+    limbs_to_hex(element.into_bigint().as_ref())
+}
+
+fn limbs_to_hex(limbs: &[u64]) -> String {
+    let digits: String = limbs
+        .iter()
+        .rev()
+        .map(|limb| format!("{:016x}", limb))
+        .collect();
+    format!("0x{}", digits)
+}
+
+/// Reads the little-endian 'limbs' of a `0x`-prefixed hexadecimal string of at most 16 digits per
+/// limb.
+fn parse_limbs(hex: &str, limbs: &mut [u64]) -> Option<()> {
+    let num_digits = 16 * limbs.len();
+    let digits = hex.strip_prefix("0x")?;
+    if digits.is_empty()
+        || digits.len() > num_digits
+        || !digits.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    let padded = format!("{:0>1$}", digits, num_digits);
+    for (i, limb) in limbs.iter_mut().rev().enumerate() {
+        *limb = u64::from_str_radix(&padded[16 * i..16 * (i + 1)], 16).ok()?;
+    }
+    Some(())
+}
+
+pub(crate) fn hex_to_field(hex: &str) -> Result<F, ProofJsonError> {
+    hex_to_prime(hex)
+}
+
+/// Same as `hex_to_field`, for any prime field.
+pub(crate) fn hex_to_prime<P: PrimeField>(hex: &str) -> Result<P, ProofJsonError> {
+    let mut limbs = P::BigInt::default();
+    parse_limbs(hex, limbs.as_mut())
+        .and_then(|()| P::from_bigint(limbs))
+        .ok_or_else(|| ProofJsonError::InvalidFieldElement(hex.to_string()))
+}
+
+/// Whether 'hex' is the modulus of 'P', in any of the forms accepted for the field elements.
+pub(crate) fn is_modulus_of<P: PrimeField>(hex: &str) -> bool {
+    let mut limbs = P::BigInt::default();
+    parse_limbs(hex, limbs.as_mut()).is_some() && limbs == P::MODULUS
+}
+
+fn hex_to_digest(hex: &str) -> Result<RoundDigest, ProofJsonError> {
+    parse_bytes(hex).ok_or_else(|| ProofJsonError::InvalidCommitment(hex.to_string()))
+}
+
+/// The hexadecimal digits of 'bytes', without prefix.
+pub(crate) fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The bytes written by `bytes_to_hex`.
+pub(crate) fn parse_bytes<const N: usize>(hex: &str) -> Option<[u8; N]> {
+    if hex.len() != 2 * N || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use crate::protocol::{
+        prove, prove_with_auxiliary_node, prove_with_round_commitments, verify, PROTOCOL_VERSION,
+    };
+
+    #[test]
+    fn test_json_round_trip() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        for proof in [
+            prove(&poly),
+            prove_with_round_commitments(&poly),
+            prove_with_auxiliary_node(&poly),
+        ] {
+            let json = proof.to_json();
+            assert_eq!(Proof::from_json(&json), Ok(proof.clone()));
+            assert!(verify(&poly, &Proof::from_json(&json).unwrap()).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_json_encoding() {
+        let proof = Proof {
+            version: PROTOCOL_VERSION,
+            claimed_sum: F::from(252),
+            instance_hash: [0x01; 32],
+            round_messages: vec![vec![F::from(1), -F::from(1)]],
+            round_commitments: vec![[0xab; 32]],
+            auxiliary_evaluations: Vec::new(),
+        };
+        let json = proof.to_json();
+        let modulus = "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+        let expected = format!(
+            r#"{{"version":2,"modulus":"{}","claimed_sum":"0x{:0>64}","instance_hash":"{}","rounds":[["0x{:0>64}","{}"]],"round_commitments":["{}"]}}"#,
+            modulus,
+            "fc",
+            "01".repeat(32),
+            "1",
+            "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffec",
+            "ab".repeat(32)
+        );
+        assert_eq!(json, expected);
+
+        // Short and uppercase digits are accepted.
+        let lenient = format!(
+            r#"{{"version":1,"modulus":"{}","claimed_sum":"0xFC","instance_hash":"{}","rounds":[["0x1","0x0"]]}}"#,
+            modulus,
+            "AB".repeat(32)
+        );
+        let decoded = Proof::from_json(&lenient).unwrap();
+        assert_eq!(decoded.claimed_sum, F::from(252));
+        assert_eq!(decoded.instance_hash, [0xab; 32]);
+        assert!(decoded.round_commitments.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let json = prove(&parse_product("x0 * (x1 + 1)").unwrap()).to_json();
+        let modulus = "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+        assert!(matches!(
+            Proof::from_json(&json.replace(modulus, "0xffffffff00000001")),
+            Err(ProofJsonError::FieldMismatch(_))
+        ));
+        // The modulus itself is not a canonical element.
+        let hash = "00".repeat(32);
+        let claimed_sum = format!(r#""claimed_sum":"{}""#, modulus);
+        let json = format!(
+            r#"{{"version":1,"modulus":"{}",{},"instance_hash":"{}","rounds":[]}}"#,
+            modulus, claimed_sum, hash
+        );
+        assert_eq!(
+            Proof::from_json(&json),
+            Err(ProofJsonError::InvalidFieldElement(modulus.to_string()))
+        );
+        let json = format!(
+            r#"{{"version":1,"modulus":"{}","claimed_sum":"0x1","instance_hash":"{}","rounds":[],"round_commitments":["ab"]}}"#,
+            modulus, hash
+        );
+        assert!(matches!(
+            Proof::from_json(&json),
+            Err(ProofJsonError::InvalidCommitment(_))
+        ));
+        let json = format!(
+            r#"{{"version":1,"modulus":"{}","claimed_sum":"0x1","instance_hash":"ab","rounds":[]}}"#,
+            modulus
+        );
+        assert!(matches!(
+            Proof::from_json(&json),
+            Err(ProofJsonError::InvalidInstanceHash(_))
+        ));
+        assert!(matches!(
+            Proof::from_json("{"),
+            Err(ProofJsonError::Syntax(_))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+//! Challenges derived from a [`merlin::Transcript`], so that the sum-check can be embedded in
+//! protocol stacks sharing a Merlin transcript.
+//!
+//! The proof is domain separated with the `dom-sep` label, as is customary with Merlin, then the
+//! claimed sum and each round polynomial are appended under the `claimed_sum` and `round` labels,
+//! and each challenge is drawn under the `challenge` label.
+
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use rand::{CryptoRng, Error, RngCore};
+
+use crate::field::Field256 as F;
+#[cfg(feature = "prover")]
+use crate::polynomial::evaluate_polynomial_on_hypercube;
+use crate::polynomial::ProductMLPolynomial;
+use crate::protocol::fiat_shamir::FiatShamirTranscript;
+#[cfg(feature = "prover")]
+use crate::protocol::proof::prove_tables;
+use crate::protocol::proof::{verify_with_transcript, Proof, PROTOCOL_LABEL};
+#[cfg(feature = "prover")]
+use crate::protocol::ProverStats;
+use crate::protocol::SumCheckError;
+
+/// Adapts a [`merlin::Transcript`] to the transcripts of the prover and the verifier.
+pub struct MerlinTranscript<'a> {
+    transcript: &'a mut merlin::Transcript,
+}
+
+impl<'a> MerlinTranscript<'a> {
+    pub fn new(transcript: &'a mut merlin::Transcript) -> Self {
+        MerlinTranscript { transcript }
+    }
+}
+
+impl FiatShamirTranscript for MerlinTranscript<'_> {
+    fn append_field_elements(&mut self, label: &'static [u8], elements: &[F]) {
+        let mut bytes = Vec::new();
+        elements.serialize_compressed(&mut bytes).unwrap();
+        self.transcript.append_message(label, &bytes);
+    }
+}
+
+impl RngCore for MerlinTranscript<'_> {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.transcript.challenge_bytes(b"challenge", dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for MerlinTranscript<'_> {}
+
+/// Same as [`prove`](crate::protocol::prove), with the challenges drawn from 'transcript', which
+/// may already hold the messages of earlier protocols.
+#[cfg(feature = "prover")]
+pub fn prove_with_merlin(poly: &ProductMLPolynomial, transcript: &mut merlin::Transcript) -> Proof {
+    let tables = poly.iter().map(evaluate_polynomial_on_hypercube).collect();
+    transcript.append_message(b"dom-sep", PROTOCOL_LABEL);
+    prove_tables(
+        poly,
+        tables,
+        MerlinTranscript::new(transcript),
+        &mut ProverStats::default(),
+    )
+}
+
+/// Verifies a proof produced by [`prove_with_merlin`] from a transcript in the same state as the
+/// prover's, and returns the random point of the final check.
+pub fn verify_with_merlin(
+    poly: &ProductMLPolynomial,
+    proof: &Proof,
+    transcript: &mut merlin::Transcript,
+) -> Result<Vec<F>, SumCheckError> {
+    transcript.append_message(b"dom-sep", PROTOCOL_LABEL);
+    verify_with_transcript(poly, proof, MerlinTranscript::new(transcript))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+
+    #[test]
+    fn test_merlin_proof() {
+        let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3)").unwrap();
+        let mut transcript = merlin::Transcript::new(b"test");
+        let proof = prove_with_merlin(&poly, &mut transcript);
+        assert_eq!(proof.claimed_sum, crate::protocol::prove(&poly).claimed_sum);
+
+        let mut transcript = merlin::Transcript::new(b"test");
+        let point = verify_with_merlin(&poly, &proof, &mut transcript).unwrap();
+        assert_eq!(point.len(), 3);
+        // Both transcripts end in the same state, for the protocols following the sum-check.
+        let mut prover_transcript = merlin::Transcript::new(b"test");
+        prove_with_merlin(&poly, &mut prover_transcript);
+        let (mut a, mut b) = ([0u8; 32], [0u8; 32]);
+        prover_transcript.challenge_bytes(b"next", &mut a);
+        transcript.challenge_bytes(b"next", &mut b);
+        assert_eq!(a, b);
+
+        // The challenges depend on the earlier messages of the transcript.
+        let mut transcript = merlin::Transcript::new(b"other");
+        assert!(verify_with_merlin(&poly, &proof, &mut transcript).is_err());
+    }
+}
@@ -1,53 +1,612 @@
+#[cfg(all(feature = "std", feature = "prover"))]
+use crate::commitment::MLCommitmentScheme;
 use crate::field::Field256 as F;
-use crate::polynomial::{get_num_vars, PolynomialDescription, ProductMLPolynomial};
-use crate::protocol::prover::{Prover, ProverState};
-use crate::protocol::verifier::{Verifier, VerifierState};
+#[cfg(feature = "prover")]
+use crate::polynomial::merge_point;
+use crate::polynomial::{
+    evaluations_to_coefficients, get_num_vars, PolynomialDescription, ProductInstance, ProductMLPolynomial,
+};
+#[cfg(feature = "prover")]
+pub(crate) use crate::protocol::prover::Prover;
+#[cfg(feature = "std")]
+use crate::security::{instance_security_bits, sampler_security_bits, security_bits};
+#[cfg(all(feature = "std", feature = "prover"))]
+use crate::security::DEFAULT_SECURITY_BITS;
+#[cfg(any(feature = "prover", feature = "async"))]
+use ark_std::vec;
+use ark_std::vec::Vec;
+#[cfg(feature = "prover")]
+use rand::{CryptoRng, RngCore, SeedableRng};
+#[cfg(feature = "prover")]
+use rand_chacha::ChaCha20Rng;
 
+#[cfg(feature = "gpu")]
+pub(crate) use crate::protocol::prover::fold_in_place;
+pub(crate) use crate::protocol::verifier::Verifier;
+
+#[cfg(feature = "mmap")]
+pub use crate::protocol::backend::{prove_out_of_core, MmapTable, ProverConfig};
+#[cfg(feature = "prover")]
+pub use crate::protocol::backend::{prove_with_backend, EvalBackend, LazyTable};
+#[cfg(feature = "prover")]
+pub use crate::protocol::batch::{prove_aggregated, prove_batch};
+pub use crate::protocol::batch::{verify_aggregated, verify_batch, AggregatedProof, BatchError};
+#[cfg(feature = "prover")]
+pub use crate::protocol::binary::prove_binary;
+pub use crate::protocol::binary::{interpolate_binary, verify_binary, BinaryProof};
+#[cfg(feature = "prover")]
+pub use crate::protocol::cancel::CancellationToken;
+#[cfg(feature = "async")]
+pub use crate::protocol::channel::{orchestrate_async, AsyncChannel, ChannelError};
+#[cfg(all(feature = "async", feature = "prover"))]
+pub use crate::protocol::channel::serve_prover_async;
+pub use crate::protocol::config::{FiatShamir, SumCheck, SumCheckBuilder};
+pub use crate::protocol::constant_time::DeferredChecks;
+#[cfg(feature = "parser")]
+pub use crate::protocol::dynamic::{DynInstance, DynProof, DynSumCheckError};
+pub use crate::protocol::error::{SetupError, SumCheckError};
+pub use crate::protocol::extension::{ExtensionVerifier, ExtensionVerifierState};
+#[cfg(feature = "prover")]
+pub use crate::protocol::extension::{
+    orchestrate_extension_protocol, ExtensionProver, ExtensionProverState,
+};
+pub use crate::protocol::fiat_shamir::{FiatShamirTranscript, Transcript};
+#[cfg(feature = "prover")]
+pub use crate::protocol::field_proof::prove_in_field;
+pub use crate::protocol::field_proof::{verify_in_field, FieldProduct, FieldProof};
+pub use crate::protocol::hash_chain::{round_hash_chain, RoundDigest, RoundRevealer};
+#[cfg(all(feature = "parser", feature = "prover"))]
+pub(crate) use crate::protocol::json::{
+    bytes_to_hex, field_to_hex, hex_to_field, modulus_hex, parse_bytes,
+};
+#[cfg(feature = "parser")]
+pub use crate::protocol::json::ProofJsonError;
+#[cfg(all(feature = "merlin", feature = "prover"))]
+pub use crate::protocol::merlin_transcript::prove_with_merlin;
+#[cfg(feature = "merlin")]
+pub use crate::protocol::merlin_transcript::{verify_with_merlin, MerlinTranscript};
+#[cfg(all(feature = "poseidon", feature = "prover"))]
+pub use crate::protocol::poseidon_transcript::prove_with_poseidon;
+pub use crate::protocol::observer::Observer;
+#[cfg(feature = "std")]
+pub use crate::protocol::observer::RoundTimer;
+#[cfg(feature = "poseidon")]
+pub use crate::protocol::poseidon_transcript::{poseidon_config, verify_with_poseidon, PoseidonTranscript};
+pub use crate::protocol::proof::{
+    verify, verify_constant_time, verify_selected, verify_vector_sum, verify_weighted,
+    CompressedProof, Proof, PROTOCOL_VERSION,
+};
+#[cfg(feature = "prover")]
+pub use crate::protocol::proof::{
+    prove, prove_cancellable, prove_selected, prove_vector_sum, prove_weighted,
+    prove_with_auxiliary_node, prove_with_progress, prove_with_round_commitments, prove_with_stats,
+};
+#[cfg(feature = "gpu")]
+pub use crate::protocol::proof::prove_with_gpu;
+pub(crate) use crate::protocol::proof::{absorb_statement, auxiliary_node, PROTOCOL_LABEL};
+#[cfg(feature = "prover")]
+pub use crate::protocol::progress::Progress;
+pub use crate::protocol::sampler::{
+    ChallengeSampler, ChallengeSpace, FullField, RejectionSampler, Subgroup,
+};
+pub use crate::protocol::statement::{
+    binary_instance_hash, instance_hash, instance_hash_elements, InstanceDigest, InstanceHash,
+};
+#[cfg(feature = "prover")]
+pub use crate::protocol::prover::{
+    EqFactor, Factor, MleHandle, ProverSnapshot, ProverState, SumCheckProver, VirtualPolynomial,
+    CHECKPOINT_VERSION,
+};
+#[cfg(feature = "prover")]
+pub use crate::protocol::stats::{OpCount, PhaseStats, ProverStats, RoundStats};
+#[cfg(feature = "prover")]
+pub use crate::protocol::typestate::{AwaitingChallenge, ProverPhase, ReadyToSend, TypedProver};
+pub use crate::protocol::typestate::{AwaitingMessage, ReadyToDecide, TypedVerifier, VerifierPhase};
+pub use crate::protocol::verifier::{
+    ChallengePolicy, DefaultRng, EvaluationClaim, InterpolationContext, ReplayableRng, SumCheckVerifier,
+    VerifierSnapshot, VerifierState,
+};
+#[cfg(feature = "r1cs")]
+pub use crate::protocol::verifier_gadget::{PoseidonTranscriptVar, SumCheckVerifierGadget};
+
+#[cfg(feature = "prover")]
+mod backend;
+mod batch;
+mod binary;
+#[cfg(feature = "prover")]
+mod cancel;
+#[cfg(feature = "async")]
+mod channel;
+mod config;
+mod constant_time;
+#[cfg(feature = "parser")]
+mod dynamic;
+mod error;
+mod extension;
+mod fiat_shamir;
+mod field_proof;
+mod hash_chain;
+#[cfg(feature = "parser")]
+mod json;
+#[cfg(feature = "merlin")]
+mod merlin_transcript;
+mod observer;
+#[cfg(feature = "poseidon")]
+mod poseidon_transcript;
+mod proof;
+#[cfg(feature = "prover")]
+mod progress;
+#[cfg(feature = "prover")]
 mod prover;
+mod sampler;
+#[cfg(feature = "prover")]
+mod stats;
+mod statement;
+mod typestate;
 mod verifier;
-mod rejection;
+#[cfg(feature = "r1cs")]
+mod verifier_gadget;
+
+/// How the prover describes the univariate round polynomials. The evaluations and the
+/// coefficients take d + 1 field elements for a polynomial of degree d, the compressed evaluations
+/// take d.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MessageFormat {
+    /// Evaluations at 0, 1, ..., d, which the verifier interpolates in O(d²) operations.
+    #[default]
+    Evaluations,
+    /// Monomial coefficients, from the constant one up, which the verifier evaluates by Horner's
+    /// rule in O(d) operations.
+    Coefficients,
+    /// Evaluations at 1, ..., d. The verifier recovers p(0) as its running evaluation minus p(1),
+    /// so that the consistency check of the round always holds, and the proof is one field element
+    /// shorter per round.
+    Compressed,
+}
+
+impl MessageFormat {
+    /// Describes in this format the polynomial given by its evaluations at 0, 1, ..., d.
+    pub(crate) fn encode(self, evaluations: PolynomialDescription) -> PolynomialDescription {
+        match self {
+            MessageFormat::Evaluations => evaluations,
+            MessageFormat::Coefficients => evaluations_to_coefficients(&evaluations),
+            MessageFormat::Compressed => evaluations[1..].to_vec(),
+        }
+    }
+}
 
+/// A message sent by the prover in one round: the univariate round polynomial, described by its
+/// evaluations at 0, 1, ..., d, or by its coefficients depending on the [`MessageFormat`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundMessage {
+    pub evaluations: PolynomialDescription,
+}
 
+/// Record of a run of the protocol.
 pub struct ProtocolTranscript {
-    _randomness: Vec<F>,
+    /// The point of the final check, i.e. the challenges with the fixed variables merged in. It is
+    /// empty if the verifier rejected a round.
+    pub point: Vec<F>,
+    /// The evaluation of the polynomial at `point` which the prover's messages claim, checked by
+    /// the verifier unless the final check is left to the caller.
+    pub final_evaluation: F,
     pub accept: bool,
+    /// The reason for rejecting the claim, if the verifier rejected.
+    pub error: Option<SumCheckError>,
+    /// Where the run failed and the values which disagreed, if the verifier rejected.
+    pub failure: Option<FailureReport>,
+    pub claimed_sum: F,
+    /// The format of the messages.
+    pub format: MessageFormat,
+    /// The messages of the prover, up to the first one rejected by the verifier.
+    pub messages: Vec<PolynomialDescription>,
+    /// The challenges of the verifier, one for each accepted message.
+    pub challenges: Vec<F>,
+}
+
+#[cfg(any(feature = "prover", feature = "async"))]
+impl ProtocolTranscript {
+    fn new(claimed_sum: F, format: MessageFormat) -> Self {
+        ProtocolTranscript {
+            point: vec![],
+            final_evaluation: F::from(0),
+            accept: false,
+            error: None,
+            failure: None,
+            claimed_sum,
+            format,
+            messages: vec![],
+            challenges: vec![],
+        }
+    }
+
+    fn reject(self, err: SumCheckError) -> Self {
+        ProtocolTranscript { error: Some(err), failure: Some(self.failure_report()), ..self }
+    }
+
+    fn conclude(self, result: Result<(), SumCheckError>, point: Vec<F>, final_evaluation: F) -> Self {
+        let failure = result.is_err().then(|| self.failure_report());
+        ProtocolTranscript {
+            point,
+            final_evaluation,
+            accept: result.is_ok(),
+            error: result.err(),
+            failure,
+            ..self
+        }
+    }
+
+    /// The report of a failure after the recorded messages, of which only the last one may have
+    /// been rejected. The running evaluations are recomputed from the accepted messages and their
+    /// challenges.
+    fn failure_report(&self) -> FailureReport {
+        let mut expected = self.claimed_sum;
+        for (message, &r) in self.messages.iter().zip(&self.challenges) {
+            let message = Verifier::decompress(self.format, expected, message.clone()).unwrap_or_default();
+            expected = Verifier::evaluate_message(self.format, &message, r);
+        }
+        let received = self.messages[self.challenges.len()..]
+            .first()
+            .and_then(|message| Verifier::decompress(self.format, expected, message.clone()))
+            .filter(|message| message.len() >= 2)
+            .and_then(|message| Verifier::round_sum(self.format, &message));
+        FailureReport { round: self.challenges.len(), expected, received }
+    }
+}
+
+/// Diagnostics of a rejected run of the protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailureReport {
+    /// The round of the rejected message, or the number of rounds when the run failed after the
+    /// last one, e.g. at the final check.
+    pub round: usize,
+    /// The running evaluation which p(0) + p(1) had to match: the claimed sum in the first round,
+    /// then the previous round polynomial at its challenge. After the last round, the evaluation
+    /// of the polynomial at the random point claimed by the prover.
+    pub expected: F,
+    /// p(0) + p(1) for the rejected message, if it has at least two entries. `None` when no
+    /// message was rejected.
+    pub received: Option<F>,
+}
+
+impl core::fmt::Display for FailureReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "round {}: expected {}", self.round, self.expected)?;
+        match self.received {
+            Some(received) => write!(f, ", received p(0) + p(1) = {}", received),
+            None => Ok(()),
+        }
+    }
+}
+
+/// An instance ready to be proven: the number of variables, the claimed sum, and the states of the
+/// prover and of the verifier.
+#[cfg(all(feature = "std", feature = "prover"))]
+pub type Instance = (usize, F, ProverState, VerifierState);
+
+/// Prepares the prover and the verifier for 'poly'. Fails if 'poly' is not a nonempty product of
+/// multilinear polynomials on the same variables, or if the protocol would have less than
+/// [`DEFAULT_SECURITY_BITS`] bits of security.
+///
+/// Both parties are set up in one process, for demonstrations and tests. In a deployment where
+/// they run apart, the prover calls [`SumCheckProver::setup`] and the verifier
+/// [`SumCheckVerifier::setup`] with the claimed sum it received, each checking the instance on its
+/// own.
+#[cfg(all(feature = "std", feature = "prover"))]
+pub fn setup_protocol(poly: &ProductMLPolynomial) -> Result<Instance, SetupError> {
+    let (claimed_sum, prover_state) = Prover::setup(poly)?;
+    let verifier_state = Verifier::setup(poly, claimed_sum)?;
+    Ok((prover_state.num_vars(), claimed_sum, prover_state, verifier_state))
+}
+
+/// Same as `setup_protocol`, requiring 'threshold_bits' bits of security.
+#[cfg(all(feature = "std", feature = "prover"))]
+pub fn setup_protocol_with_threshold(
+    poly: &ProductMLPolynomial,
+    threshold_bits: f64,
+) -> Result<Instance, SetupError> {
+    let (claimed_sum, prover_state) = Prover::setup_with_threshold(poly, threshold_bits)?;
+    let verifier_state = Verifier::setup_with_threshold(poly, claimed_sum, threshold_bits)?;
+    Ok((prover_state.num_vars(), claimed_sum, prover_state, verifier_state))
+}
+
+/// Same as `setup_protocol`, for a sum asserted from outside rather than computed by the prover:
+/// the verifier is initialized with 'claimed_sum', which the prover must then prove. Fails with
+/// [`SetupError::FalseClaim`] before any round if 'poly' does not sum to it.
+#[cfg(all(feature = "std", feature = "prover"))]
+pub fn setup_protocol_with_claim(
+    poly: &ProductMLPolynomial,
+    claimed_sum: F,
+) -> Result<Instance, SetupError> {
+    let num_vars = check_setup(poly, DEFAULT_SECURITY_BITS)?;
+    let prover_state = Prover::claim_asserted_sum(poly, claimed_sum)?;
+    let verifier_state = Verifier::setup(poly, claimed_sum)?;
+    Ok((num_vars, claimed_sum, prover_state, verifier_state))
+}
+
+/// Same as `setup_protocol`, with the prover and the verifier agreeing on 'format' for the round
+/// messages.
+#[cfg(all(feature = "std", feature = "prover"))]
+pub fn setup_protocol_with_format(
+    poly: &ProductMLPolynomial,
+    format: MessageFormat,
+) -> Result<Instance, SetupError> {
+    let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(poly)?;
+    let verifier_state = VerifierState { format, ..verifier_state };
+    Ok((num_vars, claimed_sum, prover_state.with_format(format), verifier_state))
+}
+
+/// Checks that 'poly' is a nonempty product of multilinear polynomials on the same variables, on
+/// which the protocol has at least 'threshold_bits' bits of security, and returns their number of
+/// variables.
+#[cfg(feature = "std")]
+pub(crate) fn check_setup(poly: &ProductMLPolynomial, threshold_bits: f64) -> Result<usize, SetupError> {
+    let num_vars = check_instance(poly)?;
+    check_security(instance_security_bits(poly), threshold_bits)?;
+    Ok(num_vars)
+}
+
+/// Same as [`check_setup`], for challenges drawn with 'sampler' instead of from the whole field.
+#[cfg(feature = "std")]
+pub(crate) fn check_sampler_setup(
+    poly: &ProductMLPolynomial,
+    sampler: &dyn ChallengeSampler,
+    threshold_bits: f64,
+) -> Result<usize, SetupError> {
+    let num_vars = check_instance(poly)?;
+    check_security(sampler_security_bits(sampler, poly.len(), num_vars), threshold_bits)?;
+    Ok(num_vars)
+}
+
+/// The checks of [`check_setup`] which the digest of a product allows: it has a factor, and the
+/// protocol has at least 'threshold_bits' bits of security on it. That the factors are
+/// multilinear is left to the final check, which bounds the round polynomials by the degrees of
+/// the digest.
+#[cfg(feature = "std")]
+pub(crate) fn check_digest_setup(digest: &InstanceDigest, threshold_bits: f64) -> Result<(), SetupError> {
+    if digest.num_polys == 0 {
+        return Err(SetupError::EmptyProduct);
+    }
+    check_security(security_bits::<F>(digest.num_polys, digest.num_vars), threshold_bits)
+}
+
+#[cfg(feature = "std")]
+fn check_security(security_bits: f64, threshold_bits: f64) -> Result<(), SetupError> {
+    if security_bits < threshold_bits {
+        return Err(SetupError::InsufficientSecurity { security_bits, threshold_bits });
+    }
+    Ok(())
 }
 
-pub fn setup_protocol(poly: &ProductMLPolynomial) -> (usize, F, ProverState, VerifierState) {
-    let num_vars = get_num_vars(&poly).unwrap();
-    let (claimed_sum, prover_state) = Prover::claim_sum(&poly);
-    let verifier_state = Verifier::initialize(&poly, claimed_sum);
-    (num_vars, claimed_sum, prover_state, verifier_state)
+/// Checks that 'poly' is a nonempty product of multilinear polynomials on the same variables, and
+/// returns their number of variables.
+#[cfg(feature = "std")]
+fn check_instance(poly: &ProductMLPolynomial) -> Result<usize, SetupError> {
+    let num_vars = poly.first().ok_or(SetupError::EmptyProduct)?.num_vars;
+    for (factor, p) in poly.iter().enumerate() {
+        if p.num_vars != num_vars {
+            return Err(SetupError::VariableCountMismatch { factor, expected: num_vars, got: p.num_vars });
+        }
+        let term = p.terms.iter().flat_map(|(_, term)| term.iter()).find(|&&(_, power)| power > 1);
+        if let Some(&(var, degree)) = term {
+            return Err(SetupError::NotMultilinear { factor, var, degree });
+        }
+    }
+    Ok(num_vars)
 }
 
-pub fn orchestrate_protocol(num_vars: usize,
-                        _claimed_sum: F,
-                        mut prover_state: ProverState,
-                        mut verifier_state: VerifierState)
+/// Plays both the prover and the verifier in this process, a demonstration of the protocol. The
+/// parties of a real deployment each drive their side on their own, e.g. with
+/// [`SumCheckProver`] and [`SumCheckVerifier`] over a channel.
+#[cfg(feature = "prover")]
+pub fn orchestrate_protocol<R: RngCore + CryptoRng>(num_vars: usize,
+                        claimed_sum: F,
+                        prover_state: ProverState,
+                        verifier_state: VerifierState<R>)
                         -> ProtocolTranscript {
+    orchestrate_protocol_with_observer(num_vars, claimed_sum, prover_state, verifier_state, &mut ())
+}
+
+/// Same as `orchestrate_protocol`, rejecting with [`SumCheckError::Cancelled`] once 'token' is
+/// cancelled. The token is checked before each round and before the final check.
+#[cfg(feature = "prover")]
+pub fn orchestrate_protocol_cancellable<R: RngCore + CryptoRng>(
+    num_vars: usize,
+    claimed_sum: F,
+    prover_state: ProverState,
+    verifier_state: VerifierState<R>,
+    token: &CancellationToken,
+) -> ProtocolTranscript {
+    orchestrate(num_vars, claimed_sum, prover_state, verifier_state, &mut (), Some(token))
+}
+
+/// Same as `orchestrate_protocol`, reporting the events of the run to 'observer'.
+#[cfg(feature = "prover")]
+pub fn orchestrate_protocol_with_observer<R: RngCore + CryptoRng, O: Observer>(
+    num_vars: usize,
+    claimed_sum: F,
+    prover_state: ProverState,
+    verifier_state: VerifierState<R>,
+    observer: &mut O,
+) -> ProtocolTranscript {
+    orchestrate(num_vars, claimed_sum, prover_state, verifier_state, observer, None)
+}
+
+#[cfg(feature = "prover")]
+fn orchestrate<R: RngCore + CryptoRng, O: Observer>(
+    num_vars: usize,
+    claimed_sum: F,
+    prover_state: ProverState,
+    verifier_state: VerifierState<R>,
+    observer: &mut O,
+    token: Option<&CancellationToken>,
+) -> ProtocolTranscript {
+    let (transcript, verifier_state) =
+        run_rounds(num_vars, claimed_sum, prover_state, verifier_state, observer, token);
+    let Some(verifier_state) = verifier_state else {
+        return transcript;
+    };
+    if let Some(Err(err)) = token.map(CancellationToken::check) {
+        observer.on_decision(&Err(err.clone()));
+        return transcript.reject(err);
+    }
+    let point = merge_point(&verifier_state.fixed, &verifier_state.randomness);
+    let final_evaluation = verifier_state.running_eval;
+    let (result, _) = Verifier::sanity_check(verifier_state);
+    observer.on_decision(&result);
+    transcript.conclude(result, point, final_evaluation)
+}
+
+/// Same as `orchestrate_protocol`, without the final check: the transcript is accepted once every
+/// round passes, and the caller must check that the polynomial evaluates to
+/// `final_evaluation` at `point`, e.g. by opening a commitment. The verifier does not need the
+/// polynomial. See [`SumCheckVerifier::finalize_to_claim`].
+#[cfg(feature = "prover")]
+pub fn orchestrate_protocol_deferred<R: RngCore + CryptoRng>(
+    num_vars: usize,
+    claimed_sum: F,
+    prover_state: ProverState,
+    verifier_state: VerifierState<R>,
+) -> ProtocolTranscript {
+    let (transcript, verifier_state) = run_rounds(num_vars, claimed_sum, prover_state, verifier_state, &mut (), None);
+    let Some(verifier_state) = verifier_state else {
+        return transcript;
+    };
+    match Verifier::finalize_to_claim(verifier_state) {
+        Ok(claim) => transcript.conclude(Ok(()), claim.point, claim.value),
+        Err(err) => transcript.reject(err),
+    }
+}
+
+/// Plays the rounds of the protocol, and returns the state of the verifier unless it rejected a
+/// round or 'token' was cancelled. The decision is reported to 'observer' only on a rejection.
+#[cfg(feature = "prover")]
+fn run_rounds<R: RngCore + CryptoRng, O: Observer>(
+    num_vars: usize,
+    claimed_sum: F,
+    mut prover_state: ProverState,
+    mut verifier_state: VerifierState<R>,
+    observer: &mut O,
+    token: Option<&CancellationToken>,
+) -> (ProtocolTranscript, Option<VerifierState<R>>) {
+    let mut transcript = ProtocolTranscript::new(claimed_sum, verifier_state.format);
     let mut poly_descr: PolynomialDescription;
-    for _ in 0..num_vars
+    for round in 0..num_vars
     {
+        if let Some(Err(err)) = token.map(CancellationToken::check) {
+            observer.on_decision(&Err(err.clone()));
+            return (transcript.reject(err), None);
+        }
+        observer.on_round_start(round);
         (poly_descr, prover_state) = Prover::round_phase_1(prover_state);
+        observer.on_prover_message(round, &poly_descr);
+        transcript.messages.push(poly_descr.clone());
         match Verifier::round(verifier_state, poly_descr) {
             Ok((r, state)) => {
+                observer.on_challenge(round, r);
+                transcript.challenges.push(r);
                 verifier_state = state;
                 prover_state = Prover::round_phase_2(prover_state, r) },
-            Err(_) => return ProtocolTranscript{ _randomness: vec![], accept: false}
+            Err(err) => {
+                observer.on_decision(&Err(err.clone()));
+                return (transcript.reject(err), None);
+            }
+        }
+    }
+    (transcript, Some(verifier_state))
+}
+
+/// Same as `orchestrate_protocol`, with the challenges of the verifier drawn from a ChaCha20
+/// generator seeded with 'seed', so that the run can be reproduced.
+#[cfg(feature = "prover")]
+pub fn orchestrate_protocol_with_seed<R: RngCore + CryptoRng>(
+    seed: [u8; 32],
+    num_vars: usize,
+    claimed_sum: F,
+    prover_state: ProverState,
+    verifier_state: VerifierState<R>,
+) -> ProtocolTranscript {
+    let verifier_state = verifier_state.with_rng(ChaCha20Rng::from_seed(seed));
+    orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state)
+}
+
+/// Runs the protocol where the verifier only receives commitments to the factors of 'poly'. The
+/// final check is done against the prover's openings of the factors at the random point.
+#[cfg(all(feature = "std", feature = "prover"))]
+pub fn orchestrate_committed_protocol<C: MLCommitmentScheme>(
+    poly: &ProductMLPolynomial,
+) -> ProtocolTranscript {
+    let (commitments, openings): (Vec<_>, Vec<_>) = poly.iter().map(C::commit).unzip();
+    let mut prover = SumCheckProver::new(poly);
+    let num_vars = prover.num_rounds();
+    let mut verifier = SumCheckVerifier::from_state(Verifier::initialize_committed(
+        num_vars,
+        commitments.len(),
+        prover.claimed_sum(),
+    ));
+
+    let mut transcript = ProtocolTranscript::new(prover.claimed_sum(), MessageFormat::Evaluations);
+    let mut challenge = None;
+    for _ in 0..num_vars {
+        let message = prover.next_message(challenge);
+        transcript.messages.push(message.evaluations.clone());
+        match verifier.receive_message(message) {
+            Ok(r) => {
+                transcript.challenges.push(r);
+                challenge = Some(r);
+            }
+            Err(err) => return transcript.reject(err),
         }
     }
-    let (accept, _randomness) = Verifier::sanity_check(verifier_state);
-    ProtocolTranscript{
-        _randomness,
-        accept
+    let point = transcript.challenges.clone();
+    let evaluations: Vec<_> = openings.iter().map(|opening| C::open(opening, &point)).collect();
+    let final_evaluation = verifier.running_eval();
+    let (result, _) = verifier.finalize_with_openings::<C>(&commitments, &evaluations);
+    transcript.conclude(result, point, final_evaluation)
+}
+
+/// Re-runs the checks of the verifier on the messages and challenges recorded in 'transcript',
+/// for the claim that 'poly' sums to `transcript.claimed_sum`. Returns whether a verifier which
+/// drew the same challenges would accept. The outcome only depends on the recorded data.
+pub fn replay_transcript(poly: &ProductMLPolynomial, transcript: &ProtocolTranscript) -> bool {
+    let Some(num_vars) = get_num_vars(poly) else {
+        return false;
+    };
+    if transcript.messages.len() != num_vars || transcript.challenges.len() != num_vars {
+        return false;
+    }
+    let instance = ProductInstance::new(poly.clone());
+    let mut running_eval = transcript.claimed_sum;
+    for (var, (message, &r)) in transcript.messages.iter().zip(&transcript.challenges).enumerate() {
+        let Some(message) = Verifier::decompress(transcript.format, running_eval, message.clone()) else {
+            return false;
+        };
+        if message.len() < 2
+            || message.len() > instance.round_degree(var).max(1) + 1
+            || Verifier::round_sum(transcript.format, &message) != Some(running_eval)
+        {
+            return false;
+        }
+        running_eval = Verifier::evaluate_message(transcript.format, &message, r);
     }
+    instance.evaluate(&transcript.challenges) == running_eval
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std", feature = "prover"))]
 mod tests {
     use super::*;
+    use crate::commitment::HashCommitment;
+    use crate::polynomial::{evaluate_mvml_polynomial, evaluations_to_coefficients, parse_product};
     use ark_poly::{multivariate::{SparsePolynomial, SparseTerm}, DenseMVPolynomial};
     use ark_poly::multivariate::Term;
+    /// Same as `setup_protocol`, for the factors which are not multilinear, which `setup_protocol`
+    /// rejects: the verifier then rejects them at the final check.
+    fn unchecked_setup(poly: &ProductMLPolynomial) -> Instance {
+        assert!(matches!(setup_protocol(poly), Err(SetupError::NotMultilinear { .. })));
+        let (claimed_sum, prover_state) = Prover::claim_sum(poly);
+        (get_num_vars(poly).unwrap(), claimed_sum, prover_state, Verifier::initialize(poly, claimed_sum))
+    }
+
     /// Basic test for a multilinear polynomial on 3 variables.
     #[test]
     fn test_protocol_3_variables() {
@@ -60,9 +619,17 @@ mod tests {
                 (F::from(5), SparseTerm::new(vec![])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let challenges = vec![F::from(2), F::from(3), F::from(4)];
+        let verifier_state = verifier_state.with_challenges(challenges.clone());
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(transcript.accept);
+        // The round polynomials are 22X + 21, then X + 32 and 17X + 9 after the challenges 2 and 3.
+        assert_eq!(claimed_sum, F::from(64));
+        let messages = [[21, 43], [32, 33], [9, 26]];
+        assert_eq!(transcript.messages, messages.map(|m| m.map(F::from).to_vec()));
+        assert_eq!(transcript.point, challenges);
+        assert_eq!(transcript.final_evaluation, F::from(77));
     }
 
     /// Failing test for polynomial on 3 variables, where the input is not given as a product of
@@ -80,9 +647,18 @@ mod tests {
                 (F::from(5), SparseTerm::new(vec![])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = unchecked_setup(&poly);
+        let verifier_state = verifier_state.with_challenges(vec![F::from(2), F::from(3), F::from(4)]);
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(!transcript.accept);
+        assert_eq!(transcript.error, Some(SumCheckError::FinalCheckFailed));
+        // The prover's tables read x0^2 as x0, which agrees on the hypercube only: its messages
+        // are those of 2*x0 + 7*x0*x2 + x1*x2 + 5, which is 77 at (2, 3, 4), where the
+        // polynomial is 133.
+        let messages = [[21, 43], [32, 33], [9, 26]];
+        assert_eq!(transcript.messages, messages.map(|m| m.map(F::from).to_vec()));
+        assert_eq!(transcript.final_evaluation, F::from(77));
+        assert_eq!(evaluate_mvml_polynomial(poly, &transcript.point), F::from(133));
     }
 
     /// Test for a multilinear polynomial on 6 variables.
@@ -97,7 +673,7 @@ mod tests {
                 (F::from(84), SparseTerm::new(vec![(2, 1), (4,1), (3, 1)])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(transcript.accept);
     }
@@ -115,10 +691,10 @@ mod tests {
                 (F::from(84), SparseTerm::new(vec![(2, 1), (4,1), (3, 1)])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = unchecked_setup(&poly);
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(!transcript.accept);
-        assert_eq!(transcript._randomness.len(), 6)
+        assert_eq!(transcript.point.len(), 6)
 
     }
 
@@ -134,7 +710,7 @@ mod tests {
                 (F::from(84), SparseTerm::new(vec![(2, 1), (4,1), (3, 1)])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(transcript.accept);
     }
@@ -149,10 +725,13 @@ mod tests {
                 (F::from(5), SparseTerm::new(vec![])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let verifier_state = verifier_state.with_challenges(vec![F::from(3)]);
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(transcript.accept);
-        assert_eq!(transcript._randomness.len(), 1)
+        assert_eq!(transcript.messages, vec![vec![F::from(5), F::from(7)]]);
+        assert_eq!(transcript.point, vec![F::from(3)]);
+        assert_eq!(transcript.final_evaluation, F::from(11))
 
     }
 
@@ -167,14 +746,311 @@ mod tests {
                 (F::from(5), SparseTerm::new(vec![])),
             ],
         )]);
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
         let alt_verifier_state = VerifierState{
             running_eval: F::from(0),
             ..verifier_state
         };
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, alt_verifier_state);
         assert!(!transcript.accept);
-        assert_eq!(transcript._randomness.len(), 0)
+        assert_eq!(transcript.point.len(), 0);
+        assert_eq!(
+            transcript.error,
+            Some(SumCheckError::IntermediateCheckFailed {
+                round: 0,
+                lhs: F::from(0),
+                rhs: claimed_sum,
+            })
+        );
+    }
+
+    #[test]
+    fn test_failure_report() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert_eq!(transcript.failure, None);
+
+        // A false claim is caught in the first round.
+        let wrong_sum = claimed_sum + F::from(1);
+        let (_, prover_state) = Prover::claim_sum(&poly);
+        let verifier_state = Verifier::initialize(&poly, wrong_sum);
+        let transcript = orchestrate_protocol(num_vars, wrong_sum, prover_state, verifier_state);
+        let failure = transcript.failure.unwrap();
+        assert_eq!(failure, FailureReport { round: 0, expected: wrong_sum, received: Some(claimed_sum) });
+        assert_eq!(
+            failure.to_string(),
+            format!("round 0: expected {}, received p(0) + p(1) = {}", wrong_sum, claimed_sum)
+        );
+
+        // The prover of another polynomial is caught at the final check.
+        let other = parse_product("(x0 + 2*x1) * (x1 + 4)").unwrap();
+        let (other_sum, prover_state) = Prover::claim_sum(&other);
+        let verifier_state =
+            Verifier::initialize(&poly, other_sum).with_challenges(vec![F::from(2), F::from(5)]);
+        let transcript = orchestrate_protocol(num_vars, other_sum, prover_state, verifier_state);
+        assert_eq!(transcript.error, Some(SumCheckError::FinalCheckFailed));
+        // (2 + 2*5) * (5 + 4), where the polynomial is (2 + 2*5) * (5 + 3) = 96.
+        assert_eq!(transcript.final_evaluation, F::from(108));
+        assert_eq!(
+            transcript.failure,
+            Some(FailureReport { round: 2, expected: transcript.final_evaluation, received: None })
+        );
+    }
+
+    #[test]
+    fn test_externally_claimed_sum() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) =
+            setup_protocol_with_claim(&poly, F::from(23)).unwrap();
+        assert_eq!(claimed_sum, F::from(23));
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+
+        // The prover refuses to prove a false claim, rather than running into a rejection.
+        assert_eq!(
+            setup_protocol_with_claim(&poly, F::from(24)).err(),
+            Some(SetupError::FalseClaim { claimed_sum: F::from(24), actual_sum: F::from(23) })
+        );
+    }
+
+    /// Test of the two parties set up apart, sharing only the claimed sum and the messages.
+    #[test]
+    fn test_independent_setup() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let mut prover = SumCheckProver::setup(&poly).unwrap();
+        let verifier_poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let mut verifier = SumCheckVerifier::setup(&verifier_poly, prover.claimed_sum()).unwrap();
+        let mut challenge = None;
+        for _ in 0..2 {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        assert!(verifier.finalize().0.is_ok());
+
+        // A verifier which only received the digest of the instance.
+        let mut prover = SumCheckProver::setup(&poly).unwrap();
+        let digest = InstanceDigest::new(&poly);
+        let mut verifier = SumCheckVerifier::setup_from_digest(&digest, prover.claimed_sum()).unwrap();
+        let mut challenge = None;
+        for _ in 0..2 {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        assert!(verifier.finalize_with_instance(&verifier_poly).0.is_ok());
+        let empty = InstanceDigest { num_polys: 0, degree_bounds: vec![0; 2], ..digest.clone() };
+        assert!(matches!(SumCheckVerifier::setup_from_digest(&empty, F::from(0)), Err(SetupError::EmptyProduct)));
+        assert!(matches!(
+            Verifier::setup_with_threshold(&poly, F::from(0), 300.0),
+            Err(SetupError::InsufficientSecurity { .. })
+        ));
+        // The protocol has 128 bits of security over the field, but not over a subgroup.
+        let subgroup = Subgroup::of_order(12).unwrap();
+        assert!(matches!(
+            SumCheckVerifier::setup_with_sampler(&poly, F::from(0), subgroup, DEFAULT_SECURITY_BITS),
+            Err(SetupError::InsufficientSecurity { .. })
+        ));
+        assert!(Verifier::setup_with_sampler(&poly, F::from(0), subgroup, 0.5).is_ok());
+        assert!(Verifier::setup_with_sampler(&poly, F::from(0), FullField, DEFAULT_SECURITY_BITS).is_ok());
+
+        let not_multilinear =
+            vec![SparsePolynomial::from_coefficients_vec(1, vec![(F::from(1), SparseTerm::new(vec![(0, 2)]))])];
+        assert!(matches!(SumCheckProver::setup(&not_multilinear), Err(SetupError::NotMultilinear { .. })));
+        assert!(matches!(
+            SumCheckVerifier::setup(&not_multilinear, F::from(1)),
+            Err(SetupError::NotMultilinear { .. })
+        ));
+    }
+
+    #[test]
+    fn test_scripted_challenges() {
+        // The round polynomials are 7X + 8, then (2 + 2X)(X + 3) for the challenge 2.
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let challenges = vec![F::from(2), F::from(5)];
+        let verifier_state = verifier_state.with_challenges(challenges.clone());
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        assert_eq!(claimed_sum, F::from(23));
+        assert_eq!(
+            transcript.messages,
+            vec![vec![F::from(8), F::from(15)], vec![F::from(6), F::from(16), F::from(30)]]
+        );
+        assert_eq!(transcript.challenges, challenges);
+        assert_eq!(transcript.final_evaluation, F::from(96));
+    }
+
+    /// Test driving the prover and verifier round by round, as an embedding protocol would.
+    #[test]
+    fn test_round_by_round_api() {
+        let poly = Vec::from(&[
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                    (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                    (F::from(5), SparseTerm::new(vec![])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                    (F::from(3), SparseTerm::new(vec![(2, 1)])),
+                ],
+            ),
+        ]);
+        let mut prover = SumCheckProver::new(&poly);
+        let mut verifier = SumCheckVerifier::new(&poly, prover.claimed_sum());
+        assert_eq!(prover.num_rounds(), 3);
+
+        let mut challenge = None;
+        // Only one factor depends on each of x0 and x1, so the first two round polynomials are
+        // linear.
+        for (round, degree) in [1, 1, 2].into_iter().enumerate() {
+            assert_eq!(prover.round(), round);
+            assert_eq!(verifier.round(), round);
+            let message = prover.next_message(challenge);
+            assert_eq!(message.evaluations.len(), degree + 1);
+            challenge = Some(verifier.receive_message(message).unwrap());
+        }
+        let (result, point) = verifier.finalize();
+        assert!(result.is_ok());
+        assert_eq!(point.len(), 3);
+        assert_eq!(point.last(), challenge.as_ref());
+    }
+
+    /// Test for a product of multilinear polynomials where the verifier only holds commitments.
+    #[test]
+    fn test_committed_protocol() {
+        let poly = Vec::from(&[
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                    (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                    (F::from(5), SparseTerm::new(vec![])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                    (F::from(3), SparseTerm::new(vec![(2, 1)])),
+                ],
+            ),
+        ]);
+        let transcript = orchestrate_committed_protocol::<HashCommitment>(&poly);
+        assert!(transcript.accept);
+        assert_eq!(transcript.point.len(), 3);
+    }
+
+    /// The setup fails when more bits of security are required than the field provides.
+    #[test]
+    fn test_security_threshold() {
+        let poly = parse_product("(x0 + x1) * (x1 + x2)").unwrap();
+        assert!(setup_protocol_with_threshold(&poly, 250.0).is_ok());
+        assert!(matches!(
+            setup_protocol_with_threshold(&poly, 300.0),
+            Err(SetupError::InsufficientSecurity { threshold_bits, .. }) if threshold_bits == 300.0
+        ));
+    }
+
+    #[test]
+    fn test_final_evaluation() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        assert_eq!(transcript.point, transcript.challenges);
+        assert_eq!(transcript.final_evaluation, evaluate_mvml_polynomial(poly.clone(), &transcript.point));
+
+        // The deferred verifier does not know the polynomial, and leaves the final check to the caller.
+        let (claimed_sum, prover_state) = Prover::claim_sum(&poly);
+        let verifier_state = Verifier::initialize_committed(3, 2, claimed_sum);
+        let transcript = orchestrate_protocol_deferred(3, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        assert_eq!(transcript.final_evaluation, evaluate_mvml_polynomial(poly.clone(), &transcript.point));
+
+        // The rounds are still checked.
+        let verifier_state = Verifier::initialize_committed(3, 2, claimed_sum + F::from(1));
+        let (_, prover_state) = Prover::claim_sum(&poly);
+        let transcript = orchestrate_protocol_deferred(3, claimed_sum + F::from(1), prover_state, verifier_state);
+        assert!(!transcript.accept);
+        assert!(transcript.point.is_empty());
+
+        // With fixed variables, the point has all the variables of the polynomial.
+        let fixed = [(1, F::from(4))];
+        let (claimed_sum, prover_state) = Prover::claim_partial_sum(&poly, &fixed);
+        let verifier_state = Verifier::initialize_partial(&poly, &fixed, claimed_sum);
+        let transcript = orchestrate_protocol(2, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        assert_eq!(transcript.point, vec![transcript.challenges[0], F::from(4), transcript.challenges[1]]);
+    }
+
+    #[test]
+    fn test_setup_validation() {
+        assert_eq!(setup_protocol(&vec![]).err(), Some(SetupError::EmptyProduct));
+        let mut poly = parse_product("(x0 + x1) * (x1 + 2)").unwrap();
+        poly[1] = SparsePolynomial::from_coefficients_vec(3, vec![(F::from(1), SparseTerm::new(vec![(2, 1)]))]);
+        assert_eq!(
+            setup_protocol(&poly).err(),
+            Some(SetupError::VariableCountMismatch { factor: 1, expected: 2, got: 3 })
+        );
+        poly[1] = SparsePolynomial::from_coefficients_vec(2, vec![(F::from(1), SparseTerm::new(vec![(0, 1), (1, 2)]))]);
+        assert_eq!(
+            setup_protocol(&poly).err(),
+            Some(SetupError::NotMultilinear { factor: 1, var: 1, degree: 2 })
+        );
+    }
+
+    /// Test for a partial sum, where some variables are fixed before the protocol.
+    #[test]
+    fn test_partial_sum() {
+        let poly = parse_product("(x0*x2 + x1 + x3) * (x0 + 2*x1 + x2) * (x3 + 5)").unwrap();
+        let fixed = [(1, F::from(9)), (3, F::from(4))];
+        let mut prover = SumCheckProver::with_fixed(&poly, &fixed);
+        let mut verifier = SumCheckVerifier::with_fixed(&poly, &fixed, prover.claimed_sum());
+        assert_eq!(prover.num_rounds(), 2);
+
+        let mut challenge = None;
+        for _ in 0..prover.num_rounds() {
+            let message = prover.next_message(challenge);
+            challenge = Some(verifier.receive_message(message).unwrap());
+        }
+        let (result, point) = verifier.finalize();
+        assert!(result.is_ok());
+        assert_eq!(point.len(), 2);
+    }
+
+    /// The round-by-round prover refuses to play more rounds than there are variables.
+    #[test]
+    #[should_panic(expected = "all rounds of the protocol have been played")]
+    fn test_round_by_round_too_many_rounds() {
+        let poly = Vec::from(&[SparsePolynomial::from_coefficients_vec(
+            1,
+            vec![(F::from(2), SparseTerm::new(vec![(0, 1)]))],
+        )]);
+        let mut prover = SumCheckProver::new(&poly);
+        prover.next_message(None);
+        prover.next_message(Some(F::from(3)));
+    }
+
+    /// Failing test where the verifier is asked to play fewer rounds than there are variables.
+    #[test]
+    fn test_fail_variable_count() {
+        let poly = Vec::from(&[SparsePolynomial::from_coefficients_vec(
+            3,
+            vec![
+                (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                (F::from(1), SparseTerm::new(vec![(1, 1), (2, 1)])),
+            ],
+        )]);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let transcript = orchestrate_protocol(num_vars - 1, claimed_sum, prover_state, verifier_state);
+        assert!(!transcript.accept);
+        assert_eq!(
+            transcript.error,
+            Some(SumCheckError::VariableCountMismatch { expected: 3, got: 2 })
+        );
     }
 
 
@@ -208,12 +1084,22 @@ mod tests {
         let multilinear_list = vec![
             p1, p2, p3
         ];
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&multilinear_list);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&multilinear_list).unwrap();
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(transcript.accept);
     }
 
 
+    /// Test for a product of multilinear polynomials given as an expression.
+    #[test]
+    fn test_protocol_from_expression() {
+        let poly = parse_product("(x0*x2 + x1 + x2) * (x0 + x1 + x2) * (3*x3 - x1 + 1)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        assert_eq!(num_vars, 4);
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+    }
+
     /// Failing test for a polynomial where one of the elements of the products is not multilinear.
     #[test]
     fn test_fail_product_check() {
@@ -244,7 +1130,7 @@ mod tests {
         let multilinear_list = vec![
             p1, p2, p3
         ];
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&multilinear_list);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = unchecked_setup(&multilinear_list);
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
         assert!(!transcript.accept);
     }
@@ -279,15 +1165,219 @@ mod tests {
         let multilinear_list = vec![
             p1, p2, p3
         ];
-        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&multilinear_list);
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&multilinear_list).unwrap();
         let alt_verifier_state = VerifierState{
             running_eval: F::from(0),
             ..verifier_state
         };
         let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, alt_verifier_state);
         assert!(!transcript.accept);
-        assert_eq!(transcript._randomness.len(), 0);
+        assert_eq!(transcript.point.len(), 0);
     }
 
+    #[test]
+    fn test_seeded_protocol() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)").unwrap();
+        let run = |seed: [u8; 32]| {
+            let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+            orchestrate_protocol_with_seed(seed, num_vars, claimed_sum, prover_state, verifier_state)
+        };
+        let transcript = run([7; 32]);
+        assert!(transcript.accept);
+        assert_eq!(run([7; 32]).challenges, transcript.challenges);
+        assert_eq!(run([7; 32]).messages, transcript.messages);
+        assert_ne!(run([8; 32]).challenges, transcript.challenges);
+    }
+
+    #[test]
+    fn test_replay_transcript() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let mut transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        assert_eq!(transcript.messages.len(), 3);
+        assert_eq!(transcript.challenges, transcript.point);
+        assert!(replay_transcript(&poly, &transcript));
 
+        transcript.challenges[1] += F::from(1);
+        assert!(!replay_transcript(&poly, &transcript));
+        transcript.challenges[1] -= F::from(1);
+        transcript.messages[2][0] += F::from(1);
+        assert!(!replay_transcript(&poly, &transcript));
+    }
+
+    #[test]
+    fn test_coefficient_messages() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)").unwrap();
+        let format = MessageFormat::Coefficients;
+        let (num_vars, claimed_sum, prover_state, verifier_state) =
+            setup_protocol_with_format(&poly, format).unwrap();
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        assert_eq!(transcript.format, format);
+        let (message, _) = Prover::round_phase_1(prover_state_for(&poly, MessageFormat::Evaluations));
+        assert_eq!(transcript.messages[0], evaluations_to_coefficients(&message));
+        assert!(replay_transcript(&poly, &transcript));
+
+        // A verifier expecting evaluations rejects coefficients.
+        let (num_vars, claimed_sum, _, verifier_state) = setup_protocol(&poly).unwrap();
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state_for(&poly, format), verifier_state);
+        assert!(!transcript.accept);
+    }
+
+    #[test]
+    fn test_compressed_messages() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)").unwrap();
+        let format = MessageFormat::Compressed;
+        let (num_vars, claimed_sum, prover_state, verifier_state) =
+            setup_protocol_with_format(&poly, format).unwrap();
+        let transcript = orchestrate_protocol(num_vars, claimed_sum, prover_state, verifier_state);
+        assert!(transcript.accept);
+        let (message, _) = Prover::round_phase_1(prover_state_for(&poly, MessageFormat::Evaluations));
+        assert_eq!(transcript.messages[0], message[1..]);
+        assert!(replay_transcript(&poly, &transcript));
+
+        // A wrong p(1) changes the recovered p(0) as well, which the next rounds catch.
+        let mut tampered = transcript;
+        tampered.messages[0][0] += F::from(1);
+        assert!(!replay_transcript(&poly, &tampered));
+
+        // The degree bound takes the omitted evaluation into account.
+        let (_, _, _, verifier_state) = setup_protocol_with_format(&poly, format).unwrap();
+        assert_eq!(
+            Verifier::round(verifier_state, message).err(),
+            Some(SumCheckError::DegreeMismatch { round: 0, expected: 2, got: 3 })
+        );
+        let (_, _, _, verifier_state) = setup_protocol_with_format(&poly, format).unwrap();
+        assert_eq!(
+            Verifier::round(verifier_state, vec![]).err(),
+            Some(SumCheckError::DegreeMismatch { round: 0, expected: 2, got: 0 })
+        );
+    }
+
+    fn prover_state_for(poly: &ProductMLPolynomial, format: MessageFormat) -> ProverState {
+        Prover::claim_sum(poly).1.with_format(format)
+    }
+
+    #[test]
+    fn test_transcript_of_rejected_run() {
+        let poly = parse_product("(x0 + x1) * (x1 + 1)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let verifier_state = VerifierState { running_eval: claimed_sum + F::from(1), ..verifier_state };
+        let transcript = orchestrate_protocol(num_vars, claimed_sum + F::from(1), prover_state, verifier_state);
+        assert!(!transcript.accept);
+        // The rejected message is recorded, without a challenge.
+        assert_eq!(transcript.messages.len(), 1);
+        assert!(transcript.challenges.is_empty());
+        assert!(!replay_transcript(&poly, &transcript));
+    }
+
+    /// Records the events of a run.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        messages: Vec<PolynomialDescription>,
+        challenges: Vec<F>,
+    }
+
+    impl Observer for Recorder {
+        fn on_round_start(&mut self, round: usize) {
+            self.events.push(format!("start {}", round));
+        }
+
+        fn on_prover_message(&mut self, round: usize, message: &PolynomialDescription) {
+            self.events.push(format!("message {}", round));
+            self.messages.push(message.clone());
+        }
+
+        fn on_challenge(&mut self, round: usize, challenge: F) {
+            self.events.push(format!("challenge {}", round));
+            self.challenges.push(challenge);
+        }
+
+        fn on_decision(&mut self, result: &Result<(), SumCheckError>) {
+            self.events.push(format!("decision {}", result.is_ok()));
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let mut recorder = Recorder::default();
+        let transcript =
+            orchestrate_protocol_with_observer(num_vars, claimed_sum, prover_state, verifier_state, &mut recorder);
+        assert!(transcript.accept);
+        assert_eq!(
+            recorder.events,
+            ["start 0", "message 0", "challenge 0", "start 1", "message 1", "challenge 1", "decision true"]
+        );
+        assert_eq!(recorder.messages, transcript.messages);
+        assert_eq!(recorder.challenges, transcript.challenges);
+
+        // A rejected round ends the run with the decision.
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let verifier_state = VerifierState { running_eval: claimed_sum + F::from(1), ..verifier_state };
+        let mut recorder = Recorder::default();
+        orchestrate_protocol_with_observer(num_vars, claimed_sum, prover_state, verifier_state, &mut recorder);
+        assert_eq!(recorder.events, ["start 0", "message 0", "decision false"]);
+
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let mut timer = RoundTimer::new();
+        orchestrate_protocol_with_observer(num_vars, claimed_sum, prover_state, verifier_state, &mut timer);
+        assert_eq!(timer.rounds.len(), num_vars);
+    }
+
+    #[test]
+    fn test_cancelled_protocol() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let token = CancellationToken::new();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let transcript =
+            orchestrate_protocol_cancellable(num_vars, claimed_sum, prover_state, verifier_state, &token);
+        assert!(transcript.accept);
+
+        token.cancel();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let transcript =
+            orchestrate_protocol_cancellable(num_vars, claimed_sum, prover_state, verifier_state, &token);
+        assert!(!transcript.accept);
+        assert_eq!(transcript.error, Some(SumCheckError::Cancelled));
+        assert!(transcript.messages.is_empty());
+    }
+
+    #[test]
+    fn test_sum_of_products() {
+        // a(x) * b(x) * c(x) + d(x) * e(x) + f(x). Each variable appears in at most two factors of
+        // a product, so the round polynomials have degree 2.
+        let poly = vec![
+            parse_product("(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)").unwrap(),
+            parse_product("(x0*x1 + 5) * (x2 + 7*x0)").unwrap(),
+            parse_product("x0 + x1 + x2").unwrap(),
+        ];
+        let mut prover = SumCheckProver::from_sum_of_products(&poly);
+        let expected: F = poly
+            .iter()
+            .map(|product| SumCheckProver::new(product).claimed_sum())
+            .sum();
+        assert_eq!(prover.claimed_sum(), expected);
+
+        let mut verifier = SumCheckVerifier::from_sum_of_products(&poly, prover.claimed_sum());
+        let mut challenge = None;
+        for _ in 0..prover.num_rounds() {
+            let message = prover.next_message(challenge);
+            assert_eq!(message.evaluations.len(), 3);
+            challenge = Some(verifier.receive_message(message).unwrap());
+        }
+        assert_eq!(verifier.finalize().0, Ok(()));
+
+        // The verifier catches a prover which drops a product in the final check.
+        let mut prover = SumCheckProver::from_sum_of_products(&poly[..2].to_vec());
+        let mut verifier = SumCheckVerifier::from_sum_of_products(&poly, prover.claimed_sum());
+        let mut challenge = None;
+        for _ in 0..prover.num_rounds() {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        assert_eq!(verifier.finalize().0, Err(SumCheckError::FinalCheckFailed));
+    }
 }
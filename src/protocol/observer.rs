@@ -0,0 +1,89 @@
+//! Callbacks on the events of a run of the protocol, to collect metrics, report progress or log
+//! the messages without changing the loop of [`orchestrate_protocol_with_observer`].
+//!
+//! [`orchestrate_protocol_with_observer`]: crate::protocol::orchestrate_protocol_with_observer
+
+use crate::field::Field256 as F;
+use crate::polynomial::PolynomialDescription;
+use crate::protocol::SumCheckError;
+#[cfg(feature = "std")]
+use ark_std::vec::Vec;
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+/// Receives the events of a run of the protocol, in the order in which they happen. Every method
+/// does nothing by default.
+pub trait Observer {
+    /// The prover starts computing the message of 'round'.
+    fn on_round_start(&mut self, _round: usize) {}
+
+    /// The prover sent 'message' in 'round', before the verifier checks it.
+    fn on_prover_message(&mut self, _round: usize, _message: &PolynomialDescription) {}
+
+    /// The verifier accepted the message of 'round' and drew 'challenge'.
+    fn on_challenge(&mut self, _round: usize, _challenge: F) {}
+
+    /// The verifier accepted or rejected the claim. Called once, after the final check or the first
+    /// rejected round.
+    fn on_decision(&mut self, _result: &Result<(), SumCheckError>) {}
+}
+
+/// Ignores every event.
+impl Observer for () {}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    fn on_round_start(&mut self, round: usize) {
+        (**self).on_round_start(round)
+    }
+
+    fn on_prover_message(&mut self, round: usize, message: &PolynomialDescription) {
+        (**self).on_prover_message(round, message)
+    }
+
+    fn on_challenge(&mut self, round: usize, challenge: F) {
+        (**self).on_challenge(round, challenge)
+    }
+
+    fn on_decision(&mut self, result: &Result<(), SumCheckError>) {
+        (**self).on_decision(result)
+    }
+}
+
+/// Measures the time of each round, from the start of the round to the start of the next one or to
+/// the decision: the message of the prover, its check by the verifier and the fold of the prover.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub struct RoundTimer {
+    start: Option<std::time::Instant>,
+    /// The time of each round played.
+    pub rounds: Vec<Duration>,
+}
+
+#[cfg(feature = "std")]
+impl RoundTimer {
+    pub fn new() -> Self {
+        RoundTimer::default()
+    }
+
+    pub fn total(&self) -> Duration {
+        self.rounds.iter().sum()
+    }
+
+    fn stop(&mut self) {
+        if let Some(start) = self.start.take() {
+            self.rounds.push(start.elapsed());
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Observer for RoundTimer {
+    fn on_round_start(&mut self, _round: usize) {
+        self.stop();
+        self.start = Some(std::time::Instant::now());
+    }
+
+    fn on_decision(&mut self, _result: &Result<(), SumCheckError>) {
+        self.stop();
+    }
+}
@@ -0,0 +1,179 @@
+//! Challenges derived from a Poseidon sponge over the proof field, so that a circuit verifying the
+//! sum-check derives them with native field arithmetic, instead of emulating SHA-256.
+//!
+//! Messages are absorbed as field elements, preceded by their label, read as a little-endian
+//! integer, and by their length. A challenge drawn with `F::rand` is exactly one squeezed field
+//! element: the transcript serves the bytes of its Montgomery representation, which `F::rand`
+//! reads back as is, the representation being below the modulus.
+
+use ark_crypto_primitives::sponge::poseidon::{
+    find_poseidon_ark_and_mds, PoseidonConfig, PoseidonSponge,
+};
+use ark_crypto_primitives::sponge::CryptographicSponge;
+use ark_ff::PrimeField;
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use rand::{CryptoRng, Error, RngCore};
+
+use crate::field::Field256 as F;
+#[cfg(feature = "prover")]
+use crate::polynomial::evaluate_polynomial_on_hypercube;
+use crate::polynomial::ProductMLPolynomial;
+use crate::protocol::fiat_shamir::FiatShamirTranscript;
+#[cfg(feature = "prover")]
+use crate::protocol::proof::prove_tables;
+use crate::protocol::proof::{verify_with_transcript, Proof, PROTOCOL_LABEL};
+#[cfg(feature = "prover")]
+use crate::protocol::ProverStats;
+use crate::protocol::{ReplayableRng, SumCheckError};
+
+/// Poseidon with a state of three elements, x^5 S-boxes, and 8 full and 57 partial rounds, for
+/// 128 bits of security over a field of 255 bits.
+const RATE: usize = 2;
+const ALPHA: u64 = 5;
+const FULL_ROUNDS: usize = 8;
+const PARTIAL_ROUNDS: usize = 57;
+
+/// The parameters of the sponge, with the round constants and the MDS matrix generated as in the
+/// reference implementation of Poseidon.
+pub fn poseidon_config() -> PoseidonConfig<F> {
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(
+        F::MODULUS_BIT_SIZE as u64,
+        RATE,
+        FULL_ROUNDS as u64,
+        PARTIAL_ROUNDS as u64,
+        0,
+    );
+    PoseidonConfig::new(FULL_ROUNDS, PARTIAL_ROUNDS, ALPHA, mds, ark, RATE, 1)
+}
+
+/// The field element absorbed for a label.
+pub(crate) fn label_element(label: &[u8]) -> F {
+    F::from_le_bytes_mod_order(label)
+}
+
+/// A Fiat–Shamir transcript over a Poseidon sponge.
+#[derive(Clone)]
+pub struct PoseidonTranscript {
+    sponge: PoseidonSponge<F>,
+    /// The bytes left of the last squeezed element.
+    squeezed: Vec<u8>,
+}
+
+impl PoseidonTranscript {
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = PoseidonTranscript {
+            sponge: PoseidonSponge::new(&poseidon_config()),
+            squeezed: Vec::new(),
+        };
+        transcript.absorb(b"domain", &[label_element(label)]);
+        transcript
+    }
+
+    fn absorb(&mut self, label: &[u8], elements: &[F]) {
+        let header = [label_element(label), F::from(elements.len() as u64)];
+        self.sponge.absorb(&header.as_slice());
+        self.sponge.absorb(&elements);
+        // Challenges drawn after the message do not reuse the element squeezed before it.
+        self.squeezed.clear();
+    }
+}
+
+impl FiatShamirTranscript for PoseidonTranscript {
+    fn append_field_elements(&mut self, label: &'static [u8], elements: &[F]) {
+        self.absorb(label, elements);
+    }
+}
+
+impl RngCore for PoseidonTranscript {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    /// Serves the bytes of the Montgomery representations of squeezed field elements.
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.squeezed.is_empty() {
+                let element: F = self.sponge.squeeze_field_elements(1)[0];
+                element
+                    .0
+                    .serialize_uncompressed(&mut self.squeezed)
+                    .unwrap();
+                self.squeezed.reverse();
+            }
+            *byte = self.squeezed.pop().unwrap();
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for PoseidonTranscript {}
+
+impl ReplayableRng for PoseidonTranscript {}
+
+/// Same as [`prove`](crate::protocol::prove), with the challenges drawn from a Poseidon sponge.
+#[cfg(feature = "prover")]
+pub fn prove_with_poseidon(poly: &ProductMLPolynomial) -> Proof {
+    let tables = poly.iter().map(evaluate_polynomial_on_hypercube).collect();
+    prove_tables(
+        poly,
+        tables,
+        PoseidonTranscript::new(PROTOCOL_LABEL),
+        &mut ProverStats::default(),
+    )
+}
+
+/// Verifies a proof produced by [`prove_with_poseidon`], and returns the random point of the final
+/// check.
+pub fn verify_with_poseidon(
+    poly: &ProductMLPolynomial,
+    proof: &Proof,
+) -> Result<Vec<F>, SumCheckError> {
+    verify_with_transcript(poly, proof, PoseidonTranscript::new(PROTOCOL_LABEL))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_challenges_are_squeezed_elements() {
+        let mut transcript = PoseidonTranscript::new(b"test");
+        transcript.append_field_elements(b"message", &[F::from(1), F::from(2)]);
+        let mut sponge = transcript.sponge.clone();
+        let squeezed: Vec<F> = sponge.squeeze_field_elements(2);
+        assert_eq!(F::rand(&mut transcript), squeezed[0]);
+        assert_eq!(F::rand(&mut transcript), squeezed[1]);
+
+        let mut other = PoseidonTranscript::new(b"test");
+        other.append_field_elements(b"message", &[F::from(1), F::from(3)]);
+        assert_ne!(F::rand(&mut other), squeezed[0]);
+    }
+
+    #[test]
+    fn test_poseidon_proof() {
+        let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3)").unwrap();
+        let proof = prove_with_poseidon(&poly);
+        assert_eq!(proof.claimed_sum, crate::protocol::prove(&poly).claimed_sum);
+        assert_eq!(verify_with_poseidon(&poly, &proof).unwrap().len(), 3);
+        // The challenges differ from those of the SHA-256 transcript.
+        assert!(crate::protocol::verify(&poly, &proof).is_err());
+        let mut proof = proof;
+        proof.round_messages[1][0] += F::from(1);
+        assert!(verify_with_poseidon(&poly, &proof).is_err());
+    }
+}
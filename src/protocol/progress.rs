@@ -0,0 +1,45 @@
+//! Progress of long runs of the prover, see [`prove_with_progress`].
+//!
+//! [`prove_with_progress`]: crate::protocol::prove_with_progress
+
+use core::fmt;
+
+/// A step of the prover, reported as it completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Progress {
+    /// 'points' of the 'total' points of the hypercube are evaluated for the factor 'factor' of
+    /// 'num_factors'.
+    Evaluation {
+        factor: usize,
+        num_factors: usize,
+        points: usize,
+        total: usize,
+    },
+    /// The sum over the hypercube is computed.
+    ClaimedSum,
+    /// The round 'round' of the 'num_rounds' rounds is complete.
+    Round { round: usize, num_rounds: usize },
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Progress::Evaluation {
+                factor,
+                num_factors,
+                points,
+                total,
+            } => write!(
+                f,
+                "evaluating factor {}/{}: {}%",
+                factor + 1,
+                num_factors,
+                100 * points / total
+            ),
+            Progress::ClaimedSum => write!(f, "claimed sum computed"),
+            Progress::Round { round, num_rounds } => {
+                write!(f, "round {}/{}", round + 1, num_rounds)
+            }
+        }
+    }
+}
@@ -0,0 +1,1441 @@
+use ark_ff::Field;
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm};
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use ark_std::vec;
+use ark_std::{vec::Vec, UniformRand};
+
+use crate::field::Field256 as F;
+#[cfg(feature = "gpu")]
+use crate::gpu::GpuBackend;
+use crate::polynomial::{
+    eq_eval, evaluate_mvml_polynomial, evaluate_table, PolynomialDescription, ProductMLPolynomial,
+    MLE,
+};
+#[cfg(feature = "prover")]
+use crate::polynomial::{
+    evaluate_polynomial_on_hypercube, evaluate_polynomial_on_hypercube_with_progress,
+    get_num_vars, EvalTable, ProductInstance,
+};
+#[cfg(feature = "prover")]
+use crate::protocol::cancel::CancellationToken;
+use crate::protocol::fiat_shamir::{FiatShamirTranscript, Transcript};
+#[cfg(feature = "prover")]
+use crate::protocol::hash_chain::round_hash_chain;
+use crate::protocol::hash_chain::{RoundDigest, RoundRevealer};
+#[cfg(feature = "prover")]
+use crate::protocol::progress::Progress;
+#[cfg(feature = "prover")]
+use crate::protocol::prover::{EqFactor, Factor, Prover, ProverState};
+use crate::protocol::statement::{
+    instance_hash, instance_hash_elements, read_hash, read_hashes, InstanceHash,
+};
+#[cfg(feature = "prover")]
+use crate::protocol::stats::{OpCount, PhaseStats, ProverStats, RoundStats, Stopwatch};
+use crate::protocol::verifier::Verifier;
+use crate::protocol::{MessageFormat, SumCheckError};
+
+/// Domain separator of the Fiat–Shamir transcript.
+pub(crate) const PROTOCOL_LABEL: &[u8] = b"sum-check";
+
+/// Version of the format of the proofs and of the elements absorbed by the transcript. The proofs
+/// carry it and the transcript absorbs it first, and the verifier rejects the proofs of another
+/// version, so that a change in the encoding of the messages cannot verify older proofs wrongly.
+/// Version 2 appended the auxiliary evaluations to the encoding of [`Proof`] and
+/// [`CompressedProof`].
+pub const PROTOCOL_VERSION: u8 = 2;
+
+/// Whether the encoding of the proofs of 'version' ends with the auxiliary evaluations. Those of
+/// version 1 are still read, to be rejected for their version rather than as truncated.
+fn encodes_auxiliary_evaluations(version: u8) -> bool {
+    version >= 2
+}
+
+/// A non-interactive sum-check proof: the claimed sum and the prover's round messages, the
+/// challenges being derived from a Fiat–Shamir transcript, after the [`PROTOCOL_VERSION`] of the
+/// proof. The proof is bound to its statement by
+/// the [`instance_hash`] of the polynomial and the claimed sum, which the transcript absorbs first.
+/// Proofs produced by [`prove_with_round_commitments`] also carry the hash chain of the round
+/// messages, checked by the verifier before the rounds; it is empty otherwise. Proofs produced by
+/// [`prove_with_auxiliary_node`] carry the evaluation of each round polynomial at an auxiliary
+/// node; it is empty otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub version: u8,
+    pub claimed_sum: F,
+    pub instance_hash: InstanceHash,
+    pub round_messages: Vec<PolynomialDescription>,
+    pub round_commitments: Vec<RoundDigest>,
+    pub auxiliary_evaluations: Vec<F>,
+}
+
+// Not derived, as `ark-serialize` panics on an array cut short: the hashes are read with
+// `read_hash`, so that malformed proofs are rejected with an error.
+impl Valid for Proof {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.claimed_sum.check()?;
+        self.round_messages.check()?;
+        self.auxiliary_evaluations.check()
+    }
+}
+
+impl CanonicalSerialize for Proof {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.version.serialize_with_mode(&mut writer, compress)?;
+        self.claimed_sum.serialize_with_mode(&mut writer, compress)?;
+        self.instance_hash.serialize_with_mode(&mut writer, compress)?;
+        self.round_messages.serialize_with_mode(&mut writer, compress)?;
+        self.round_commitments.serialize_with_mode(&mut writer, compress)?;
+        if encodes_auxiliary_evaluations(self.version) {
+            self.auxiliary_evaluations.serialize_with_mode(&mut writer, compress)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        let auxiliary = if encodes_auxiliary_evaluations(self.version) {
+            self.auxiliary_evaluations.serialized_size(compress)
+        } else {
+            0
+        };
+        self.version.serialized_size(compress)
+            + self.claimed_sum.serialized_size(compress)
+            + self.instance_hash.serialized_size(compress)
+            + self.round_messages.serialized_size(compress)
+            + self.round_commitments.serialized_size(compress)
+            + auxiliary
+    }
+}
+
+impl CanonicalDeserialize for Proof {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let version = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(Proof {
+            version,
+            claimed_sum: F::deserialize_with_mode(&mut reader, compress, validate)?,
+            instance_hash: read_hash(&mut reader)?,
+            round_messages: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            round_commitments: read_hashes(&mut reader)?,
+            auxiliary_evaluations: if encodes_auxiliary_evaluations(version) {
+                Vec::deserialize_with_mode(&mut reader, compress, validate)?
+            } else {
+                Vec::new()
+            },
+        })
+    }
+}
+
+impl Proof {
+    /// Size of the serialization of the proof. The field elements are in their canonical compressed
+    /// encoding, which takes 32 bytes as the uncompressed one.
+    pub fn size_in_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+
+    /// The proof without the evaluation p(0) of each round message, which the verifier recovers
+    /// from p(0) + p(1) = running evaluation: one field element shorter per round.
+    pub fn compress(&self) -> CompressedProof {
+        CompressedProof {
+            version: self.version,
+            claimed_sum: self.claimed_sum,
+            instance_hash: self.instance_hash,
+            round_messages: self
+                .round_messages
+                .iter()
+                .map(|message| MessageFormat::Compressed.encode(message.clone()))
+                .collect(),
+            round_commitments: self.round_commitments.clone(),
+            auxiliary_evaluations: self.auxiliary_evaluations.clone(),
+        }
+    }
+}
+
+/// A [`Proof`] of [`prove`] or [`prove_with_round_commitments`] whose round messages omit p(0),
+/// for transmission. The challenges, which the omitted evaluations are recovered from, are derived
+/// from the transcript of [`prove`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompressedProof {
+    pub version: u8,
+    pub claimed_sum: F,
+    pub instance_hash: InstanceHash,
+    pub round_messages: Vec<PolynomialDescription>,
+    pub round_commitments: Vec<RoundDigest>,
+    pub auxiliary_evaluations: Vec<F>,
+}
+
+impl Valid for CompressedProof {
+    fn check(&self) -> Result<(), SerializationError> {
+        self.claimed_sum.check()?;
+        self.round_messages.check()?;
+        self.auxiliary_evaluations.check()
+    }
+}
+
+impl CanonicalSerialize for CompressedProof {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.version.serialize_with_mode(&mut writer, compress)?;
+        self.claimed_sum.serialize_with_mode(&mut writer, compress)?;
+        self.instance_hash.serialize_with_mode(&mut writer, compress)?;
+        self.round_messages.serialize_with_mode(&mut writer, compress)?;
+        self.round_commitments.serialize_with_mode(&mut writer, compress)?;
+        if encodes_auxiliary_evaluations(self.version) {
+            self.auxiliary_evaluations.serialize_with_mode(&mut writer, compress)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        let auxiliary = if encodes_auxiliary_evaluations(self.version) {
+            self.auxiliary_evaluations.serialized_size(compress)
+        } else {
+            0
+        };
+        self.version.serialized_size(compress)
+            + self.claimed_sum.serialized_size(compress)
+            + self.instance_hash.serialized_size(compress)
+            + self.round_messages.serialized_size(compress)
+            + self.round_commitments.serialized_size(compress)
+            + auxiliary
+    }
+}
+
+impl CanonicalDeserialize for CompressedProof {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let version = u8::deserialize_with_mode(&mut reader, compress, validate)?;
+        Ok(CompressedProof {
+            version,
+            claimed_sum: F::deserialize_with_mode(&mut reader, compress, validate)?,
+            instance_hash: read_hash(&mut reader)?,
+            round_messages: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            round_commitments: read_hashes(&mut reader)?,
+            auxiliary_evaluations: if encodes_auxiliary_evaluations(version) {
+                Vec::deserialize_with_mode(&mut reader, compress, validate)?
+            } else {
+                Vec::new()
+            },
+        })
+    }
+}
+
+impl CompressedProof {
+    /// Size of the serialization of the proof.
+    pub fn size_in_bytes(&self) -> usize {
+        self.compressed_size()
+    }
+
+    /// Recovers the evaluations p(0) by replaying the Fiat–Shamir transcript. The recovered proof
+    /// is checked by [`verify`] as any other.
+    pub fn decompress(&self) -> Proof {
+        let mut transcript = Transcript::new(PROTOCOL_LABEL);
+        absorb_statement(&mut transcript, &self.instance_hash, self.claimed_sum);
+        if !self.auxiliary_evaluations.is_empty() {
+            auxiliary_node(&mut transcript);
+        }
+        let mut running_eval = self.claimed_sum;
+        let round_messages = self
+            .round_messages
+            .iter()
+            .enumerate()
+            .map(|(round, message)| {
+                // An empty message, which the verifier rejects, is left as is.
+                let message =
+                    Verifier::decompress(MessageFormat::Compressed, running_eval, message.clone())
+                        .unwrap_or_default();
+                transcript.append_field_elements(b"round", &message);
+                if let Some(evaluation) = self.auxiliary_evaluations.get(round) {
+                    transcript.append_field_elements(b"auxiliary", &[*evaluation]);
+                }
+                let r = F::rand(&mut transcript);
+                if !message.is_empty() {
+                    running_eval = Verifier::evaluate_at_random_point(&message, r);
+                }
+                message
+            })
+            .collect();
+        Proof {
+            version: self.version,
+            claimed_sum: self.claimed_sum,
+            instance_hash: self.instance_hash,
+            round_messages,
+            round_commitments: self.round_commitments.clone(),
+            auxiliary_evaluations: self.auxiliary_evaluations.clone(),
+        }
+    }
+}
+
+/// Proves the sum of 'poly' over the hypercube non-interactively. The factors may have any degree
+/// in each variable, the round messages having the degree of the product in its variable.
+#[cfg(feature = "prover")]
+pub fn prove(poly: &ProductMLPolynomial) -> Proof {
+    prove_with_stats(poly).0
+}
+
+/// Same as [`prove`], also returning the cost profile of the prover.
+#[cfg(feature = "prover")]
+pub fn prove_with_stats(poly: &ProductMLPolynomial) -> (Proof, ProverStats) {
+    let mut stats = ProverStats::default();
+    let proof = prove_profiled(poly, &mut stats, &mut |_| Ok(())).expect(UNCANCELLED);
+    (proof, stats)
+}
+
+/// Same as [`prove`], calling 'progress' as the evaluation of the factors on the hypercube, the
+/// sum and the rounds advance, e.g. to show a progress bar for instances of many variables.
+#[cfg(feature = "prover")]
+pub fn prove_with_progress(
+    poly: &ProductMLPolynomial,
+    mut progress: impl FnMut(Progress),
+) -> Proof {
+    let mut report = |step| {
+        progress(step);
+        Ok(())
+    };
+    prove_profiled(poly, &mut ProverStats::default(), &mut report).expect(UNCANCELLED)
+}
+
+/// Same as [`prove`], failing with [`SumCheckError::Cancelled`] once 'token' is cancelled. The
+/// token is checked every few thousand points while the factors are evaluated on the hypercube
+/// and summed, and after each round.
+#[cfg(feature = "prover")]
+pub fn prove_cancellable(
+    poly: &ProductMLPolynomial,
+    token: &CancellationToken,
+) -> Result<Proof, SumCheckError> {
+    let instance = ProductInstance::new(poly.clone());
+    if !instance.is_multilinear() {
+        return prove_instance(&instance, &mut |_| token.check());
+    }
+    let num_vars = get_num_vars(poly).unwrap();
+    let (claimed_sum, state) = Prover::claim_sum_cancellable(poly, token)?;
+    prove_claimed_sum(
+        poly,
+        num_vars,
+        claimed_sum,
+        state,
+        Transcript::new(PROTOCOL_LABEL),
+        &mut ProverStats::default(),
+        &mut |_| token.check(),
+    )
+}
+
+/// Message of the runs which nothing can cancel.
+#[cfg(feature = "prover")]
+const UNCANCELLED: &str = "the run cannot be cancelled";
+
+/// Runs the prover, reporting its steps to 'progress', which aborts the run by failing.
+#[cfg(feature = "prover")]
+fn prove_profiled(
+    poly: &ProductMLPolynomial,
+    stats: &mut ProverStats,
+    progress: &mut dyn FnMut(Progress) -> Result<(), SumCheckError>,
+) -> Result<Proof, SumCheckError> {
+    let num_vars = get_num_vars(poly).unwrap();
+    let instance = ProductInstance::new(poly.clone());
+    if !instance.is_multilinear() {
+        return prove_instance(&instance, progress);
+    }
+    let start = Stopwatch::start();
+    let mut factors = Vec::with_capacity(poly.len());
+    for (factor, p) in poly.iter().enumerate() {
+        let mut result = Ok(());
+        let table = evaluate_polynomial_on_hypercube_with_progress(p, |points| {
+            result = progress(Progress::Evaluation {
+                factor,
+                num_factors: poly.len(),
+                points,
+                total: 1 << num_vars,
+            });
+        });
+        result?;
+        factors.push(Factor::Table(table));
+    }
+    stats.evaluation_time = start.elapsed();
+    prove_factors(
+        poly,
+        num_vars,
+        factors,
+        Transcript::new(PROTOCOL_LABEL),
+        stats,
+        progress,
+    )
+}
+
+/// Runs the prover on factors of degree above one in some variable, which the tables cannot
+/// represent, with messages of exactly the degree of the product in the variable of each round.
+/// The phases are not profiled.
+#[cfg(feature = "prover")]
+fn prove_instance(
+    instance: &ProductInstance,
+    progress: &mut dyn FnMut(Progress) -> Result<(), SumCheckError>,
+) -> Result<Proof, SumCheckError> {
+    let num_vars = instance.num_vars();
+    let mut first = (num_vars > 0).then(|| Prover::round_of_instance(instance, &[]));
+    let claimed_sum = match &first {
+        Some(message) => message[0] + message[1],
+        None => instance.evaluate(&[]),
+    };
+    progress(Progress::ClaimedSum)?;
+
+    let instance_hash = instance_hash(instance.poly(), claimed_sum);
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    absorb_statement(&mut transcript, &instance_hash, claimed_sum);
+    let mut challenges = Vec::with_capacity(num_vars);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    for round in 0..num_vars {
+        let message = first
+            .take()
+            .unwrap_or_else(|| Prover::round_of_instance(instance, &challenges));
+        transcript.append_field_elements(b"round", &message);
+        challenges.push(F::rand(&mut transcript));
+        round_messages.push(message);
+        progress(Progress::Round {
+            round,
+            num_rounds: num_vars,
+        })?;
+    }
+    Ok(Proof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+        round_commitments: Vec::new(),
+        auxiliary_evaluations: Vec::new(),
+    })
+}
+
+/// Same as [`prove`], with the hash chain of the round messages, so that they can be sent after it
+/// and checked one by one with a [`RoundRevealer`].
+#[cfg(feature = "prover")]
+pub fn prove_with_round_commitments(poly: &ProductMLPolynomial) -> Proof {
+    let mut proof = prove(poly);
+    proof.round_commitments = round_hash_chain(proof.claimed_sum, &proof.round_messages);
+    proof
+}
+
+/// Same as [`prove`], with the evaluation of each round polynomial at an auxiliary node z, drawn
+/// from the transcript after the statement and before the rounds. The prover computes p(z) apart
+/// from the message, by folding the tables at z, or term by term for factors of degree above one
+/// in some variable, and the verifier checks it against the
+/// interpolation of the message, which catches a message describing a polynomial of lower degree
+/// than the prover's round polynomial.
+///
+/// The check does not add to the soundness against a malicious prover, which may compute p(z)
+/// from its message as well: the verifier already bounds the degree by the length of the messages.
+/// It detects a faulty prover, whose round polynomial of degree D above that of its message
+/// still agrees with the message at z with probability at most D/|F|, at the cost of a field
+/// element per round in the proof, and of a fold of the tables per round for the prover.
+#[cfg(feature = "prover")]
+pub fn prove_with_auxiliary_node(poly: &ProductMLPolynomial) -> Proof {
+    prove_with_auxiliary_node_in(poly, Transcript::new(PROTOCOL_LABEL))
+}
+
+/// Same as [`prove_with_auxiliary_node`], with the challenges derived from 'transcript'.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_with_auxiliary_node_in<T: FiatShamirTranscript>(
+    poly: &ProductMLPolynomial,
+    mut transcript: T,
+) -> Proof {
+    let instance = ProductInstance::new(poly.clone());
+    if !instance.is_multilinear() {
+        return prove_instance_with_auxiliary_node(&instance, transcript);
+    }
+    let num_vars = get_num_vars(poly).unwrap();
+    let (claimed_sum, mut state) = Prover::claim_sum(poly);
+    let instance_hash = instance_hash(poly, claimed_sum);
+    absorb_statement(&mut transcript, &instance_hash, claimed_sum);
+    let node = auxiliary_node(&mut transcript);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    let mut auxiliary_evaluations = Vec::with_capacity(num_vars);
+    for round in 0..num_vars {
+        let message;
+        (message, state) = Prover::round_phase_1(state);
+        let evaluation = state.round_evaluation_at(node);
+        transcript.append_field_elements(b"round", &message);
+        transcript.append_field_elements(b"auxiliary", &[evaluation]);
+        let challenge = F::rand(&mut transcript);
+        round_messages.push(message);
+        auxiliary_evaluations.push(evaluation);
+        if round + 1 < num_vars {
+            state = Prover::round_phase_2(state, challenge);
+        }
+    }
+    Proof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+        round_commitments: Vec::new(),
+        auxiliary_evaluations,
+    }
+}
+
+/// Same as [`prove_with_auxiliary_node_in`] for factors of degree above one in some variable, as
+/// [`prove_instance`]: p(z) is summed term by term, apart from the message, as the message itself.
+#[cfg(feature = "prover")]
+fn prove_instance_with_auxiliary_node<T: FiatShamirTranscript>(
+    instance: &ProductInstance,
+    mut transcript: T,
+) -> Proof {
+    let num_vars = instance.num_vars();
+    let mut first = (num_vars > 0).then(|| Prover::round_of_instance(instance, &[]));
+    let claimed_sum = match &first {
+        Some(message) => message[0] + message[1],
+        None => instance.evaluate(&[]),
+    };
+    let instance_hash = instance_hash(instance.poly(), claimed_sum);
+    absorb_statement(&mut transcript, &instance_hash, claimed_sum);
+    let node = auxiliary_node(&mut transcript);
+    let mut challenges = Vec::with_capacity(num_vars);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    let mut auxiliary_evaluations = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let message = first
+            .take()
+            .unwrap_or_else(|| Prover::round_of_instance(instance, &challenges));
+        let evaluation = Prover::round_of_instance_at(instance, &challenges, node);
+        transcript.append_field_elements(b"round", &message);
+        transcript.append_field_elements(b"auxiliary", &[evaluation]);
+        challenges.push(F::rand(&mut transcript));
+        round_messages.push(message);
+        auxiliary_evaluations.push(evaluation);
+    }
+    Proof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+        round_commitments: Vec::new(),
+        auxiliary_evaluations,
+    }
+}
+
+/// Draws the auxiliary node of [`prove_with_auxiliary_node`] from 'transcript', after the
+/// statement.
+pub(crate) fn auxiliary_node<T: FiatShamirTranscript>(transcript: &mut T) -> F {
+    transcript.append_field_elements(b"auxiliary_node", &[]);
+    F::rand(transcript)
+}
+
+/// Same as [`prove`], with the evaluation of the factors on the hypercube and the folds of the
+/// tables offloaded to the GPU by 'backend' when they are large enough.
+#[cfg(feature = "gpu")]
+pub fn prove_with_gpu(poly: &ProductMLPolynomial, backend: &GpuBackend) -> Proof {
+    let num_vars = get_num_vars(poly).unwrap();
+    let tables = poly
+        .iter()
+        .map(|p| backend.evaluate_on_hypercube(p))
+        .collect();
+    let (claimed_sum, mut state) = Prover::claim_sum_from_tables(num_vars, tables);
+    let instance_hash = instance_hash(poly, claimed_sum);
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    absorb_statement(&mut transcript, &instance_hash, claimed_sum);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    for round in 0..num_vars {
+        let message;
+        (message, state) = Prover::round_phase_1(state);
+        transcript.append_field_elements(b"round", &message);
+        let challenge = F::rand(&mut transcript);
+        round_messages.push(message);
+        if round + 1 < num_vars {
+            state = Prover::round_phase_2_with(state, challenge, |num_vars, r, table| {
+                backend.fold_in_place(num_vars, r, table)
+            });
+        }
+    }
+    Proof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+        round_commitments: Vec::new(),
+        auxiliary_evaluations: Vec::new(),
+    }
+}
+
+/// Proves the sum over the hypercube of eq(b, r)·poly(b), i.e. the evaluation at 'r' of the
+/// multilinear extension of the hypercube evaluations of 'poly'.
+#[cfg(feature = "prover")]
+pub fn prove_weighted(poly: &ProductMLPolynomial, r: &[F]) -> Proof {
+    let num_vars = get_num_vars(poly).unwrap();
+    assert_eq!(
+        r.len(),
+        num_vars,
+        "the point must have one coordinate per variable"
+    );
+    // The eq factor is never materialized.
+    let mut factors = vec![Factor::Eq(EqFactor::new(r))];
+    factors.extend(
+        poly.iter()
+            .map(|p| Factor::Table(evaluate_polynomial_on_hypercube(p))),
+    );
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"eq_point", r);
+    prove_factors(
+        poly,
+        num_vars,
+        factors,
+        transcript,
+        &mut ProverStats::default(),
+        &mut |_| Ok(()),
+    )
+    .expect(UNCANCELLED)
+}
+
+/// Proves the sum of 'poly' over the points b of the hypercube where the selector is 1, given
+/// the evaluation table of the selector on the hypercube (see
+/// [`selector_from_bitmask`](crate::polynomial::selector_from_bitmask) and
+/// [`selector_from_indices`](crate::polynomial::selector_from_indices)). The selector is
+/// multiplied into the product as an additional factor.
+#[cfg(feature = "prover")]
+pub fn prove_selected(poly: &ProductMLPolynomial, selector: &[F]) -> Proof {
+    let num_vars = get_num_vars(poly).unwrap();
+    assert_eq!(
+        selector.len(),
+        1 << num_vars,
+        "the selector must have one entry per point of the hypercube"
+    );
+    let mut tables = vec![selector.to_vec()];
+    tables.extend(poly.iter().map(evaluate_polynomial_on_hypercube));
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"selector", selector);
+    prove_tables(poly, tables, transcript, &mut ProverStats::default())
+}
+
+/// Proves the sum of 'values', as the sum over the hypercube of their multilinear extension
+/// ([`MLE::from_values`]), and returns the sum with its proof. The simplest entry point, for a
+/// verifiable sum of a column of data.
+#[cfg(feature = "prover")]
+pub fn prove_vector_sum(values: &[F]) -> (F, Proof) {
+    let mle = MLE::from_values(values.to_vec());
+    let poly = vec![mle.to_polynomial()];
+    let proof = prove_tables(
+        &poly,
+        vec![mle.into_table()],
+        Transcript::new(PROTOCOL_LABEL),
+        &mut ProverStats::default(),
+    );
+    (proof.claimed_sum, proof)
+}
+
+/// Proves the sum of the product of 'tables', which are the evaluation tables of the factors of
+/// 'poly', possibly with additional factors, for the statement of 'poly'.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_tables<T: FiatShamirTranscript>(
+    poly: &ProductMLPolynomial,
+    tables: Vec<EvalTable>,
+    transcript: T,
+    stats: &mut ProverStats,
+) -> Proof {
+    let num_vars = get_num_vars(poly).unwrap();
+    let factors = tables.into_iter().map(Factor::Table).collect();
+    prove_factors(poly, num_vars, factors, transcript, stats, &mut |_| Ok(())).expect(UNCANCELLED)
+}
+
+/// Same as `prove_tables`, for a product of any kind of [`Factor`], reporting the sum and the
+/// rounds to 'progress', which aborts the run by failing.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_factors<T: FiatShamirTranscript>(
+    poly: &ProductMLPolynomial,
+    num_vars: usize,
+    factors: Vec<Factor>,
+    transcript: T,
+    stats: &mut ProverStats,
+    progress: &mut dyn FnMut(Progress) -> Result<(), SumCheckError>,
+) -> Result<Proof, SumCheckError> {
+    let num_polys = factors.len();
+    let start = Stopwatch::start();
+    let (claimed_sum, state) = Prover::claim_sum_of_factors(num_vars, vec![factors]);
+    stats.claim_sum = PhaseStats {
+        ops: OpCount::claim_sum(num_vars, num_polys),
+        time: start.elapsed(),
+    };
+    progress(Progress::ClaimedSum)?;
+    prove_claimed_sum(poly, num_vars, claimed_sum, state, transcript, stats, progress)
+}
+
+/// The rounds of `prove_factors`, from the sum claimed by the prover and its state.
+#[cfg(feature = "prover")]
+fn prove_claimed_sum<T: FiatShamirTranscript>(
+    poly: &ProductMLPolynomial,
+    num_vars: usize,
+    claimed_sum: F,
+    state: ProverState,
+    mut transcript: T,
+    stats: &mut ProverStats,
+    progress: &mut dyn FnMut(Progress) -> Result<(), SumCheckError>,
+) -> Result<Proof, SumCheckError> {
+    let instance_hash = instance_hash(poly, claimed_sum);
+    absorb_statement(&mut transcript, &instance_hash, claimed_sum);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    if num_vars == 0 {
+        return Ok(Proof {
+            version: PROTOCOL_VERSION,
+            claimed_sum,
+            instance_hash,
+            round_messages,
+            round_commitments: Vec::new(),
+            auxiliary_evaluations: Vec::new(),
+        });
+    }
+    let mut message_ops = state.message_cost();
+    let start = Stopwatch::start();
+    let (mut message, mut state) = Prover::round_phase_1(state);
+    let mut message_time = start.elapsed();
+    for round in 0..num_vars {
+        let remaining = num_vars - round - 1;
+        let mut round_stats = RoundStats {
+            table_size: 2 << remaining,
+            message: PhaseStats {
+                ops: message_ops,
+                time: message_time,
+            },
+            fold: PhaseStats::default(),
+        };
+        transcript.append_field_elements(b"round", &message);
+        let challenge = F::rand(&mut transcript);
+
+        if round + 1 < num_vars {
+            // The fold is fused with the message of the next round, which is timed with it.
+            round_stats.fold.ops = state.fold_cost();
+            let start = Stopwatch::start();
+            let (next_message, next_state) = Prover::fold_and_round(state, challenge);
+            message_time = start.elapsed();
+            message_ops = next_state.message_cost();
+            round_messages.push(core::mem::replace(&mut message, next_message));
+            state = next_state;
+        } else {
+            round_messages.push(core::mem::take(&mut message));
+        }
+        stats.rounds.push(round_stats);
+        progress(Progress::Round {
+            round,
+            num_rounds: num_vars,
+        })?;
+    }
+    Ok(Proof {
+        version: PROTOCOL_VERSION,
+        claimed_sum,
+        instance_hash,
+        round_messages,
+        round_commitments: Vec::new(),
+        auxiliary_evaluations: Vec::new(),
+    })
+}
+
+/// Absorbs the version of the protocol and the statement of a proof, by its hash and its claimed
+/// sum, before the rounds.
+pub(crate) fn absorb_statement<T: FiatShamirTranscript>(
+    transcript: &mut T,
+    instance_hash: &InstanceHash,
+    claimed_sum: F,
+) {
+    transcript.append_field_elements(b"version", &[F::from(PROTOCOL_VERSION)]);
+    transcript.append_field_elements(b"instance", &instance_hash_elements(instance_hash));
+    transcript.append_field_elements(b"claimed_sum", &[claimed_sum]);
+}
+
+/// The number of variables of the factors of 'poly', without trusting them to agree: fails with
+/// [`SumCheckError::VariableCountMismatch`] on the first factor whose number of variables differs
+/// from that of the first one, and with [`SumCheckError::InstanceMismatch`] on a product without
+/// factors, of which no proof is made.
+pub(crate) fn checked_num_vars<G: Field, E>(
+    poly: &[SparsePolynomial<G, SparseTerm>],
+) -> Result<usize, SumCheckError<E>> {
+    let num_vars = poly.first().ok_or(SumCheckError::InstanceMismatch)?.num_vars;
+    match poly.iter().find(|p| p.num_vars != num_vars) {
+        Some(p) => Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: p.num_vars,
+        }),
+        None => Ok(num_vars),
+    }
+}
+
+/// Checks that 'proof' is of the current version, and for the claim of its claimed sum on 'poly',
+/// whose factors must have the same variables.
+pub(crate) fn check_statement(poly: &ProductMLPolynomial, proof: &Proof) -> Result<(), SumCheckError> {
+    checked_num_vars(poly)?;
+    if proof.version != PROTOCOL_VERSION {
+        return Err(SumCheckError::UnsupportedVersion {
+            version: proof.version,
+        });
+    }
+    if proof.instance_hash != instance_hash(poly, proof.claimed_sum) {
+        return Err(SumCheckError::InstanceMismatch);
+    }
+    Ok(())
+}
+
+/// Verifies a proof produced by [`prove`], and returns the random point of the final check.
+pub fn verify(poly: &ProductMLPolynomial, proof: &Proof) -> Result<Vec<F>, SumCheckError> {
+    verify_with_transcript(poly, proof, Transcript::new(PROTOCOL_LABEL))
+}
+
+/// Same as [`verify`], with the verifier in constant-time mode: the checks of the rounds do not
+/// branch on the messages, and the proof is only rejected after the last round. See
+/// [`VerifierState::constant_time`](crate::protocol::VerifierState::constant_time).
+pub fn verify_constant_time(
+    poly: &ProductMLPolynomial,
+    proof: &Proof,
+) -> Result<Vec<F>, SumCheckError> {
+    verify_in_mode(poly, proof, Transcript::new(PROTOCOL_LABEL), true)
+}
+
+pub(crate) fn verify_with_transcript<T: FiatShamirTranscript>(
+    poly: &ProductMLPolynomial,
+    proof: &Proof,
+    transcript: T,
+) -> Result<Vec<F>, SumCheckError> {
+    verify_in_mode(poly, proof, transcript, false)
+}
+
+pub(crate) fn verify_in_mode<T: FiatShamirTranscript>(
+    poly: &ProductMLPolynomial,
+    proof: &Proof,
+    mut transcript: T,
+    constant_time: bool,
+) -> Result<Vec<F>, SumCheckError> {
+    check_statement(poly, proof)?;
+    check_round_commitments(proof)?;
+    absorb_statement(&mut transcript, &proof.instance_hash, proof.claimed_sum);
+    let node = if proof.auxiliary_evaluations.is_empty() {
+        None
+    } else if proof.auxiliary_evaluations.len() != proof.round_messages.len() {
+        return Err(SumCheckError::AuxiliaryCountMismatch {
+            expected: proof.round_messages.len(),
+            got: proof.auxiliary_evaluations.len(),
+        });
+    } else {
+        Some(auxiliary_node(&mut transcript))
+    };
+    let mut state = Verifier::initialize_with_rng(poly, proof.claimed_sum, transcript);
+    if constant_time {
+        state = state.constant_time();
+    }
+
+    for (round, message) in proof.round_messages.iter().enumerate() {
+        state.rng.append_field_elements(b"round", message);
+        let auxiliary = node.map(|node| (node, proof.auxiliary_evaluations[round]));
+        if let Some((_, evaluation)) = auxiliary {
+            state.rng.append_field_elements(b"auxiliary", &[evaluation]);
+        }
+        (_, state) = Verifier::round(state, message.clone())?;
+        // The round has checked the length of the message.
+        if let Some((node, evaluation)) = auxiliary {
+            let expected = state.interpolation.evaluate(message, node);
+            match state.deferred.as_mut() {
+                Some(deferred) => deferred.record_auxiliary(round, expected, evaluation),
+                None if expected != evaluation => {
+                    return Err(SumCheckError::AuxiliaryCheckFailed { round })
+                }
+                None => {}
+            }
+        }
+    }
+    let (result, point) = Verifier::sanity_check(state);
+    result.map(|()| point)
+}
+
+/// Verifies a proof produced by [`prove_vector_sum`] for 'values', and returns their sum.
+pub fn verify_vector_sum(values: &[F], proof: &Proof) -> Result<F, SumCheckError> {
+    let poly = vec![MLE::from_values(values.to_vec()).to_polynomial()];
+    verify(&poly, proof).map(|_| proof.claimed_sum)
+}
+
+/// Verifies a proof produced by [`prove_weighted`], and returns the random point of the final
+/// check.
+pub fn verify_weighted(
+    poly: &ProductMLPolynomial,
+    r: &[F],
+    proof: &Proof,
+) -> Result<Vec<F>, SumCheckError> {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"eq_point", r);
+    verify_with_factor(poly, proof, transcript, |point| eq_eval(point, r))
+}
+
+/// Verifies a proof produced by [`prove_selected`], and returns the random point of the final
+/// check.
+pub fn verify_selected(
+    poly: &ProductMLPolynomial,
+    selector: &[F],
+    proof: &Proof,
+) -> Result<Vec<F>, SumCheckError> {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"selector", selector);
+    verify_with_factor(poly, proof, transcript, |point| {
+        evaluate_table(selector, point)
+    })
+}
+
+/// Reveals the round messages of 'proof' against its hash chain, if it has one. A proof without
+/// a chain passes, so that a verifier expecting one must require it, as [`SumCheck::verify`]
+/// does when configured with round commitments.
+///
+/// [`SumCheck::verify`]: crate::protocol::SumCheck::verify
+fn check_round_commitments(proof: &Proof) -> Result<(), SumCheckError> {
+    if proof.round_commitments.is_empty() {
+        return Ok(());
+    }
+    let mut revealer = RoundRevealer::new(proof.claimed_sum, proof.round_commitments.clone());
+    for message in &proof.round_messages {
+        revealer.reveal(message)?;
+    }
+    revealer.finish()
+}
+
+/// Verifies a proof for the product of 'poly' with an additional factor, which the verifier
+/// evaluates itself at the random point with 'factor'.
+fn verify_with_factor(
+    poly: &ProductMLPolynomial,
+    proof: &Proof,
+    mut transcript: Transcript,
+    factor: impl FnOnce(&[F]) -> F,
+) -> Result<Vec<F>, SumCheckError> {
+    check_statement(poly, proof)?;
+    check_round_commitments(proof)?;
+    let num_vars = checked_num_vars(poly)?;
+    absorb_statement(&mut transcript, &proof.instance_hash, proof.claimed_sum);
+    // The verifier evaluates the additional factor itself, so it only needs the number of
+    // factors.
+    let mut state = Verifier::initialize_committed_with_rng(
+        num_vars,
+        poly.len() + 1,
+        proof.claimed_sum,
+        transcript,
+    );
+
+    for message in &proof.round_messages {
+        state.rng.append_field_elements(b"round", message);
+        (_, state) = Verifier::round(state, message.clone())?;
+    }
+    if state.randomness.len() != num_vars {
+        return Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: state.randomness.len(),
+        });
+    }
+    let point = state.randomness;
+    if factor(&point) * evaluate_mvml_polynomial(poly.clone(), &point) != state.running_eval {
+        return Err(SumCheckError::FinalCheckFailed);
+    }
+    Ok(point)
+}
+
+#[cfg(all(test, feature = "std", feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::{selector_from_bitmask, selector_from_indices};
+    use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+    use ark_poly::DenseMVPolynomial;
+
+    fn poly() -> ProductMLPolynomial {
+        vec![
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(2), SparseTerm::new(vec![(0, 1)])),
+                    (F::from(7), SparseTerm::new(vec![(0, 1), (2, 1)])),
+                    (F::from(5), SparseTerm::new(vec![])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                    (F::from(3), SparseTerm::new(vec![(2, 1)])),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_prove_verify() {
+        let proof = prove(&poly());
+        assert_eq!(proof.round_messages.len(), 3);
+        let point = verify(&poly(), &proof).unwrap();
+        assert_eq!(point.len(), 3);
+        // The challenges are deterministic.
+        assert_eq!(verify(&poly(), &prove(&poly())), Ok(point));
+    }
+
+    #[test]
+    fn test_mixed_degree() {
+        // Of degree 3 in x0, 1 + 1 in x1 and 1 in x2.
+        let poly = vec![
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(2), SparseTerm::new(vec![(0, 3)])),
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                3,
+                vec![
+                    (F::from(4), SparseTerm::new(vec![(1, 1), (2, 1)])),
+                    (F::from(3), SparseTerm::new(vec![])),
+                ],
+            ),
+        ];
+        let proof = prove(&poly);
+        let lengths: Vec<usize> = proof.round_messages.iter().map(Vec::len).collect();
+        assert_eq!(lengths, vec![4, 3, 2]);
+        let sum: F = (0..8)
+            .map(|n| {
+                let point = (0..3).map(|var| F::from((n >> (2 - var)) & 1 == 1)).collect();
+                evaluate_mvml_polynomial(poly.clone(), &point)
+            })
+            .sum();
+        assert_eq!(proof.claimed_sum, sum);
+        assert!(verify(&poly, &proof).is_ok());
+
+        // A message of a degree above that of the product in the variable is rejected.
+        let mut padded = proof.clone();
+        padded.round_messages[2].push(F::from(0));
+        assert!(matches!(
+            verify(&poly, &padded),
+            Err(SumCheckError::DegreeMismatch { round: 2, expected: 2, got: 3 })
+        ));
+        // The evaluation at 3 only enters the check of the next round.
+        let mut tampered = proof;
+        tampered.round_messages[0][3] += F::from(1);
+        assert!(matches!(
+            verify(&poly, &tampered),
+            Err(SumCheckError::IntermediateCheckFailed { round: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_serialization() {
+        let proof = prove(&poly());
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = Proof::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, proof);
+
+        // Every truncation is an error, including in the middle of the hashes.
+        let proof = prove_with_round_commitments(&poly());
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(Proof::deserialize_compressed(&bytes[..]).unwrap(), proof);
+        for len in 0..bytes.len() {
+            assert!(Proof::deserialize_compressed(&bytes[..len]).is_err());
+        }
+
+        // A proof of version 1, without the auxiliary evaluations, is read and then rejected for
+        // its version.
+        let mut legacy = prove(&poly());
+        legacy.version = 1;
+        let mut bytes = Vec::new();
+        legacy.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len() + 8, prove(&poly()).size_in_bytes());
+        let decoded = Proof::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, legacy);
+        assert_eq!(
+            verify(&poly(), &decoded),
+            Err(SumCheckError::UnsupportedVersion { version: 1 })
+        );
+    }
+
+    #[test]
+    fn test_compressed_proof() {
+        let proof = prove_with_round_commitments(&poly());
+        let compressed = proof.compress();
+        assert!(compressed
+            .round_messages
+            .iter()
+            .zip(&proof.round_messages)
+            .all(|(short, full)| short[..] == full[1..]));
+        // 32 bytes saved per round.
+        assert_eq!(proof.size_in_bytes() - compressed.size_in_bytes(), 3 * 32);
+        let mut bytes = Vec::new();
+        compressed.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), compressed.size_in_bytes());
+        let decoded = CompressedProof::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded.decompress(), proof);
+        assert!(verify(&poly(), &decoded.decompress()).is_ok());
+
+        let mut tampered = compressed.clone();
+        tampered.round_messages[1][0] += F::from(1);
+        assert!(verify(&poly(), &tampered.decompress()).is_err());
+        tampered.round_messages[1].clear();
+        assert!(matches!(
+            verify(&poly(), &tampered.decompress()),
+            Err(SumCheckError::RoundCommitmentMismatch { round: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let mut proof = prove(&poly());
+        proof.claimed_sum += F::from(1);
+        assert_eq!(
+            verify(&poly(), &proof),
+            Err(SumCheckError::InstanceMismatch)
+        );
+        // With the hash of the new claim, the first round is inconsistent with it.
+        proof.instance_hash = instance_hash(&poly(), proof.claimed_sum);
+        assert!(matches!(
+            verify(&poly(), &proof),
+            Err(SumCheckError::IntermediateCheckFailed { round: 0, .. })
+        ));
+
+        // Changing a later message changes the challenges, so the final check fails.
+        let mut proof = prove(&poly());
+        proof.round_messages[2][0] += F::from(1);
+        proof.round_messages[2][1] -= F::from(1);
+        assert_eq!(verify(&poly(), &proof), Err(SumCheckError::FinalCheckFailed));
+    }
+
+    #[test]
+    fn test_verify_constant_time() {
+        let proof = prove(&poly());
+        assert_eq!(verify_constant_time(&poly(), &proof), verify(&poly(), &proof));
+
+        // The first failed round is reported after the last one.
+        let mut tampered = proof.clone();
+        tampered.round_messages[1][0] += F::from(1);
+        tampered.round_messages[2][0] += F::from(1);
+        assert!(matches!(
+            verify_constant_time(&poly(), &tampered),
+            Err(SumCheckError::IntermediateCheckFailed { round: 1, .. })
+        ));
+        let mut tampered = proof;
+        tampered.round_messages[2].swap(0, 1);
+        assert_eq!(
+            verify_constant_time(&poly(), &tampered),
+            Err(SumCheckError::FinalCheckFailed)
+        );
+    }
+
+    #[test]
+    fn test_proof_version() {
+        let proof = prove(&poly());
+        assert_eq!(proof.version, PROTOCOL_VERSION);
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes[0], PROTOCOL_VERSION);
+
+        let mut other = proof.clone();
+        other.version += 1;
+        assert_eq!(
+            verify(&poly(), &other),
+            Err(SumCheckError::UnsupportedVersion {
+                version: PROTOCOL_VERSION + 1
+            })
+        );
+        assert!(matches!(
+            verify(&poly(), &other.compress().decompress()),
+            Err(SumCheckError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_replayed_proof() {
+        let proof = prove(&poly());
+        assert_eq!(
+            proof.instance_hash,
+            instance_hash(&poly(), proof.claimed_sum)
+        );
+        // A polynomial with the same sum over the hypercube.
+        let mut other = poly();
+        other.swap(0, 1);
+        assert_eq!(prove(&other).claimed_sum, proof.claimed_sum);
+        assert_eq!(verify(&other, &proof), Err(SumCheckError::InstanceMismatch));
+
+        // Rebinding the proof to the other polynomial changes the challenges.
+        let mut replayed = proof.clone();
+        replayed.instance_hash = instance_hash(&other, proof.claimed_sum);
+        assert!(verify(&other, &replayed).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_factors() {
+        let proof = prove(&poly());
+        // The statement is rebound to the malformed instance, so that only its shape is wrong.
+        let mut mismatched = poly();
+        mismatched[1].num_vars = 4;
+        let mut rebound = proof.clone();
+        rebound.instance_hash = instance_hash(&mismatched, proof.claimed_sum);
+        let expected = Err(SumCheckError::VariableCountMismatch {
+            expected: 3,
+            got: 4,
+        });
+        assert_eq!(verify(&mismatched, &rebound), expected);
+        assert_eq!(verify_constant_time(&mismatched, &rebound), expected);
+        let r = vec![F::from(1); 3];
+        let mut weighted = prove_weighted(&poly(), &r);
+        weighted.instance_hash = rebound.instance_hash;
+        assert_eq!(verify_weighted(&mismatched, &r, &weighted), expected);
+
+        let mut empty = proof;
+        empty.instance_hash = instance_hash(&[], empty.claimed_sum);
+        assert_eq!(verify(&vec![], &empty), Err(SumCheckError::InstanceMismatch));
+    }
+
+    #[test]
+    fn test_prover_stats() {
+        let (proof, stats) = prove_with_stats(&poly());
+        assert_eq!(proof, prove(&poly()));
+        assert_eq!(
+            stats.claim_sum.ops,
+            OpCount {
+                multiplications: 16,
+                additions: 8
+            }
+        );
+        let table_sizes: Vec<_> = stats.rounds.iter().map(|round| round.table_size).collect();
+        assert_eq!(table_sizes, vec![8, 4, 2]);
+        // In the first round, only the first factor depends on x0: 4 pairs of its entries are
+        // extended to 2 points, and scaled by the entries of the second factor.
+        assert_eq!(
+            stats.rounds[0].message.ops,
+            OpCount {
+                multiplications: 36,
+                additions: 24
+            }
+        );
+        assert_eq!(stats.rounds[2].fold, PhaseStats::default());
+        assert_eq!(
+            stats.total_ops(),
+            OpCount {
+                multiplications: 16 + (36 + 4) + (18 + 2) + 18,
+                additions: 8 + (24 + 8) + (12 + 4) + 15
+            }
+        );
+    }
+
+    #[test]
+    fn test_prove_with_progress() {
+        let mut steps = Vec::new();
+        let proof = prove_with_progress(&poly(), |step| steps.push(step));
+        assert_eq!(proof, prove(&poly()));
+        let evaluation = |factor| Progress::Evaluation {
+            factor,
+            num_factors: 2,
+            points: 8,
+            total: 8,
+        };
+        let round = |round| Progress::Round {
+            round,
+            num_rounds: 3,
+        };
+        assert_eq!(
+            steps,
+            vec![
+                evaluation(0),
+                evaluation(1),
+                Progress::ClaimedSum,
+                round(0),
+                round(1),
+                round(2)
+            ]
+        );
+        assert_eq!(steps[2].to_string(), "claimed sum computed");
+        assert_eq!(steps[5].to_string(), "round 3/3");
+    }
+
+    #[test]
+    fn test_prove_cancellable() {
+        let token = CancellationToken::new();
+        assert_eq!(prove_cancellable(&poly(), &token), Ok(prove(&poly())));
+        // The clones share the state of the token.
+        token.clone().cancel();
+        assert!(token.is_cancelled());
+        assert_eq!(
+            prove_cancellable(&poly(), &token),
+            Err(SumCheckError::Cancelled)
+        );
+        let expired = CancellationToken::with_timeout(core::time::Duration::ZERO);
+        assert_eq!(
+            prove_cancellable(&poly(), &expired),
+            Err(SumCheckError::Cancelled)
+        );
+    }
+
+    #[test]
+    fn test_prove_weighted() {
+        let r = vec![F::from(3), F::from(11), F::from(7)];
+        let proof = prove_weighted(&poly(), &r);
+        // The claimed sum is the evaluation at 'r' of the multilinear extension of poly.
+        let tables: Vec<_> = poly()
+            .iter()
+            .map(evaluate_polynomial_on_hypercube)
+            .collect();
+        let product: Vec<F> = (0..8).map(|pt| tables[0][pt] * tables[1][pt]).collect();
+        assert_eq!(proof.claimed_sum, evaluate_table(&product, &r));
+        // The second factor does not depend on x0.
+        assert_eq!(proof.round_messages[0].len(), 3);
+        assert!(verify_weighted(&poly(), &r, &proof).is_ok());
+
+        let other_r = vec![F::from(3), F::from(11), F::from(8)];
+        assert!(verify_weighted(&poly(), &other_r, &proof).is_err());
+        let mut proof = proof;
+        proof.claimed_sum += F::from(1);
+        assert!(verify_weighted(&poly(), &r, &proof).is_err());
+    }
+
+    #[test]
+    fn test_prove_selected() {
+        let selector = selector_from_indices(3, &[1, 4, 6]);
+        let mask = [false, true, false, false, true, false, true, false];
+        assert_eq!(selector, selector_from_bitmask(&mask));
+        let proof = prove_selected(&poly(), &selector);
+        let tables: Vec<_> = poly()
+            .iter()
+            .map(evaluate_polynomial_on_hypercube)
+            .collect();
+        let expected: F = [1, 4, 6]
+            .iter()
+            .map(|&pt| tables[0][pt] * tables[1][pt])
+            .sum();
+        assert_eq!(proof.claimed_sum, expected);
+        assert!(verify_selected(&poly(), &selector, &proof).is_ok());
+
+        let other = selector_from_indices(3, &[1, 4]);
+        assert!(verify_selected(&poly(), &other, &proof).is_err());
+        let mut proof = proof;
+        proof.claimed_sum += F::from(1);
+        assert!(verify_selected(&poly(), &selector, &proof).is_err());
+    }
+
+    #[test]
+    fn test_prove_vector_sum() {
+        let values: Vec<F> = [3, 1, 4, 1, 5].into_iter().map(F::from).collect();
+        let (sum, proof) = prove_vector_sum(&values);
+        assert_eq!(sum, F::from(14));
+        assert_eq!(proof.round_messages.len(), 3);
+        assert_eq!(
+            proof,
+            prove(&vec![MLE::from_values(values.clone()).to_polynomial()])
+        );
+        assert_eq!(verify_vector_sum(&values, &proof), Ok(sum));
+
+        let mut other = values.clone();
+        other[2] += F::from(1);
+        assert_eq!(
+            verify_vector_sum(&other, &proof),
+            Err(SumCheckError::InstanceMismatch)
+        );
+    }
+
+    #[test]
+    fn test_round_commitments() {
+        let proof = prove_with_round_commitments(&poly());
+        assert_eq!(proof.round_commitments.len(), proof.round_messages.len());
+        assert_eq!(proof.round_messages, prove(&poly()).round_messages);
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = Proof::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(verify(&poly(), &decoded).is_ok());
+
+        let mut tampered = proof.clone();
+        tampered.round_messages[1][0] += F::from(1);
+        assert_eq!(
+            verify(&poly(), &tampered),
+            Err(SumCheckError::RoundCommitmentMismatch { round: 1 })
+        );
+        let mut truncated = proof;
+        truncated.round_messages.pop();
+        assert!(matches!(
+            verify(&poly(), &truncated),
+            Err(SumCheckError::VariableCountMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_auxiliary_node() {
+        let proof = prove_with_auxiliary_node(&poly());
+        assert_eq!(proof.auxiliary_evaluations.len(), 3);
+        let point = verify(&poly(), &proof).unwrap();
+        assert_eq!(point.len(), 3);
+        // The auxiliary evaluations change the challenges.
+        assert_ne!(Ok(point), verify(&poly(), &prove(&poly())));
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(Proof::deserialize_compressed(&bytes[..]).unwrap(), proof);
+        assert_eq!(proof.compress().decompress(), proof);
+
+        let mut tampered = proof.clone();
+        tampered.auxiliary_evaluations[0] += F::from(1);
+        assert_eq!(
+            verify(&poly(), &tampered),
+            Err(SumCheckError::AuxiliaryCheckFailed { round: 0 })
+        );
+        // A message of a polynomial of degree 1 instead of 2, which passes the check of the round.
+        let mut understated = proof.clone();
+        understated.round_messages[2].pop();
+        assert_eq!(
+            verify(&poly(), &understated),
+            Err(SumCheckError::AuxiliaryCheckFailed { round: 2 })
+        );
+        // In constant-time mode, the failed auxiliary check is reported once all the rounds are
+        // played.
+        assert_eq!(
+            verify_constant_time(&poly(), &tampered),
+            Err(SumCheckError::AuxiliaryCheckFailed { round: 0 })
+        );
+        assert_eq!(verify_constant_time(&poly(), &proof), verify(&poly(), &proof));
+        let mut truncated = proof;
+        truncated.auxiliary_evaluations.pop();
+        assert_eq!(
+            verify(&poly(), &truncated),
+            Err(SumCheckError::AuxiliaryCountMismatch { expected: 3, got: 2 })
+        );
+    }
+
+    #[test]
+    fn test_auxiliary_node_of_higher_degree() {
+        // x0 has degree 3 in the product, which the tables cannot represent.
+        let poly = vec![
+            SparsePolynomial::from_coefficients_vec(
+                2,
+                vec![
+                    (F::from(1), SparseTerm::new(vec![(0, 2), (1, 1)])),
+                    (F::from(1), SparseTerm::new(vec![(1, 1)])),
+                ],
+            ),
+            SparsePolynomial::from_coefficients_vec(
+                2,
+                vec![(F::from(1), SparseTerm::new(vec![(0, 1)])), (F::from(1), SparseTerm::new(vec![]))],
+            ),
+        ];
+        let proof = prove_with_auxiliary_node(&poly);
+        assert_eq!(proof.round_messages[0].len(), 4);
+        assert_eq!(proof.claimed_sum, prove(&poly).claimed_sum);
+        assert!(verify(&poly, &proof).is_ok());
+        let mut understated = proof;
+        understated.round_messages[0].pop();
+        understated.round_messages[0].pop();
+        assert!(verify(&poly, &understated).is_err());
+    }
+
+    #[cfg(feature = "gpu")]
+    #[test]
+    fn test_prove_with_gpu() {
+        use crate::gpu::{GpuBackend, GpuConfig};
+        let config = GpuConfig { min_vars: 0 };
+        for backend in [GpuBackend::new(config), GpuBackend::cpu(config)] {
+            assert_eq!(prove_with_gpu(&poly(), &backend), prove(&poly()));
+        }
+    }
+}
@@ -1,121 +1,1198 @@
-use crate::field::Field256 as F;
+use crate::field::{batch, Field256 as F, Subfield};
 use crate::polynomial::*;
+use crate::protocol::stats::OpCount;
+#[cfg(feature = "std")]
+use crate::protocol::{check_setup, SetupError};
+use crate::protocol::{CancellationToken, MessageFormat, RoundMessage, SumCheckError};
+#[cfg(feature = "std")]
+use crate::security::DEFAULT_SECURITY_BITS;
 use ark_ff::Field;
-use ark_std::iterable::Iterable;
-use std::ops::{Add, Mul};
+use ark_poly::Polynomial;
+use ark_serialize::{
+    CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Valid, Validate,
+    Write,
+};
+use ark_std::{iterable::Iterable, vec, vec::Vec, Zero};
+use core::convert::Infallible;
+use core::ops::Mul;
 
+#[derive(Clone)]
 pub struct ProverState {
     last_round: usize,
     num_vars: usize,
-    num_polys: usize,
-    maps: Vec<EvalTable>,
+    /// The multilinears, each stored and folded once, however many products it is a factor of.
+    mles: Vec<Factor>,
+    /// The indices in `mles` of the factors of each product.
+    products: Vec<Vec<usize>>,
+    /// For each multilinear, whether it depends on each variable. A factor which does not depend
+    /// on the variable of a round only scales the round polynomial, and does not add to its
+    /// degree.
+    supports: Vec<Vec<bool>>,
+    /// The format of the round messages.
+    format: MessageFormat,
 }
 
+impl ProverState {
+    /// Sends the round messages in 'format', which the verifier must expect.
+    pub fn with_format(self, format: MessageFormat) -> Self {
+        ProverState { format, ..self }
+    }
+
+    /// Number of variables of the protocol, i.e. its number of rounds.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Index of the round whose variable is bound next.
+    pub fn round(&self) -> usize {
+        self.last_round
+    }
+
+    /// The value of each multilinear once every variable is bound, in the order they were added.
+    #[cfg(feature = "std")]
+    pub(crate) fn final_evaluations(&self) -> Vec<F> {
+        assert_eq!(self.last_round, self.num_vars, "not every variable is bound");
+        self.mles.iter().map(|mle| mle.get(0)).collect()
+    }
+
+    /// Whether each factor of 'product' depends on the variable of the current round.
+    fn active(&self, product: usize) -> impl Iterator<Item = bool> + '_ {
+        self.products[product]
+            .iter()
+            .map(move |&mle| self.supports[mle][self.last_round])
+    }
+
+    /// The factors of 'product'.
+    fn factors(&self, product: usize) -> Vec<&Factor> {
+        self.products[product]
+            .iter()
+            .map(|&mle| &self.mles[mle])
+            .collect()
+    }
+
+    /// The round polynomial of the current round at 'z', computed apart from its message: the
+    /// tables are folded at 'z' in a copy of the state, and the products summed over the rest of
+    /// the hypercube.
+    pub(crate) fn round_evaluation_at(&self, z: F) -> F {
+        let folded = Prover::round_phase_2(self.clone(), z);
+        let size = 1 << (folded.num_vars - folded.last_round);
+        (0..folded.products.len())
+            .map(|product| {
+                let factors = folded.factors(product);
+                (0..size)
+                    .map(|pt| factors.iter().map(|factor| factor.get(pt)).product::<F>())
+                    .sum::<F>()
+            })
+            .sum()
+    }
+
+    /// Degree of the round polynomial, i.e. the largest number of factors of a product which
+    /// depend on the variable of the round.
+    pub fn round_degree(&self) -> usize {
+        (0..self.products.len())
+            .map(|product| self.active(product).filter(|&active| active).count())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Cost of `Prover::round_phase_1` in the current round.
+    pub(crate) fn message_cost(&self) -> OpCount {
+        let num_vars = self.num_vars - self.last_round - 1;
+        let num_points = self.round_degree().max(1) + 1;
+        (0..self.products.len())
+            .map(|product| {
+                let active = self.active(product).filter(|&active| active).count();
+                let inactive = self.products[product].len() - active;
+                OpCount::round_message(num_vars, active, inactive, num_points)
+            })
+            .fold(OpCount::default(), |total, cost| total + cost)
+    }
+
+    /// Cost of `Prover::round_phase_2` in the current round.
+    pub(crate) fn fold_cost(&self) -> OpCount {
+        let num_vars = self.num_vars - self.last_round - 1;
+        let active = self
+            .supports
+            .iter()
+            .filter(|support| support[self.last_round])
+            .count();
+        OpCount::fold(num_vars, active)
+    }
+}
+
+/// A factor of a product in the prover state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Factor {
+    /// The evaluation table of the factor on the hypercube.
+    Table(EvalTable),
+    /// The eq polynomial of a point, whose entries are computed on demand.
+    Eq(EqFactor),
+    /// The nonzero entries of a factor which vanishes on most of the hypercube. The round
+    /// polynomials of its products are computed on the lines where it does not vanish only.
+    Sparse(SparseEvalTable),
+}
+
+/// Tables with at most one nonzero entry in this many are kept sparse by `claim_sum`.
+const SPARSE_DENSITY: usize = 16;
+
+impl Factor {
+    /// The table as a factor, kept sparse if few of its entries are nonzero.
+    pub fn from_table(table: EvalTable) -> Self {
+        let nonzero = table.iter().filter(|entry| !entry.is_zero()).count();
+        if nonzero * SPARSE_DENSITY <= table.len() {
+            Factor::Sparse(SparseEvalTable::from_table(&table))
+        } else {
+            Factor::Table(table)
+        }
+    }
+
+    /// Number of variables left.
+    pub fn num_vars(&self) -> usize {
+        match self {
+            Factor::Table(table) => table.len().trailing_zeros() as usize,
+            Factor::Eq(eq) => eq.num_vars(),
+            Factor::Sparse(sparse) => sparse.num_vars(),
+        }
+    }
+
+    /// The entry of the factor at 'pt', in the order of the evaluation tables.
+    pub fn get(&self, pt: usize) -> F {
+        match self {
+            Factor::Table(table) => table[pt],
+            Factor::Eq(eq) => eq.get(pt),
+            Factor::Sparse(sparse) => sparse.get(pt),
+        }
+    }
+
+    /// Whether the factor depends on each of its 'num_vars' variables.
+    fn support(&self, num_vars: usize) -> Vec<bool> {
+        match self {
+            Factor::Table(table) => support(num_vars, table),
+            Factor::Eq(_) => vec![true; num_vars],
+            Factor::Sparse(sparse) => sparse.support(),
+        }
+    }
+
+    /// Binds the first of the 'num_vars' + 1 variables of the factor to 'r', the tables being
+    /// folded with 'fold' if they depend on it.
+    fn fold(
+        &mut self,
+        num_vars: usize,
+        r: F,
+        active: bool,
+        fold: &mut impl FnMut(usize, F, &mut Vec<F>),
+    ) {
+        match self {
+            Factor::Table(map) if active => fold(num_vars, r, map),
+            // Both halves of the table are equal.
+            Factor::Table(map) => map.truncate(1 << num_vars),
+            Factor::Eq(eq) => eq.fold(r),
+            Factor::Sparse(sparse) => sparse.fold(r),
+        }
+    }
+}
+
+/// The sparse factor of 'product' with the fewest nonzero entries, if any.
+fn sparsest<'a>(product: &[&'a Factor]) -> Option<&'a SparseEvalTable> {
+    product
+        .iter()
+        .filter_map(|factor| match factor {
+            Factor::Sparse(sparse) => Some(sparse),
+            _ => None,
+        })
+        .min_by_key(|sparse| sparse.num_nonzero())
+}
+
+/// Handle of a multilinear of a [`VirtualPolynomial`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MleHandle(usize);
+
+/// A sum of products of multilinears, which refer to them by handle: a multilinear which is a
+/// factor of several products, such as an eq table or a witness column, is stored and folded once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VirtualPolynomial {
+    num_vars: usize,
+    mles: Vec<Factor>,
+    products: Vec<Vec<usize>>,
+}
+
+impl VirtualPolynomial {
+    /// The zero polynomial on 'num_vars' variables.
+    pub fn new(num_vars: usize) -> Self {
+        VirtualPolynomial {
+            num_vars,
+            mles: Vec::new(),
+            products: Vec::new(),
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Adds a multilinear, to be used as a factor of products.
+    pub fn add_mle(&mut self, mle: Factor) -> MleHandle {
+        assert_eq!(
+            mle.num_vars(),
+            self.num_vars,
+            "the multilinears must have {} variables",
+            self.num_vars
+        );
+        self.mles.push(mle);
+        MleHandle(self.mles.len() - 1)
+    }
+
+    /// Adds the product of the multilinears of 'factors' to the sum.
+    pub fn add_product(&mut self, factors: &[MleHandle]) {
+        self.products
+            .push(factors.iter().map(|&MleHandle(mle)| mle).collect());
+    }
+
+    /// Largest number of factors of a product, which bounds the degree of the round polynomials.
+    pub fn degree(&self) -> usize {
+        self.products.iter().map(Vec::len).max().unwrap_or(0)
+    }
+}
+
+/// The eq polynomial eq(x, point), without its table on the hypercube. The variables are split
+/// into a first and a second half, whose eq tables are multiplied on demand, so that the factor
+/// takes O(2^(n/2)) memory instead of O(2^n). The folds bind the variables of the first half, and
+/// then those of the second half.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct EqFactor {
+    /// The eq table of the first half of the variables left.
+    high: EvalTable,
+    /// The eq table of the second half of the variables left.
+    low: EvalTable,
+}
+
+impl EqFactor {
+    pub fn new(point: &[F]) -> Self {
+        let (high, low) = point.split_at(point.len() / 2);
+        EqFactor {
+            high: eq_extension(high),
+            low: eq_extension(low),
+        }
+    }
+
+    /// Number of variables left.
+    pub fn num_vars(&self) -> usize {
+        (self.high.len() * self.low.len()).trailing_zeros() as usize
+    }
+
+    /// The entry at 'pt', in the order of [`eq_extension`].
+    pub fn get(&self, pt: usize) -> F {
+        let low_vars = self.low.len().trailing_zeros();
+        self.high[pt >> low_vars] * self.low[pt & (self.low.len() - 1)]
+    }
+
+    /// Binds the first variable left to 'r'.
+    fn fold(&mut self, r: F) {
+        if self.high.len() > 1 {
+            let num_vars = self.high.len().trailing_zeros() as usize;
+            fold_in_place(num_vars - 1, r, &mut self.high);
+        } else {
+            let num_vars = self.low.len().trailing_zeros() as usize;
+            fold_in_place(num_vars - 1, r, &mut self.low);
+        }
+    }
+}
+
+// The state is written field by field, the factors and the format after a tag. The checks of
+// `Valid` ensure that a state read back from a checkpoint cannot make the rounds panic.
+impl CanonicalSerialize for ProverState {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        self.last_round.serialize_with_mode(&mut writer, compress)?;
+        self.num_vars.serialize_with_mode(&mut writer, compress)?;
+        self.mles.serialize_with_mode(&mut writer, compress)?;
+        self.products.serialize_with_mode(&mut writer, compress)?;
+        self.supports.serialize_with_mode(&mut writer, compress)?;
+        format_tag(self.format).serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        self.last_round.serialized_size(compress)
+            + self.num_vars.serialized_size(compress)
+            + self.mles.serialized_size(compress)
+            + self.products.serialized_size(compress)
+            + self.supports.serialized_size(compress)
+            + format_tag(self.format).serialized_size(compress)
+    }
+}
+
+impl Valid for ProverState {
+    fn check(&self) -> Result<(), SerializationError> {
+        let consistent = self.last_round <= self.num_vars
+            && self.num_vars < usize::BITS as usize
+            && self.supports.len() == self.mles.len()
+            && self.supports.iter().all(|support| support.len() == self.num_vars)
+            && self.mles.iter().all(|mle| mle.num_vars() == self.num_vars - self.last_round)
+            && self.products.iter().flatten().all(|&mle| mle < self.mles.len());
+        if !consistent {
+            return Err(SerializationError::InvalidData);
+        }
+        self.mles.check()
+    }
+}
+
+impl CanonicalDeserialize for ProverState {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let state = ProverState {
+            last_round: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            num_vars: usize::deserialize_with_mode(&mut reader, compress, validate)?,
+            mles: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            products: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            supports: Vec::deserialize_with_mode(&mut reader, compress, validate)?,
+            format: format_from_tag(u8::deserialize_with_mode(reader, compress, validate)?)?,
+        };
+        if validate == Validate::Yes {
+            state.check()?;
+        }
+        Ok(state)
+    }
+}
+
+fn format_tag(format: MessageFormat) -> u8 {
+    match format {
+        MessageFormat::Evaluations => 0,
+        MessageFormat::Coefficients => 1,
+        MessageFormat::Compressed => 2,
+    }
+}
+
+fn format_from_tag(tag: u8) -> Result<MessageFormat, SerializationError> {
+    match tag {
+        0 => Ok(MessageFormat::Evaluations),
+        1 => Ok(MessageFormat::Coefficients),
+        2 => Ok(MessageFormat::Compressed),
+        _ => Err(SerializationError::InvalidData),
+    }
+}
+
+impl CanonicalSerialize for Factor {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match self {
+            Factor::Table(table) => {
+                0u8.serialize_with_mode(&mut writer, compress)?;
+                table.serialize_with_mode(writer, compress)
+            }
+            Factor::Eq(eq) => {
+                1u8.serialize_with_mode(&mut writer, compress)?;
+                eq.serialize_with_mode(writer, compress)
+            }
+            Factor::Sparse(sparse) => {
+                2u8.serialize_with_mode(&mut writer, compress)?;
+                sparse.serialize_with_mode(writer, compress)
+            }
+        }
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        1 + match self {
+            Factor::Table(table) => table.serialized_size(compress),
+            Factor::Eq(eq) => eq.serialized_size(compress),
+            Factor::Sparse(sparse) => sparse.serialized_size(compress),
+        }
+    }
+}
+
+impl Valid for Factor {
+    fn check(&self) -> Result<(), SerializationError> {
+        let valid = match self {
+            Factor::Table(table) => table.len().is_power_of_two(),
+            Factor::Eq(eq) => eq.high.len().is_power_of_two() && eq.low.len().is_power_of_two(),
+            Factor::Sparse(sparse) => {
+                sparse.num_vars() < usize::BITS as usize
+                    && sparse.entries().all(|(pt, _)| pt >> sparse.num_vars() == 0)
+            }
+        };
+        if !valid {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(())
+    }
+}
+
+impl CanonicalDeserialize for Factor {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        let factor = match u8::deserialize_with_mode(&mut reader, compress, validate)? {
+            0 => Factor::Table(Vec::deserialize_with_mode(reader, compress, validate)?),
+            1 => Factor::Eq(EqFactor::deserialize_with_mode(reader, compress, validate)?),
+            2 => Factor::Sparse(SparseEvalTable::deserialize_with_mode(
+                reader, compress, validate,
+            )?),
+            _ => return Err(SerializationError::InvalidData),
+        };
+        if validate == Validate::Yes {
+            factor.check()?;
+        }
+        Ok(factor)
+    }
+}
+
+/// Number of points of the hypercube summed between two checks of `claim_sum_cancellable`.
+const SUM_BLOCK: usize = 1 << 12;
+
 pub struct Prover {}
 
 impl Prover {
     pub fn claim_sum(poly: &ProductMLPolynomial) -> (F, ProverState) {
-        let num_vars = get_num_vars(&poly).unwrap();
+        let num_vars = get_num_vars(poly).unwrap();
+        let maps = poly.iter().map(mle_table_from_sparse).collect();
+        Self::claim_sum_from_tables(num_vars, maps)
+    }
+
+    /// Same as `claim_sum`, failing with [`SumCheckError::Cancelled`] once 'token' is cancelled.
+    /// The token is checked every few thousand points, while the factors are evaluated on the
+    /// hypercube and while their product is summed.
+    pub fn claim_sum_cancellable(
+        poly: &ProductMLPolynomial,
+        token: &CancellationToken,
+    ) -> Result<(F, ProverState), SumCheckError> {
+        let num_vars = get_num_vars(poly).unwrap();
+        let factors = poly
+            .iter()
+            .map(|p| evaluate_polynomial_on_hypercube_cancellable(p, token).map(Factor::from_table))
+            .collect::<Result<_, _>>()?;
+        Self::claim_sum_of_factors_cancellable(num_vars, vec![factors], token)
+    }
+
+    /// The prover's side of [`setup_protocol`](crate::protocol::setup_protocol): checks that
+    /// 'poly' is an instance of the protocol, then computes its sum, to be sent to the verifier.
+    #[cfg(feature = "std")]
+    pub fn setup(poly: &ProductMLPolynomial) -> Result<(F, ProverState), SetupError> {
+        Self::setup_with_threshold(poly, DEFAULT_SECURITY_BITS)
+    }
+
+    /// Same as `setup`, requiring 'threshold_bits' bits of security.
+    #[cfg(feature = "std")]
+    pub fn setup_with_threshold(
+        poly: &ProductMLPolynomial,
+        threshold_bits: f64,
+    ) -> Result<(F, ProverState), SetupError> {
+        check_setup(poly, threshold_bits)?;
+        Ok(Self::claim_sum(poly))
+    }
+
+    /// Same as `claim_sum`, for a sum asserted by someone else, e.g. the statement of an
+    /// enclosing protocol. Fails up front if 'poly' does not sum to 'claimed_sum', since no
+    /// transcript of the honest prover could then convince the verifier.
+    #[cfg(feature = "std")]
+    pub fn claim_asserted_sum(poly: &ProductMLPolynomial, claimed_sum: F) -> Result<ProverState, SetupError> {
+        let (actual_sum, state) = Self::claim_sum(poly);
+        if actual_sum != claimed_sum {
+            return Err(SetupError::FalseClaim { claimed_sum, actual_sum });
+        }
+        Ok(state)
+    }
+
+    /// Round polynomial of 'instance' in the round of its variable of index `challenges.len()`,
+    /// with the previous variables set to 'challenges', by its evaluations at 0, 1, ..., d for the
+    /// degree d of the product in that variable. The factors are evaluated term by term at every
+    /// point, since the tables of the other rounds cannot represent the factors of degree above one.
+    pub fn round_of_instance(instance: &ProductInstance, challenges: &[F]) -> PolynomialDescription {
+        (0..=instance.round_degree(challenges.len()).max(1))
+            .map(|node| Self::round_of_instance_at(instance, challenges, F::from(node as u64)))
+            .collect()
+    }
+
+    /// The round polynomial of [`Prover::round_of_instance`] at 'z', summed over the rest of the
+    /// hypercube apart from the message.
+    pub(crate) fn round_of_instance_at(instance: &ProductInstance, challenges: &[F], z: F) -> F {
+        let var = challenges.len();
+        let remaining = instance.num_vars() - var - 1;
+        let mut point = challenges.to_vec();
+        point.resize(instance.num_vars(), F::ZERO);
+        point[var] = z;
+        (0..1usize << remaining)
+            .map(|n| {
+                for bit in 0..remaining {
+                    point[var + 1 + bit] = F::from((n >> (remaining - bit - 1)) & 1 == 1);
+                }
+                instance.poly().iter().map(|p| p.evaluate(&point)).fold(F::ONE, F::mul)
+            })
+            .sum()
+    }
+
+    /// Sums 'poly' over the hypercube of the variables which are not fixed. Each pair of 'fixed'
+    /// sets a variable to a constant, and the protocol then runs over the remaining variables, in
+    /// their original order.
+    pub fn claim_partial_sum(poly: &ProductMLPolynomial, fixed: &[(usize, F)]) -> (F, ProverState) {
+        let num_vars = get_num_vars(poly).unwrap();
+        let mut fixed = fixed.to_vec();
+        // Fixing the variables from the last one keeps the indices of the others valid.
+        fixed.sort_by_key(|&(var, _)| core::cmp::Reverse(var));
+        assert!(fixed.windows(2).all(|w| w[0].0 != w[1].0), "a variable is fixed twice");
+        assert!(fixed.iter().all(|&(var, _)| var < num_vars), "a fixed variable is out of range");
+        let maps = poly
+            .iter()
+            .map(|p| {
+                fixed.iter().fold(mle_table_from_sparse(p), |table, &(var, value)| {
+                    fix_variable(&table, var, value)
+                })
+            })
+            .collect();
+        Self::claim_sum_from_tables(num_vars - fixed.len(), maps)
+    }
+
+    /// Same as `claim_sum`, for a product of multilinear polynomials given directly by their
+    /// evaluation tables on the hypercube.
+    pub fn claim_sum_from_tables(num_vars: usize, maps: Vec<EvalTable>) -> (F, ProverState) {
+        Self::claim_sum_of_products_from_tables(num_vars, vec![maps])
+    }
+
+    /// Same as `claim_sum`, for a sum of products of multilinear polynomials.
+    pub fn claim_sum_of_products(poly: &SumOfProducts) -> (F, ProverState) {
+        let num_vars = get_sum_num_vars(poly).unwrap();
+        let products = poly
+            .iter()
+            .map(|product| product.iter().map(mle_table_from_sparse).collect())
+            .collect();
+        Self::claim_sum_of_products_from_tables(num_vars, products)
+    }
+
+    /// Same as `claim_sum_of_products`, for products given by the evaluation tables of their
+    /// factors. The tables with few nonzero entries are kept sparse.
+    pub fn claim_sum_of_products_from_tables(
+        num_vars: usize,
+        products: Vec<Vec<EvalTable>>,
+    ) -> (F, ProverState) {
+        let products = products
+            .into_iter()
+            .map(|maps| maps.into_iter().map(Factor::from_table).collect())
+            .collect();
+        Self::claim_sum_of_factors(num_vars, products)
+    }
+
+    /// Same as `claim_sum_of_products_from_tables`, for products of any kind of [`Factor`].
+    pub fn claim_sum_of_factors(num_vars: usize, products: Vec<Vec<Factor>>) -> (F, ProverState) {
+        let mut poly = VirtualPolynomial::new(num_vars);
+        for maps in products {
+            let factors: Vec<MleHandle> = maps.into_iter().map(|map| poly.add_mle(map)).collect();
+            poly.add_product(&factors);
+        }
+        Self::claim_virtual_sum(poly)
+    }
+
+    /// Same as `claim_sum_of_factors`, checking 'token' as `claim_sum_cancellable` does.
+    pub(crate) fn claim_sum_of_factors_cancellable(
+        num_vars: usize,
+        products: Vec<Vec<Factor>>,
+        token: &CancellationToken,
+    ) -> Result<(F, ProverState), SumCheckError> {
+        let mut poly = VirtualPolynomial::new(num_vars);
+        for maps in products {
+            let factors: Vec<MleHandle> = maps.into_iter().map(|map| poly.add_mle(map)).collect();
+            poly.add_product(&factors);
+        }
+        Self::claim_virtual_sum_in_blocks(poly, || token.check())
+    }
+
+    /// Same as `claim_sum_of_factors`, for products sharing multilinears.
+    pub fn claim_virtual_sum(poly: VirtualPolynomial) -> (F, ProverState) {
+        let Ok(claim) = Self::claim_virtual_sum_in_blocks(poly, || Ok::<(), Infallible>(()));
+        claim
+    }
+
+    /// Same as `claim_virtual_sum`, calling 'check' before each block of `SUM_BLOCK` points of
+    /// the hypercube and before each sparse product, and stopping at its first failure.
+    fn claim_virtual_sum_in_blocks<E>(
+        poly: VirtualPolynomial,
+        mut check: impl FnMut() -> Result<(), E>,
+    ) -> Result<(F, ProverState), E> {
+        let VirtualPolynomial {
+            num_vars,
+            mles,
+            products,
+        } = poly;
+        trace_span!("prover.claim_sum", num_vars, products = products.len());
+        let supports = mles.iter().map(|mle| mle.support(num_vars)).collect();
         let initial_state = ProverState {
             last_round: 0,
             num_vars,
-            num_polys: poly.len(),
-            maps: poly.iter().map(evaluate_polynomial_on_hypercube).collect(),
+            mles,
+            products,
+            supports,
+            format: MessageFormat::default(),
         };
         let mut claim = F::ZERO;
-        let mut product;
-        for pt in 0..1 << num_vars as usize {
-            product = initial_state
-                .maps
-                .iter()
-                .map(|m| m.get(pt).unwrap())
-                .fold(F::ONE, F::mul);
-            claim += product;
+        for product in 0..initial_state.products.len() {
+            let maps = initial_state.factors(product);
+            let product = |pt| maps.iter().map(|m| m.get(pt)).fold(F::ONE, F::mul);
+            if let Some(sparse) = sparsest(&maps) {
+                check()?;
+                claim += sparse.entries().map(|(pt, _)| product(pt)).sum::<F>();
+                continue;
+            }
+            for start in (0..1usize << num_vars).step_by(SUM_BLOCK) {
+                check()?;
+                let end = (start + SUM_BLOCK).min(1 << num_vars);
+                claim += (start..end).map(product).sum::<F>();
+            }
         }
-        return (claim, initial_state);
+        Ok((claim, initial_state))
     }
 
+    /// Computes the round polynomial, by its evaluations at 0, 1, ..., d where d is the degree
+    /// of the round (at least 1, so that p(0) and p(1) are always sent), or by its d + 1
+    /// coefficients.
+    /// The tables are summed over on all the available threads with the `std` feature, and the
+    /// message does not depend on their number.
     pub fn round_phase_1(state: ProverState) -> (PolynomialDescription, ProverState) {
         let num_vars = state.num_vars - state.last_round - 1;
-        let mut polynomial_points: PolynomialDescription = vec![F::ZERO; state.num_polys + 1];
-        for pt in 0..1 << num_vars as usize {
-            polynomial_points = polynomial_points
+        let num_points = state.round_degree().max(1) + 1;
+        trace_span!(
+            "prover.round",
+            round = state.last_round,
+            phase = "message",
+            table_size = 2usize << num_vars,
+            evaluations = num_points
+        );
+        let mut evaluations = vec![F::ZERO; num_points];
+        for product in 0..state.products.len() {
+            let maps = state.factors(product);
+            if let Some(sparse) = sparsest(&maps) {
+                let active: Vec<bool> = state.active(product).collect();
+                let lines = sparse.lines();
+                batch::add_assign(
+                    &mut evaluations,
+                    &factor_round_evaluations(num_vars, &maps, &active, lines, num_points),
+                );
+                continue;
+            }
+            let tables: Option<Vec<&[F]>> = maps
                 .iter()
-                .zip(Self::get_polynomial_points(&state, pt, pt + (1 << num_vars)).iter())
-                .map(|(&b, &v)| b.add(v))
+                .map(|map| match map {
+                    Factor::Table(table) => Some(&table[..]),
+                    _ => None,
+                })
                 .collect();
+            let Some(tables) = tables else {
+                let active: Vec<bool> = state.active(product).collect();
+                batch::add_assign(
+                    &mut evaluations,
+                    &factor_round_evaluations(
+                        num_vars,
+                        &maps,
+                        &active,
+                        0..1 << num_vars,
+                        num_points,
+                    ),
+                );
+                continue;
+            };
+            let (active, inactive): (Vec<_>, Vec<_>) = tables
+                .into_iter()
+                .zip(state.active(product))
+                .partition(|&(_, active)| active);
+            let active: Vec<&[F]> = active.into_iter().map(|(map, _)| map).collect();
+            let inactive: Vec<&[F]> = inactive.into_iter().map(|(map, _)| map).collect();
+            batch::add_assign(
+                &mut evaluations,
+                &round_evaluations(num_vars, &active, &inactive, num_points),
+            );
         }
-        return (polynomial_points, state);
+        (state.format.encode(evaluations), state)
     }
 
-    fn get_polynomial_points(state: &ProverState, pt0: usize, pt1: usize) -> PolynomialDescription {
-        let mut poly_description: PolynomialDescription = vec![F::ONE; state.num_polys + 1];
-        for k in 0..state.num_polys {
-            poly_description = poly_description
-                .iter()
-                .zip(
-                    Self::get_polynomial_descr_points(
-                        state.maps.get(k).unwrap(),
-                        pt0,
-                        pt1,
-                        state.num_polys,
-                    )
-                    .iter(),
-                )
-                .map(|(&b, &v)| b * v)
-                .collect();
-        }
-        poly_description
+    /// Binds the variable of the round to 'r'. The tables are folded in place, and keep their
+    /// allocation across rounds.
+    pub fn round_phase_2(state: ProverState, r: F) -> ProverState {
+        Self::round_phase_2_with(state, r, fold_in_place)
     }
 
-    fn get_polynomial_descr_points(
-        eval_table: &EvalTable,
-        pt0: usize,
-        pt1: usize,
-        num_polys: usize,
-    ) -> PolynomialDescription {
-        let mut points: PolynomialDescription = Vec::new();
-        let mut t0: &F;
-        let mut t1: &F;
-        let mut jf: F;
-        for j in 0..=num_polys {
-            t0 = eval_table.get(pt0).unwrap();
-            t1 = eval_table.get(pt1).unwrap();
-            jf = F::from(j as u16);
-            points.push(*t0 - (jf * t0) + (jf * t1))
+    /// Same as `round_phase_2`, folding the tables which depend on the variable of the round with
+    /// 'fold', which takes the same arguments as `fold_in_place`.
+    pub(crate) fn round_phase_2_with(
+        mut state: ProverState,
+        r: F,
+        mut fold: impl FnMut(usize, F, &mut Vec<F>),
+    ) -> ProverState {
+        let num_vars = state.num_vars - state.last_round - 1;
+        trace_span!(
+            "prover.round",
+            round = state.last_round,
+            phase = "fold",
+            table_size = 2usize << num_vars
+        );
+        for (mle, support) in state.mles.iter_mut().zip(&state.supports) {
+            mle.fold(num_vars, r, support[state.last_round], &mut fold);
         }
-        points
+        state.last_round += 1;
+        state
     }
 
-    pub fn round_phase_2(state: ProverState, r: F) -> ProverState {
+    /// Same as `round_phase_2` followed by `round_phase_1`, in a single pass over the tables: the
+    /// entries of the folded tables are used for the next round polynomial as soon as they are
+    /// written, instead of being read again. There must be a round left after the one of 'r'.
+    pub fn fold_and_round(mut state: ProverState, r: F) -> (PolynomialDescription, ProverState) {
         let num_vars = state.num_vars - state.last_round - 1;
-        let new_map = reduce(num_vars, r, &state.maps);
-        let new_state = ProverState {
-            last_round: state.last_round + 1,
-            maps: new_map,
-            ..state
+        assert!(num_vars > 0, "no round is left after the fold");
+        let half = 1 << (num_vars - 1);
+        let (fold_round, round) = (state.last_round, state.last_round + 1);
+        state.last_round = round;
+        let num_points = state.round_degree().max(1) + 1;
+        trace_span!(
+            "prover.round",
+            round,
+            phase = "fold_and_message",
+            table_size = 2usize << num_vars,
+            evaluations = num_points
+        );
+        let mut evaluations = vec![F::ZERO; num_points];
+        let mut points = vec![F::ZERO; num_points];
+        // The eq and sparse factors are small, and folded ahead of the pass.
+        for (mle, support) in state.mles.iter_mut().zip(&state.supports) {
+            if !matches!(mle, Factor::Table(_)) {
+                mle.fold(num_vars, r, support[fold_round], &mut fold_in_place);
+            }
+        }
+        // The products with a sparse factor are only summed over its lines, after the pass.
+        let (sparse, dense): (Vec<usize>, Vec<usize>) = (0..state.products.len())
+            .partition(|&product| sparsest(&state.factors(product)).is_some());
+        // The entries of the folded tables at pt and pt + half, which are the two ends of the
+        // lines of the next round.
+        let mut ends = vec![(F::ZERO, F::ZERO); state.mles.len()];
+        for pt in 0..half {
+            for ((mle, support), end) in state.mles.iter_mut().zip(&state.supports).zip(&mut ends) {
+                let Factor::Table(map) = mle else {
+                    continue;
+                };
+                let (mut a0, mut a1) = (map[pt], map[pt + half]);
+                if support[fold_round] {
+                    a0 += (map[pt + 2 * half] - a0) * r;
+                    a1 += (map[pt + 3 * half] - a1) * r;
+                    map[pt] = a0;
+                    map[pt + half] = a1;
+                }
+                *end = (a0, a1);
+            }
+            for &product in &dense {
+                points.fill(F::ONE);
+                for &mle in &state.products[product] {
+                    let (a0, a1) = match &state.mles[mle] {
+                        Factor::Table(_) => ends[mle],
+                        other => (other.get(pt), other.get(pt + half)),
+                    };
+                    if state.supports[mle][round] {
+                        multiply_by_line(&mut points, a0, a1);
+                    } else {
+                        batch::scale(&mut points, a0);
+                    }
+                }
+                batch::add_assign(&mut evaluations, &points);
+            }
+        }
+        for mle in state.mles.iter_mut() {
+            if let Factor::Table(table) = mle {
+                table.truncate(1 << num_vars);
+            }
+        }
+        for product in sparse {
+            let maps = state.factors(product);
+            let active: Vec<bool> = state.active(product).collect();
+            let lines = sparsest(&maps).unwrap().lines();
+            batch::add_assign(
+                &mut evaluations,
+                &factor_round_evaluations(num_vars - 1, &maps, &active, lines, num_points),
+            );
+        }
+        (state.format.encode(evaluations), state)
+    }
+}
+
+/// Round-by-round driver for the prover, for protocols that manage the transcript themselves.
+///
+/// The first call to [`SumCheckProver::next_message`] takes no challenge; every following call
+/// takes the verifier's challenge for the previous round, binds the corresponding variable to it
+/// and returns the message of the next round.
+pub struct SumCheckProver {
+    claimed_sum: F,
+    state: Option<ProverState>,
+    awaiting_challenge: bool,
+}
+
+impl SumCheckProver {
+    /// Computes the sum of 'poly' over the hypercube and prepares the first round.
+    pub fn new(poly: &ProductMLPolynomial) -> Self {
+        let (claimed_sum, state) = Prover::claim_sum(poly);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Same as `new`, failing if 'poly' is not an instance of the protocol. The prover needs
+    /// nothing from the verifier: it sends it the claimed sum, then the messages.
+    #[cfg(feature = "std")]
+    pub fn setup(poly: &ProductMLPolynomial) -> Result<Self, SetupError> {
+        let (claimed_sum, state) = Prover::setup(poly)?;
+        Ok(Self::from_state(claimed_sum, state))
+    }
+
+    /// Same as `new`, for the partial sum where the variables of 'fixed' are set to constants.
+    pub fn with_fixed(poly: &ProductMLPolynomial, fixed: &[(usize, F)]) -> Self {
+        let (claimed_sum, state) = Prover::claim_partial_sum(poly, fixed);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Same as `new`, for a product of multilinear polynomials given by their evaluation tables.
+    pub fn from_tables(num_vars: usize, tables: Vec<EvalTable>) -> Self {
+        let (claimed_sum, state) = Prover::claim_sum_from_tables(num_vars, tables);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Same as `new`, for a sum of products of multilinear polynomials.
+    pub fn from_sum_of_products(poly: &SumOfProducts) -> Self {
+        let (claimed_sum, state) = Prover::claim_sum_of_products(poly);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Same as `from_sum_of_products`, for products given by the evaluation tables of their
+    /// factors.
+    pub fn from_sum_of_products_tables(num_vars: usize, products: Vec<Vec<EvalTable>>) -> Self {
+        let (claimed_sum, state) = Prover::claim_sum_of_products_from_tables(num_vars, products);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Same as `from_sum_of_products`, for products of any kind of [`Factor`].
+    pub fn from_factors(num_vars: usize, products: Vec<Vec<Factor>>) -> Self {
+        let (claimed_sum, state) = Prover::claim_sum_of_factors(num_vars, products);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Same as `from_factors`, for products sharing multilinears.
+    pub fn from_virtual(poly: VirtualPolynomial) -> Self {
+        let (claimed_sum, state) = Prover::claim_virtual_sum(poly);
+        Self::from_state(claimed_sum, state)
+    }
+
+    /// Resumes from 'snapshot', e.g. a checkpoint read back after the process was restarted.
+    pub fn from_snapshot(snapshot: ProverSnapshot) -> Self {
+        SumCheckProver {
+            claimed_sum: snapshot.claimed_sum,
+            state: Some(snapshot.state),
+            awaiting_challenge: snapshot.awaiting_challenge,
+        }
+    }
+
+    /// Resumes from a state obtained with [`setup_protocol`](crate::protocol::setup_protocol).
+    pub fn from_state(claimed_sum: F, state: ProverState) -> Self {
+        SumCheckProver {
+            claimed_sum,
+            state: Some(state),
+            awaiting_challenge: false,
+        }
+    }
+
+    pub fn claimed_sum(&self) -> F {
+        self.claimed_sum
+    }
+
+    /// Number of rounds of the protocol, i.e. the number of variables of the polynomial.
+    pub fn num_rounds(&self) -> usize {
+        self.state.as_ref().unwrap().num_vars
+    }
+
+    /// Index of the round whose message will be returned by the next call to `next_message`.
+    pub fn round(&self) -> usize {
+        let state = self.state.as_ref().unwrap();
+        state.last_round + usize::from(self.awaiting_challenge)
+    }
+
+    /// Produces the message of the next round.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'challenge' is given in the first round or missing in a later one, or if all
+    /// rounds have already been played.
+    pub fn next_message(&mut self, challenge: Option<F>) -> RoundMessage {
+        let state = self.state.take().unwrap();
+        match (self.awaiting_challenge, challenge) {
+            (true, Some(_)) | (false, None) => {}
+            (true, None) => panic!("the prover expects the challenge of the previous round"),
+            (false, Some(_)) => panic!("no challenge is expected before the first round"),
+        }
+        let next_round = state.last_round + usize::from(self.awaiting_challenge);
+        assert!(
+            next_round < state.num_vars,
+            "all rounds of the protocol have been played"
+        );
+        let (evaluations, state) = match challenge {
+            Some(r) => Prover::fold_and_round(state, r),
+            None => Prover::round_phase_1(state),
         };
-        new_state
+        self.state = Some(state);
+        self.awaiting_challenge = true;
+        RoundMessage { evaluations }
+    }
+
+    /// A copy of the state of the prover, with the tables folded so far, to branch the interaction
+    /// from the current round.
+    pub fn snapshot(&self) -> ProverSnapshot {
+        ProverSnapshot {
+            claimed_sum: self.claimed_sum,
+            state: self.state.clone().unwrap(),
+            awaiting_challenge: self.awaiting_challenge,
+        }
+    }
+
+    /// Returns to the state of 'snapshot', which may be restored again.
+    pub fn restore(&mut self, snapshot: &ProverSnapshot) {
+        self.claimed_sum = snapshot.claimed_sum;
+        self.state = Some(snapshot.state.clone());
+        self.awaiting_challenge = snapshot.awaiting_challenge;
     }
 }
 
-fn reduce(num_vars: usize, r: F, tables: &Vec<EvalTable>) -> Vec<EvalTable> {
-    tables
-        .iter()
-        .map(|table| reduce_map(num_vars, r, table))
+/// Version of the checkpoint format of [`ProverSnapshot`], written first so that a checkpoint of
+/// another version is rejected instead of being misread.
+pub const CHECKPOINT_VERSION: u8 = 1;
+
+/// A saved [`SumCheckProver`], taken with [`SumCheckProver::snapshot`]. It holds a copy of the
+/// tables of the prover, i.e. as much memory as the prover itself.
+///
+/// Serialized, it is a checkpoint from which [`SumCheckProver::from_snapshot`] resumes a long run
+/// in another process: the [`CHECKPOINT_VERSION`], the claimed sum, whether the prover awaits a
+/// challenge, then its state with the tables folded so far.
+#[derive(Clone)]
+pub struct ProverSnapshot {
+    claimed_sum: F,
+    state: ProverState,
+    awaiting_challenge: bool,
+}
+
+impl ProverSnapshot {
+    /// Index of the round whose message the prover was to return next when the snapshot was
+    /// taken.
+    pub fn round(&self) -> usize {
+        self.state.last_round + usize::from(self.awaiting_challenge)
+    }
+}
+
+impl CanonicalSerialize for ProverSnapshot {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        CHECKPOINT_VERSION.serialize_with_mode(&mut writer, compress)?;
+        self.claimed_sum.serialize_with_mode(&mut writer, compress)?;
+        self.awaiting_challenge.serialize_with_mode(&mut writer, compress)?;
+        self.state.serialize_with_mode(writer, compress)
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        CHECKPOINT_VERSION.serialized_size(compress)
+            + self.claimed_sum.serialized_size(compress)
+            + self.awaiting_challenge.serialized_size(compress)
+            + self.state.serialized_size(compress)
+    }
+}
+
+impl Valid for ProverSnapshot {
+    fn check(&self) -> Result<(), SerializationError> {
+        // The message of the round of the challenge has been sent, so that the round is left.
+        if self.awaiting_challenge && self.state.last_round == self.state.num_vars {
+            return Err(SerializationError::InvalidData);
+        }
+        self.state.check()
+    }
+}
+
+impl CanonicalDeserialize for ProverSnapshot {
+    fn deserialize_with_mode<R: Read>(
+        mut reader: R,
+        compress: Compress,
+        validate: Validate,
+    ) -> Result<Self, SerializationError> {
+        if u8::deserialize_with_mode(&mut reader, compress, validate)? != CHECKPOINT_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        let snapshot = ProverSnapshot {
+            claimed_sum: F::deserialize_with_mode(&mut reader, compress, validate)?,
+            awaiting_challenge: bool::deserialize_with_mode(&mut reader, compress, validate)?,
+            state: ProverState::deserialize_with_mode(reader, compress, validate)?,
+        };
+        if validate == Validate::Yes {
+            snapshot.check()?;
+        }
+        Ok(snapshot)
+    }
+}
+
+/// Number of points of the hypercube in a chunk of `round_evaluations`.
+const ROUND_CHUNK: usize = 1 << 12;
+
+/// Evaluations at 0, 1, ..., 'num_points' - 1 of the round polynomial of the product of the
+/// tables of 'active' and 'inactive', which are on 'num_vars' + 1 variables. The tables of
+/// 'inactive' must not depend on the first variable, and there must be more points than tables in
+/// 'active'. The tables may live in any field.
+///
+/// The sum is split into partial sums over contiguous chunks of the hypercube, computed by
+/// `map_chunks` and added up in the order of the chunks. Since the addition of a field is exact,
+/// associative and commutative, the result is the same whatever the number of threads, and
+/// whichever thread computed each chunk.
+pub(crate) fn round_evaluations<T: Field>(
+    num_vars: usize,
+    active: &[&[T]],
+    inactive: &[&[T]],
+    num_points: usize,
+) -> Vec<T> {
+    let size = 1usize << num_vars;
+    let partials = map_chunks(size.div_ceil(ROUND_CHUNK), |chunk| {
+        let start = chunk * ROUND_CHUNK;
+        chunk_round_evaluations(active, inactive, num_vars, num_points, start..size.min(start + ROUND_CHUNK))
+    });
+    partials
+        .into_iter()
+        .fold(vec![T::ZERO; num_points], |mut sum, partial| {
+            batch::add_assign(&mut sum, &partial);
+            sum
+        })
+}
+
+/// Same as `round_evaluations`, summed over the points 'pts' of the hypercube only.
+fn chunk_round_evaluations<T: Field>(
+    active: &[&[T]],
+    inactive: &[&[T]],
+    num_vars: usize,
+    num_points: usize,
+    pts: core::ops::Range<usize>,
+) -> Vec<T> {
+    // The accumulators are allocated once, and updated in place for every point of the chunk.
+    let mut polynomial_points = vec![T::ZERO; num_points];
+    let mut points = vec![T::ZERO; num_points];
+    for pt in pts {
+        points.fill(T::ONE);
+        for map in active {
+            multiply_by_line(&mut points, map[pt], map[pt + (1 << num_vars)]);
+        }
+        if !inactive.is_empty() {
+            let weight = inactive.iter().map(|map| map[pt]).fold(T::ONE, T::mul);
+            batch::scale(&mut points, weight);
+        }
+        batch::add_assign(&mut polynomial_points, &points);
+    }
+    polynomial_points
+}
+
+/// The results of 'chunk' on each of 0, 1, ..., 'num_chunks' - 1, in this order. With the `std`
+/// feature, the chunks are shared by all the available threads, each taking the next chunk left
+/// as soon as it is done with its last one, so that a slow thread does not hold the others.
+fn map_chunks<T: Send>(num_chunks: usize, chunk: impl Fn(usize) -> T + Sync) -> Vec<T> {
+    #[cfg(feature = "std")]
+    {
+        use core::sync::atomic::{AtomicUsize, Ordering};
+
+        let num_threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(num_chunks);
+        if num_threads > 1 {
+            let next = AtomicUsize::new(0);
+            let mut results: Vec<(usize, T)> = std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..num_threads)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut results = Vec::new();
+                            loop {
+                                let index = next.fetch_add(1, Ordering::Relaxed);
+                                if index >= num_chunks {
+                                    return results;
+                                }
+                                results.push((index, chunk(index)));
+                            }
+                        })
+                    })
+                    .collect();
+                workers
+                    .into_iter()
+                    .flat_map(|worker| worker.join().expect("a prover thread panicked"))
+                    .collect()
+            });
+            results.sort_unstable_by_key(|&(index, _)| index);
+            return results.into_iter().map(|(_, result)| result).collect();
+        }
+    }
+    (0..num_chunks).map(chunk).collect()
+}
+
+/// Same as `round_evaluations`, for a product of any kind of factors, whose entries are read one
+/// by one. 'active' tells whether each factor depends on the first variable. Only the lines
+/// through 'pts' and 'pts' + 2^'num_vars' are summed, the product vanishing on the others.
+fn factor_round_evaluations(
+    num_vars: usize,
+    factors: &[&Factor],
+    active: &[bool],
+    pts: impl IntoIterator<Item = usize>,
+    num_points: usize,
+) -> Vec<F> {
+    let mut polynomial_points = vec![F::ZERO; num_points];
+    let mut points = vec![F::ZERO; num_points];
+    for pt in pts {
+        points.fill(F::ONE);
+        for (factor, &active) in factors.iter().zip(active) {
+            if active {
+                multiply_by_line(
+                    &mut points,
+                    factor.get(pt),
+                    factor.get(pt + (1 << num_vars)),
+                );
+            } else {
+                batch::scale(&mut points, factor.get(pt));
+            }
+        }
+        batch::add_assign(&mut polynomial_points, &points);
+    }
+    polynomial_points
+}
+
+/// Multiplies 'points[j]' by the evaluation at j of the line through (0, t0) and (1, t1).
+pub(crate) fn multiply_by_line<T: Field>(points: &mut [T], t0: T, t1: T) {
+    for (j, point) in points.iter_mut().enumerate() {
+        let jf = T::from(j as u16);
+        *point *= t0 - (jf * t0) + (jf * t1);
+    }
+}
+
+/// Whether the multilinear extension of 'table' depends on each of its 'num_vars' variables.
+fn support<T: Field>(num_vars: usize, table: &[T]) -> Vec<bool> {
+    (0..num_vars)
+        .map(|var| {
+            let stride = 1 << (num_vars - var - 1);
+            (0..table.len())
+                .filter(|pt| pt & stride == 0)
+                .any(|pt| table[pt] != table[pt + stride])
+        })
         .collect()
 }
 
-fn reduce_map(num_vars: usize, r: F, map: &Vec<F>) -> EvalTable {
-    (0..(1 << num_vars) as usize)
-        .map(|pt| (combine_table_elements(pt, pt + (1 << num_vars), r, map)))
-        .collect::<Vec<F>>()
+/// Binds the first of the 'num_vars' + 1 variables of 'map' to 'r'. The challenge may live in an
+/// extension of the field of the table, in which case the folded table lives in the extension.
+pub(crate) fn reduce_map<T: Subfield<E>, E: Field>(num_vars: usize, r: E, map: &[T]) -> Vec<E> {
+    let (low, high) = map.split_at(1 << num_vars);
+    batch::fold(low, high, r)
 }
 
-fn combine_table_elements(pt0: usize, pt1: usize, r: F, table: &EvalTable) -> F {
-    let a0 = table.get(pt0).unwrap();
-    let a1 = table.get(pt1).unwrap();
-    return *a0 - (r * a0) + (r * a1);
+/// Same as `reduce_map` when the challenge lives in the field of the table, overwriting the first
+/// half of 'map' with the folded table and dropping the second half.
+pub(crate) fn fold_in_place<T: Field>(num_vars: usize, r: T, map: &mut Vec<T>) {
+    let (low, high) = map.split_at_mut(1 << num_vars);
+    batch::fold_in_place(low, high, r);
+    map.truncate(1 << num_vars);
 }
 
 #[cfg(test)]
@@ -125,6 +1202,14 @@ mod tests {
     use ark_poly::multivariate::{SparsePolynomial, SparseTerm};
     use ark_poly::DenseMVPolynomial;
 
+    #[test]
+    fn test_support() {
+        // 3 * x0 + x2, on 3 variables.
+        let table: Vec<F> = [0, 1, 0, 1, 3, 4, 3, 4].into_iter().map(F::from).collect();
+        assert_eq!(support(3, &table), vec![true, false, true]);
+        assert_eq!(support(2, &[F::from(5); 4]), vec![false, false]);
+    }
+
     #[test]
     fn test_reduce_map() {
         let our_map = Vec::from([
@@ -142,6 +1227,225 @@ mod tests {
         let expected = Vec::from([F::from(2225), F::from(1005), F::from(3680), F::from(5343)]);
 
         assert!(reduced.eq(&expected));
+
+        let mut folded = our_map.clone();
+        fold_in_place(2, r, &mut folded);
+        assert_eq!(folded, expected);
+        assert!(folded.capacity() >= our_map.len());
+    }
+
+    #[test]
+    fn test_chunked_round_evaluations() {
+        use ark_std::UniformRand;
+        let mut rng = rand::thread_rng();
+        let num_vars = 14;
+        let tables: Vec<Vec<F>> = (0..3)
+            .map(|_| (0..2 << num_vars).map(|_| F::rand(&mut rng)).collect())
+            .collect();
+        let active: Vec<&[F]> = tables[..2].iter().map(|table| &table[..]).collect();
+        let inactive = [&tables[2][..]];
+        assert_eq!(
+            round_evaluations(num_vars, &active, &inactive, 3),
+            chunk_round_evaluations(&active, &inactive, num_vars, 3, 0..1 << num_vars)
+        );
+        assert_eq!(map_chunks(100, |chunk| chunk * chunk), (0..100).map(|chunk| chunk * chunk).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_fold_and_round() {
+        use ark_std::UniformRand;
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(0);
+        let random = |rng: &mut StdRng| -> EvalTable { (0..16).map(|_| F::rand(rng)).collect() };
+        // x1 + x3 does not depend on x0 nor x2.
+        let sparse: EvalTable = (0..16u64)
+            .map(|pt| F::from((pt >> 2 & 1) + (pt & 1)))
+            .collect();
+        let products = vec![
+            vec![random(&mut rng), sparse.clone(), random(&mut rng)],
+            vec![sparse, random(&mut rng)],
+        ];
+        for format in [
+            MessageFormat::Evaluations,
+            MessageFormat::Coefficients,
+            MessageFormat::Compressed,
+        ] {
+            let state = |products: &Vec<Vec<EvalTable>>| {
+                let (_, state) = Prover::claim_sum_of_products_from_tables(4, products.clone());
+                state.with_format(format)
+            };
+            let (mut separate, mut fused) = (state(&products), state(&products));
+            (_, separate) = Prover::round_phase_1(separate);
+            (_, fused) = Prover::round_phase_1(fused);
+            for round in 1..4 {
+                let r = F::from(round as u64 + 10);
+                let (message, fused_message);
+                (fused_message, fused) = Prover::fold_and_round(fused, r);
+                separate = Prover::round_phase_2(separate, r);
+                (message, separate) = Prover::round_phase_1(separate);
+                assert_eq!(fused_message, message);
+                assert_eq!(fused.mles, separate.mles);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sparse_factor() {
+        use ark_std::UniformRand;
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut selector = vec![F::ZERO; 32];
+        selector[3] = F::from(2);
+        selector[22] = F::from(5);
+        let table: EvalTable = (0..32).map(|_| F::rand(&mut rng)).collect();
+        let (claim, mut sparse) = Prover::claim_sum_of_products_from_tables(
+            5,
+            vec![vec![selector.clone(), table.clone()]],
+        );
+        assert!(matches!(sparse.mles[0], Factor::Sparse(_)));
+        assert!(matches!(sparse.mles[1], Factor::Table(_)));
+        let products = vec![vec![Factor::Table(selector), Factor::Table(table)]];
+        let (expected_claim, mut dense) = Prover::claim_sum_of_factors(5, products);
+        assert_eq!(claim, expected_claim);
+        let (mut message, mut expected);
+        (message, sparse) = Prover::round_phase_1(sparse);
+        (expected, dense) = Prover::round_phase_1(dense);
+        assert_eq!(message, expected);
+        for round in 1..5 {
+            let r = F::rand(&mut rng);
+            if round % 2 == 0 {
+                (message, sparse) = Prover::fold_and_round(sparse, r);
+            } else {
+                sparse = Prover::round_phase_2(sparse, r);
+                (message, sparse) = Prover::round_phase_1(sparse);
+            }
+            (expected, dense) = Prover::fold_and_round(dense, r);
+            assert_eq!(message, expected);
+        }
+    }
+
+    #[test]
+    fn test_virtual_polynomial() {
+        use ark_std::UniformRand;
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(3);
+        let point: Vec<F> = (0..4).map(|_| F::rand(&mut rng)).collect();
+        let [a, b, c]: [EvalTable; 3] =
+            core::array::from_fn(|_| (0..16).map(|_| F::rand(&mut rng)).collect());
+        let eq = || Factor::Eq(EqFactor::new(&point));
+        let mut poly = VirtualPolynomial::new(4);
+        let shared =
+            [eq(), Factor::Table(a.clone()), Factor::Table(b.clone())].map(|mle| poly.add_mle(mle));
+        let c_mle = poly.add_mle(Factor::Table(c.clone()));
+        poly.add_product(&[shared[0], shared[1], shared[2]]);
+        poly.add_product(&[shared[0], shared[1], c_mle]);
+        poly.add_product(&[shared[2], c_mle]);
+        assert_eq!(poly.degree(), 3);
+        let products = vec![
+            vec![eq(), Factor::Table(a.clone()), Factor::Table(b.clone())],
+            vec![eq(), Factor::Table(a), Factor::Table(c.clone())],
+            vec![Factor::Table(b), Factor::Table(c)],
+        ];
+        let (claim, mut state) = Prover::claim_virtual_sum(poly);
+        let (expected_claim, mut duplicated) = Prover::claim_sum_of_factors(4, products);
+        assert_eq!(claim, expected_claim);
+        assert_eq!((state.mles.len(), duplicated.mles.len()), (4, 8));
+        assert!(state.fold_cost().multiplications < duplicated.fold_cost().multiplications);
+        let (mut message, mut expected);
+        (message, state) = Prover::round_phase_1(state);
+        (expected, duplicated) = Prover::round_phase_1(duplicated);
+        assert_eq!(message, expected);
+        for round in 1..4 {
+            let r = F::rand(&mut rng);
+            if round % 2 == 0 {
+                (message, state) = Prover::fold_and_round(state, r);
+            } else {
+                state = Prover::round_phase_2(state, r);
+                (message, state) = Prover::round_phase_1(state);
+            }
+            (expected, duplicated) = Prover::fold_and_round(duplicated, r);
+            assert_eq!(message, expected);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "the multilinears must have 3 variables")]
+    fn test_virtual_polynomial_variable_count() {
+        VirtualPolynomial::new(3).add_mle(Factor::Table(vec![F::ONE; 16]));
+    }
+
+    #[test]
+    fn test_eq_factor() {
+        use ark_std::UniformRand;
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(1);
+        let point: Vec<F> = (0..5).map(|_| F::rand(&mut rng)).collect();
+        let table: EvalTable = (0..32).map(|_| F::rand(&mut rng)).collect();
+        let state = |eq: Factor| {
+            let products = vec![vec![eq, Factor::Table(table.clone())]];
+            Prover::claim_sum_of_factors(5, products)
+        };
+        let (claim, mut lazy) = state(Factor::Eq(EqFactor::new(&point)));
+        let (expected_claim, mut dense) = state(Factor::Table(eq_extension(&point)));
+        assert_eq!(claim, expected_claim);
+        let (mut message, mut expected);
+        (message, lazy) = Prover::round_phase_1(lazy);
+        (expected, dense) = Prover::round_phase_1(dense);
+        assert_eq!(message, expected);
+        for round in 1..5 {
+            let r = F::rand(&mut rng);
+            // The eq factor goes through both the separate and the fused fold.
+            if round % 2 == 0 {
+                (message, lazy) = Prover::fold_and_round(lazy, r);
+            } else {
+                lazy = Prover::round_phase_2(lazy, r);
+                (message, lazy) = Prover::round_phase_1(lazy);
+            }
+            (expected, dense) = Prover::fold_and_round(dense, r);
+            assert_eq!(message, expected);
+            let (eq, expected_eq) = (&lazy.mles[0], &dense.mles[0]);
+            assert!((0..1 << (5 - round)).all(|pt| eq.get(pt) == expected_eq.get(pt)));
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        use ark_std::UniformRand;
+        use rand::{rngs::StdRng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(3);
+        let point: Vec<F> = (0..4).map(|_| F::rand(&mut rng)).collect();
+        let mut selector = vec![F::ZERO; 16];
+        selector[5] = F::from(3);
+        let table: EvalTable = (0..16).map(|_| F::rand(&mut rng)).collect();
+        let products = vec![vec![
+            Factor::Eq(EqFactor::new(&point)),
+            Factor::from_table(selector),
+            Factor::Table(table),
+        ]];
+        let mut prover = SumCheckProver::from_factors(4, products);
+        let challenges: Vec<F> = (0..4).map(|_| F::rand(&mut rng)).collect();
+        prover.next_message(None);
+        prover.next_message(Some(challenges[0]));
+
+        let mut bytes = Vec::new();
+        prover.snapshot().serialize_compressed(&mut bytes).unwrap();
+        let snapshot = ProverSnapshot::deserialize_compressed(&bytes[..]).unwrap();
+        assert_eq!(snapshot.round(), 2);
+        let mut resumed = SumCheckProver::from_snapshot(snapshot);
+        assert_eq!(resumed.claimed_sum(), prover.claimed_sum());
+        for &r in &challenges[1..3] {
+            assert_eq!(resumed.next_message(Some(r)), prover.next_message(Some(r)));
+        }
+
+        // Another version, a cut checkpoint and an inconsistent round are rejected.
+        let mut other_version = bytes.clone();
+        other_version[0] += 1;
+        assert!(ProverSnapshot::deserialize_compressed(&other_version[..]).is_err());
+        assert!(ProverSnapshot::deserialize_compressed(&bytes[..bytes.len() - 1]).is_err());
+        let mut wrong_round = bytes.clone();
+        // The round index follows the version, the claimed sum and the flag.
+        wrong_round[1 + 32 + 1] += 1;
+        assert!(ProverSnapshot::deserialize_compressed(&wrong_round[..]).is_err());
     }
 
     #[test]
@@ -168,11 +1472,76 @@ mod tests {
         let (prover_claim, prover_state) = Prover::claim_sum(&multilinear_list);
         assert_eq!(prover_claim, F::from(93));
         let (poly_descr, _) = Prover::round_phase_1(prover_state);
-        let expected: PolynomialDescription =
-            Vec::from([F::from(21), F::from(72), F::from(135), F::from(210)]);
+        let expected: PolynomialDescription = Vec::from([F::from(21), F::from(72), F::from(135)]);
+        // p3 does not depend on x0, so the round polynomial has degree 2.
         assert_eq!(poly_descr, expected)
     }
 
+    #[test]
+    fn test_cancelled_claim_sum() {
+        // x0 + ... + x12, times 1 + x0, dense on 13 variables.
+        let p1 = SparsePolynomial::from_coefficients_vec(
+            13,
+            (0..13).map(|var| (F::from(1), SparseTerm::new(vec![(var, 1)]))).collect(),
+        );
+        let p2 = SparsePolynomial::from_coefficients_vec(
+            13,
+            vec![(F::from(1), SparseTerm::new(vec![])), (F::from(1), SparseTerm::new(vec![(0, 1)]))],
+        );
+        let poly = vec![p1, p2];
+        let token = CancellationToken::new();
+        let (claim, _) = Prover::claim_sum_cancellable(&poly, &token).unwrap();
+        assert_eq!(claim, Prover::claim_sum(&poly).0);
+        token.cancel();
+        assert!(matches!(
+            Prover::claim_sum_cancellable(&poly, &token),
+            Err(SumCheckError::Cancelled)
+        ));
+        // The sum stops at the first failed check, before its second block of 2^12 points.
+        let mut virtual_poly = VirtualPolynomial::new(13);
+        let factors: Vec<MleHandle> = poly
+            .iter()
+            .map(|p| virtual_poly.add_mle(Factor::Table(evaluate_polynomial_on_hypercube(p))))
+            .collect();
+        virtual_poly.add_product(&factors);
+        let mut checks = 0;
+        let result = Prover::claim_virtual_sum_in_blocks(virtual_poly, || {
+            checks += 1;
+            if checks == 2 { Err(()) } else { Ok(()) }
+        });
+        assert!(result.is_err());
+        assert_eq!(checks, 2);
+    }
+
+    #[test]
+    fn test_claimed_partial_sum() {
+        let p1 = SparsePolynomial::from_coefficients_vec(
+            3,
+            Vec::from([
+                (F::from(1), SparseTerm::new(vec![(0, 1), (1, 1)])),
+                (F::from(7), SparseTerm::new(vec![(2, 1)])),
+            ]),
+        );
+        let p2 = SparsePolynomial::from_coefficients_vec(
+            3,
+            Vec::from([
+                (F::from(2), SparseTerm::new(vec![(1, 1)])),
+                (F::from(1), SparseTerm::new(vec![])),
+            ]),
+        );
+        let multilinear_list = vec![p1, p2];
+        let (prover_claim, prover_state) =
+            Prover::claim_partial_sum(&multilinear_list, &[(1, F::from(5))]);
+        // The sum over x0 and x2 of (5 x0 + 7 x2) * 11.
+        assert_eq!(prover_claim, F::from(264));
+        assert_eq!(prover_state.num_vars, 2);
+
+        let (prover_claim, _) =
+            Prover::claim_partial_sum(&multilinear_list, &[(2, F::from(0)), (0, F::from(3))]);
+        // The sum over x1 of (3 x1) * (2 x1 + 1).
+        assert_eq!(prover_claim, F::from(9));
+    }
+
     #[test]
     fn test_claimed_sum_2() {
         let p1 = SparsePolynomial::from_coefficients_vec(
@@ -0,0 +1,223 @@
+//! The set from which the verifier draws its challenges. The protocol draws them from the whole
+//! field, but some applications restrict them, e.g. to a multiplicative subgroup, on which the
+//! random point can be encoded more cheaply, or to the field without a few values. A wrong round
+//! polynomial then survives a challenge with probability d/|S| for the set S instead of d/|F|, so
+//! that the restriction costs log2(|F|/|S|) bits of security, which the estimates of
+//! [`security`](crate::security) take into account through the [`ChallengeSpace`] of the sampler.
+//!
+//! The samplers only apply to the interactive protocol, where the verifier sends its challenges
+//! to the prover: the non-interactive [`prove`](crate::protocol::prove) and
+//! [`verify`](crate::protocol::verify), and the [`SumCheck`](crate::protocol::SumCheck) entry
+//! points, draw them from the whole field.
+
+use ark_ff::{FftField, Field, PrimeField};
+use ark_std::{vec::Vec, UniformRand};
+use rand::{Rng, RngCore};
+
+use crate::field::Field256 as F;
+
+/// The size of the set from which a [`ChallengeSampler`] draws uniformly.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChallengeSpace {
+    /// The whole field.
+    Field,
+    /// A subset of 'size' elements.
+    Subset(u128),
+    /// The field without 'excluded' of its elements.
+    FieldExcept(u64),
+    /// A part of the field, holding a fraction 2^'log2_density' of its elements.
+    Part { log2_density: f64 },
+}
+
+impl ChallengeSpace {
+    /// Whether the space surely holds more than 'count' elements. That of a part of the field is
+    /// only bounded from below, by 2^⌊log2 |F| + 'log2_density'⌋.
+    pub fn exceeds(&self, count: usize) -> bool {
+        let log2_order = F::MODULUS_BIT_SIZE - 1;
+        match *self {
+            ChallengeSpace::Field | ChallengeSpace::FieldExcept(_) => true,
+            ChallengeSpace::Subset(size) => size > count as u128,
+            ChallengeSpace::Part { log2_density } => {
+                let log2_size = log2_order as f64 + log2_density;
+                log2_size >= 0.0
+                    && (log2_size >= 64.0 || (count as u64) < 1 << (log2_size as u32))
+            }
+        }
+    }
+}
+
+/// Draws the challenges of the interactive verifier uniformly from a set of field elements.
+pub trait ChallengeSampler: Send + Sync {
+    /// A challenge drawn from 'rng'.
+    fn sample(&self, rng: &mut dyn RngCore) -> F;
+
+    /// The set from which the challenges are drawn.
+    fn space(&self) -> ChallengeSpace;
+}
+
+/// Any field element, as in the protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FullField;
+
+impl ChallengeSampler for FullField {
+    fn sample(&self, rng: &mut dyn RngCore) -> F {
+        F::rand(rng)
+    }
+
+    fn space(&self) -> ChallengeSpace {
+        ChallengeSpace::Field
+    }
+}
+
+/// The powers g^0, ..., g^(n − 1) of a generator g of a multiplicative subgroup of order n.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Subgroup {
+    generator: F,
+    order: u64,
+}
+
+impl Subgroup {
+    /// The subgroup of order 'order', or None if 'order' does not divide |F| − 1.
+    pub fn of_order(order: u64) -> Option<Self> {
+        if order == 0 {
+            return None;
+        }
+        // (|F| − 1) / order, by long division of the limbs from the most significant one. The
+        // modulus is odd, so that subtracting one does not borrow.
+        let mut limbs: Vec<u64> = F::MODULUS.as_ref().to_vec();
+        limbs[0] -= 1;
+        let mut remainder = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let current = remainder << 64 | *limb as u128;
+            *limb = (current / order as u128) as u64;
+            remainder = current % order as u128;
+        }
+        if remainder != 0 {
+            return None;
+        }
+        Some(Subgroup {
+            generator: F::GENERATOR.pow(limbs),
+            order,
+        })
+    }
+
+    pub fn generator(&self) -> F {
+        self.generator
+    }
+
+    pub fn order(&self) -> u64 {
+        self.order
+    }
+
+    /// Whether 'x' is in the subgroup.
+    pub fn contains(&self, x: F) -> bool {
+        x.pow([self.order]) == F::from(1)
+    }
+}
+
+impl ChallengeSampler for Subgroup {
+    fn sample(&self, rng: &mut dyn RngCore) -> F {
+        self.generator.pow([rng.gen_range(0..self.order)])
+    }
+
+    fn space(&self) -> ChallengeSpace {
+        ChallengeSpace::Subset(self.order as u128)
+    }
+}
+
+/// Field elements drawn again until 'accept' holds, uniform on the accepted subset, whose size
+/// the caller gives. The expected number of draws is |F| / |S|, so that the subset must be a large
+/// part of the field.
+pub struct RejectionSampler<P> {
+    accept: P,
+    space: ChallengeSpace,
+}
+
+impl<P: Fn(&F) -> bool + Send + Sync> RejectionSampler<P> {
+    pub fn new(accept: P, space: ChallengeSpace) -> Self {
+        RejectionSampler { accept, space }
+    }
+}
+
+impl RejectionSampler<fn(&F) -> bool> {
+    /// The field without the nodes 0 and 1, at which the round polynomials are checked.
+    pub fn nonbinary() -> Self {
+        RejectionSampler {
+            accept: |x: &F| *x != F::from(0) && *x != F::from(1),
+            space: ChallengeSpace::FieldExcept(2),
+        }
+    }
+}
+
+impl<P: Fn(&F) -> bool + Send + Sync> ChallengeSampler for RejectionSampler<P> {
+    fn sample(&self, rng: &mut dyn RngCore) -> F {
+        loop {
+            let r = F::rand(rng);
+            if (self.accept)(&r) {
+                return r;
+            }
+        }
+    }
+
+    fn space(&self) -> ChallengeSpace {
+        self.space
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::BigInteger;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_subgroup() {
+        // |F| − 1 = 2^255 − 20 = 2^2 · 3 · ...
+        let subgroup = Subgroup::of_order(12).unwrap();
+        assert_eq!(subgroup.order(), 12);
+        assert!(subgroup.contains(subgroup.generator()));
+        // The generator has order exactly 12.
+        assert!((1..12).all(|i| subgroup.generator().pow([i]) != F::from(1)));
+        assert_eq!(Subgroup::of_order(2).unwrap().generator(), -F::from(1));
+        assert!(Subgroup::of_order(8).is_none());
+        assert!(Subgroup::of_order(0).is_none());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let samples: Vec<F> = (0..64).map(|_| subgroup.sample(&mut rng)).collect();
+        assert!(samples.iter().all(|&x| subgroup.contains(x)));
+        assert!(samples.iter().any(|&x| x != samples[0]));
+        assert_eq!(subgroup.space(), ChallengeSpace::Subset(12));
+    }
+
+    #[test]
+    fn test_rejection_sampler() {
+        let mut rng = StdRng::seed_from_u64(0);
+        // Half of the field, by the parity of the canonical representative.
+        let even = RejectionSampler::new(
+            |x: &F| x.into_bigint().is_even(),
+            ChallengeSpace::Part { log2_density: -1.0 },
+        );
+        assert!((0..32).all(|_| even.sample(&mut rng).into_bigint().is_even()));
+        let nonbinary = RejectionSampler::nonbinary();
+        let r = nonbinary.sample(&mut rng);
+        assert!(r != F::from(0) && r != F::from(1));
+        assert_eq!(nonbinary.space(), ChallengeSpace::FieldExcept(2));
+        assert_eq!(FullField.space(), ChallengeSpace::Field);
+    }
+
+    #[test]
+    fn test_space_size() {
+        assert!(ChallengeSpace::Field.exceeds(usize::MAX));
+        assert!(ChallengeSpace::FieldExcept(2).exceeds(usize::MAX));
+        assert!(ChallengeSpace::Subset(12).exceeds(11));
+        assert!(!ChallengeSpace::Subset(12).exceeds(12));
+        assert!(ChallengeSpace::Part { log2_density: -1.0 }.exceeds(usize::MAX));
+        // 2^⌊254 − 250.5⌋ = 8 elements at least.
+        let part = ChallengeSpace::Part { log2_density: -250.5 };
+        assert!(part.exceeds(7));
+        assert!(!part.exceeds(8));
+        assert!(ChallengeSpace::Part { log2_density: -254.0 }.exceeds(0));
+        assert!(!ChallengeSpace::Part { log2_density: -254.5 }.exceeds(0));
+    }
+}
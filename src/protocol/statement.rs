@@ -0,0 +1,181 @@
+//! Canonical hash of the statement of a [`Proof`](crate::protocol::Proof): the field, the factors
+//! of the product and the claimed sum. The hash is stored in the proof and absorbed into the
+//! Fiat–Shamir transcript before the claimed sum, so that a proof cannot be replayed against
+//! another polynomial or another claim. A verifier which does not hold the polynomial keeps its
+//! [`InstanceDigest`] instead.
+
+use ark_ff::PrimeField;
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Read, SerializationError, Validate};
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::field::{BinaryField128, Field256 as F};
+use crate::polynomial::{get_num_vars, variable_degree, ProductMLPolynomial};
+
+/// SHA-256 hash of a statement.
+pub type InstanceHash = [u8; 32];
+
+/// Reads a hash from 'reader'. The proofs read their hashes with it, as `ark-serialize` panics on
+/// an array cut short.
+pub(crate) fn read_hash<R: Read>(mut reader: R) -> Result<[u8; 32], SerializationError> {
+    let mut hash = [0; 32];
+    reader.read_exact(&mut hash)?;
+    Ok(hash)
+}
+
+/// Reads a vector of hashes from 'reader', in the encoding of `ark-serialize`.
+pub(crate) fn read_hashes<R: Read>(mut reader: R) -> Result<Vec<[u8; 32]>, SerializationError> {
+    let len = u64::deserialize_with_mode(&mut reader, Compress::No, Validate::No)?;
+    (0..len).map(|_| read_hash(&mut reader)).collect()
+}
+
+/// The hash of the statement that 'poly', over any prime field 'G', sums to 'claimed_sum' over the
+/// hypercube. It absorbs, in this order and with the integers as 8 little-endian bytes: the
+/// modulus of the field, the number of variables and of factors, then for each factor its number
+/// of terms and each term as its coefficient, its number of variables and its pairs (variable,
+/// degree), and last the claimed sum. The modulus and the field elements are in their canonical
+/// compressed serialization. The terms of a factor and the variables of a term are in their
+/// canonical sorted order, so that two descriptions of a factor which only differ in the order of
+/// their terms have the same hash.
+pub fn instance_hash<G: PrimeField>(
+    poly: &[SparsePolynomial<G, SparseTerm>],
+    claimed_sum: G,
+) -> InstanceHash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"sum-check instance");
+    absorb_element(&mut hasher, &G::MODULUS);
+    absorb_integer(&mut hasher, poly.first().map_or(0, |p| p.num_vars));
+    absorb_factors(&mut hasher, poly);
+    absorb_element(&mut hasher, &claimed_sum);
+    hasher.finalize().into()
+}
+
+/// What a verifier needs to know of a product of multilinear polynomials to run the protocol,
+/// instead of the factors themselves: the number of variables and of factors, the degree of the
+/// round polynomial of each round, and a hash of the coefficient lists of the factors. The hash is
+/// that of [`instance_hash`] without the claimed sum, under a label of its own. It binds the
+/// final check to the factors revealed after the rounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceDigest {
+    pub num_vars: usize,
+    pub num_polys: usize,
+    /// The degree of the product in the variable of each round.
+    pub degree_bounds: Vec<usize>,
+    pub hash: InstanceHash,
+}
+
+impl InstanceDigest {
+    /// The digest of 'poly', whose factors must all have the same number of variables. A verifier
+    /// given the factors checks them with [`matches`](Self::matches), which does not panic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'poly' has no factor, or factors of different numbers of variables.
+    pub fn new(poly: &ProductMLPolynomial) -> Self {
+        let num_vars = get_num_vars(poly).expect("the factors must have the same variables");
+        let mut hasher = Sha256::new();
+        hasher.update(b"sum-check factors");
+        absorb_element(&mut hasher, &F::MODULUS);
+        absorb_integer(&mut hasher, num_vars);
+        absorb_factors(&mut hasher, poly);
+        InstanceDigest {
+            num_vars,
+            num_polys: poly.len(),
+            degree_bounds: (0..num_vars)
+                .map(|var| poly.iter().map(|p| variable_degree(p, var)).sum())
+                .collect(),
+            hash: hasher.finalize().into(),
+        }
+    }
+
+    /// Whether 'poly' is the product of which this is the digest.
+    pub fn matches(&self, poly: &ProductMLPolynomial) -> bool {
+        get_num_vars(poly).is_some() && *self == Self::new(poly)
+    }
+}
+
+/// Absorbs the number of factors, then for each factor its number of terms and each term as its
+/// coefficient, its number of variables and its pairs (variable, degree).
+fn absorb_factors<G: PrimeField>(hasher: &mut Sha256, poly: &[SparsePolynomial<G, SparseTerm>]) {
+    absorb_integer(hasher, poly.len());
+    for factor in poly {
+        absorb_integer(hasher, factor.terms.len());
+        for (coefficient, term) in &factor.terms {
+            absorb_element(hasher, coefficient);
+            absorb_integer(hasher, term.len());
+            for &(var, degree) in term.iter() {
+                absorb_integer(hasher, var);
+                absorb_integer(hasher, degree);
+            }
+        }
+    }
+}
+
+/// The hash of the statement that the product of the multilinear extensions of 'tables', over
+/// the binary field [`BinaryField128`], sums to 'claimed_sum' over the hypercube. It absorbs the
+/// number of variables and of factors, then the entries of each table and last the claimed sum,
+/// under a label of its own so that it never collides with the hash of a prime-field statement.
+pub fn binary_instance_hash(
+    tables: &[Vec<BinaryField128>],
+    claimed_sum: BinaryField128,
+) -> InstanceHash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"sum-check binary instance");
+    absorb_integer(&mut hasher, tables.first().map_or(0, |t| t.len().trailing_zeros() as usize));
+    absorb_integer(&mut hasher, tables.len());
+    for entry in tables.iter().flatten() {
+        absorb_element(&mut hasher, entry);
+    }
+    absorb_element(&mut hasher, &claimed_sum);
+    hasher.finalize().into()
+}
+
+fn absorb_integer(hasher: &mut Sha256, n: usize) {
+    hasher.update((n as u64).to_le_bytes());
+}
+
+fn absorb_element(hasher: &mut Sha256, element: &impl CanonicalSerialize) {
+    let mut bytes = Vec::new();
+    element.serialize_compressed(&mut bytes).unwrap();
+    hasher.update(bytes);
+}
+
+/// The hash as the two field elements of its halves, each read as a little-endian integer of 128
+/// bits, in which form the transcripts absorb it.
+pub fn instance_hash_elements(hash: &InstanceHash) -> [F; 2] {
+    let half = |i: usize| u128::from_le_bytes(hash[16 * i..16 * (i + 1)].try_into().unwrap());
+    [F::from(half(0)), F::from(half(1))]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use ark_std::vec;
+
+    #[test]
+    fn test_instance_hash() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        let hash = instance_hash(&poly, F::from(5));
+        // The order of the terms does not change the hash.
+        let reordered = parse_product("(2*x1 + x0) * (3 + x2*x1)").unwrap();
+        assert_eq!(instance_hash(&reordered, F::from(5)), hash);
+        assert_ne!(instance_hash(&poly, F::from(6)), hash);
+        let other = parse_product("(x0 + 2*x1) * (x1*x2 + 4)").unwrap();
+        assert_ne!(instance_hash(&other, F::from(5)), hash);
+        let swapped = parse_product("(x1*x2 + 3) * (x0 + 2*x1)").unwrap();
+        assert_ne!(instance_hash(&swapped, F::from(5)), hash);
+    }
+
+    #[test]
+    fn test_instance_digest() {
+        let poly = parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        let digest = InstanceDigest::new(&poly);
+        assert_eq!((digest.num_vars, digest.num_polys), (3, 2));
+        assert_eq!(digest.degree_bounds, vec![1, 2, 1]);
+        assert!(digest.matches(&parse_product("(2*x1 + x0) * (3 + x2*x1)").unwrap()));
+        assert!(!digest.matches(&parse_product("(x0 + 2*x1) * (x1*x2 + 4)").unwrap()));
+        assert!(!digest.matches(&vec![]));
+    }
+}
@@ -0,0 +1,140 @@
+//! Cost profile of the prover.
+
+use ark_std::vec::Vec;
+use core::ops::{Add, AddAssign};
+use core::time::Duration;
+
+/// Number of field operations, subtractions counting as additions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OpCount {
+    pub multiplications: u64,
+    pub additions: u64,
+}
+
+impl OpCount {
+    /// Cost of summing the product of 'num_polys' tables over a hypercube of dimension 'num_vars'.
+    pub(crate) fn claim_sum(num_vars: usize, num_polys: usize) -> Self {
+        let points = 1u64 << num_vars;
+        OpCount {
+            multiplications: points * num_polys as u64,
+            additions: points,
+        }
+    }
+
+    /// Cost of the round message of a product when 'num_vars' variables remain after the round:
+    /// for each of the 2^num_vars pairs of entries, the tables of the 'active' factors, which
+    /// depend on the variable of the round, are extended to the 'num_points' evaluation points,
+    /// which costs two multiplications and two additions per point, and multiplied together. The
+    /// 'inactive' factors are multiplied into a weight scaling the points.
+    pub(crate) fn round_message(
+        num_vars: usize,
+        active: usize,
+        inactive: usize,
+        num_points: usize,
+    ) -> Self {
+        let pairs = 1u64 << num_vars;
+        let (active, inactive, points) = (active as u64, inactive as u64, num_points as u64);
+        let weight = if inactive > 0 { inactive + points } else { 0 };
+        OpCount {
+            multiplications: pairs * (active * points * 3 + weight),
+            additions: pairs * (active * points * 2 + points),
+        }
+    }
+
+    /// Cost of binding a variable in 'num_polys' tables which depend on it, leaving 'num_vars'
+    /// variables. The other tables are only truncated.
+    pub(crate) fn fold(num_vars: usize, num_polys: usize) -> Self {
+        let entries = (1u64 << num_vars) * num_polys as u64;
+        OpCount {
+            multiplications: entries,
+            additions: entries * 2,
+        }
+    }
+}
+
+impl Add for OpCount {
+    type Output = OpCount;
+
+    fn add(self, other: OpCount) -> OpCount {
+        OpCount {
+            multiplications: self.multiplications + other.multiplications,
+            additions: self.additions + other.additions,
+        }
+    }
+}
+
+impl AddAssign for OpCount {
+    fn add_assign(&mut self, other: OpCount) {
+        *self = *self + other;
+    }
+}
+
+/// Cost of a phase of the prover.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PhaseStats {
+    pub ops: OpCount,
+    pub time: Duration,
+}
+
+/// Cost of a round: computing the message, then binding the variable of the round to the
+/// challenge. The prover does not bind the variable of the last round. The fold is fused with the
+/// message of the next round, whose time includes it, so that the time of the fold is zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RoundStats {
+    /// Number of entries of each table at the start of the round.
+    pub table_size: usize,
+    pub message: PhaseStats,
+    pub fold: PhaseStats,
+}
+
+/// Cost profile of a run of the prover.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProverStats {
+    /// Evaluation of the factors on the hypercube. Its operations are not counted, as they depend
+    /// on the number of terms of the factors.
+    pub evaluation_time: Duration,
+    pub claim_sum: PhaseStats,
+    pub rounds: Vec<RoundStats>,
+}
+
+/// Measures the time elapsed since its start. Without the `std` feature there is no clock, and
+/// the elapsed time is always zero.
+pub(crate) struct Stopwatch {
+    #[cfg(feature = "std")]
+    start: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Stopwatch {
+            #[cfg(feature = "std")]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    #[cfg(not(feature = "std"))]
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::ZERO
+    }
+}
+
+impl ProverStats {
+    pub fn total_ops(&self) -> OpCount {
+        self.rounds.iter().fold(self.claim_sum.ops, |ops, round| {
+            ops + round.message.ops + round.fold.ops
+        })
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.rounds
+            .iter()
+            .fold(self.evaluation_time + self.claim_sum.time, |time, round| {
+                time + round.message.time + round.fold.time
+            })
+    }
+}
@@ -0,0 +1,247 @@
+//! Drivers of the two parties whose phase of the protocol is part of their type, so that calls out
+//! of order do not compile: a [`TypedProver`] sends the message of a round before it can receive
+//! its challenge, and a [`TypedVerifier`] is consumed by a rejected message and only reaches its
+//! final check once every round has been played. The number of rounds is only known at run time,
+//! so each step returns an enum of the phases which may follow it.
+//!
+//! [`SumCheckProver`](crate::protocol::SumCheckProver) and
+//! [`SumCheckVerifier`](crate::protocol::SumCheckVerifier) check the same order when called, by
+//! panicking.
+
+use ark_std::vec::Vec;
+use core::marker::PhantomData;
+
+use rand::{CryptoRng, RngCore};
+
+use crate::field::Field256 as F;
+#[cfg(feature = "prover")]
+use crate::polynomial::PolynomialDescription;
+#[cfg(feature = "prover")]
+use crate::protocol::prover::{Prover, ProverState};
+use crate::protocol::verifier::{EvaluationClaim, Verifier, VerifierState};
+use crate::protocol::{RoundMessage, SumCheckError};
+
+/// Phase of a [`TypedProver`] which sends the message of its round next.
+#[cfg(feature = "prover")]
+pub struct ReadyToSend;
+
+/// Phase of a [`TypedProver`] which has sent the message of its round and waits for its challenge.
+#[cfg(feature = "prover")]
+pub struct AwaitingChallenge;
+
+/// A prover in the phase 'P' of a round.
+#[cfg(feature = "prover")]
+pub struct TypedProver<P> {
+    claimed_sum: F,
+    state: ProverState,
+    /// The message of the round, computed with the fold of the previous one.
+    message: Option<PolynomialDescription>,
+    phase: PhantomData<P>,
+}
+
+/// What follows the challenge of a round for a [`TypedProver`].
+#[cfg(feature = "prover")]
+pub enum ProverPhase {
+    /// The message of the next round is to be sent.
+    Ready(TypedProver<ReadyToSend>),
+    /// All the rounds have been played.
+    Finished,
+}
+
+#[cfg(feature = "prover")]
+impl ProverPhase {
+    /// Starts the protocol from a state obtained with
+    /// [`setup_protocol`](crate::protocol::setup_protocol). A sum over no variables has no rounds.
+    pub fn start(claimed_sum: F, state: ProverState) -> Self {
+        if state.round() == state.num_vars() {
+            return ProverPhase::Finished;
+        }
+        ProverPhase::Ready(TypedProver {
+            claimed_sum,
+            state,
+            message: None,
+            phase: PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "prover")]
+impl<P> TypedProver<P> {
+    pub fn claimed_sum(&self) -> F {
+        self.claimed_sum
+    }
+
+    /// Index of the current round.
+    pub fn round(&self) -> usize {
+        self.state.round()
+    }
+}
+
+#[cfg(feature = "prover")]
+impl TypedProver<ReadyToSend> {
+    /// Produces the message of the round.
+    pub fn send(self) -> (RoundMessage, TypedProver<AwaitingChallenge>) {
+        let (evaluations, state) = match self.message {
+            Some(message) => (message, self.state),
+            None => Prover::round_phase_1(self.state),
+        };
+        let prover = TypedProver {
+            claimed_sum: self.claimed_sum,
+            state,
+            message: None,
+            phase: PhantomData,
+        };
+        (RoundMessage { evaluations }, prover)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl TypedProver<AwaitingChallenge> {
+    /// Binds the variable of the round to the challenge 'r', and computes the message of the next
+    /// round, if any, in the same pass.
+    pub fn receive(self, r: F) -> ProverPhase {
+        if self.state.round() + 1 == self.state.num_vars() {
+            return ProverPhase::Finished;
+        }
+        let (message, state) = Prover::fold_and_round(self.state, r);
+        ProverPhase::Ready(TypedProver {
+            claimed_sum: self.claimed_sum,
+            state,
+            message: Some(message),
+            phase: PhantomData,
+        })
+    }
+}
+
+/// Phase of a [`TypedVerifier`] which expects the message of a round.
+pub struct AwaitingMessage;
+
+/// Phase of a [`TypedVerifier`] which has played every round, and is left with its final check.
+pub struct ReadyToDecide;
+
+/// A verifier in the phase 'P' of the protocol, drawing its challenges from 'R'.
+pub struct TypedVerifier<R: RngCore + CryptoRng, P> {
+    state: VerifierState<R>,
+    phase: PhantomData<P>,
+}
+
+/// What follows an accepted message, or the setup, for a [`TypedVerifier`].
+pub enum VerifierPhase<R: RngCore + CryptoRng> {
+    /// The message of the next round is expected.
+    Rounds(TypedVerifier<R, AwaitingMessage>),
+    /// All the rounds have been played.
+    Final(TypedVerifier<R, ReadyToDecide>),
+}
+
+impl<R: RngCore + CryptoRng> VerifierPhase<R> {
+    /// Starts the protocol from a state obtained with
+    /// [`setup_protocol`](crate::protocol::setup_protocol) or initialized by the caller.
+    pub fn start(state: VerifierState<R>) -> Self {
+        if state.last_round < state.num_vars {
+            VerifierPhase::Rounds(TypedVerifier {
+                state,
+                phase: PhantomData,
+            })
+        } else {
+            VerifierPhase::Final(TypedVerifier {
+                state,
+                phase: PhantomData,
+            })
+        }
+    }
+}
+
+impl<R: RngCore + CryptoRng, P> TypedVerifier<R, P> {
+    /// Index of the round whose message is expected next, or the number of rounds once they
+    /// have all been played.
+    pub fn round(&self) -> usize {
+        self.state.last_round
+    }
+
+    /// The claimed sum before the first round, then the value of the last message at its
+    /// challenge.
+    pub fn running_eval(&self) -> F {
+        self.state.running_eval
+    }
+}
+
+impl<R: RngCore + CryptoRng> TypedVerifier<R, AwaitingMessage> {
+    /// Checks the prover's message for the round, and returns the challenge to send back with the
+    /// phase that follows. A rejected message ends the protocol.
+    pub fn receive(self, message: RoundMessage) -> Result<(F, VerifierPhase<R>), SumCheckError> {
+        let (r, state) = Verifier::round(self.state, message.evaluations)?;
+        Ok((r, VerifierPhase::start(state)))
+    }
+}
+
+impl<R: RngCore + CryptoRng> TypedVerifier<R, ReadyToDecide> {
+    /// Performs the final check, and returns the random point of the protocol if it passes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the verifier was initialized with commitments only.
+    pub fn finalize(self) -> Result<Vec<F>, SumCheckError> {
+        let (result, point) = Verifier::sanity_check(self.state);
+        result.map(|()| point)
+    }
+
+    /// Same as `finalize`, leaving the final check to the caller.
+    pub fn finalize_to_claim(self) -> Result<EvaluationClaim, SumCheckError> {
+        Verifier::finalize_to_claim(self.state)
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use crate::protocol::setup_protocol;
+
+    /// Plays the rounds between the two parties, and returns the verifier in its final phase.
+    fn play(
+        mut prover: ProverPhase,
+        mut verifier: VerifierPhase<rand::rngs::ThreadRng>,
+    ) -> Result<TypedVerifier<rand::rngs::ThreadRng, ReadyToDecide>, SumCheckError> {
+        loop {
+            let (ProverPhase::Ready(ready), VerifierPhase::Rounds(awaiting)) = (prover, verifier)
+            else {
+                unreachable!("both parties play the same number of rounds");
+            };
+            let (message, waiting) = ready.send();
+            let (r, next) = awaiting.receive(message)?;
+            prover = waiting.receive(r);
+            verifier = next;
+            if let VerifierPhase::Final(verifier) = verifier {
+                assert!(matches!(prover, ProverPhase::Finished));
+                return Ok(verifier);
+            }
+        }
+    }
+
+    #[test]
+    fn test_typestate() {
+        let poly = parse_product("(x0 + 2*x1) * (x1 + x2 + 1)").unwrap();
+        let (num_vars, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let verifier = play(
+            ProverPhase::start(claimed_sum, prover_state),
+            VerifierPhase::start(verifier_state),
+        )
+        .unwrap();
+        assert_eq!(verifier.round(), num_vars);
+        assert_eq!(verifier.finalize().unwrap().len(), num_vars);
+
+        // A wrong claim is rejected in the first round, which consumes the verifier.
+        let (_, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let verifier_state = VerifierState {
+            running_eval: claimed_sum + F::from(1),
+            ..verifier_state
+        };
+        assert!(matches!(
+            play(
+                ProverPhase::start(claimed_sum, prover_state),
+                VerifierPhase::start(verifier_state),
+            ),
+            Err(SumCheckError::IntermediateCheckFailed { round: 0, .. })
+        ));
+    }
+}
@@ -1,91 +1,921 @@
-use std::ops::{Add, Mul};
+use alloc::sync::Arc;
+use core::ops::Mul;
 use ark_ff::Field;
-use ark_std::{UniformRand};
+use ark_std::{vec, vec::Vec};
+#[cfg(feature = "std")]
+use rand::rngs::ThreadRng;
+#[cfg(feature = "std")]
 use rand::thread_rng;
-use crate::field::Field256 as F;
-use crate::polynomial::{evaluate_mvml_polynomial, PolynomialDescription, ProductMLPolynomial};
-use crate::protocol::rejection::RejectError;
+use rand::{CryptoRng, RngCore, SeedableRng};
+use crate::field::{batch_invert, Field256 as F};
+use crate::polynomial::{
+    evaluate_coefficients, evaluate_mvml_polynomial, evaluate_sum_of_products, get_sum_num_vars, merge_point, PolynomialDescription,
+    ProductMLPolynomial, SumOfProducts, variable_degree,
+};
+use crate::commitment::MLCommitmentScheme;
+use crate::protocol::constant_time::DeferredChecks;
+use crate::protocol::statement::InstanceDigest;
+use crate::protocol::error::SumCheckError;
+use crate::protocol::sampler::{ChallengeSampler, FullField};
+#[cfg(feature = "std")]
+use crate::protocol::{check_digest_setup, check_sampler_setup, check_setup, SetupError};
+#[cfg(feature = "std")]
+use crate::security::DEFAULT_SECURITY_BITS;
+use crate::protocol::{MessageFormat, RoundMessage};
 
-pub struct VerifierState {
+/// Generator of the challenges when none is given: the thread-local generator with the `std`
+/// feature. Without it, the caller always provides the generator, and this default is only the
+/// Fiat–Shamir transcript.
+#[cfg(feature = "std")]
+pub type DefaultRng = ThreadRng;
+#[cfg(not(feature = "std"))]
+pub type DefaultRng = crate::protocol::Transcript;
+
+/// A generator of challenges whose clones draw the same values as the original and independently
+/// of it, as the seedable generators and the Fiat–Shamir transcripts do, so that a
+/// [`VerifierSnapshot`] replays the challenges. The thread-local generator is not one, as its
+/// clones share its state.
+pub trait ReplayableRng: RngCore + CryptoRng + Clone {}
+
+impl<R: RngCore + CryptoRng + Clone + SeedableRng> ReplayableRng for R {}
+
+impl ReplayableRng for crate::protocol::Transcript {}
+
+/// State of the verifier, which draws its challenges from 'rng'.
+#[derive(Clone)]
+pub struct VerifierState<R: RngCore + CryptoRng = DefaultRng> {
     pub last_round: usize,
-    pub poly: ProductMLPolynomial,
+    pub num_vars: usize,
+    /// Number of factors, or the largest number of factors of a product for a sum of products. It
+    /// bounds the degree of the round polynomials, which is lower in rounds where some factors do
+    /// not depend on the variable, unless a factor has degree above one in a variable, in which
+    /// case it is the largest degree bound.
+    pub num_polys: usize,
+    /// Largest degree of the round polynomial of each round, at most `num_polys`. When the
+    /// verifier knows the polynomial, it is the largest sum of the degrees of the factors of a
+    /// product in the variable of the round.
+    pub degree_bounds: Vec<usize>,
+    /// The polynomial, as a sum of products, used for the final check. It is `None` when the
+    /// verifier only holds commitments to the factors, or their digest.
+    pub poly: Option<SumOfProducts>,
+    /// The digest of the product, when the verifier holds it instead of the polynomial. The
+    /// factors revealed for the final check must match it.
+    pub digest: Option<InstanceDigest>,
+    /// Variables fixed to constants before the protocol, which only runs over the other ones.
+    pub fixed: Vec<(usize, F)>,
+    /// The format of the round messages, agreed on with the prover.
+    pub format: MessageFormat,
+    /// Barycentric weights of the nodes 0, 1, ..., d, for each degree d up to `num_polys`, used to
+    /// evaluate the round polynomials given by their evaluations without inversions.
+    pub interpolation: InterpolationContext,
     pub running_eval: F,
     pub randomness: Vec<F>,
+    pub rng: R,
+    /// The outcome of the rounds in constant-time mode, where a failed check only rejects at the
+    /// end. `None` otherwise.
+    pub deferred: Option<DeferredChecks>,
+    /// How the challenges are drawn.
+    pub challenge_policy: ChallengePolicy,
+    /// The set from which the challenges are drawn, the whole field by default.
+    pub sampler: Arc<dyn ChallengeSampler>,
+    /// The challenge of each round, given in advance in tests instead of being drawn, see
+    /// `with_challenges`. Empty otherwise.
+    pub(crate) challenges: Vec<F>,
+}
+
+/// How the verifier draws its challenges. A challenge equal to one of the nodes 0, 1, ..., d of
+/// the round polynomial reveals that the next running evaluation is one of the transmitted
+/// evaluations, and a repeated challenge evaluates two rounds at the same point. Both happen with
+/// probability (d + n) / |F| at most for n rounds, negligible for [`Field256`](F), but can be
+/// ruled out.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChallengePolicy {
+    /// Any field element, as in the protocol.
+    #[default]
+    Any,
+    /// Draws again a challenge which is a node of the round polynomial or a challenge of an
+    /// earlier round. With Fiat–Shamir, the prover must draw its challenges in the same way.
+    Resample,
+}
+
+impl<R: RngCore + CryptoRng> VerifierState<R> {
+    /// Same state, drawing the next challenges from 'rng'.
+    pub fn with_rng<S: RngCore + CryptoRng>(self, rng: S) -> VerifierState<S> {
+        VerifierState {
+            last_round: self.last_round,
+            num_vars: self.num_vars,
+            num_polys: self.num_polys,
+            degree_bounds: self.degree_bounds,
+            poly: self.poly,
+            digest: self.digest,
+            fixed: self.fixed,
+            format: self.format,
+            interpolation: self.interpolation,
+            running_eval: self.running_eval,
+            randomness: self.randomness,
+            rng,
+            deferred: self.deferred,
+            challenge_policy: self.challenge_policy,
+            sampler: self.sampler,
+            challenges: self.challenges,
+        }
+    }
+
+    /// Same state, rejecting the round polynomials of degree above 'degree_bounds', one per
+    /// round, as negotiated with the prover.
+    pub fn with_degree_bounds(self, degree_bounds: Vec<usize>) -> Self {
+        assert_eq!(degree_bounds.len(), self.num_vars, "there must be one degree bound per round");
+        assert!(
+            degree_bounds.iter().all(|&degree| degree <= self.num_polys),
+            "the degree bounds must not exceed the number of factors"
+        );
+        VerifierState { degree_bounds, ..self }
+    }
+
+    /// Same state, in constant-time mode: the verifier keeps playing the rounds after a failed
+    /// check, comparing the field elements without branching on them, and only rejects at the
+    /// final check, with the first failed round. See [`DeferredChecks`].
+    pub fn constant_time(self) -> Self {
+        VerifierState { deferred: Some(DeferredChecks::default()), ..self }
+    }
+
+    /// Same state, drawing the next challenges according to 'challenge_policy'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policy resamples challenges from a set too small to always hold one, see
+    /// [`VerifierState::with_challenge_sampler`].
+    pub fn with_challenge_policy(self, challenge_policy: ChallengePolicy) -> Self {
+        let state = VerifierState { challenge_policy, ..self };
+        state.check_resampling();
+        state
+    }
+
+    /// Same state, drawing the next challenges with 'sampler', for the interactive protocol only,
+    /// see [`sampler`](crate::protocol::sampler). With a subset S of the field, the soundness
+    /// error grows from d/|F| to d/|S| per round, see [`VerifierState::security_bits`], which
+    /// [`Verifier::setup`] does not account for: [`Verifier::setup_with_sampler`] does.
+    ///
+    /// # Panics
+    ///
+    /// Panics with [`ChallengePolicy::Resample`] if S may have at most d + 1 + n elements for the
+    /// largest degree d and n rounds, since the nodes and the earlier challenges could then
+    /// exhaust it and the verifier would draw forever.
+    pub fn with_challenge_sampler(self, sampler: impl ChallengeSampler + 'static) -> Self {
+        let state = VerifierState { sampler: Arc::new(sampler), ..self };
+        state.check_resampling();
+        state
+    }
+
+    /// Asserts that resampling always finds a challenge, which avoids the d + 1 nodes of the
+    /// round polynomial and the challenges of the earlier rounds.
+    fn check_resampling(&self) {
+        if self.challenge_policy == ChallengePolicy::Resample {
+            let avoided = self.num_polys.max(1) + 1 + self.num_vars;
+            let space = self.sampler.space();
+            assert!(
+                space.exceeds(avoided),
+                "resampling challenges needs more than {avoided} of them, the sampler draws from {space:?}"
+            );
+        }
+    }
+
+    /// Bits of security of the remaining rounds, with their degree bounds and the challenges
+    /// drawn by the sampler of the state.
+    #[cfg(feature = "std")]
+    pub fn security_bits(&self) -> f64 {
+        let degrees: usize = self.degree_bounds.iter().skip(self.last_round).sum();
+        crate::security::log2_space_size(self.sampler.space()) - (degrees.max(1) as f64).log2()
+    }
+
+    /// Same state, answering each round with its challenge in 'challenges' instead of drawing
+    /// it, whatever the policy, so that tests can check the exact messages of a run.
+    pub fn with_challenges(self, challenges: Vec<F>) -> Self {
+        assert_eq!(challenges.len(), self.num_vars, "there must be one challenge per round");
+        VerifierState { challenges, ..self }
+    }
+
+    /// A challenge for the round polynomial of degree 'degree', according to the policy.
+    fn draw_challenge(&mut self, degree: usize) -> F {
+        if let Some(&r) = self.challenges.get(self.last_round) {
+            return r;
+        }
+        loop {
+            let r = self.sampler.sample(&mut self.rng);
+            let collides = || (0..=degree as u64).any(|node| r == F::from(node)) || self.randomness.contains(&r);
+            if self.challenge_policy == ChallengePolicy::Any || !collides() {
+                return r;
+            }
+        }
+    }
+}
+
+impl<R: ReplayableRng> VerifierState<R> {
+    /// A copy of the state, including the generator of the challenges, to return to with
+    /// [`VerifierState::restore`], e.g. to replay the rounds from there with other messages.
+    pub fn snapshot(&self) -> VerifierSnapshot<R> {
+        VerifierSnapshot { state: self.clone() }
+    }
+
+    /// Returns to the state of 'snapshot', which may be restored again.
+    pub fn restore(&mut self, snapshot: &VerifierSnapshot<R>) {
+        *self = snapshot.state.clone();
+    }
+}
+
+/// A saved [`VerifierState`], taken with [`VerifierState::snapshot`] or
+/// [`SumCheckVerifier::snapshot`]. The challenges drawn after restoring it are the ones drawn
+/// after taking it, for the same messages, as its generator is a [`ReplayableRng`].
+#[derive(Clone)]
+pub struct VerifierSnapshot<R: RngCore + CryptoRng = DefaultRng> {
+    state: VerifierState<R>,
+}
+
+impl<R: RngCore + CryptoRng> VerifierSnapshot<R> {
+    /// Index of the round whose message was expected when the snapshot was taken.
+    pub fn round(&self) -> usize {
+        self.state.last_round
+    }
+}
+
+/// Largest degree of a product of 'poly' in each of 'vars', i.e. the sum of the degrees of its
+/// factors, which is the degree of the round polynomial of the round of the variable.
+fn degree_bounds(poly: &SumOfProducts, vars: impl Iterator<Item = usize>) -> Vec<usize> {
+    vars.map(|var| {
+        poly.iter()
+            .map(|product| {
+                product.iter().map(|factor| variable_degree(factor, var)).sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0)
+    })
+    .collect()
+}
+
+/// 1 / i! for i = 0, 1, ..., 'n', with a single batch inversion.
+fn inverse_factorials<E: Field>(n: usize) -> Vec<E> {
+    let mut factorials = vec![E::ONE; n + 1];
+    for i in 1..=n {
+        factorials[i] = factorials[i - 1] * E::from(i as u64);
+    }
+    batch_invert(&mut factorials);
+    factorials
+}
+
+/// The barycentric weights w_i = (-1)^(d - i) / (i! (d - i)!) of the nodes 0, 1, ..., 'degree',
+/// from the inverses of the factorials up to 'degree' at least.
+fn weights_from_inverse_factorials<E: Field>(inverses: &[E], degree: usize) -> Vec<E> {
+    (0..=degree)
+        .map(|i| {
+            let w = inverses[i] * inverses[degree - i];
+            if (degree - i) % 2 == 1 { -w } else { w }
+        })
+        .collect()
+}
+
+/// The barycentric weights of the nodes 0, 1, ..., d for each degree d up to a bound, computed once
+/// per instance when the verifier is initialized, so that it evaluates the round polynomials given
+/// by their evaluations without any field inversion in the rounds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterpolationContext {
+    weights: Vec<Vec<F>>,
+}
+
+impl InterpolationContext {
+    /// The weights for each degree up to 'max_degree', from a single inversion.
+    pub fn new(max_degree: usize) -> Self {
+        let inverses = inverse_factorials(max_degree);
+        let weights = (0..=max_degree).map(|degree| weights_from_inverse_factorials(&inverses, degree)).collect();
+        InterpolationContext { weights }
+    }
+
+    pub fn max_degree(&self) -> usize {
+        self.weights.len() - 1
+    }
+
+    /// The barycentric weights of the nodes 0, 1, ..., 'degree'.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'degree' is above the maximum degree of the context.
+    pub fn weights(&self, degree: usize) -> &[F] {
+        &self.weights[degree]
+    }
+
+    /// Evaluates at 'r' the polynomial given by its 'evaluations' at 0, 1, ..., d, for d at most
+    /// the maximum degree of the context.
+    pub fn evaluate(&self, evaluations: &[F], r: F) -> F {
+        Verifier::evaluate_with_weights(evaluations, self.weights(evaluations.len() - 1), r)
+    }
+}
+
+/// What the rounds of the protocol reduce the claimed sum to: the claim that the polynomial
+/// evaluates to 'value' at 'point', which includes the fixed variables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvaluationClaim {
+    pub point: Vec<F>,
+    pub value: F,
+}
+
+impl EvaluationClaim {
+    /// Checks the claim by evaluating 'poly'.
+    pub fn check(&self, poly: &SumOfProducts) -> Result<(), SumCheckError> {
+        if evaluate_sum_of_products(poly, &self.point).ne(&self.value) {
+            return Err(SumCheckError::FinalCheckFailed);
+        }
+        Ok(())
+    }
 }
 
 pub struct Verifier{
 }
 
 impl Verifier {
+    #[cfg(feature = "std")]
     pub fn initialize(poly: &ProductMLPolynomial, claimed: F) -> VerifierState {
+        Self::initialize_with_rng(poly, claimed, thread_rng())
+    }
+
+    /// The verifier's side of [`setup_protocol`](crate::protocol::setup_protocol): checks on its
+    /// own copy of the instance that 'poly' is an instance of the protocol, with challenges drawn
+    /// from the whole field, then initializes the verification of the claim received from the
+    /// prover.
+    #[cfg(feature = "std")]
+    pub fn setup(poly: &ProductMLPolynomial, claimed: F) -> Result<VerifierState, SetupError> {
+        Self::setup_with_threshold(poly, claimed, DEFAULT_SECURITY_BITS)
+    }
+
+    /// Same as `setup`, requiring 'threshold_bits' bits of security.
+    #[cfg(feature = "std")]
+    pub fn setup_with_threshold(
+        poly: &ProductMLPolynomial,
+        claimed: F,
+        threshold_bits: f64,
+    ) -> Result<VerifierState, SetupError> {
+        check_setup(poly, threshold_bits)?;
+        Ok(Self::initialize(poly, claimed))
+    }
+
+    /// Same as `setup_with_threshold`, drawing the challenges with 'sampler', on which the protocol
+    /// must have 'threshold_bits' bits of security.
+    ///
+    /// # Panics
+    ///
+    /// Panics as [`VerifierState::with_challenge_sampler`] does.
+    #[cfg(feature = "std")]
+    pub fn setup_with_sampler(
+        poly: &ProductMLPolynomial,
+        claimed: F,
+        sampler: impl ChallengeSampler + 'static,
+        threshold_bits: f64,
+    ) -> Result<VerifierState, SetupError> {
+        check_sampler_setup(poly, &sampler, threshold_bits)?;
+        Ok(Self::initialize(poly, claimed).with_challenge_sampler(sampler))
+    }
+
+    /// Same as `setup` for a verifier which only holds the digest of the instance, e.g. received
+    /// from the prover or from whoever fixed the statement. The digest does not reveal whether the
+    /// factors are multilinear: the rounds are bounded by its degrees instead, and the final
+    /// check is against the factors revealed after them, see
+    /// [`Verifier::initialize_with_digest`].
+    #[cfg(feature = "std")]
+    pub fn setup_with_digest(digest: &InstanceDigest, claimed: F) -> Result<VerifierState, SetupError> {
+        check_digest_setup(digest, DEFAULT_SECURITY_BITS)?;
+        Ok(Self::initialize_with_digest(digest, claimed))
+    }
+
+    /// Same as `initialize`, drawing the challenges from 'rng' instead of the thread-local
+    /// generator. A seeded generator makes the whole run reproducible.
+    pub fn initialize_with_rng<R: RngCore + CryptoRng>(
+        poly: &ProductMLPolynomial,
+        claimed: F,
+        rng: R,
+    ) -> VerifierState<R> {
+        Self::initialize_sum_of_products_with_rng(&vec![poly.clone()], claimed, rng)
+    }
+
+    /// Initializes the verification of the claim that the sum of products 'poly' sums to
+    /// 'claimed'.
+    #[cfg(feature = "std")]
+    pub fn initialize_sum_of_products(poly: &SumOfProducts, claimed: F) -> VerifierState {
+        Self::initialize_sum_of_products_with_rng(poly, claimed, thread_rng())
+    }
+
+    pub fn initialize_sum_of_products_with_rng<R: RngCore + CryptoRng>(
+        poly: &SumOfProducts,
+        claimed: F,
+        rng: R,
+    ) -> VerifierState<R> {
+        let num_vars = get_sum_num_vars(poly).unwrap();
+        let degree_bounds = degree_bounds(poly, 0..num_vars);
+        // Factors of degree above one in a variable raise the bound above the number of factors.
+        let degree = poly.iter().map(Vec::len).chain(degree_bounds.iter().copied()).max().unwrap_or(0);
+        VerifierState{
+            poly: Some(poly.clone()),
+            degree_bounds,
+            ..Self::initialize_committed_with_rng(num_vars, degree, claimed, rng)
+        }
+    }
+
+    /// Initializes the verification of a partial sum, where the variables of 'fixed' are set to
+    /// constants and the sum is over the remaining ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'fixed' has more pairs than 'poly' has variables, a variable out of range, or the
+    /// same variable twice, as [`SumCheckProver::with_fixed`](crate::protocol::SumCheckProver::with_fixed) does.
+    #[cfg(feature = "std")]
+    pub fn initialize_partial(poly: &ProductMLPolynomial, fixed: &[(usize, F)], claimed: F) -> VerifierState {
+        let num_vars = crate::polynomial::get_num_vars(poly).unwrap();
+        assert!(fixed.len() <= num_vars, "more variables are fixed than the polynomial has");
+        assert!(fixed.iter().all(|&(var, _)| var < num_vars), "a fixed variable is out of range");
+        assert!(
+            fixed.iter().enumerate().all(|(i, &(var, _))| fixed[..i].iter().all(|&(other, _)| other != var)),
+            "a variable is fixed twice"
+        );
+        let vars = (0..num_vars).filter(|var| fixed.iter().all(|&(fixed, _)| fixed != *var));
+        VerifierState{
+            num_vars: num_vars - fixed.len(),
+            fixed: fixed.to_vec(),
+            degree_bounds: degree_bounds(&vec![poly.clone()], vars),
+            ..Self::initialize(poly, claimed)
+        }
+    }
+
+    /// Initializes a verifier that does not know the polynomial itself, only its number of
+    /// variables and of factors. The final check is then done against commitment openings.
+    #[cfg(all(feature = "std", feature = "prover"))]
+    pub fn initialize_committed(num_vars: usize, num_polys: usize, claimed: F) -> VerifierState {
+        Self::initialize_committed_with_rng(num_vars, num_polys, claimed, thread_rng())
+    }
+
+    pub fn initialize_committed_with_rng<R: RngCore + CryptoRng>(
+        num_vars: usize,
+        num_polys: usize,
+        claimed: F,
+        rng: R,
+    ) -> VerifierState<R> {
         VerifierState{
             last_round: 0,
-            poly: poly.clone(),
+            num_vars,
+            num_polys,
+            degree_bounds: vec![num_polys; num_vars],
+            poly: None,
+            digest: None,
+            fixed: Vec::new(),
+            format: MessageFormat::default(),
+            // Messages of two evaluations are accepted even for a degree bound of zero.
+            interpolation: InterpolationContext::new(num_polys.max(1)),
             running_eval: claimed,
             randomness: Vec::new(),
+            rng,
+            deferred: None,
+            challenge_policy: ChallengePolicy::default(),
+            sampler: Arc::new(FullField),
+            challenges: Vec::new(),
+        }
+    }
+
+    /// Initializes a verifier that only holds the digest of the polynomial. The final check is
+    /// then done against the factors revealed after the rounds, which must match the digest, or
+    /// against an evaluation oracle which the verifier trusts. The digest does not bind
+    /// commitments to the factors, for which [`Verifier::initialize_committed`] is meant.
+    #[cfg(feature = "std")]
+    pub fn initialize_with_digest(digest: &InstanceDigest, claimed: F) -> VerifierState {
+        Self::initialize_with_digest_and_rng(digest, claimed, thread_rng())
+    }
+
+    pub fn initialize_with_digest_and_rng<R: RngCore + CryptoRng>(
+        digest: &InstanceDigest,
+        claimed: F,
+        rng: R,
+    ) -> VerifierState<R> {
+        let degree = digest.degree_bounds.iter().copied().max().unwrap_or(0).max(digest.num_polys);
+        VerifierState {
+            degree_bounds: digest.degree_bounds.clone(),
+            digest: Some(digest.clone()),
+            ..Self::initialize_committed_with_rng(digest.num_vars, degree, claimed, rng)
         }
     }
 
     /// Execute a round of the verifier. First it checks the consistency with the previous checks,
-    /// then generates randomness and returns its updated state, as well as the randomness.
-    pub fn round(state: VerifierState, mvml_desc: PolynomialDescription) -> Result<(F, VerifierState), RejectError> {
-        if Self::evaluate_intermediate(&mvml_desc).ne(&state.running_eval) {
-            return Err(RejectError::new("Rejecting the Prover's claim!"));
+    /// then generates randomness and returns its updated state, as well as the randomness. The
+    /// round polynomial is described in the agreed format, for some degree d between 1 and the
+    /// degree bound of the round, by d + 1 field elements, or d for a compressed message.
+    pub fn round<R: RngCore + CryptoRng>(
+        mut state: VerifierState<R>,
+        mvml_desc: PolynomialDescription,
+    ) -> Result<(F, VerifierState<R>), SumCheckError> {
+        trace_span!("verifier.round", round = state.last_round, evaluations = mvml_desc.len());
+        // Rounds beyond the number of variables are rejected by the final check.
+        let degree = state.degree_bounds.get(state.last_round).copied().unwrap_or(state.num_polys);
+        let omitted = usize::from(state.format == MessageFormat::Compressed);
+        let expected = degree.max(1) + 1 - omitted;
+        let degree_mismatch = SumCheckError::DegreeMismatch {
+            round: state.last_round,
+            expected,
+            got: mvml_desc.len(),
+        };
+        if mvml_desc.len() + omitted < 2 || mvml_desc.len() > expected {
+            return Err(degree_mismatch);
+        }
+        let Some(mvml_desc) = Self::decompress(state.format, state.running_eval, mvml_desc) else {
+            return Err(degree_mismatch);
+        };
+        let Some(intermediate) = Self::round_sum(state.format, &mvml_desc) else {
+            return Err(degree_mismatch);
+        };
+        match state.deferred.as_mut() {
+            Some(deferred) => deferred.record(state.last_round, state.running_eval, intermediate),
+            None if intermediate.ne(&state.running_eval) => {
+                return Err(SumCheckError::IntermediateCheckFailed {
+                    round: state.last_round,
+                    lhs: state.running_eval,
+                    rhs: intermediate,
+                });
+            }
+            None => {}
         }
-        let mut rng = thread_rng();
-        let r = F::rand(&mut rng);
+        let r = state.draw_challenge(mvml_desc.len() - 1);
         let mut new_rand = state.randomness.clone();
         new_rand.push(r);
         let new_state = VerifierState{
             last_round: state.last_round + 1,
-            running_eval: Self::evaluate_at_random_point(&mvml_desc, r),
+            running_eval: match state.format {
+                MessageFormat::Evaluations | MessageFormat::Compressed => state.interpolation.evaluate(&mvml_desc, r),
+                MessageFormat::Coefficients => evaluate_coefficients(&mvml_desc, r),
+            },
             randomness: new_rand,
             ..state
         };
-        return Ok((r, new_state))
+        Ok((r, new_state))
     }
 
-    /// Evaluate p(0) + p(1).
-    pub fn evaluate_intermediate(mvml_desc: &PolynomialDescription) -> F{
-        mvml_desc.get(0).unwrap().add(mvml_desc.get(1).unwrap())
+    /// Evaluate p(0) + p(1), or `None` if the description has fewer than two points.
+    pub fn evaluate_intermediate<E: Field>(mvml_desc: &[E]) -> Option<E> {
+        Some(*mvml_desc.first()? + mvml_desc.get(1)?)
     }
 
-    /// Evaluate the polynomial at a random point thanks to Lagrange interpolation.
-    pub fn evaluate_at_random_point(mvml_descr: &PolynomialDescription, r: F) -> F{
-        let k = mvml_descr.len() - 1;
-        let mut result = F::ZERO;
+    /// Completes a compressed message with p(0) = 'running_eval' - p(1), and returns the other
+    /// messages as they are. Fails on an empty compressed message.
+    pub fn decompress(
+        format: MessageFormat,
+        running_eval: F,
+        mut mvml_desc: PolynomialDescription,
+    ) -> Option<PolynomialDescription> {
+        if format == MessageFormat::Compressed {
+            let at_zero = running_eval - mvml_desc.first()?;
+            mvml_desc.insert(0, at_zero);
+        }
+        Some(mvml_desc)
+    }
 
-        for i in 0..=k {
-            let (x_i, y_i) = (F::from(i as u16), mvml_descr[i]);
+    /// The sum p(0) + p(1) of the round polynomial p described by 'mvml_desc' in 'format', or
+    /// `None` if the description is too short. A compressed message must first be completed by
+    /// `decompress`.
+    pub fn round_sum(format: MessageFormat, mvml_desc: &[F]) -> Option<F> {
+        match format {
+            MessageFormat::Evaluations | MessageFormat::Compressed => Self::evaluate_intermediate(mvml_desc),
+            MessageFormat::Coefficients => Some(*mvml_desc.first()? + mvml_desc.iter().sum::<F>()),
+        }
+    }
 
-            // Calculate the Lagrange basis polynomial l_i(r)
-            let mut l_i_r = F::ONE;
-            for j in 0..=k {
-                if i != j {
-                    let x_j = F::from(j as u16);
-                    l_i_r *= (r - x_j) / (x_i - x_j);
-                }
+    /// Evaluates at 'r' the round polynomial described by 'mvml_desc' in 'format'. A compressed
+    /// message must first be completed by `decompress`.
+    pub fn evaluate_message(format: MessageFormat, mvml_desc: &[F], r: F) -> F {
+        match format {
+            MessageFormat::Evaluations | MessageFormat::Compressed => {
+                Self::evaluate_at_random_point(mvml_desc, r)
             }
-
-            // Add the term to the result
-            result = result.add(y_i.mul(l_i_r));
+            MessageFormat::Coefficients => evaluate_coefficients(mvml_desc, r),
         }
+    }
 
+    /// Evaluate the polynomial at a random point thanks to barycentric interpolation on the nodes
+    /// 0, 1, ..., d. The messages and the challenge live in the same field 'E', which is an
+    /// extension of the field of the polynomial when the latter is small. The weights are computed
+    /// on each call, whereas the verifier caches them in its [`InterpolationContext`].
+    pub fn evaluate_at_random_point<E: Field>(mvml_descr: &[E], r: E) -> E{
+        let weights = Self::barycentric_weights(mvml_descr.len() - 1);
+        Self::evaluate_with_weights(mvml_descr, &weights, r)
+    }
+
+    /// Barycentric weights w_i = 1 / Π_{j != i} (i - j) = (-1)^(d - i) / (i! (d - i)!) of the nodes
+    /// 0, 1, ..., 'degree'.
+    pub fn barycentric_weights<E: Field>(degree: usize) -> Vec<E> {
+        weights_from_inverse_factorials(&inverse_factorials(degree), degree)
+    }
+
+    /// Evaluates at 'r' the polynomial given by its evaluations at 0, 1, ..., d, with the
+    /// barycentric formula p(r) = Σ_i w_i y_i Π_{j != i} (r - j), in O(d) operations and no
+    /// inversion, the products being shared between prefixes and suffixes.
+    pub fn evaluate_with_weights<E: Field>(mvml_descr: &[E], weights: &[E], r: E) -> E {
+        let differences: Vec<E> = (0..mvml_descr.len()).map(|i| r - E::from(i as u64)).collect();
+        let mut prefixes = Vec::with_capacity(differences.len());
+        let mut prefix = E::ONE;
+        for &difference in &differences {
+            prefixes.push(prefix);
+            prefix *= difference;
+        }
+        let mut result = E::ZERO;
+        let mut suffix = E::ONE;
+        for i in (0..mvml_descr.len()).rev() {
+            result += mvml_descr[i] * weights[i] * prefixes[i] * suffix;
+            suffix *= differences[i];
+        }
         result
     }
 
     /// Last check to see if the polynomial evaluated at a random point agrees with the prover's
-    /// messages.
-    pub fn sanity_check(state: VerifierState) -> (bool, Vec<F>) {
-        (evaluate_mvml_polynomial(state.poly, &state.randomness).eq(&state.running_eval), state.randomness)
+    /// messages. Also returns the randomness generated over the course of the protocol.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the verifier was initialized with commitments only.
+    pub fn sanity_check<R: RngCore + CryptoRng>(
+        state: VerifierState<R>,
+    ) -> (Result<(), SumCheckError>, Vec<F>) {
+        trace_span!("verifier.final_check", num_vars = state.num_vars);
+        if let Err(err) = Self::check_variable_count(&state) {
+            return (Err(err), state.randomness);
+        }
+        let poly = state.poly.expect("the verifier only holds commitments to the polynomial");
+        let point = merge_point(&state.fixed, &state.randomness);
+        let evaluation = evaluate_sum_of_products(&poly, &point);
+        if let Some(deferred) = state.deferred {
+            return (deferred.decide(Some((evaluation, state.running_eval))), state.randomness);
+        }
+        if evaluation.ne(&state.running_eval) {
+            return (Err(SumCheckError::FinalCheckFailed), state.randomness);
+        }
+        (Ok(()), state.randomness)
     }
+
+    /// Last check for a verifier holding the digest of the polynomial, evaluating the factors
+    /// 'poly' revealed after the rounds. Fails with [`SumCheckError::InstanceMismatch`] if they do
+    /// not match the digest.
+    pub fn sanity_check_with_instance<R: RngCore + CryptoRng>(
+        state: VerifierState<R>,
+        poly: &ProductMLPolynomial,
+    ) -> (Result<(), SumCheckError>, Vec<F>) {
+        if !state.digest.as_ref().is_some_and(|digest| digest.matches(poly)) {
+            return (Err(SumCheckError::InstanceMismatch), state.randomness);
+        }
+        Self::sanity_check_with_oracle(state, |point| evaluate_mvml_polynomial(poly.clone(), &point.to_vec()))
+    }
+
+    /// Last check, with the evaluation of the polynomial at the random point given by 'oracle',
+    /// which the verifier trusts, e.g. a party holding the polynomial.
+    pub fn sanity_check_with_oracle<R: RngCore + CryptoRng>(
+        state: VerifierState<R>,
+        oracle: impl FnOnce(&[F]) -> F,
+    ) -> (Result<(), SumCheckError>, Vec<F>) {
+        if let Err(err) = Self::check_variable_count(&state) {
+            return (Err(err), state.randomness);
+        }
+        let evaluation = oracle(&merge_point(&state.fixed, &state.randomness));
+        if let Some(deferred) = state.deferred {
+            return (deferred.decide(Some((evaluation, state.running_eval))), state.randomness);
+        }
+        if evaluation.ne(&state.running_eval) {
+            return (Err(SumCheckError::FinalCheckFailed), state.randomness);
+        }
+        (Ok(()), state.randomness)
+    }
+
+    /// Ends the protocol without the final check, which is left to the caller: through a
+    /// polynomial commitment, a recursive sum-check, or [`EvaluationClaim::check`]. Fails if not
+    /// every round was played.
+    pub fn finalize_to_claim<R: RngCore + CryptoRng>(
+        state: VerifierState<R>,
+    ) -> Result<EvaluationClaim, SumCheckError> {
+        Self::check_variable_count(&state)?;
+        if let Some(deferred) = state.deferred {
+            deferred.decide(None)?;
+        }
+        Ok(EvaluationClaim {
+            point: merge_point(&state.fixed, &state.randomness),
+            value: state.running_eval,
+        })
+    }
+
+    /// Last check, where the evaluations of the factors at the random point are given by the
+    /// prover along with opening proofs for their commitments.
+    pub fn sanity_check_with_openings<C: MLCommitmentScheme, R: RngCore + CryptoRng>(
+        state: VerifierState<R>,
+        commitments: &[C::Commitment],
+        openings: &[(F, C::Proof)],
+    ) -> (Result<(), SumCheckError>, Vec<F>) {
+        if let Err(err) = Self::check_variable_count(&state) {
+            return (Err(err), state.randomness);
+        }
+        if let Some(Err(err)) = state.deferred.map(|deferred| deferred.decide(None)) {
+            return (Err(err), state.randomness);
+        }
+        if commitments.len() != state.num_polys || openings.len() != state.num_polys {
+            return (Err(SumCheckError::FinalCheckFailed), state.randomness);
+        }
+        let point = merge_point(&state.fixed, &state.randomness);
+        for (factor, (commitment, (value, proof))) in commitments.iter().zip(openings).enumerate() {
+            if !C::verify(commitment, &point, *value, proof) {
+                return (Err(SumCheckError::OpeningCheckFailed { factor }), state.randomness);
+            }
+        }
+        let product = openings.iter().map(|(value, _)| *value).fold(F::ONE, F::mul);
+        if product.ne(&state.running_eval) {
+            return (Err(SumCheckError::FinalCheckFailed), state.randomness);
+        }
+        (Ok(()), state.randomness)
+    }
+
+    fn check_variable_count<R: RngCore + CryptoRng>(
+        state: &VerifierState<R>,
+    ) -> Result<(), SumCheckError> {
+        if state.randomness.len() != state.num_vars {
+            return Err(SumCheckError::VariableCountMismatch {
+                expected: state.num_vars,
+                got: state.randomness.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Round-by-round driver for the verifier, the counterpart of
+/// [`SumCheckProver`](crate::protocol::SumCheckProver).
+///
+/// Each message is checked against the running evaluation and answered with a fresh challenge.
+/// Once every round has been played, [`SumCheckVerifier::finalize`] performs the final check.
+pub struct SumCheckVerifier<R: RngCore + CryptoRng = DefaultRng> {
+    state: Option<VerifierState<R>>,
 }
 
-#[cfg(test)]
+#[cfg(feature = "std")]
+impl SumCheckVerifier {
+    /// Prepares the verification of the claim that 'poly' sums to 'claimed_sum' over the hypercube.
+    pub fn new(poly: &ProductMLPolynomial, claimed_sum: F) -> Self {
+        Self::from_state(Verifier::initialize(poly, claimed_sum))
+    }
+
+    /// Same as `new`, failing if 'poly' is not an instance of the protocol. The verifier needs
+    /// nothing from the prover but the claimed sum, then the messages.
+    pub fn setup(poly: &ProductMLPolynomial, claimed_sum: F) -> Result<Self, SetupError> {
+        Ok(Self::from_state(Verifier::setup(poly, claimed_sum)?))
+    }
+
+    /// Same as `setup`, drawing the challenges with 'sampler', on which the protocol must have
+    /// 'threshold_bits' bits of security, see [`Verifier::setup_with_sampler`].
+    pub fn setup_with_sampler(
+        poly: &ProductMLPolynomial,
+        claimed_sum: F,
+        sampler: impl ChallengeSampler + 'static,
+        threshold_bits: f64,
+    ) -> Result<Self, SetupError> {
+        Ok(Self::from_state(Verifier::setup_with_sampler(poly, claimed_sum, sampler, threshold_bits)?))
+    }
+
+    /// Prepares the verification of a partial sum, where the variables of 'fixed' are set to
+    /// constants.
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'fixed' has more pairs than 'poly' has variables, a variable out of range, or the
+    /// same variable twice.
+    pub fn with_fixed(poly: &ProductMLPolynomial, fixed: &[(usize, F)], claimed_sum: F) -> Self {
+        Self::from_state(Verifier::initialize_partial(poly, fixed, claimed_sum))
+    }
+
+    /// Prepares the verification of the claim that the product of which 'digest' is the digest
+    /// sums to 'claimed_sum', without holding the product.
+    pub fn from_digest(digest: &InstanceDigest, claimed_sum: F) -> Self {
+        Self::from_state(Verifier::initialize_with_digest(digest, claimed_sum))
+    }
+
+    /// Same as `from_digest`, failing if the digest is not that of an instance of the protocol.
+    pub fn setup_from_digest(digest: &InstanceDigest, claimed_sum: F) -> Result<Self, SetupError> {
+        Ok(Self::from_state(Verifier::setup_with_digest(digest, claimed_sum)?))
+    }
+
+    /// Prepares the verification of the claim that the sum of products 'poly' sums to
+    /// 'claimed_sum'.
+    pub fn from_sum_of_products(poly: &SumOfProducts, claimed_sum: F) -> Self {
+        Self::from_state(Verifier::initialize_sum_of_products(poly, claimed_sum))
+    }
+}
+
+impl<R: RngCore + CryptoRng> SumCheckVerifier<R> {
+    /// Same as `new`, drawing the challenges from 'rng'.
+    pub fn with_rng(poly: &ProductMLPolynomial, claimed_sum: F, rng: R) -> Self {
+        Self::from_state(Verifier::initialize_with_rng(poly, claimed_sum, rng))
+    }
+
+    /// Same as `from_digest`, drawing the challenges from 'rng'.
+    pub fn from_digest_with_rng(digest: &InstanceDigest, claimed_sum: F, rng: R) -> Self {
+        Self::from_state(Verifier::initialize_with_digest_and_rng(digest, claimed_sum, rng))
+    }
+
+    /// Resumes from a state obtained with [`setup_protocol`](crate::protocol::setup_protocol).
+    pub fn from_state(state: VerifierState<R>) -> Self {
+        SumCheckVerifier { state: Some(state) }
+    }
+
+    /// Index of the round whose message is expected next.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the verifier has rejected a message.
+    pub fn round(&self) -> usize {
+        self.state.as_ref().expect("the verifier has already rejected").last_round
+    }
+
+    /// The claimed sum before the first round, then the value of the last message at its
+    /// challenge: after the last round, the claimed evaluation of the polynomial at the random
+    /// point.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the verifier has rejected a message.
+    pub fn running_eval(&self) -> F {
+        self.state.as_ref().expect("the verifier has already rejected").running_eval
+    }
+
+    /// Whether the verifier has rejected a message, after which only [`SumCheckVerifier::restore`]
+    /// may be called.
+    pub fn rejected(&self) -> bool {
+        self.state.is_none()
+    }
+
+    /// Checks the prover's message for the current round and returns the challenge to send back.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called again after having rejected a message.
+    pub fn receive_message(&mut self, message: RoundMessage) -> Result<F, SumCheckError> {
+        let state = self.state.take().expect("the verifier has already rejected");
+        let (r, state) = Verifier::round(state, message.evaluations)?;
+        self.state = Some(state);
+        Ok(r)
+    }
+
+    /// Performs the final check, and returns its outcome along with the random point generated
+    /// over the course of the protocol.
+    pub fn finalize(self) -> (Result<(), SumCheckError>, Vec<F>) {
+        Verifier::sanity_check(self.state.expect("the verifier has already rejected"))
+    }
+
+    /// Same as `finalize`, for a verifier created with [`SumCheckVerifier::from_digest`],
+    /// evaluating the factors 'poly' revealed after the rounds.
+    pub fn finalize_with_instance(self, poly: &ProductMLPolynomial) -> (Result<(), SumCheckError>, Vec<F>) {
+        Verifier::sanity_check_with_instance(self.state.expect("the verifier has already rejected"), poly)
+    }
+
+    /// Same as `finalize`, with the evaluation of the polynomial at the random point given by
+    /// 'oracle'.
+    pub fn finalize_with_oracle(self, oracle: impl FnOnce(&[F]) -> F) -> (Result<(), SumCheckError>, Vec<F>) {
+        Verifier::sanity_check_with_oracle(self.state.expect("the verifier has already rejected"), oracle)
+    }
+
+    /// Same as `finalize`, leaving the final check to the caller.
+    pub fn finalize_to_claim(self) -> Result<EvaluationClaim, SumCheckError> {
+        Verifier::finalize_to_claim(self.state.expect("the verifier has already rejected"))
+    }
+
+    /// Same as `finalize`, checking the prover's openings of the committed factors instead of
+    /// evaluating the polynomial.
+    pub fn finalize_with_openings<C: MLCommitmentScheme>(
+        self,
+        commitments: &[C::Commitment],
+        openings: &[(F, C::Proof)],
+    ) -> (Result<(), SumCheckError>, Vec<F>) {
+        let state = self.state.expect("the verifier has already rejected");
+        Verifier::sanity_check_with_openings::<C, R>(state, commitments, openings)
+    }
+}
+
+impl<R: ReplayableRng> SumCheckVerifier<R> {
+    /// A copy of the state of the verifier, to branch the interaction from the current round.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the verifier has rejected a message.
+    pub fn snapshot(&self) -> VerifierSnapshot<R> {
+        self.state.as_ref().expect("the verifier has already rejected").snapshot()
+    }
+
+    /// Returns to the state of 'snapshot', even after a rejected message.
+    pub fn restore(&mut self, snapshot: &VerifierSnapshot<R>) {
+        self.state = Some(snapshot.state.clone());
+    }
+}
+
+#[cfg(all(test, feature = "std", feature = "prover"))]
 mod tests {
     use ark_poly::DenseMVPolynomial;
     use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
-    use crate::protocol::prover::Prover;
+    use crate::commitment::HashCommitment;
+    use crate::protocol::prover::{Prover, SumCheckProver};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
     use crate::protocol::setup_protocol;
     use super::*;
 
@@ -101,7 +931,7 @@ mod tests {
             ])
         )];
 
-        let (_, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly);
+        let (_, claimed_sum, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
 
         assert_eq!(claimed_sum, F::from(179));
         assert_eq!(verifier_state.running_eval, claimed_sum);
@@ -110,7 +940,403 @@ mod tests {
         let expected: PolynomialDescription = vec![F::from(85), F::from(94)];
         assert_eq!(poly_descr, expected);
         let evaluation = Verifier::evaluate_intermediate(&poly_descr);
-        assert_eq!(evaluation, verifier_state.running_eval);
+        assert_eq!(evaluation, Some(verifier_state.running_eval));
+        assert_eq!(Verifier::evaluate_intermediate(&poly_descr[..1]), None);
         let _ = Verifier::round(verifier_state, poly_descr);
     }
+
+    #[test]
+    fn test_barycentric_interpolation() {
+        // 4 - 3x + 2x^3
+        let coefficients = vec![F::from(4), -F::from(3), F::from(0), F::from(2)];
+        let evaluations: Vec<F> = (0..4u64)
+            .map(|x| evaluate_coefficients(&coefficients, F::from(x)))
+            .collect();
+        let weights: Vec<F> = Verifier::barycentric_weights(3);
+        let expected = [-F::from(6), F::from(2), -F::from(2), F::from(6)];
+        for (w, e) in weights.iter().zip(expected) {
+            assert_eq!(*w * e, F::ONE);
+        }
+        for r in [F::from(2), F::from(17), -F::from(5)] {
+            assert_eq!(
+                Verifier::evaluate_at_random_point(&evaluations, r),
+                evaluate_coefficients(&coefficients, r)
+            );
+        }
+    }
+
+    #[test]
+    fn test_interpolation_context() {
+        let context = InterpolationContext::new(5);
+        assert_eq!(context.max_degree(), 5);
+        for degree in 0..=5 {
+            assert_eq!(context.weights(degree), Verifier::barycentric_weights::<F>(degree).as_slice());
+        }
+        // 1 + x^2, of degree 2 given by 4 evaluations, including at the nodes themselves.
+        let evaluations: Vec<F> = (0..4u64).map(|x| F::from(1 + x * x)).collect();
+        for x in [0, 3, 10] {
+            assert_eq!(context.evaluate(&evaluations, F::from(x)), F::from(1 + x * x));
+        }
+        let state = Verifier::initialize_committed(2, 3, F::from(0));
+        assert_eq!(state.interpolation, InterpolationContext::new(3));
+    }
+
+    #[test]
+    fn test_seeded_challenges() {
+        let poly = vec![SparsePolynomial::from_coefficients_vec(
+            2,
+            Vec::from([
+                (F::from(3), SparseTerm::new(vec![(0, 1)])),
+                (F::from(1), SparseTerm::new(vec![(0, 1), (1, 1)])),
+            ])
+        )];
+        let run = |seed: u64| {
+            let mut prover = SumCheckProver::new(&poly);
+            let rng = StdRng::seed_from_u64(seed);
+            let mut verifier = SumCheckVerifier::with_rng(&poly, prover.claimed_sum(), rng);
+            let mut challenge = None;
+            for _ in 0..2 {
+                challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+            }
+            let (result, point) = verifier.finalize();
+            assert!(result.is_ok());
+            point
+        };
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+
+    #[test]
+    fn test_finalize_to_claim() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        let mut prover = SumCheckProver::new(&poly);
+        let mut verifier = SumCheckVerifier::from_state(Verifier::initialize_committed(3, 2, prover.claimed_sum()));
+        let mut challenge = None;
+        for _ in 0..2 {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        assert_eq!(
+            Verifier::finalize_to_claim(Verifier::initialize_committed(3, 2, F::from(0))).err(),
+            Some(SumCheckError::VariableCountMismatch { expected: 3, got: 0 })
+        );
+        verifier.receive_message(prover.next_message(challenge)).unwrap();
+        let claim = verifier.finalize_to_claim().unwrap();
+        assert_eq!(claim.point.len(), 3);
+        assert_eq!(claim.check(&vec![poly.clone()]), Ok(()));
+        assert_eq!(claim.check(&vec![poly[..1].to_vec()]), Err(SumCheckError::FinalCheckFailed));
+    }
+
+    #[test]
+    fn test_digest_verifier() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        let digest = InstanceDigest::new(&poly);
+        let run = || {
+            let mut prover = SumCheckProver::new(&poly);
+            let mut verifier = SumCheckVerifier::from_digest(&digest, prover.claimed_sum());
+            assert!(verifier.state.as_ref().unwrap().poly.is_none());
+            let mut challenge = None;
+            for _ in 0..3 {
+                challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+            }
+            verifier
+        };
+        assert!(run().finalize_with_instance(&poly).0.is_ok());
+        let other = crate::polynomial::parse_product("(x0 + 2*x1) * (x1*x2 + 4)").unwrap();
+        assert_eq!(run().finalize_with_instance(&other).0, Err(SumCheckError::InstanceMismatch));
+
+        let oracle = |point: &[F]| evaluate_mvml_polynomial(poly.clone(), &point.to_vec());
+        assert!(run().finalize_with_oracle(oracle).0.is_ok());
+        assert_eq!(run().finalize_with_oracle(|_| F::from(0)).0, Err(SumCheckError::FinalCheckFailed));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1*x2 + 3)").unwrap();
+        let mut prover = SumCheckProver::new(&poly);
+        let mut verifier = SumCheckVerifier::with_rng(&poly, prover.claimed_sum(), StdRng::seed_from_u64(0));
+        let r = verifier.receive_message(prover.next_message(None)).unwrap();
+        let (prover_snapshot, verifier_snapshot) = (prover.snapshot(), verifier.snapshot());
+        assert_eq!((prover_snapshot.round(), verifier_snapshot.round()), (1, 1));
+
+        let mut message = prover.next_message(Some(r));
+        message.evaluations[0] += F::from(1);
+        assert!(verifier.receive_message(message).is_err());
+
+        // The same challenges follow the same messages after restoring.
+        let mut points = vec![];
+        for _ in 0..2 {
+            prover.restore(&prover_snapshot);
+            verifier.restore(&verifier_snapshot);
+            let mut challenge = Some(r);
+            for _ in 1..3 {
+                challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+            }
+            let mut state = verifier.snapshot().state;
+            state.restore(&verifier_snapshot);
+            assert_eq!(state.last_round, 1);
+            let (result, point) = verifier.finalize();
+            assert!(result.is_ok());
+            points.push(point);
+            verifier = SumCheckVerifier::from_state(state);
+        }
+        assert_eq!(points[0], points[1]);
+    }
+
+    #[test]
+    fn test_invalid_opening() {
+        let poly = vec![SparsePolynomial::from_coefficients_vec(
+            1,
+            Vec::from([
+                (F::from(3), SparseTerm::new(vec![(0, 1)])),
+                (F::from(1), SparseTerm::new(vec![])),
+            ])
+        )];
+        let (commitment, opening) = HashCommitment::commit(&poly[0]);
+        let (claimed_sum, prover_state) = Prover::claim_sum(&poly);
+        let verifier_state = Verifier::initialize_committed(1, 1, claimed_sum);
+        let (poly_descr, _) = Prover::round_phase_1(prover_state);
+        let (r, verifier_state) = Verifier::round(verifier_state, poly_descr).unwrap();
+
+        let (value, mut proof) = HashCommitment::open(&opening, &[r]);
+        proof[1] += F::from(1);
+        let (result, _) = Verifier::sanity_check_with_openings::<HashCommitment, _>(
+            verifier_state,
+            &[commitment],
+            &[(value, proof)],
+        );
+        assert_eq!(result, Err(SumCheckError::OpeningCheckFailed { factor: 0 }));
+    }
+
+    #[test]
+    fn test_shorter_description() {
+        let poly = vec![SparsePolynomial::from_coefficients_vec(
+            2,
+            Vec::from([
+                (F::from(3), SparseTerm::new(vec![(0, 1)])),
+                (F::from(1), SparseTerm::new(vec![(1, 1)])),
+            ])
+        )];
+
+        let (_, claimed_sum, _, verifier_state) = setup_protocol(&poly).unwrap();
+        let result = Verifier::round(verifier_state, vec![claimed_sum]);
+        assert_eq!(
+            result.err(),
+            Some(SumCheckError::DegreeMismatch { round: 0, expected: 2, got: 1 })
+        );
+    }
+
+    #[test]
+    fn test_empty_message() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        for format in [MessageFormat::Evaluations, MessageFormat::Coefficients, MessageFormat::Compressed] {
+            let state = VerifierState { format, ..Verifier::initialize(&poly, F::from(0)) };
+            // Only the first factor depends on x0.
+            let expected = if format == MessageFormat::Compressed { 1 } else { 2 };
+            assert_eq!(
+                Verifier::round(state, vec![]).err(),
+                Some(SumCheckError::DegreeMismatch { round: 0, expected, got: 0 })
+            );
+        }
+        assert_eq!(Verifier::round_sum(MessageFormat::Coefficients, &[]), None);
+    }
+
+    /// Zero words, then those of a seeded generator.
+    struct LeadingZeros(usize, StdRng);
+
+    impl RngCore for LeadingZeros {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            match self.0.checked_sub(1) {
+                Some(zeros) => {
+                    self.0 = zeros;
+                    0
+                }
+                None => self.1.next_u64(),
+            }
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for LeadingZeros {}
+
+    #[test]
+    fn test_challenge_policy() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x0 + x1 + 3)").unwrap();
+        let run = |policy| {
+            let mut prover = SumCheckProver::new(&poly);
+            let rng = LeadingZeros(4, StdRng::seed_from_u64(0));
+            let state = Verifier::initialize_with_rng(&poly, prover.claimed_sum(), rng).with_challenge_policy(policy);
+            let mut verifier = SumCheckVerifier::from_state(state);
+            let mut challenge = None;
+            for _ in 0..2 {
+                challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+            }
+            let (result, point) = verifier.finalize();
+            assert!(result.is_ok());
+            point
+        };
+        // The first challenge is the node 0 of the first round polynomial.
+        assert_eq!(run(ChallengePolicy::Any)[0], F::from(0));
+        let point = run(ChallengePolicy::Resample);
+        assert!(point.iter().all(|&r| r != F::from(0) && r != F::from(1) && r != F::from(2)));
+        assert_ne!(point[0], point[1]);
+    }
+
+    #[test]
+    fn test_challenge_sampler() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x0 + x1 + 3)").unwrap();
+        let subgroup = crate::protocol::Subgroup::of_order(12).unwrap();
+        let mut prover = SumCheckProver::new(&poly);
+        let state = Verifier::initialize_with_rng(&poly, prover.claimed_sum(), StdRng::seed_from_u64(0))
+            .with_challenge_sampler(subgroup);
+        // log2(12) - log2(2 + 2) bits.
+        assert!((state.security_bits() - (12f64.log2() - 2.0)).abs() < 1e-9);
+        let mut verifier = SumCheckVerifier::from_state(state);
+        let mut challenge = None;
+        for _ in 0..2 {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        let (result, point) = verifier.finalize();
+        assert!(result.is_ok());
+        assert!(point.iter().all(|&r| subgroup.contains(r)));
+
+        // The rejection sampler never draws the nodes, even from a generator biased towards 0.
+        let rng = LeadingZeros(4, StdRng::seed_from_u64(0));
+        let mut state = Verifier::initialize_with_rng(&poly, F::from(0), rng)
+            .with_challenge_sampler(crate::protocol::RejectionSampler::nonbinary());
+        assert!(state.draw_challenge(2) != F::from(0));
+        assert!(state.security_bits() > 250.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "more variables are fixed than the polynomial has")]
+    fn test_partial_too_many_fixed() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let fixed = [(0, F::from(1)), (1, F::from(2)), (2, F::from(3))];
+        let _ = Verifier::initialize_partial(&poly, &fixed, F::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "a fixed variable is out of range")]
+    fn test_partial_out_of_range() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let _ = Verifier::initialize_partial(&poly, &[(2, F::from(1))], F::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "a variable is fixed twice")]
+    fn test_partial_fixed_twice() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let _ = Verifier::initialize_partial(&poly, &[(1, F::from(1)), (1, F::from(2))], F::from(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "resampling challenges needs more than 5 of them, the sampler draws from Subset(2)")]
+    fn test_resample_small_subgroup() {
+        // The subgroup {1, −1} would be exhausted by the node 1 and the first challenge.
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x0 + x1 + 3)").unwrap();
+        let subgroup = crate::protocol::Subgroup::of_order(2).unwrap();
+        let _ = Verifier::initialize_with_rng(&poly, F::from(0), StdRng::seed_from_u64(0))
+            .with_challenge_policy(ChallengePolicy::Resample)
+            .with_challenge_sampler(subgroup);
+    }
+
+    #[test]
+    #[should_panic(expected = "the sampler draws from Part { log2_density: -252.0 }")]
+    fn test_resample_small_part() {
+        // At least 2^⌊254 − 252⌋ = 4 elements, fewer than the 3 nodes and 2 rounds.
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x0 + x1 + 3)").unwrap();
+        let space = crate::protocol::ChallengeSpace::Part { log2_density: -252.0 };
+        let part = crate::protocol::RejectionSampler::new(|_: &F| true, space);
+        let _ = Verifier::initialize_with_rng(&poly, F::from(0), StdRng::seed_from_u64(0))
+            .with_challenge_sampler(part)
+            .with_challenge_policy(ChallengePolicy::Resample);
+    }
+
+    #[test]
+    fn test_resample_subgroup() {
+        // In either order, an order-12 subgroup leaves room for the 3 nodes and 2 rounds, and the
+        // run terminates with distinct challenges off the nodes.
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x0 + x1 + 3)").unwrap();
+        let subgroup = crate::protocol::Subgroup::of_order(12).unwrap();
+        let mut prover = SumCheckProver::new(&poly);
+        let state = Verifier::initialize_with_rng(&poly, prover.claimed_sum(), StdRng::seed_from_u64(0))
+            .with_challenge_sampler(subgroup)
+            .with_challenge_policy(ChallengePolicy::Resample);
+        let mut verifier = SumCheckVerifier::from_state(state);
+        let mut challenge = None;
+        for _ in 0..2 {
+            challenge = Some(verifier.receive_message(prover.next_message(challenge)).unwrap());
+        }
+        let (result, point) = verifier.finalize();
+        assert!(result.is_ok());
+        assert!(point.iter().all(|&r| subgroup.contains(r) && r != F::from(1)));
+        assert_ne!(point[0], point[1]);
+    }
+
+    #[test]
+    fn test_degree_bounds() {
+        // x1 only appears in the second factor.
+        let poly = vec![
+            SparsePolynomial::from_coefficients_vec(2, vec![(F::from(3), SparseTerm::new(vec![(0, 1)]))]),
+            SparsePolynomial::from_coefficients_vec(
+                2,
+                vec![(F::from(1), SparseTerm::new(vec![(0, 1), (1, 1)])), (F::from(2), SparseTerm::new(vec![]))],
+            ),
+        ];
+        let state = Verifier::initialize(&poly, F::from(0));
+        assert_eq!(state.degree_bounds, vec![2, 1]);
+        assert_eq!(Verifier::initialize_partial(&poly, &[(0, F::from(5))], F::from(0)).degree_bounds, vec![1]);
+        assert_eq!(Verifier::initialize_committed(2, 2, F::from(0)).degree_bounds, vec![2, 2]);
+
+        // A message of degree 2 in the round of x1 is rejected, although there are two factors.
+        let (claimed_sum, prover_state) = Prover::claim_sum(&poly);
+        let verifier_state = Verifier::initialize(&poly, claimed_sum);
+        let (poly_descr, prover_state) = Prover::round_phase_1(prover_state);
+        let (r, verifier_state) = Verifier::round(verifier_state, poly_descr).unwrap();
+        let (mut poly_descr, _) = Prover::fold_and_round(prover_state, r);
+        assert_eq!(poly_descr.len(), 2);
+        let line = poly_descr[1] - poly_descr[0];
+        poly_descr.push(poly_descr[1] + line);
+        assert_eq!(
+            Verifier::round(verifier_state, poly_descr).err(),
+            Some(SumCheckError::DegreeMismatch { round: 1, expected: 2, got: 3 })
+        );
+
+        let state = Verifier::initialize_committed(2, 2, F::from(0)).with_degree_bounds(vec![1, 1]);
+        assert_eq!(state.degree_bounds, vec![1, 1]);
+    }
+
+    #[test]
+    fn test_degree_mismatch() {
+        let poly = vec![SparsePolynomial::from_coefficients_vec(
+            2,
+            Vec::from([
+                (F::from(3), SparseTerm::new(vec![(0, 1)])),
+                (F::from(1), SparseTerm::new(vec![(1, 1)])),
+            ])
+        )];
+
+        let (_, _, prover_state, verifier_state) = setup_protocol(&poly).unwrap();
+        let (mut poly_descr, _) = Prover::round_phase_1(prover_state);
+        poly_descr.push(F::from(0));
+        let result = Verifier::round(verifier_state, poly_descr);
+        assert_eq!(
+            result.err(),
+            Some(SumCheckError::DegreeMismatch { round: 0, expected: 2, got: 3 })
+        );
+    }
 }
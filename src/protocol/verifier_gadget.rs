@@ -0,0 +1,167 @@
+//! The verifier of the sum-check as R1CS constraints, for proofs made non-interactive with the
+//! Poseidon transcript, so that a sum-check proof can be verified inside a Groth16 or Marlin
+//! circuit over the proof field.
+//!
+//! The gadget derives the challenges with a Poseidon sponge gadget absorbing the same elements as
+//! [`PoseidonTranscript`](crate::protocol::PoseidonTranscript), and enforces the check
+//! p(0) + p(1) = running evaluation of each round. As with the committed verifier, the final
+//! check, of the running evaluation against the polynomial at the random point, is left to the
+//! caller.
+
+use ark_crypto_primitives::sponge::constraints::CryptographicSpongeVar;
+use ark_crypto_primitives::sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_std::vec::Vec;
+
+use crate::field::Field256 as F;
+use crate::protocol::poseidon_transcript::{label_element, poseidon_config};
+use crate::protocol::proof::{PROTOCOL_LABEL, PROTOCOL_VERSION};
+use crate::protocol::Verifier;
+
+/// The gadget of [`PoseidonTranscript`](crate::protocol::PoseidonTranscript).
+#[derive(Clone)]
+pub struct PoseidonTranscriptVar {
+    sponge: PoseidonSpongeVar<F>,
+}
+
+impl PoseidonTranscriptVar {
+    pub fn new(cs: ConstraintSystemRef<F>, label: &[u8]) -> Result<Self, SynthesisError> {
+        let mut transcript = PoseidonTranscriptVar {
+            sponge: PoseidonSpongeVar::new(cs, &poseidon_config()),
+        };
+        transcript.append_field_elements(b"domain", &[FpVar::constant(label_element(label))])?;
+        Ok(transcript)
+    }
+
+    pub fn append_field_elements(
+        &mut self,
+        label: &[u8],
+        elements: &[FpVar<F>],
+    ) -> Result<(), SynthesisError> {
+        let header = [
+            FpVar::constant(label_element(label)),
+            FpVar::constant(F::from(elements.len() as u64)),
+        ];
+        self.sponge.absorb(&header.as_slice())?;
+        self.sponge.absorb(&elements)
+    }
+
+    pub fn challenge(&mut self) -> Result<FpVar<F>, SynthesisError> {
+        Ok(self.sponge.squeeze_field_elements(1)?.remove(0))
+    }
+}
+
+/// The gadget of the sum-check verifier, for round messages given by their evaluations.
+pub struct SumCheckVerifierGadget;
+
+impl SumCheckVerifierGadget {
+    /// Enforces the rounds of a proof produced by
+    /// [`prove_with_poseidon`](crate::protocol::prove_with_poseidon), and returns the random point
+    /// and the running evaluation that the polynomial must have there. 'instance_hash' holds the
+    /// [`instance_hash_elements`](crate::protocol::instance_hash_elements) of the statement, which
+    /// the caller binds to the polynomial it checks.
+    pub fn verify(
+        cs: ConstraintSystemRef<F>,
+        instance_hash: &[FpVar<F>],
+        claimed_sum: &FpVar<F>,
+        round_messages: &[Vec<FpVar<F>>],
+    ) -> Result<(Vec<FpVar<F>>, FpVar<F>), SynthesisError> {
+        let mut transcript = PoseidonTranscriptVar::new(cs, PROTOCOL_LABEL)?;
+        let version = FpVar::constant(F::from(PROTOCOL_VERSION));
+        transcript.append_field_elements(b"version", &[version])?;
+        transcript.append_field_elements(b"instance", instance_hash)?;
+        transcript.append_field_elements(b"claimed_sum", core::slice::from_ref(claimed_sum))?;
+        let mut running_eval = claimed_sum.clone();
+        let mut point = Vec::with_capacity(round_messages.len());
+        for message in round_messages {
+            transcript.append_field_elements(b"round", message)?;
+            let r = transcript.challenge()?;
+            running_eval = Self::round(&running_eval, message, &r)?;
+            point.push(r);
+        }
+        Ok((point, running_eval))
+    }
+
+    /// Enforces p(0) + p(1) = 'running_eval' for the round polynomial p given by its evaluations
+    /// 'message' at 0, 1, ..., d, and returns p(r).
+    pub fn round(
+        running_eval: &FpVar<F>,
+        message: &[FpVar<F>],
+        r: &FpVar<F>,
+    ) -> Result<FpVar<F>, SynthesisError> {
+        if message.len() < 2 {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+        (&message[0] + &message[1]).enforce_equal(running_eval)?;
+        Self::interpolate(message, r)
+    }
+
+    /// Evaluates at 'r' the polynomial given by its evaluations at 0, 1, ..., d, as
+    /// Σ_i w_i y_i Π_{j != i} (r - j) with the barycentric weights w_i, the products being shared
+    /// between prefixes and suffixes.
+    fn interpolate(message: &[FpVar<F>], r: &FpVar<F>) -> Result<FpVar<F>, SynthesisError> {
+        let degree = message.len() - 1;
+        let weights: Vec<F> = Verifier::barycentric_weights(degree);
+        let shifts: Vec<FpVar<F>> = (0..=degree).map(|j| r - F::from(j as u64)).collect();
+        let mut prefixes = Vec::with_capacity(degree + 1);
+        let mut prefix = FpVar::one();
+        for shift in &shifts {
+            prefixes.push(prefix.clone());
+            prefix *= shift;
+        }
+        let mut result = FpVar::zero();
+        let mut suffix = FpVar::one();
+        for i in (0..=degree).rev() {
+            result += &message[i] * weights[i] * &prefixes[i] * &suffix;
+            suffix *= &shifts[i];
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::{evaluate_mvml_polynomial, parse_product};
+    use crate::protocol::{instance_hash_elements, prove_with_poseidon, verify_with_poseidon};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    fn synthesize(proof: &crate::protocol::Proof) -> (ConstraintSystemRef<F>, Vec<F>, F) {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let instance_hash = Vec::new_input(cs.clone(), || {
+            Ok(instance_hash_elements(&proof.instance_hash).to_vec())
+        })
+        .unwrap();
+        let claimed_sum = FpVar::new_input(cs.clone(), || Ok(proof.claimed_sum)).unwrap();
+        let messages: Vec<Vec<FpVar<F>>> = proof
+            .round_messages
+            .iter()
+            .map(|message| Vec::new_witness(cs.clone(), || Ok(message.clone())).unwrap())
+            .collect();
+        let (point, running_eval) =
+            SumCheckVerifierGadget::verify(cs.clone(), &instance_hash, &claimed_sum, &messages)
+                .unwrap();
+        (cs, point.value().unwrap(), running_eval.value().unwrap())
+    }
+
+    #[test]
+    fn test_gadget_matches_verifier() {
+        let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3) * (x0*x2 + 1)").unwrap();
+        let proof = prove_with_poseidon(&poly);
+        let (cs, point, running_eval) = synthesize(&proof);
+        assert!(cs.is_satisfied().unwrap());
+        assert_eq!(point, verify_with_poseidon(&poly, &proof).unwrap());
+        assert_eq!(running_eval, evaluate_mvml_polynomial(poly, &point));
+    }
+
+    #[test]
+    fn test_gadget_rejects_wrong_sum() {
+        let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3)").unwrap();
+        let mut proof = prove_with_poseidon(&poly);
+        proof.claimed_sum += F::from(1);
+        let (cs, _, _) = synthesize(&proof);
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,12 @@
+//! Protocols reduced to the sum-check, built on the prover and the verifier of
+//! [`protocol`](crate::protocol).
+
+pub mod claim_accumulator;
+pub mod gate_check;
+pub mod lookup;
+pub mod matvec;
+pub mod product_check;
+#[cfg(feature = "r1cs")]
+pub mod r1cs;
+pub mod recursive;
+pub mod zerocheck;
@@ -0,0 +1,234 @@
+//! Aggregation of evaluation claims: the claims f(z_1) = v_1, …, f(z_m) = v_m about a single
+//! multilinear f, left by sequential sum-checks (e.g. the layers of a GKR-style pipeline), are
+//! batched into one with a random linear combination
+//!
+//!   Σ_i α_i v_i = Σ_b (Σ_i α_i eq(b, z_i)) · f(b),
+//!
+//! proven by one more sum-check, so that f is opened at a single point ρ instead of once per
+//! claim. The verifier evaluates the weights Σ_i α_i eq(ρ, z_i) itself, and is left with the
+//! claim about f(ρ).
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "prover")]
+use ark_std::vec;
+use ark_std::{vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{eq_eval, PolynomialDescription};
+#[cfg(feature = "prover")]
+use crate::polynomial::{eq_extension, evaluate_table};
+#[cfg(feature = "prover")]
+use crate::protocol::SumCheckProver;
+use crate::protocol::{EvaluationClaim, SumCheckError, Transcript, Verifier};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"claim-accumulator";
+
+/// A non-interactive proof that the claims of a [`ClaimAccumulator`] reduce to the claim about
+/// f(ρ), with the evaluation f(ρ).
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchedClaimProof {
+    pub round_messages: Vec<PolynomialDescription>,
+    pub evaluation: F,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum AccumulatorError {
+    #[error("there is no claim to batch")]
+    NoClaims,
+    #[error(transparent)]
+    SumCheck(#[from] SumCheckError),
+    /// The evaluation of f is inconsistent with the last sum-check round.
+    #[error("the evaluation does not match the sum-check")]
+    FinalCheckFailed,
+}
+
+/// Collects evaluation claims about a multilinear on 'num_vars' variables, to be batched into a
+/// single one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClaimAccumulator {
+    num_vars: usize,
+    claims: Vec<EvaluationClaim>,
+}
+
+impl ClaimAccumulator {
+    pub fn new(num_vars: usize) -> Self {
+        ClaimAccumulator {
+            num_vars,
+            claims: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, claim: EvaluationClaim) {
+        assert_eq!(
+            claim.point.len(),
+            self.num_vars,
+            "the claims must be about a multilinear on {} variables",
+            self.num_vars
+        );
+        self.claims.push(claim);
+    }
+
+    pub fn claims(&self) -> &[EvaluationClaim] {
+        &self.claims
+    }
+
+    /// Proves the batched claim, given the evaluation table of the multilinear, and returns the
+    /// claim it reduces to.
+    #[cfg(feature = "prover")]
+    pub fn prove(
+        &self,
+        table: &[F],
+    ) -> Result<(BatchedClaimProof, EvaluationClaim), AccumulatorError> {
+        assert_eq!(
+            table.len(),
+            1 << self.num_vars,
+            "the table must have {} variables",
+            self.num_vars
+        );
+        let (mut transcript, coefficients, _) = self.batch()?;
+        let mut weights = vec![F::from(0); 1 << self.num_vars];
+        for (claim, &alpha) in self.claims.iter().zip(&coefficients) {
+            for (weight, eq) in weights.iter_mut().zip(eq_extension(&claim.point)) {
+                *weight += alpha * eq;
+            }
+        }
+
+        let mut prover = SumCheckProver::from_tables(self.num_vars, vec![weights, table.to_vec()]);
+        let mut round_messages = Vec::with_capacity(self.num_vars);
+        let mut point = Vec::with_capacity(self.num_vars);
+        for _ in 0..self.num_vars {
+            let message = prover.next_message(point.last().copied()).evaluations;
+            transcript.append_field_elements(b"round", &message);
+            point.push(F::rand(&mut transcript));
+            round_messages.push(message);
+        }
+        let evaluation = evaluate_table(table, &point);
+        let proof = BatchedClaimProof {
+            round_messages,
+            evaluation,
+        };
+        Ok((
+            proof,
+            EvaluationClaim {
+                point,
+                value: evaluation,
+            },
+        ))
+    }
+
+    /// Verifies a proof produced by [`ClaimAccumulator::prove`], and returns the claim left to
+    /// check, e.g. by opening a commitment to the multilinear.
+    pub fn verify(&self, proof: &BatchedClaimProof) -> Result<EvaluationClaim, AccumulatorError> {
+        let (transcript, coefficients, batched) = self.batch()?;
+        let mut state =
+            Verifier::initialize_committed_with_rng(self.num_vars, 2, batched, transcript);
+        for message in &proof.round_messages {
+            state.rng.append_field_elements(b"round", message);
+            (_, state) = Verifier::round(state, message.clone())?;
+        }
+        let claim = Verifier::finalize_to_claim(state)?;
+        let weight: F = self
+            .claims
+            .iter()
+            .zip(&coefficients)
+            .map(|(c, &alpha)| alpha * eq_eval(&c.point, &claim.point))
+            .sum();
+        if weight * proof.evaluation != claim.value {
+            return Err(AccumulatorError::FinalCheckFailed);
+        }
+        Ok(EvaluationClaim {
+            point: claim.point,
+            value: proof.evaluation,
+        })
+    }
+
+    /// The transcript after the claims, the coefficients of the linear combination, and the
+    /// combination of the claimed values.
+    fn batch(&self) -> Result<(Transcript, Vec<F>, F), AccumulatorError> {
+        if self.claims.is_empty() {
+            return Err(AccumulatorError::NoClaims);
+        }
+        let mut transcript = Transcript::new(PROTOCOL_LABEL);
+        transcript.append_field_elements(b"num_vars", &[F::from(self.num_vars as u64)]);
+        for claim in &self.claims {
+            transcript.append_field_elements(b"point", &claim.point);
+            transcript.append_field_elements(b"value", &[claim.value]);
+        }
+        let coefficients: Vec<F> = self
+            .claims
+            .iter()
+            .map(|_| F::rand(&mut transcript))
+            .collect();
+        let batched = self
+            .claims
+            .iter()
+            .zip(&coefficients)
+            .map(|(claim, &alpha)| alpha * claim.value)
+            .sum();
+        Ok((transcript, coefficients, batched))
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::{evaluate_polynomial_on_hypercube, parse_product, EvalTable};
+    use crate::protocol::{prove, prove_weighted, verify, verify_weighted};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_claim_accumulator() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let table: EvalTable = (0..16).map(|_| F::rand(&mut rng)).collect();
+        let mut accumulator = ClaimAccumulator::new(4);
+        assert_eq!(
+            accumulator.prove(&table).err(),
+            Some(AccumulatorError::NoClaims)
+        );
+        for _ in 0..3 {
+            let point: Vec<F> = (0..4).map(|_| F::rand(&mut rng)).collect();
+            let value = evaluate_table(&table, &point);
+            accumulator.push(EvaluationClaim { point, value });
+        }
+        let (proof, claim) = accumulator.prove(&table).unwrap();
+        assert_eq!(accumulator.verify(&proof), Ok(claim.clone()));
+        assert_eq!(evaluate_table(&table, &claim.point), claim.value);
+
+        let mut wrong = proof.clone();
+        wrong.evaluation += F::from(1);
+        assert_eq!(
+            accumulator.verify(&wrong),
+            Err(AccumulatorError::FinalCheckFailed)
+        );
+
+        // A wrong claim breaks the first round.
+        let mut wrong = accumulator.clone();
+        wrong.claims[1].value += F::from(1);
+        let (proof, _) = wrong.prove(&table).unwrap();
+        assert!(matches!(
+            wrong.verify(&proof),
+            Err(AccumulatorError::SumCheck(_))
+        ));
+    }
+
+    #[test]
+    fn test_sum_check_claims() {
+        // The claims left by two sum-checks over the same multilinear.
+        let poly = parse_product("(x0 + 3*x1*x2 + 2)").unwrap();
+        let table = evaluate_polynomial_on_hypercube(&poly[0]);
+        let point = verify(&poly, &prove(&poly)).unwrap();
+        let r = [F::from(5), F::from(7), F::from(11)];
+        let weighted_point = verify_weighted(&poly, &r, &prove_weighted(&poly, &r)).unwrap();
+        assert_ne!(point, weighted_point);
+        let mut accumulator = ClaimAccumulator::new(3);
+        for point in [point, weighted_point] {
+            let value = evaluate_table(&table, &point);
+            accumulator.push(EvaluationClaim { point, value });
+        }
+        let (proof, claim) = accumulator.prove(&table).unwrap();
+        assert_eq!(accumulator.verify(&proof), Ok(claim));
+    }
+}
@@ -0,0 +1,331 @@
+//! Gate checks: the sum-check of the common relations between the left and right inputs L, R and
+//! the output O of the gates of a layer,
+//!
+//!   Σ_b eq(r, b) · (gate(L(b), R(b)) − O(b)),
+//!
+//! for a point r, e.g. drawn by the verifier or reached by the previous layer of a GKR proof. The
+//! sum is 0 for every r if the gates hold on the whole hypercube, and is 0 with high probability
+//! over a random r only then. The instances are built from the three witness tables, sharing a
+//! single eq factor between the products of the relation, so that the caller does not assemble
+//! them term by term. The sum-check ends at a random point ρ with the claimed evaluations of L, R
+//! and O there, left to the caller to check.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::vec::Vec;
+#[cfg(feature = "prover")]
+use ark_std::UniformRand;
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{eq_eval, PolynomialDescription};
+#[cfg(feature = "prover")]
+use crate::polynomial::{evaluate_table, EvalTable};
+#[cfg(feature = "prover")]
+use crate::protocol::{EqFactor, Factor, SumCheckProver, VirtualPolynomial};
+use crate::protocol::{SumCheckError, Transcript, Verifier};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"gate-check";
+
+/// The relation between the inputs and the output of a gate. The boolean gates assume boolean
+/// inputs, which a separate check, such as the zero-check of L · (1 − L), has to enforce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateRelation {
+    /// O = L · R.
+    Mul,
+    /// O = L + R.
+    Add,
+    /// O = L ∧ R = L · R.
+    And,
+    /// O = L ∨ R = L + R − L · R.
+    Or,
+    /// O = L ⊕ R = L + R − 2 · L · R.
+    Xor,
+}
+
+impl GateRelation {
+    /// The output of the gate on the inputs 'left' and 'right', on any field elements.
+    pub fn apply(self, left: F, right: F) -> F {
+        match self {
+            GateRelation::Mul | GateRelation::And => left * right,
+            GateRelation::Add => left + right,
+            GateRelation::Or => left + right - left * right,
+            GateRelation::Xor => left + right - F::from(2) * left * right,
+        }
+    }
+
+    /// Degree of the relation, counting the eq factor, which bounds the degree of the rounds.
+    pub fn degree(self) -> usize {
+        match self {
+            GateRelation::Add => 2,
+            _ => 3,
+        }
+    }
+
+    fn id(self) -> u64 {
+        match self {
+            GateRelation::Mul => 0,
+            GateRelation::Add => 1,
+            GateRelation::And => 2,
+            GateRelation::Or => 3,
+            GateRelation::Xor => 4,
+        }
+    }
+}
+
+/// The tables of the inputs and the output of a layer of gates of the same relation, on the same
+/// variables.
+#[cfg(feature = "prover")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateInstance {
+    pub relation: GateRelation,
+    pub left: EvalTable,
+    pub right: EvalTable,
+    pub output: EvalTable,
+}
+
+#[cfg(feature = "prover")]
+impl GateInstance {
+    pub fn new(
+        relation: GateRelation,
+        left: EvalTable,
+        right: EvalTable,
+        output: EvalTable,
+    ) -> Self {
+        assert!(
+            left.len().is_power_of_two() && right.len() == left.len() && output.len() == left.len(),
+            "the tables must have the same variables"
+        );
+        GateInstance {
+            relation,
+            left,
+            right,
+            output,
+        }
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.left.len().trailing_zeros() as usize
+    }
+
+    /// Σ_b eq('r', b) · (gate(L(b), R(b)) − O(b)) as a sum of products, with the coefficients of
+    /// the relation folded into copies of the tables of L and O.
+    pub fn virtual_polynomial(&self, r: &[F]) -> VirtualPolynomial {
+        assert_eq!(
+            r.len(),
+            self.num_vars(),
+            "the point must be on the variables of the tables"
+        );
+        let scaled =
+            |table: &EvalTable, c: F| Factor::Table(table.iter().map(|&v| c * v).collect());
+        let mut poly = VirtualPolynomial::new(self.num_vars());
+        let eq = poly.add_mle(Factor::Eq(EqFactor::new(r)));
+        let left = poly.add_mle(Factor::Table(self.left.clone()));
+        let right = poly.add_mle(Factor::Table(self.right.clone()));
+        let output = poly.add_mle(scaled(&self.output, -F::from(1)));
+        match self.relation {
+            GateRelation::Mul | GateRelation::And => poly.add_product(&[eq, left, right]),
+            GateRelation::Add => {
+                poly.add_product(&[eq, left]);
+                poly.add_product(&[eq, right]);
+            }
+            GateRelation::Or | GateRelation::Xor => {
+                let c = if self.relation == GateRelation::Or {
+                    -F::from(1)
+                } else {
+                    -F::from(2)
+                };
+                let scaled_left = poly.add_mle(scaled(&self.left, c));
+                poly.add_product(&[eq, left]);
+                poly.add_product(&[eq, right]);
+                poly.add_product(&[eq, scaled_left, right]);
+            }
+        }
+        poly.add_product(&[eq, output]);
+        poly
+    }
+}
+
+/// A non-interactive proof of the sum of a gate relation, with the evaluations of the tables at
+/// the random point of the sum-check.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GateProof {
+    pub claimed_sum: F,
+    pub round_messages: Vec<PolynomialDescription>,
+    pub left: F,
+    pub right: F,
+    pub output: F,
+}
+
+/// What remains to check after a successful [`verify_gate`]: L, R and O evaluate to 'left',
+/// 'right' and 'output' at 'point'. The gates hold on the hypercube only if 'claimed_sum' is 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateSubclaim {
+    pub claimed_sum: F,
+    pub point: Vec<F>,
+    pub left: F,
+    pub right: F,
+    pub output: F,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum GateCheckError {
+    #[error(transparent)]
+    SumCheck(#[from] SumCheckError),
+    /// The evaluations of the tables are inconsistent with the last sum-check round.
+    #[error("the evaluations of the tables do not match the sum-check")]
+    FinalCheckFailed,
+}
+
+/// Proves the sum of the relation of 'instance' weighted by eq('r', ·).
+#[cfg(feature = "prover")]
+pub fn prove_gate(instance: &GateInstance, r: &[F]) -> GateProof {
+    let num_vars = instance.num_vars();
+    let mut prover = SumCheckProver::from_virtual(instance.virtual_polynomial(r));
+    let claimed_sum = prover.claimed_sum();
+    let mut transcript = gate_transcript(instance.relation, r, claimed_sum);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    let mut challenges = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let message = prover.next_message(challenges.last().copied()).evaluations;
+        transcript.append_field_elements(b"round", &message);
+        challenges.push(F::rand(&mut transcript));
+        round_messages.push(message);
+    }
+    GateProof {
+        claimed_sum,
+        round_messages,
+        left: evaluate_table(&instance.left, &challenges),
+        right: evaluate_table(&instance.right, &challenges),
+        output: evaluate_table(&instance.output, &challenges),
+    }
+}
+
+/// Verifies a proof produced by [`prove_gate`] for 'relation' at 'r', and returns the evaluations
+/// of the tables left to check.
+pub fn verify_gate(
+    relation: GateRelation,
+    r: &[F],
+    proof: &GateProof,
+) -> Result<GateSubclaim, GateCheckError> {
+    let num_vars = r.len();
+    let transcript = gate_transcript(relation, r, proof.claimed_sum);
+    let mut state = Verifier::initialize_committed_with_rng(
+        num_vars,
+        relation.degree(),
+        proof.claimed_sum,
+        transcript,
+    );
+    for message in &proof.round_messages {
+        state.rng.append_field_elements(b"round", message);
+        (_, state) = Verifier::round(state, message.clone())?;
+    }
+    if state.randomness.len() != num_vars {
+        return Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: state.randomness.len(),
+        }
+        .into());
+    }
+    let value = relation.apply(proof.left, proof.right) - proof.output;
+    if eq_eval(r, &state.randomness) * value != state.running_eval {
+        return Err(GateCheckError::FinalCheckFailed);
+    }
+    Ok(GateSubclaim {
+        claimed_sum: proof.claimed_sum,
+        point: state.randomness,
+        left: proof.left,
+        right: proof.right,
+        output: proof.output,
+    })
+}
+
+fn gate_transcript(relation: GateRelation, r: &[F], claimed_sum: F) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"relation", &[F::from(relation.id())]);
+    transcript.append_field_elements(b"point", r);
+    transcript.append_field_elements(b"claimed_sum", &[claimed_sum]);
+    transcript
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::eq_extension;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const RELATIONS: [GateRelation; 5] = [
+        GateRelation::Mul,
+        GateRelation::Add,
+        GateRelation::And,
+        GateRelation::Or,
+        GateRelation::Xor,
+    ];
+
+    fn bits(seed: u64) -> EvalTable {
+        (0..8)
+            .map(|i| F::from((i * seed + 1).is_multiple_of(3)))
+            .collect()
+    }
+
+    #[test]
+    fn test_gate_check() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let r: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+        for relation in RELATIONS {
+            let (left, right) = (bits(5), bits(7));
+            let output = left
+                .iter()
+                .zip(&right)
+                .map(|(&l, &r)| relation.apply(l, r))
+                .collect();
+            let instance = GateInstance::new(relation, left, right, output);
+            let proof = prove_gate(&instance, &r);
+            let subclaim = verify_gate(relation, &r, &proof).unwrap();
+            assert_eq!(subclaim.claimed_sum, F::from(0));
+            assert_eq!(
+                evaluate_table(&instance.left, &subclaim.point),
+                subclaim.left
+            );
+            assert_eq!(
+                evaluate_table(&instance.output, &subclaim.point),
+                subclaim.output
+            );
+            assert!(proof
+                .round_messages
+                .iter()
+                .all(|m| m.len() <= relation.degree() + 1));
+        }
+    }
+
+    #[test]
+    fn test_wrong_gates() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let r: Vec<F> = (0..3).map(|_| F::rand(&mut rng)).collect();
+        let (left, right) = (bits(5), bits(7));
+        // The AND outputs, checked as an OR.
+        let output: EvalTable = left.iter().zip(&right).map(|(&l, &r)| l * r).collect();
+        let instance = GateInstance::new(GateRelation::Or, left, right, output);
+        let proof = prove_gate(&instance, &r);
+        let subclaim = verify_gate(GateRelation::Or, &r, &proof).unwrap();
+        assert_ne!(subclaim.claimed_sum, F::from(0));
+        let expected: F = eq_extension(&r)
+            .iter()
+            .enumerate()
+            .map(|(b, &eq)| {
+                eq * (GateRelation::Or.apply(instance.left[b], instance.right[b])
+                    - instance.output[b])
+            })
+            .sum();
+        assert_eq!(subclaim.claimed_sum, expected);
+
+        assert!(verify_gate(GateRelation::And, &r, &proof).is_err());
+        let mut wrong = proof;
+        wrong.output += F::from(1);
+        assert_eq!(
+            verify_gate(GateRelation::Or, &r, &wrong),
+            Err(GateCheckError::FinalCheckFailed)
+        );
+    }
+}
@@ -0,0 +1,343 @@
+//! logUp-style lookup argument: proves that every entry of a witness table appears in a lookup
+//! table, through the identity
+//!
+//!   Σ_b 1 / (x + f(b)) = Σ_t m(t) / (x + t)
+//!
+//! for a random x, where m(t) counts the occurrences of t in the witness f.
+//!
+//! Each side is a sum of fractions p(b) / q(b), proven by a fractional sum-check: the layers of a
+//! binary tree of fraction additions are given by
+//!
+//!   P_i(x) = P_{i+1}(x, 0) · Q_{i+1}(x, 1) + P_{i+1}(x, 1) · Q_{i+1}(x, 0)
+//!   Q_i(x) = Q_{i+1}(x, 0) · Q_{i+1}(x, 1)
+//!
+//! and the claims about P_i and Q_i at a point r are batched with a random μ, and reduced to
+//! claims about P_{i+1} and Q_{i+1} by the sum-check of a sum of products:
+//!
+//!   P_i(r) + μ · Q_i(r) = Σ_x eq(r, x) · (p0 · q1 + p1 · q0 + μ · q0 · q1).
+//!
+//! As in the [`product_check`](super::product_check), the claims at (ρ, 0) and (ρ, 1) are then
+//! merged at (ρ, λ), and the verifier checks the last claims against the tables itself.
+
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "prover")]
+use ark_std::collections::BTreeMap;
+#[cfg(feature = "prover")]
+use ark_std::vec;
+use ark_std::{vec::Vec, UniformRand, Zero};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{eq_eval, evaluate_table, PolynomialDescription};
+#[cfg(feature = "prover")]
+use crate::polynomial::{eq_extension, EvalTable};
+#[cfg(feature = "prover")]
+use crate::protocol::{Factor, SumCheckProver, VirtualPolynomial};
+use crate::protocol::{SumCheckError, Transcript, Verifier};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"logup";
+
+/// The sum-check reducing a layer to the next one, with the evaluations of the numerators and
+/// the denominators of the next layer at (ρ, 0) and (ρ, 1).
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct FractionLayerProof {
+    pub round_messages: Vec<PolynomialDescription>,
+    pub numerators: (F, F),
+    pub denominators: (F, F),
+}
+
+/// A proof of the sum of the fractions p(b) / q(b) over the hypercube, the sum being given as an
+/// unreduced fraction.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct FractionalProof {
+    pub numerator: F,
+    pub denominator: F,
+    pub layers: Vec<FractionLayerProof>,
+}
+
+/// A non-interactive lookup proof, with the multiplicities of the entries of the lookup table.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LookupProof {
+    pub multiplicities: Vec<F>,
+    pub witness: FractionalProof,
+    pub table: FractionalProof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LookupError {
+    /// The prover cannot prove a witness whose entry 'index' is not in the table.
+    #[error("witness entry {index} is not in the table")]
+    MissingEntry { index: usize },
+    #[error("expected {expected} multiplicities, got {got}")]
+    MultiplicityCountMismatch { expected: usize, got: usize },
+    #[error("expected {expected} layers, got {got}")]
+    LayerCountMismatch { expected: usize, got: usize },
+    #[error("layer {layer}: {source}")]
+    SumCheck {
+        layer: usize,
+        #[source]
+        source: SumCheckError,
+    },
+    /// The evaluations of the next layer are inconsistent with the last sum-check round.
+    #[error("layer {layer}: the evaluations of the next layer do not match the sum-check")]
+    LayerCheckFailed { layer: usize },
+    /// The sums of the two sides differ, or one of them has a zero denominator.
+    #[error("the sums over the witness and over the table differ")]
+    SumMismatch,
+    /// The last claims do not match the multilinear extensions of the tables.
+    #[error("the final claims do not match the tables")]
+    FinalCheckFailed,
+}
+
+/// Proves that every entry of 'witness' is an entry of 'table'. The lengths of both must be
+/// powers of two.
+#[cfg(feature = "prover")]
+pub fn prove_lookup(witness: &[F], table: &[F]) -> Result<LookupProof, LookupError> {
+    let positions: BTreeMap<F, usize> = table
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(position, &entry)| (entry, position))
+        .collect();
+    let mut multiplicities = vec![F::ZERO; table.len()];
+    for (index, entry) in witness.iter().enumerate() {
+        let position = positions
+            .get(entry)
+            .ok_or(LookupError::MissingEntry { index })?;
+        multiplicities[*position] += F::ONE;
+    }
+
+    let mut transcript = lookup_transcript(witness, table, &multiplicities);
+    let x = F::rand(&mut transcript);
+    let witness_proof = prove_fractional(
+        vec![F::ONE; witness.len()],
+        witness.iter().map(|&w| x + w).collect(),
+        &mut transcript,
+    );
+    let table_proof = prove_fractional(
+        multiplicities.clone(),
+        table.iter().map(|&t| x + t).collect(),
+        &mut transcript,
+    );
+    Ok(LookupProof {
+        multiplicities,
+        witness: witness_proof,
+        table: table_proof,
+    })
+}
+
+/// Verifies a proof produced by [`prove_lookup`].
+pub fn verify_lookup(witness: &[F], table: &[F], proof: &LookupProof) -> Result<(), LookupError> {
+    if proof.multiplicities.len() != table.len() {
+        return Err(LookupError::MultiplicityCountMismatch {
+            expected: table.len(),
+            got: proof.multiplicities.len(),
+        });
+    }
+    let (witness_proof, table_proof) = (&proof.witness, &proof.table);
+    if witness_proof.denominator.is_zero()
+        || table_proof.denominator.is_zero()
+        || witness_proof.numerator * table_proof.denominator
+            != table_proof.numerator * witness_proof.denominator
+    {
+        return Err(LookupError::SumMismatch);
+    }
+
+    let mut transcript = lookup_transcript(witness, table, &proof.multiplicities);
+    let x = F::rand(&mut transcript);
+    let (point, p, q) = verify_fractional(num_vars(witness), witness_proof, &mut transcript)?;
+    if p != F::ONE || q != x + evaluate_table(witness, &point) {
+        return Err(LookupError::FinalCheckFailed);
+    }
+    let (point, p, q) = verify_fractional(num_vars(table), table_proof, &mut transcript)?;
+    if p != evaluate_table(&proof.multiplicities, &point) || q != x + evaluate_table(table, &point)
+    {
+        return Err(LookupError::FinalCheckFailed);
+    }
+    Ok(())
+}
+
+fn num_vars(table: &[F]) -> usize {
+    assert!(
+        table.len().is_power_of_two(),
+        "the table must cover a hypercube"
+    );
+    table.len().trailing_zeros() as usize
+}
+
+fn lookup_transcript(witness: &[F], table: &[F], multiplicities: &[F]) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"witness", witness);
+    transcript.append_field_elements(b"table", table);
+    transcript.append_field_elements(b"multiplicities", multiplicities);
+    transcript
+}
+
+/// Proves the sum of the fractions 'numerators' / 'denominators' over the hypercube.
+#[cfg(feature = "prover")]
+fn prove_fractional(
+    numerators: EvalTable,
+    denominators: EvalTable,
+    transcript: &mut Transcript,
+) -> FractionalProof {
+    let num_vars = num_vars(&numerators);
+    // layers[i] holds P_i and Q_i, on i variables.
+    let mut layers = vec![(numerators, denominators)];
+    while layers.last().unwrap().0.len() > 1 {
+        let (p, q) = layers.last().unwrap();
+        let next = (0..p.len() / 2)
+            .map(|x| {
+                let (p0, p1, q0, q1) = (p[2 * x], p[2 * x + 1], q[2 * x], q[2 * x + 1]);
+                (p0 * q1 + p1 * q0, q0 * q1)
+            })
+            .unzip();
+        layers.push(next);
+    }
+    layers.reverse();
+
+    let (numerator, denominator) = (layers[0].0[0], layers[0].1[0]);
+    transcript.append_field_elements(b"fraction", &[numerator, denominator]);
+    let mut point = Vec::new();
+    let mut layer_proofs = Vec::with_capacity(num_vars);
+    for (layer, (p, q)) in layers.iter().enumerate().skip(1) {
+        let mu = F::rand(transcript);
+        let halves = |table: &EvalTable, bit: usize| -> EvalTable {
+            table.iter().skip(bit).step_by(2).copied().collect()
+        };
+        let (p0, p1, q0, q1) = (halves(p, 0), halves(p, 1), halves(q, 0), halves(q, 1));
+        let eq = eq_extension(&point);
+        let eq_mu: EvalTable = eq.iter().map(|&e| mu * e).collect();
+        let mut poly = VirtualPolynomial::new(layer - 1);
+        let [eq, eq_mu, p0_mle, p1_mle, q0_mle, q1_mle] = [&eq, &eq_mu, &p0, &p1, &q0, &q1]
+            .map(|table| poly.add_mle(Factor::from_table(table.clone())));
+        poly.add_product(&[eq, p0_mle, q1_mle]);
+        poly.add_product(&[eq, p1_mle, q0_mle]);
+        poly.add_product(&[eq_mu, q0_mle, q1_mle]);
+        let mut prover = SumCheckProver::from_virtual(poly);
+        let mut round_messages = Vec::with_capacity(layer - 1);
+        let mut challenges = Vec::with_capacity(layer);
+        for _ in 0..layer - 1 {
+            let message = prover.next_message(challenges.last().copied()).evaluations;
+            transcript.append_field_elements(b"round", &message);
+            challenges.push(F::rand(transcript));
+            round_messages.push(message);
+        }
+        let [p0, p1, q0, q1] = [&p0, &p1, &q0, &q1].map(|t| evaluate_table(t, &challenges));
+        transcript.append_field_elements(b"layer_evaluations", &[p0, p1, q0, q1]);
+        challenges.push(F::rand(transcript));
+        point = challenges;
+        layer_proofs.push(FractionLayerProof {
+            round_messages,
+            numerators: (p0, p1),
+            denominators: (q0, q1),
+        });
+    }
+    FractionalProof {
+        numerator,
+        denominator,
+        layers: layer_proofs,
+    }
+}
+
+/// Verifies a fractional sum-check over 'num_vars' variables, and returns the point of the final
+/// claims along with the claimed evaluations of the numerators and the denominators there.
+fn verify_fractional(
+    num_vars: usize,
+    proof: &FractionalProof,
+    transcript: &mut Transcript,
+) -> Result<(Vec<F>, F, F), LookupError> {
+    if proof.layers.len() != num_vars {
+        return Err(LookupError::LayerCountMismatch {
+            expected: num_vars,
+            got: proof.layers.len(),
+        });
+    }
+    transcript.append_field_elements(b"fraction", &[proof.numerator, proof.denominator]);
+    let (mut p, mut q) = (proof.numerator, proof.denominator);
+    let mut point = Vec::new();
+    for (layer, layer_proof) in proof.layers.iter().enumerate() {
+        let mu = F::rand(transcript);
+        let mut state =
+            Verifier::initialize_committed_with_rng(layer, 3, p + mu * q, transcript.clone());
+        for message in &layer_proof.round_messages {
+            state.rng.append_field_elements(b"round", message);
+            (_, state) = Verifier::round(state, message.clone())
+                .map_err(|source| LookupError::SumCheck { layer, source })?;
+        }
+        if state.randomness.len() != layer {
+            let source = SumCheckError::VariableCountMismatch {
+                expected: layer,
+                got: state.randomness.len(),
+            };
+            return Err(LookupError::SumCheck { layer, source });
+        }
+        let ((p0, p1), (q0, q1)) = (layer_proof.numerators, layer_proof.denominators);
+        let combined = p0 * q1 + p1 * q0 + mu * q0 * q1;
+        if eq_eval(&point, &state.randomness) * combined != state.running_eval {
+            return Err(LookupError::LayerCheckFailed { layer });
+        }
+        *transcript = state.rng;
+        transcript.append_field_elements(b"layer_evaluations", &[p0, p1, q0, q1]);
+        let lambda = F::rand(transcript);
+        point = state.randomness;
+        point.push(lambda);
+        p = p0 + lambda * (p1 - p0);
+        q = q0 + lambda * (q1 - q0);
+    }
+    Ok((point, p, q))
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+
+    fn table() -> EvalTable {
+        (0..8u64).map(|v| F::from(3 * v + 1)).collect()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let witness: EvalTable = [1, 4, 4, 22, 1, 1, 13, 16, 7, 7, 22, 4, 1, 1, 10, 10]
+            .map(|v: u64| F::from(v))
+            .to_vec();
+        let proof = prove_lookup(&witness, &table()).unwrap();
+        assert_eq!(proof.multiplicities[0], F::from(5));
+        assert_eq!(proof.multiplicities[6], F::from(0));
+        assert_eq!(verify_lookup(&witness, &table(), &proof), Ok(()));
+
+        // A single lookup into a single entry.
+        let proof = prove_lookup(&[F::from(5)], &[F::from(5)]).unwrap();
+        assert_eq!(verify_lookup(&[F::from(5)], &[F::from(5)], &proof), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_entry() {
+        let witness = [F::from(1), F::from(2)];
+        assert_eq!(
+            prove_lookup(&witness, &table()),
+            Err(LookupError::MissingEntry { index: 1 })
+        );
+    }
+
+    #[test]
+    fn test_wrong_proofs() {
+        let witness: EvalTable = [1, 4, 4, 22].map(|v: u64| F::from(v)).to_vec();
+        let proof = prove_lookup(&witness, &table()).unwrap();
+
+        // Multiplicities which do not balance the witness.
+        let mut wrong = proof.clone();
+        wrong.multiplicities.swap(0, 1);
+        assert!(verify_lookup(&witness, &table(), &wrong).is_err());
+        let mut wrong = proof.clone();
+        wrong.table.layers[2].numerators.0 += F::from(1);
+        assert_eq!(
+            verify_lookup(&witness, &table(), &wrong),
+            Err(LookupError::LayerCheckFailed { layer: 2 })
+        );
+        // The same proof for a witness with an entry outside of the table.
+        let other: EvalTable = [1, 4, 5, 22].map(|v: u64| F::from(v)).to_vec();
+        assert!(verify_lookup(&other, &table(), &proof).is_err());
+    }
+}
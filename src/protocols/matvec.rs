@@ -0,0 +1,452 @@
+//! Matrix–vector product check: proves that y = M · x for a sparse matrix M, the core of the
+//! Spartan verification of R1CS instances.
+//!
+//! With M on m row variables and n column variables, the multilinear extensions satisfy
+//!
+//!   ỹ(r) = Σ_b M̃(r, b) · x̃(b)
+//!
+//! for any point r, which is drawn at random after y is fixed, and the sum-check over the n
+//! variables of b reduces the claim to M̃(r, ρ) · x̃(ρ) at a random point ρ. The verifier
+//! evaluates M̃(r, ρ) itself from the nonzero entries of M, in O(2^m + 2^n + k) operations for k
+//! entries, and is left with the evaluation x̃(ρ) to check against the commitment to x of
+//! [`commit_vector`], which the transcript absorbs before r is drawn, so that the prover cannot
+//! choose x after r.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::collections::BTreeMap;
+use ark_std::{vec, vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::commitment::{HashCommitment, TableCommitment};
+use crate::field::Field256 as F;
+use crate::polynomial::{
+    eq_extension, evaluate_table, pad_to_num_vars, EvalTable, PolynomialDescription,
+};
+#[cfg(feature = "prover")]
+use crate::protocol::SumCheckProver;
+use crate::protocol::{SumCheckError, Transcript, Verifier};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"matvec";
+
+/// A matrix by its nonzero entries, with its numbers of rows and columns padded to powers of two
+/// so that it is the table of a multilinear polynomial M̃(row, column) on the hypercube. The row
+/// variables come first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseMatrix {
+    row_vars: usize,
+    column_vars: usize,
+    entries: Vec<(usize, usize, F)>,
+}
+
+impl SparseMatrix {
+    /// The matrix of 'num_rows' rows and 'num_columns' columns with the entries (row, column,
+    /// value) of 'entries', and zeros elsewhere. The entries at the same position add up.
+    pub fn from_triplets(
+        num_rows: usize,
+        num_columns: usize,
+        entries: Vec<(usize, usize, F)>,
+    ) -> Self {
+        assert!(
+            entries
+                .iter()
+                .all(|&(row, column, _)| row < num_rows && column < num_columns),
+            "the entries must be in a {num_rows}x{num_columns} matrix"
+        );
+        SparseMatrix {
+            row_vars: num_rows.next_power_of_two().trailing_zeros() as usize,
+            column_vars: num_columns.next_power_of_two().trailing_zeros() as usize,
+            entries: entries
+                .into_iter()
+                .filter(|(_, _, value)| *value != F::from(0))
+                .collect(),
+        }
+    }
+
+    /// The matrix of the rows 'rows', which must have the same length.
+    pub fn from_dense(rows: &[Vec<F>]) -> Self {
+        let num_columns = rows.first().map_or(0, Vec::len);
+        assert!(
+            rows.iter().all(|row| row.len() == num_columns),
+            "the rows must have the same length"
+        );
+        let entries = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| row.iter().enumerate().map(move |(j, &value)| (i, j, value)))
+            .collect();
+        Self::from_triplets(rows.len(), num_columns, entries)
+    }
+
+    /// The matrix Σ_i c_i · M_i of the pairs (c_i, M_i) of 'terms', which must have the same
+    /// shape.
+    pub fn linear_combination(terms: &[(F, &SparseMatrix)]) -> Self {
+        let (row_vars, column_vars) = terms
+            .first()
+            .map_or((0, 0), |(_, m)| (m.row_vars, m.column_vars));
+        assert!(
+            terms
+                .iter()
+                .all(|(_, m)| (m.row_vars, m.column_vars) == (row_vars, column_vars)),
+            "the matrices must have the same shape"
+        );
+        let mut entries: BTreeMap<(usize, usize), F> = BTreeMap::new();
+        for &(coefficient, matrix) in terms {
+            for &(row, column, value) in &matrix.entries {
+                *entries.entry((row, column)).or_insert(F::from(0)) += coefficient * value;
+            }
+        }
+        SparseMatrix {
+            row_vars,
+            column_vars,
+            entries: entries
+                .into_iter()
+                .filter(|(_, value)| *value != F::from(0))
+                .map(|((row, column), value)| (row, column, value))
+                .collect(),
+        }
+    }
+
+    pub fn row_vars(&self) -> usize {
+        self.row_vars
+    }
+
+    pub fn column_vars(&self) -> usize {
+        self.column_vars
+    }
+
+    /// The nonzero entries (row, column, value).
+    pub fn entries(&self) -> &[(usize, usize, F)] {
+        &self.entries
+    }
+
+    /// The product M · 'x', with 'x' padded with zeros to the number of columns, as a table on the
+    /// row variables.
+    pub fn mul_vector(&self, x: &[F]) -> EvalTable {
+        assert!(
+            x.len() <= 1 << self.column_vars,
+            "the vector must have at most one entry per column"
+        );
+        let mut y = vec![F::from(0); 1 << self.row_vars];
+        for &(row, column, value) in &self.entries {
+            if let Some(&x_j) = x.get(column) {
+                y[row] += value * x_j;
+            }
+        }
+        y
+    }
+
+    /// The table of M̃('r', ·) on the column variables, Σ_i eq('r', i) · M[i][·], built from the
+    /// table of eq(·, 'r') in O(2^m + k) operations.
+    pub fn bind_rows(&self, r: &[F]) -> EvalTable {
+        assert_eq!(
+            r.len(),
+            self.row_vars,
+            "the point must be on the row variables"
+        );
+        let eq = eq_extension(r);
+        let mut table = vec![F::from(0); 1 << self.column_vars];
+        for &(row, column, value) in &self.entries {
+            table[column] += eq[row] * value;
+        }
+        table
+    }
+
+    /// M̃('r', 's'), at a point on the row variables and a point on the column variables.
+    pub fn evaluate(&self, r: &[F], s: &[F]) -> F {
+        assert_eq!(
+            r.len(),
+            self.row_vars,
+            "the point must be on the row variables"
+        );
+        assert_eq!(
+            s.len(),
+            self.column_vars,
+            "the point must be on the column variables"
+        );
+        let (eq_r, eq_s) = (eq_extension(r), eq_extension(s));
+        self.entries
+            .iter()
+            .map(|&(row, column, value)| eq_r[row] * eq_s[column] * value)
+            .sum()
+    }
+}
+
+/// A non-interactive proof that y = M · x, with the evaluation of x̃ at the random point of the
+/// sum-check.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MatVecProof {
+    pub round_messages: Vec<PolynomialDescription>,
+    pub x_evaluation: F,
+}
+
+/// What remains to check after a successful [`verify_matvec`]: x̃ evaluates to 'x_evaluation' at
+/// 'point'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatVecSubclaim {
+    pub point: Vec<F>,
+    pub x_evaluation: F,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MatVecError {
+    #[error("expected a vector of {expected} entries, got {got}")]
+    LengthMismatch { expected: usize, got: usize },
+    #[error(transparent)]
+    SumCheck(#[from] SumCheckError),
+    /// The evaluation of x̃ is inconsistent with the last sum-check round.
+    #[error("the evaluation of the vector does not match the sum-check")]
+    FinalCheckFailed,
+}
+
+/// The commitment to 'x', padded with zeros to the number of columns of 'matrix', which the
+/// verifier of [`prove_matvec`] holds.
+pub fn commit_vector(matrix: &SparseMatrix, x: &[F]) -> TableCommitment {
+    HashCommitment::commit_table(&pad_to_num_vars(x.to_vec(), matrix.column_vars))
+}
+
+/// Proves the product of 'matrix' and 'x', padded with zeros to the number of columns, and
+/// returns it with its proof.
+#[cfg(feature = "prover")]
+pub fn prove_matvec(matrix: &SparseMatrix, x: &[F]) -> (EvalTable, MatVecProof) {
+    let y = matrix.mul_vector(x);
+    let mut transcript = matvec_transcript(matrix, &y, &commit_vector(matrix, x));
+    let r: Vec<F> = (0..matrix.row_vars)
+        .map(|_| F::rand(&mut transcript))
+        .collect();
+    let proof = prove_matvec_claim(matrix, x, &r, &mut transcript);
+    (y, proof)
+}
+
+/// Verifies a proof produced by [`prove_matvec`] that 'y' is the product of 'matrix' with the
+/// vector of commitment 'x', and returns the evaluation of the vector left to check against it.
+pub fn verify_matvec(
+    matrix: &SparseMatrix,
+    y: &[F],
+    x: &TableCommitment,
+    proof: &MatVecProof,
+) -> Result<MatVecSubclaim, MatVecError> {
+    if y.len() != 1 << matrix.row_vars {
+        return Err(MatVecError::LengthMismatch {
+            expected: 1 << matrix.row_vars,
+            got: y.len(),
+        });
+    }
+    let mut transcript = matvec_transcript(matrix, y, x);
+    let r: Vec<F> = (0..matrix.row_vars)
+        .map(|_| F::rand(&mut transcript))
+        .collect();
+    verify_matvec_claim(matrix, &r, evaluate_table(y, &r), proof, &mut transcript)
+}
+
+/// Proves the claim ỹ('r') = Σ_b M̃('r', b) · x̃(b) for y = 'matrix' · 'x', with the round messages
+/// absorbed by 'transcript', for protocols which reach a claim about ỹ at a point of their own.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_matvec_claim(
+    matrix: &SparseMatrix,
+    x: &[F],
+    r: &[F],
+    transcript: &mut Transcript,
+) -> MatVecProof {
+    let x = pad_to_num_vars(x.to_vec(), matrix.column_vars);
+    let num_vars = matrix.column_vars;
+    let mut prover = SumCheckProver::from_tables(num_vars, vec![matrix.bind_rows(r), x.clone()]);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    let mut challenges = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let message = prover.next_message(challenges.last().copied()).evaluations;
+        transcript.append_field_elements(b"round", &message);
+        challenges.push(F::rand(&mut *transcript));
+        round_messages.push(message);
+    }
+    MatVecProof {
+        round_messages,
+        x_evaluation: evaluate_table(&x, &challenges),
+    }
+}
+
+/// Verifies a proof produced by [`prove_matvec_claim`] that ỹ('r') = 'claim'.
+pub(crate) fn verify_matvec_claim(
+    matrix: &SparseMatrix,
+    r: &[F],
+    claim: F,
+    proof: &MatVecProof,
+    transcript: &mut Transcript,
+) -> Result<MatVecSubclaim, MatVecError> {
+    let num_vars = matrix.column_vars;
+    let mut state = Verifier::initialize_committed_with_rng(num_vars, 2, claim, &mut *transcript);
+    for message in &proof.round_messages {
+        state.rng.append_field_elements(b"round", message);
+        (_, state) = Verifier::round(state, message.clone())?;
+    }
+    if state.randomness.len() != num_vars {
+        return Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: state.randomness.len(),
+        }
+        .into());
+    }
+    if matrix.evaluate(r, &state.randomness) * proof.x_evaluation != state.running_eval {
+        return Err(MatVecError::FinalCheckFailed);
+    }
+    Ok(MatVecSubclaim {
+        point: state.randomness,
+        x_evaluation: proof.x_evaluation,
+    })
+}
+
+/// The transcript after the statement: the matrix, the product and the commitment to the vector.
+fn matvec_transcript(matrix: &SparseMatrix, y: &[F], x: &TableCommitment) -> Transcript {
+    let mut transcript = product_transcript(matrix, y);
+    transcript.append_message(b"vector", x);
+    transcript
+}
+
+/// The transcript after the shape and the entries of the matrix, and the product.
+fn product_transcript(matrix: &SparseMatrix, y: &[F]) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(
+        b"shape",
+        &[
+            F::from(matrix.row_vars as u64),
+            F::from(matrix.column_vars as u64),
+            F::from(matrix.entries.len() as u64),
+        ],
+    );
+    let entries: Vec<F> = matrix
+        .entries
+        .iter()
+        .flat_map(|&(row, column, value)| [F::from(row as u64), F::from(column as u64), value])
+        .collect();
+    transcript.append_field_elements(b"matrix", &entries);
+    transcript.append_field_elements(b"product", y);
+    transcript
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn matrix() -> SparseMatrix {
+        SparseMatrix::from_triplets(
+            3,
+            5,
+            vec![
+                (0, 0, F::from(2)),
+                (0, 4, F::from(1)),
+                (1, 2, F::from(7)),
+                (2, 1, -F::from(3)),
+                (2, 3, F::from(5)),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_sparse_matrix() {
+        let m = matrix();
+        assert_eq!((m.row_vars(), m.column_vars()), (2, 3));
+        let x: Vec<F> = (1..=5).map(F::from).collect();
+        let y = m.mul_vector(&x);
+        assert_eq!(y, vec![F::from(7), F::from(21), F::from(14), F::from(0)]);
+        let dense: Vec<Vec<F>> = (0..3)
+            .map(|i| {
+                (0..5)
+                    .map(|j| {
+                        m.entries()
+                            .iter()
+                            .find(|&&(row, column, _)| (row, column) == (i, j))
+                            .map_or(F::from(0), |&(_, _, value)| value)
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(SparseMatrix::from_dense(&dense), m);
+
+        let doubled = SparseMatrix::linear_combination(&[(F::from(3), &m), (-F::from(1), &m)]);
+        assert_eq!(
+            doubled.mul_vector(&x),
+            y.iter().map(|&v| v + v).collect::<Vec<_>>()
+        );
+        assert!(
+            SparseMatrix::linear_combination(&[(F::from(1), &m), (-F::from(1), &m)])
+                .entries()
+                .is_empty()
+        );
+
+        // M̃(r, ·) is the row combination of eq(·, r), and M̃(r, s) its extension at s.
+        let mut rng = StdRng::seed_from_u64(0);
+        let r = vec![F::rand(&mut rng), F::rand(&mut rng)];
+        let s = vec![F::rand(&mut rng), F::rand(&mut rng), F::rand(&mut rng)];
+        assert_eq!(evaluate_table(&m.bind_rows(&r), &s), m.evaluate(&r, &s));
+    }
+
+    #[test]
+    fn test_matvec() {
+        let m = matrix();
+        let x: Vec<F> = (1..=5).map(F::from).collect();
+        let (y, proof) = prove_matvec(&m, &x);
+        let commitment = commit_vector(&m, &x);
+        let subclaim = verify_matvec(&m, &y, &commitment, &proof).unwrap();
+        let padded = pad_to_num_vars(x.clone(), 3);
+        assert_eq!(
+            evaluate_table(&padded, &subclaim.point),
+            subclaim.x_evaluation
+        );
+
+        let mut wrong = y.clone();
+        wrong[1] += F::from(1);
+        assert!(verify_matvec(&m, &wrong, &commitment, &proof).is_err());
+        let mut other = x.clone();
+        other[0] += F::from(1);
+        assert!(verify_matvec(&m, &y, &commit_vector(&m, &other), &proof).is_err());
+        assert_eq!(
+            verify_matvec(&m, &y[..2], &commitment, &proof),
+            Err(MatVecError::LengthMismatch {
+                expected: 4,
+                got: 2
+            })
+        );
+        let mut wrong = proof.clone();
+        wrong.x_evaluation += F::from(1);
+        assert_eq!(
+            verify_matvec(&m, &y, &commitment, &wrong),
+            Err(MatVecError::FinalCheckFailed)
+        );
+        let mut wrong = proof;
+        wrong.round_messages.pop();
+        assert!(matches!(
+            verify_matvec(&m, &y, &commitment, &wrong),
+            Err(MatVecError::SumCheck(_))
+        ));
+    }
+
+    #[test]
+    fn test_vector_chosen_for_r() {
+        // r as drawn before the commitment to the vector, and a vector x' with M x' ≠ y but
+        // Σ_b M̃(r, b) · x̃'(b) = ỹ(r), moving weight between two columns of M̃(r, ·).
+        let m = matrix();
+        let x: Vec<F> = (1..=5).map(F::from).collect();
+        let y = m.mul_vector(&x);
+        let mut transcript = product_transcript(&m, &y);
+        let r: Vec<F> = (0..m.row_vars()).map(|_| F::rand(&mut transcript)).collect();
+        let row = m.bind_rows(&r);
+        let mut forged = x.clone();
+        forged[1] += row[2];
+        forged[2] -= row[1];
+        assert_ne!(m.mul_vector(&forged), y);
+        let padded = pad_to_num_vars(forged.clone(), 3);
+        assert_eq!(
+            row.iter().zip(&padded).map(|(a, b)| *a * b).sum::<F>(),
+            evaluate_table(&y, &r)
+        );
+
+        // The proof of the honest prover for x' and the commitment to x'.
+        let commitment = commit_vector(&m, &forged);
+        let mut transcript = matvec_transcript(&m, &y, &commitment);
+        let r: Vec<F> = (0..m.row_vars()).map(|_| F::rand(&mut transcript)).collect();
+        let proof = prove_matvec_claim(&m, &forged, &r, &mut transcript);
+        assert!(verify_matvec(&m, &y, &commitment, &proof).is_err());
+    }
+}
@@ -0,0 +1,239 @@
+//! Grand-product check: proves that the product of the entries of a table over the hypercube is
+//! the claimed one, e.g. to compare the products of two columns in a permutation argument.
+//!
+//! The layers of a binary tree of products, V_n = f down to V_0 = Π_b f(b), are given by
+//! V_i(x) = V_{i+1}(x, 0) · V_{i+1}(x, 1). A claim about V_i at a point r is reduced to claims
+//! about V_{i+1} at (ρ, 0) and (ρ, 1) by the sum-check of
+//!
+//!   V_i(r) = Σ_x eq(r, x) · V_{i+1}(x, 0) · V_{i+1}(x, 1)
+//!
+//! over the i variables of x, ending at the random point ρ. The two claims are then merged into
+//! one about V_{i+1} at (ρ, λ) for a random λ. The verifier checks the last claim, about f, itself.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "prover")]
+use ark_std::vec;
+use ark_std::{vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{eq_eval, evaluate_table, PolynomialDescription};
+#[cfg(feature = "prover")]
+use crate::polynomial::{eq_extension, EvalTable};
+#[cfg(feature = "prover")]
+use crate::protocol::SumCheckProver;
+use crate::protocol::{SumCheckError, Transcript, Verifier};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"product-check";
+
+/// The sum-check reducing a layer to the next one, with the evaluations V_{i+1}(ρ, 0) and
+/// V_{i+1}(ρ, 1) at its random point ρ.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct LayerProof {
+    pub round_messages: Vec<PolynomialDescription>,
+    pub left: F,
+    pub right: F,
+}
+
+/// A non-interactive proof that the entries of a table multiply to 'claimed_product', with one
+/// layer proof per variable.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProductProof {
+    pub claimed_product: F,
+    pub layers: Vec<LayerProof>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ProductCheckError {
+    #[error("expected {expected} layers, got {got}")]
+    LayerCountMismatch { expected: usize, got: usize },
+    #[error("layer {layer}: {source}")]
+    SumCheck {
+        layer: usize,
+        #[source]
+        source: SumCheckError,
+    },
+    /// The evaluations of the next layer are inconsistent with the last sum-check round.
+    #[error("layer {layer}: the evaluations of the next layer do not match the sum-check")]
+    LayerCheckFailed { layer: usize },
+    /// The last claim does not match the multilinear extension of the table.
+    #[error("the final claim does not match the table")]
+    FinalCheckFailed,
+}
+
+/// Proves the product of the entries of 'table', whose length must be a power of two.
+#[cfg(feature = "prover")]
+pub fn prove_product(table: &[F]) -> ProductProof {
+    assert!(
+        table.len().is_power_of_two(),
+        "the table must cover a hypercube"
+    );
+    let num_vars = table.len().trailing_zeros() as usize;
+    // layers[i] is V_i, on i variables.
+    let mut layers: Vec<EvalTable> = vec![table.to_vec()];
+    while layers.last().unwrap().len() > 1 {
+        let next = layers
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| pair[0] * pair[1])
+            .collect();
+        layers.push(next);
+    }
+    layers.reverse();
+
+    let claimed_product = layers[0][0];
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"claimed_product", &[claimed_product]);
+    let mut point = Vec::new();
+    let mut layer_proofs = Vec::with_capacity(num_vars);
+    for (layer, next) in layers.iter().enumerate().skip(1) {
+        let left: EvalTable = next.iter().step_by(2).copied().collect();
+        let right: EvalTable = next.iter().skip(1).step_by(2).copied().collect();
+        let tables = vec![eq_extension(&point), left.clone(), right.clone()];
+        let mut prover = SumCheckProver::from_tables(layer - 1, tables);
+        let mut round_messages = Vec::with_capacity(layer - 1);
+        let mut challenges = Vec::with_capacity(layer);
+        for _ in 0..layer - 1 {
+            let message = prover.next_message(challenges.last().copied()).evaluations;
+            transcript.append_field_elements(b"round", &message);
+            challenges.push(F::rand(&mut transcript));
+            round_messages.push(message);
+        }
+        let (left, right) = (
+            evaluate_table(&left, &challenges),
+            evaluate_table(&right, &challenges),
+        );
+        transcript.append_field_elements(b"layer_evaluations", &[left, right]);
+        challenges.push(F::rand(&mut transcript));
+        point = challenges;
+        layer_proofs.push(LayerProof {
+            round_messages,
+            left,
+            right,
+        });
+    }
+    ProductProof {
+        claimed_product,
+        layers: layer_proofs,
+    }
+}
+
+/// Verifies a proof produced by [`prove_product`] for 'table', and returns the point of the final
+/// check.
+pub fn verify_product(table: &[F], proof: &ProductProof) -> Result<Vec<F>, ProductCheckError> {
+    assert!(
+        table.len().is_power_of_two(),
+        "the table must cover a hypercube"
+    );
+    let num_vars = table.len().trailing_zeros() as usize;
+    if proof.layers.len() != num_vars {
+        return Err(ProductCheckError::LayerCountMismatch {
+            expected: num_vars,
+            got: proof.layers.len(),
+        });
+    }
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(b"claimed_product", &[proof.claimed_product]);
+    let mut claim = proof.claimed_product;
+    let mut point = Vec::new();
+    for (layer, layer_proof) in proof.layers.iter().enumerate() {
+        let mut state = Verifier::initialize_committed_with_rng(layer, 3, claim, transcript);
+        for message in &layer_proof.round_messages {
+            state.rng.append_field_elements(b"round", message);
+            (_, state) = Verifier::round(state, message.clone())
+                .map_err(|source| ProductCheckError::SumCheck { layer, source })?;
+        }
+        if state.randomness.len() != layer {
+            let source = SumCheckError::VariableCountMismatch {
+                expected: layer,
+                got: state.randomness.len(),
+            };
+            return Err(ProductCheckError::SumCheck { layer, source });
+        }
+        let (left, right) = (layer_proof.left, layer_proof.right);
+        if eq_eval(&point, &state.randomness) * left * right != state.running_eval {
+            return Err(ProductCheckError::LayerCheckFailed { layer });
+        }
+        transcript = state.rng;
+        transcript.append_field_elements(b"layer_evaluations", &[left, right]);
+        let lambda = F::rand(&mut transcript);
+        point = state.randomness;
+        point.push(lambda);
+        claim = left + lambda * (right - left);
+    }
+    if evaluate_table(table, &point) != claim {
+        return Err(ProductCheckError::FinalCheckFailed);
+    }
+    Ok(point)
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn random_table(num_vars: usize, seed: u64) -> EvalTable {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..1 << num_vars).map(|_| F::rand(&mut rng)).collect()
+    }
+
+    #[test]
+    fn test_prove_product() {
+        for num_vars in 0..5 {
+            let table = random_table(num_vars, num_vars as u64);
+            let proof = prove_product(&table);
+            assert_eq!(proof.claimed_product, table.iter().product::<F>());
+            assert_eq!(proof.layers.len(), num_vars);
+            assert_eq!(verify_product(&table, &proof).unwrap().len(), num_vars);
+        }
+    }
+
+    #[test]
+    fn test_permutation() {
+        // The columns a and b are permutations of each other iff Π (γ + a_i) = Π (γ + b_i) for a
+        // random γ, with high probability.
+        let gamma = F::from(1234567);
+        let a: EvalTable = (1..=8u64).map(|v| gamma + F::from(v)).collect();
+        let b: EvalTable = [3, 1, 4, 8, 5, 2, 7, 6]
+            .map(|v: u64| gamma + F::from(v))
+            .to_vec();
+        let (proof_a, proof_b) = (prove_product(&a), prove_product(&b));
+        assert!(verify_product(&a, &proof_a).is_ok());
+        assert!(verify_product(&b, &proof_b).is_ok());
+        assert_eq!(proof_a.claimed_product, proof_b.claimed_product);
+    }
+
+    #[test]
+    fn test_wrong_proofs() {
+        let table = random_table(3, 7);
+        let proof = prove_product(&table);
+
+        let mut wrong = proof.clone();
+        wrong.claimed_product += F::from(1);
+        assert_eq!(
+            verify_product(&table, &wrong),
+            Err(ProductCheckError::LayerCheckFailed { layer: 0 })
+        );
+        let mut wrong = proof.clone();
+        wrong.layers[2].right += F::from(1);
+        assert_eq!(
+            verify_product(&table, &wrong),
+            Err(ProductCheckError::LayerCheckFailed { layer: 2 })
+        );
+        let mut wrong = proof.clone();
+        wrong.layers[2].round_messages[0][0] += F::from(1);
+        assert!(matches!(
+            verify_product(&table, &wrong),
+            Err(ProductCheckError::SumCheck { layer: 2, .. })
+        ));
+        let mut other = table.clone();
+        other[5] += F::from(1);
+        assert_eq!(
+            verify_product(&other, &proof),
+            Err(ProductCheckError::FinalCheckFailed)
+        );
+    }
+}
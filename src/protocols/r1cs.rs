@@ -0,0 +1,373 @@
+//! R1CS satisfiability: proves that an assignment z satisfies (A·z) ∘ (B·z) = C·z for the
+//! constraint matrices of an `ark-relations` constraint system, in the manner of Spartan but
+//! without a polynomial commitment.
+//!
+//! The outer [`zerocheck`](crate::protocols::zerocheck) proves that Az · Bz − Cz vanishes on the
+//! hypercube of the constraints, and ends at a random point ρ with the claimed evaluations of the
+//! multilinear extensions of Az, Bz and Cz. The three claims are merged with a random γ into the
+//! single [`matvec`](crate::protocols::matvec) claim
+//!
+//!   Ãz(ρ) + γ · B̃z(ρ) + γ² · C̃z(ρ) = Σ_b (Ã + γ · B̃ + γ² · C̃)(ρ, b) · z̃(b)
+//!
+//! over the variables of z, whose matrix the verifier evaluates itself. The verifier is left with
+//! the evaluation of z̃ at the last random point, which a SNARK checks against a commitment to the
+//! witness, and which [`R1csSubclaim::check`] checks against the whole assignment.
+//!
+//! The transcript absorbs the matrices, the public inputs and the commitment to the witness of
+//! [`commit_witness`] before the point of the zero-check is drawn, so that the prover cannot choose
+//! a witness which only satisfies the constraints combined at a point it knows in advance.
+
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "prover")]
+use ark_std::vec;
+use ark_std::{vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::commitment::{HashCommitment, TableCommitment};
+use crate::field::Field256 as F;
+use crate::polynomial::{evaluate_table, pad_to_num_vars, PolynomialDescription};
+use crate::protocol::{SumCheckError, Transcript};
+#[cfg(feature = "prover")]
+use crate::protocols::matvec::prove_matvec_claim;
+use crate::protocols::matvec::{verify_matvec_claim, MatVecError, MatVecProof, SparseMatrix};
+#[cfg(feature = "prover")]
+use crate::protocols::zerocheck::prove_zero_rounds;
+use crate::protocols::zerocheck::verify_zero_rounds;
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"r1cs";
+
+/// The constraint matrices of an R1CS instance, on the variables (1, public inputs, witness).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csInstance {
+    pub a: SparseMatrix,
+    pub b: SparseMatrix,
+    pub c: SparseMatrix,
+    /// Number of public variables, including the constant 1.
+    pub num_instance_variables: usize,
+}
+
+/// A non-interactive proof that an assignment satisfies an [`R1csInstance`]: the outer zero-check
+/// with the evaluations of Az, Bz and Cz at its random point, and the inner matrix–vector check.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct R1csProof {
+    pub outer_messages: Vec<PolynomialDescription>,
+    pub az: F,
+    pub bz: F,
+    pub cz: F,
+    pub inner: MatVecProof,
+}
+
+/// What remains to check after a successful [`verify_r1cs`]: z̃ evaluates to 'z_evaluation' at
+/// 'point'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct R1csSubclaim {
+    pub point: Vec<F>,
+    pub z_evaluation: F,
+}
+
+impl R1csSubclaim {
+    /// Checks the subclaim against the whole assignment 'z', as returned by [`assignment`].
+    pub fn check(&self, z: &[F]) -> bool {
+        z.len() <= 1 << self.point.len()
+            && evaluate_table(&pad_to_num_vars(z.to_vec(), self.point.len()), &self.point)
+                == self.z_evaluation
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum R1csError {
+    #[error("expected {expected} public inputs, got {got}")]
+    PublicInputCountMismatch { expected: usize, got: usize },
+    #[error("the constraint system has no matrices, or is not finalized")]
+    MissingMatrices,
+    #[error("the constraint system has no assignment: {0}")]
+    MissingAssignment(SynthesisError),
+    #[error("outer zero-check: {0}")]
+    Outer(#[from] SumCheckError),
+    /// The evaluations of Az, Bz and Cz are inconsistent with the last round of the zero-check.
+    #[error("the evaluations of Az, Bz and Cz do not match the zero-check")]
+    OuterCheckFailed,
+    #[error("inner matrix-vector check: {0}")]
+    Inner(#[from] MatVecError),
+}
+
+impl R1csInstance {
+    /// The matrices of 'cs', which must be in setup mode or construct its matrices while proving.
+    /// Finalizes 'cs' first.
+    pub fn from_constraint_system(cs: &ConstraintSystemRef<F>) -> Result<Self, R1csError> {
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or(R1csError::MissingMatrices)?;
+        let num_variables = matrices.num_instance_variables + matrices.num_witness_variables;
+        let matrix = |rows: &[Vec<(F, usize)>]| {
+            let entries = rows
+                .iter()
+                .enumerate()
+                .flat_map(|(i, row)| row.iter().map(move |&(value, j)| (i, j, value)))
+                .collect();
+            SparseMatrix::from_triplets(matrices.num_constraints, num_variables, entries)
+        };
+        Ok(R1csInstance {
+            a: matrix(&matrices.a),
+            b: matrix(&matrices.b),
+            c: matrix(&matrices.c),
+            num_instance_variables: matrices.num_instance_variables,
+        })
+    }
+
+    /// The combination A + γ · B + γ² · C of the inner check.
+    fn combine(&self, gamma: F) -> SparseMatrix {
+        SparseMatrix::linear_combination(&[
+            (F::from(1), &self.a),
+            (gamma, &self.b),
+            (gamma * gamma, &self.c),
+        ])
+    }
+}
+
+/// The assignment z = (1, public inputs, witness) of 'cs', in the order of the columns of the
+/// matrices.
+pub fn assignment(cs: &ConstraintSystemRef<F>) -> Result<Vec<F>, R1csError> {
+    let cs = cs
+        .borrow()
+        .ok_or(R1csError::MissingAssignment(SynthesisError::MissingCS))?;
+    if cs.witness_assignment.len() != cs.num_witness_variables {
+        return Err(R1csError::MissingAssignment(
+            SynthesisError::AssignmentMissing,
+        ));
+    }
+    let mut z = cs.instance_assignment.clone();
+    z.extend_from_slice(&cs.witness_assignment);
+    Ok(z)
+}
+
+/// The commitment to the witness part of the assignment 'z' of 'instance', after the constant 1
+/// and the public inputs, which the verifier holds.
+pub fn commit_witness(instance: &R1csInstance, z: &[F]) -> TableCommitment {
+    HashCommitment::commit_table(&z[instance.num_instance_variables..].to_vec())
+}
+
+/// Proves that the assignment 'z' satisfies 'instance'. Does not check it: the proof of an
+/// unsatisfying assignment is rejected by the verifier. The verifier holds the public inputs of
+/// 'z' and its [`commit_witness`].
+#[cfg(feature = "prover")]
+pub fn prove_r1cs(instance: &R1csInstance, z: &[F]) -> R1csProof {
+    let (az, bz, cz) = (
+        instance.a.mul_vector(z),
+        instance.b.mul_vector(z),
+        instance.c.mul_vector(z),
+    );
+    let minus_cz = cz.iter().map(|&v| -v).collect();
+    let public_inputs = &z[1..instance.num_instance_variables];
+    let mut transcript = r1cs_transcript(instance, public_inputs, &commit_witness(instance, z));
+    let (outer_messages, point) = prove_zero_rounds(
+        instance.a.row_vars(),
+        vec![vec![az.clone(), bz.clone()], vec![minus_cz]],
+        &mut transcript,
+    );
+    let (az, bz, cz) = (
+        evaluate_table(&az, &point),
+        evaluate_table(&bz, &point),
+        evaluate_table(&cz, &point),
+    );
+    transcript.append_field_elements(b"evaluations", &[az, bz, cz]);
+    let gamma = F::rand(&mut transcript);
+    let inner = prove_matvec_claim(&instance.combine(gamma), z, &point, &mut transcript);
+    R1csProof {
+        outer_messages,
+        az,
+        bz,
+        cz,
+        inner,
+    }
+}
+
+/// Verifies a proof produced by [`prove_r1cs`] for 'instance', with 'public_inputs' and the
+/// commitment 'witness' to the rest of the assignment, and returns the evaluation of the
+/// assignment left to check.
+pub fn verify_r1cs(
+    instance: &R1csInstance,
+    public_inputs: &[F],
+    witness: &TableCommitment,
+    proof: &R1csProof,
+) -> Result<R1csSubclaim, R1csError> {
+    let expected = instance.num_instance_variables - 1;
+    if public_inputs.len() != expected {
+        return Err(R1csError::PublicInputCountMismatch {
+            expected,
+            got: public_inputs.len(),
+        });
+    }
+    let mut transcript = r1cs_transcript(instance, public_inputs, witness);
+    let rounds = verify_zero_rounds(
+        instance.a.row_vars(),
+        3,
+        &proof.outer_messages,
+        &mut transcript,
+    )?;
+    if rounds.eq_at_point * (proof.az * proof.bz - proof.cz) != rounds.running_eval {
+        return Err(R1csError::OuterCheckFailed);
+    }
+    transcript.append_field_elements(b"evaluations", &[proof.az, proof.bz, proof.cz]);
+    let gamma = F::rand(&mut transcript);
+    let claim = proof.az + gamma * proof.bz + gamma * gamma * proof.cz;
+    let subclaim = verify_matvec_claim(
+        &instance.combine(gamma),
+        &rounds.point,
+        claim,
+        &proof.inner,
+        &mut transcript,
+    )?;
+    Ok(R1csSubclaim {
+        point: subclaim.point,
+        z_evaluation: subclaim.x_evaluation,
+    })
+}
+
+/// The transcript after the statement: the matrices, the public inputs and the commitment to the
+/// witness.
+fn r1cs_transcript(
+    instance: &R1csInstance,
+    public_inputs: &[F],
+    witness: &TableCommitment,
+) -> Transcript {
+    let mut transcript = matrices_transcript(instance);
+    transcript.append_field_elements(b"public_inputs", public_inputs);
+    transcript.append_message(b"witness", witness);
+    transcript
+}
+
+/// The transcript after the shape and the entries of the matrices.
+fn matrices_transcript(instance: &R1csInstance) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(
+        b"shape",
+        &[
+            F::from(instance.a.row_vars() as u64),
+            F::from(instance.a.column_vars() as u64),
+            F::from(instance.num_instance_variables as u64),
+        ],
+    );
+    for (label, matrix) in [
+        (b"a" as &[u8], &instance.a),
+        (b"b", &instance.b),
+        (b"c", &instance.c),
+    ] {
+        let entries: Vec<F> = matrix
+            .entries()
+            .iter()
+            .flat_map(|&(row, column, value)| [F::from(row as u64), F::from(column as u64), value])
+            .collect();
+        transcript.append_field_elements(label, &entries);
+    }
+    transcript
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use ark_relations::lc;
+    use ark_relations::r1cs::{ConstraintSystem, Variable};
+    use crate::polynomial::eq_extension;
+
+    /// Verifies 'proof' with the public inputs and the commitment to the witness of 'z'.
+    fn verify(instance: &R1csInstance, z: &[F], proof: &R1csProof) -> Result<R1csSubclaim, R1csError> {
+        let public_inputs = &z[1..instance.num_instance_variables];
+        verify_r1cs(instance, public_inputs, &commit_witness(instance, z), proof)
+    }
+
+    /// x³ + x + 5 = out, with the public output 'out' and the witness x, with 'x' as the value of
+    /// the witness.
+    fn cubic(x: u64, out: u64) -> ConstraintSystemRef<F> {
+        let cs = ConstraintSystem::<F>::new_ref();
+        let out = cs.new_input_variable(|| Ok(F::from(out))).unwrap();
+        let x_value = F::from(x);
+        let x = cs.new_witness_variable(|| Ok(x_value)).unwrap();
+        let x2 = cs.new_witness_variable(|| Ok(x_value * x_value)).unwrap();
+        let x3 = cs
+            .new_witness_variable(|| Ok(x_value * x_value * x_value))
+            .unwrap();
+        cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x2)
+            .unwrap();
+        cs.enforce_constraint(lc!() + x2, lc!() + x, lc!() + x3)
+            .unwrap();
+        cs.enforce_constraint(
+            lc!() + x3 + x + (F::from(5), Variable::One),
+            lc!() + Variable::One,
+            lc!() + out,
+        )
+        .unwrap();
+        cs
+    }
+
+    #[test]
+    fn test_r1cs() {
+        let cs = cubic(3, 35);
+        assert!(cs.is_satisfied().unwrap());
+        let instance = R1csInstance::from_constraint_system(&cs).unwrap();
+        assert_eq!(instance.num_instance_variables, 2);
+        assert_eq!((instance.a.row_vars(), instance.a.column_vars()), (2, 3));
+        let z = assignment(&cs).unwrap();
+        let proof = prove_r1cs(&instance, &z);
+        let subclaim = verify(&instance, &z, &proof).unwrap();
+        assert!(subclaim.check(&z));
+
+        let mut other = z.clone();
+        other[1] += F::from(1);
+        assert!(!subclaim.check(&other));
+    }
+
+    #[test]
+    fn test_unsatisfied_r1cs() {
+        let cs = cubic(3, 36);
+        assert!(!cs.is_satisfied().unwrap());
+        let instance = R1csInstance::from_constraint_system(&cs).unwrap();
+        let z = assignment(&cs).unwrap();
+        let proof = prove_r1cs(&instance, &z);
+        assert!(verify(&instance, &z, &proof).is_err());
+
+        let cs = cubic(3, 35);
+        let instance = R1csInstance::from_constraint_system(&cs).unwrap();
+        let z = assignment(&cs).unwrap();
+        let proof = prove_r1cs(&instance, &z);
+        let mut wrong = proof.clone();
+        wrong.cz += F::from(1);
+        assert_eq!(verify(&instance, &z, &wrong), Err(R1csError::OuterCheckFailed));
+        // The proof is bound to the public inputs and to the witness.
+        let witness = commit_witness(&instance, &z);
+        assert!(verify_r1cs(&instance, &[F::from(36)], &witness, &proof).is_err());
+        let mut other = z.clone();
+        other[2] += F::from(1);
+        assert!(verify_r1cs(&instance, &z[1..2], &commit_witness(&instance, &other), &proof).is_err());
+        assert_eq!(
+            verify_r1cs(&instance, &[], &witness, &proof),
+            Err(R1csError::PublicInputCountMismatch { expected: 1, got: 0 })
+        );
+    }
+
+    #[test]
+    fn test_witness_chosen_for_tau() {
+        // τ as drawn from the matrices alone, and a witness of x³ + x + 5 = 35 which breaks every
+        // constraint but whose residuals r_i = (Az ∘ Bz − Cz)_i cancel in Σ_i eq(τ, i) · r_i.
+        let cs = cubic(3, 35);
+        let instance = R1csInstance::from_constraint_system(&cs).unwrap();
+        let mut transcript = matrices_transcript(&instance);
+        let tau: Vec<F> = (0..2).map(|_| F::rand(&mut transcript)).collect();
+        let eq = eq_extension(&tau);
+        let (out, x) = (F::from(35), F::from(2));
+        let x2 = x * x + F::from(1);
+        // eq_0 · (x² − x2) + eq_1 · (x2 · x − x3) + eq_2 · (x3 + x + 5 − out) = 0, solved for x3.
+        let x3 = (eq[0] * (x * x - x2) + eq[1] * x2 * x + eq[2] * (x + F::from(5) - out))
+            / (eq[1] - eq[2]);
+        let z = vec![F::from(1), out, x, x2, x3];
+        let residual = |row: usize| {
+            instance.a.mul_vector(&z)[row] * instance.b.mul_vector(&z)[row]
+                - instance.c.mul_vector(&z)[row]
+        };
+        assert!((0..3).all(|row| residual(row) != F::from(0)));
+        assert_eq!((0..3).map(|row| eq[row] * residual(row)).sum::<F>(), F::from(0));
+        let proof = prove_r1cs(&instance, &z);
+        assert!(verify(&instance, &z, &proof).is_err());
+    }
+}
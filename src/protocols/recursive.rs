@@ -0,0 +1,401 @@
+//! Recursive sum-check: the round checks of a sum-check [`Proof`] arithmetized as the sum of a
+//! product of two multilinears over the bits of the transcript, so that a second sum-check attests
+//! that the first one verifies.
+//!
+//! The round messages p_0, …, p_{n−1} of the proof, each given by its evaluations at 0, 1, …, d_j,
+//! make the table M(j, k) = p_j(k) on the bits of the round j and of the node k, padded with zeros.
+//! With the challenges r_j, the verifier checks the residuals
+//!
+//!   c_j = p_j(0) + p_j(1) − p_{j−1}(r_{j−1}) = 0, for j = 0, …, n,
+//!
+//! where p_{−1}(r_{−1}) is the claimed sum, p_n(0) + p_n(1) stands for the evaluation of the
+//! polynomial at the random point, and each p_j(r_j) = Σ_k L_k(r_j) · p_j(k) is linear in the
+//! evaluations through the Lagrange basis L_k of the nodes. The prover commits to M with
+//! [`RoundCheckInstance::commit_messages`] before τ is drawn, so that the residuals are fixed
+//! before τ: unless they all vanish, Σ_j eq(τ, j) · c_j, multilinear in τ, vanishes only with
+//! probability at most the number of variables of τ over the size of the field. The verifier
+//! checks that
+//!
+//!   Σ_{j, k} W(j, k) · M(j, k) = eq(τ, 0) · claimed sum − eq(τ, n) · final evaluation,
+//!
+//! with the weights W(j, k) = eq(τ, j) · [k ∈ {0, 1}] − eq(τ, j + 1) · L_k(r_j) for j < n, and 0
+//! on the padding. The verifier of this instance computes W itself, and is left with the claim
+//! about M at the random point, to check against the commitment to the round messages.
+//!
+//! As with the [`SumCheckVerifierGadget`](crate::protocol::SumCheckVerifierGadget), only the round
+//! checks are arithmetized: the challenges r_j are replayed from the Fiat–Shamir transcript of the
+//! proof outside the instance, and the number of evaluations of each message is public, so that
+//! the caller checks it against the degrees of the polynomial.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{vec, vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::commitment::{HashCommitment, TableCommitment};
+use crate::field::Field256 as F;
+use crate::polynomial::{eq_extension, evaluate_table, EvalTable, PolynomialDescription};
+#[cfg(feature = "prover")]
+use crate::protocol::SumCheckProver;
+use crate::protocol::{
+    absorb_statement, auxiliary_node, EvaluationClaim, Proof, SumCheckError, Transcript, Verifier,
+    PROTOCOL_LABEL as SUM_CHECK_LABEL,
+};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"recursive-sum-check";
+
+/// The public part of the round checks of a sum-check proof: what the verifier of the instance
+/// needs to build the weights W.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundCheckInstance {
+    pub claimed_sum: F,
+    /// The evaluation of the polynomial at the random point of the proof.
+    pub final_eval: F,
+    /// The number of evaluations of each round message.
+    pub widths: Vec<usize>,
+    /// The challenges of the proof, replayed from its transcript.
+    pub challenges: Vec<F>,
+}
+
+/// A non-interactive proof that the round checks of a [`RoundCheckInstance`] hold, with the
+/// evaluation of the table of the round messages at the random point of the sum-check.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RecursiveProof {
+    pub round_messages: Vec<PolynomialDescription>,
+    pub evaluation: F,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RecursiveError {
+    /// A round message has fewer than the two evaluations at 0 and 1.
+    #[error("round {round}: the message has {got} evaluations, at least 2 are needed")]
+    MalformedMessage { round: usize, got: usize },
+    #[error(transparent)]
+    SumCheck(#[from] SumCheckError),
+    /// The evaluation of the table of the messages is inconsistent with the last sum-check round.
+    #[error("the evaluation of the round messages does not match the sum-check")]
+    FinalCheckFailed,
+}
+
+impl RoundCheckInstance {
+    /// The round checks of 'proof', whose polynomial evaluates to 'final_eval' at its random
+    /// point. The challenges are replayed from the transcript of [`prove`](crate::protocol::prove),
+    /// or of [`prove_with_auxiliary_node`](crate::protocol::prove_with_auxiliary_node) when the
+    /// proof carries auxiliary evaluations, which are left to the verifier of the proof to check.
+    pub fn new(proof: &Proof, final_eval: F) -> Result<Self, RecursiveError> {
+        if let Some((round, message)) =
+            proof.round_messages.iter().enumerate().find(|(_, m)| m.len() < 2)
+        {
+            return Err(RecursiveError::MalformedMessage {
+                round,
+                got: message.len(),
+            });
+        }
+        let auxiliary = &proof.auxiliary_evaluations;
+        if !auxiliary.is_empty() && auxiliary.len() != proof.round_messages.len() {
+            return Err(SumCheckError::AuxiliaryCountMismatch {
+                expected: proof.round_messages.len(),
+                got: auxiliary.len(),
+            }
+            .into());
+        }
+        let mut transcript = Transcript::new(SUM_CHECK_LABEL);
+        absorb_statement(&mut transcript, &proof.instance_hash, proof.claimed_sum);
+        if !auxiliary.is_empty() {
+            auxiliary_node(&mut transcript);
+        }
+        let challenges = proof
+            .round_messages
+            .iter()
+            .enumerate()
+            .map(|(round, message)| {
+                transcript.append_field_elements(b"round", message);
+                if let Some(evaluation) = auxiliary.get(round) {
+                    transcript.append_field_elements(b"auxiliary", &[*evaluation]);
+                }
+                F::rand(&mut transcript)
+            })
+            .collect();
+        Ok(RoundCheckInstance {
+            claimed_sum: proof.claimed_sum,
+            final_eval,
+            widths: proof.round_messages.iter().map(Vec::len).collect(),
+            challenges,
+        })
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        self.widths.len()
+    }
+
+    /// The number of variables of the round j, enough for the n + 1 residuals.
+    pub fn round_vars(&self) -> usize {
+        (self.num_rounds() + 1).next_power_of_two().trailing_zeros() as usize
+    }
+
+    /// The number of variables of the node k.
+    pub fn node_vars(&self) -> usize {
+        self.widths.iter().max().map_or(1, |w| w.next_power_of_two()).trailing_zeros() as usize
+    }
+
+    /// The number of variables of the instance, those of the round first.
+    pub fn num_vars(&self) -> usize {
+        self.round_vars() + self.node_vars()
+    }
+
+    /// The table M of the round messages of 'proof', which must be that of the instance.
+    pub fn messages_table(&self, proof: &Proof) -> EvalTable {
+        assert_eq!(
+            proof.round_messages.len(),
+            self.num_rounds(),
+            "the proof must be that of the instance"
+        );
+        let node_vars = self.node_vars();
+        let mut table = vec![F::from(0); 1 << self.num_vars()];
+        for (round, message) in proof.round_messages.iter().enumerate() {
+            table[round << node_vars..][..message.len()].copy_from_slice(message);
+        }
+        table
+    }
+
+    /// The commitment to the table M of the round messages of 'proof', which the verifier of
+    /// [`RoundCheckInstance::prove`] holds.
+    pub fn commit_messages(&self, proof: &Proof) -> TableCommitment {
+        HashCommitment::commit_table(&self.messages_table(proof))
+    }
+
+    /// The table of the weights W for the point τ.
+    pub fn weights(&self, tau: &[F]) -> EvalTable {
+        let node_vars = self.node_vars();
+        let eq = eq_extension(tau);
+        let mut table = vec![F::from(0); 1 << self.num_vars()];
+        for (round, (&width, &r)) in self.widths.iter().zip(&self.challenges).enumerate() {
+            let row = &mut table[round << node_vars..][..width];
+            row[0] = eq[round];
+            row[1] = eq[round];
+            for (node, weight) in row.iter_mut().enumerate() {
+                *weight -= eq[round + 1] * lagrange_basis(width, node, r);
+            }
+        }
+        table
+    }
+
+    /// The sum of W · M over the hypercube if the round checks hold.
+    pub fn target(&self, tau: &[F]) -> F {
+        let eq = eq_extension(tau);
+        eq[0] * self.claimed_sum - eq[self.num_rounds()] * self.final_eval
+    }
+
+    /// The transcript of the instance and of the commitment to the round messages, after τ is
+    /// drawn from it.
+    fn transcript(&self, messages: &TableCommitment) -> (Transcript, Vec<F>) {
+        let mut transcript = self.statement_transcript();
+        transcript.append_message(b"messages", messages);
+        let tau = (0..self.round_vars())
+            .map(|_| F::rand(&mut transcript))
+            .collect();
+        (transcript, tau)
+    }
+
+    /// The transcript after the instance.
+    fn statement_transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(PROTOCOL_LABEL);
+        let widths: Vec<F> = self.widths.iter().map(|&w| F::from(w as u64)).collect();
+        transcript.append_field_elements(b"widths", &widths);
+        transcript.append_field_elements(b"challenges", &self.challenges);
+        transcript.append_field_elements(b"claim", &[self.claimed_sum, self.final_eval]);
+        transcript
+    }
+
+    /// Proves that the round checks of 'proof', which must be that of the instance, hold, and
+    /// returns the claim about the table of its messages it reduces to.
+    #[cfg(feature = "prover")]
+    pub fn prove(&self, proof: &Proof) -> (RecursiveProof, EvaluationClaim) {
+        let num_vars = self.num_vars();
+        let (mut transcript, tau) = self.transcript(&self.commit_messages(proof));
+        let messages = self.messages_table(proof);
+        let mut prover =
+            SumCheckProver::from_tables(num_vars, vec![self.weights(&tau), messages.clone()]);
+        let mut round_messages = Vec::with_capacity(num_vars);
+        let mut point = Vec::with_capacity(num_vars);
+        for _ in 0..num_vars {
+            let message = prover.next_message(point.last().copied()).evaluations;
+            transcript.append_field_elements(b"round", &message);
+            point.push(F::rand(&mut transcript));
+            round_messages.push(message);
+        }
+        let evaluation = evaluate_table(&messages, &point);
+        let proof = RecursiveProof {
+            round_messages,
+            evaluation,
+        };
+        (
+            proof,
+            EvaluationClaim {
+                point,
+                value: evaluation,
+            },
+        )
+    }
+
+    /// Verifies a proof produced by [`RoundCheckInstance::prove`] for the round messages of
+    /// commitment 'messages', and returns the claim left to check about their table against it.
+    pub fn verify(
+        &self,
+        messages: &TableCommitment,
+        proof: &RecursiveProof,
+    ) -> Result<EvaluationClaim, RecursiveError> {
+        let (transcript, tau) = self.transcript(messages);
+        let mut state = Verifier::initialize_committed_with_rng(
+            self.num_vars(),
+            2,
+            self.target(&tau),
+            transcript,
+        );
+        for message in &proof.round_messages {
+            state.rng.append_field_elements(b"round", message);
+            (_, state) = Verifier::round(state, message.clone())?;
+        }
+        let claim = Verifier::finalize_to_claim(state)?;
+        let weight = evaluate_table(&self.weights(&tau), &claim.point);
+        if weight * proof.evaluation != claim.value {
+            return Err(RecursiveError::FinalCheckFailed);
+        }
+        Ok(EvaluationClaim {
+            point: claim.point,
+            value: proof.evaluation,
+        })
+    }
+}
+
+/// L_node(r) for the Lagrange basis of the nodes 0, 1, …, 'width' − 1.
+fn lagrange_basis(width: usize, node: usize, r: F) -> F {
+    let mut unit = vec![F::from(0); width];
+    unit[node] = F::from(1);
+    Verifier::evaluate_at_random_point(&unit, r)
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use crate::polynomial::{evaluate_mvml_polynomial, parse_product};
+    use crate::protocol::{prove, prove_with_auxiliary_node, verify};
+
+    fn instance(proof: &Proof, poly: &str) -> RoundCheckInstance {
+        let poly = parse_product(poly).unwrap();
+        let point = verify(&poly, proof).unwrap();
+        RoundCheckInstance::new(proof, evaluate_mvml_polynomial(poly, &point)).unwrap()
+    }
+
+    #[test]
+    fn test_recursive_sum_check() {
+        let text = "(x0 + 2*x1*x2) * (x1 + x2 + 3) * (x0*x2 + x3 + 1)";
+        let proof = prove(&parse_product(text).unwrap());
+        let instance = instance(&proof, text);
+        assert_eq!(instance.num_rounds(), 4);
+        assert_eq!((instance.round_vars(), instance.node_vars()), (3, 2));
+        assert_eq!(instance.challenges, verify(&parse_product(text).unwrap(), &proof).unwrap());
+
+        let (recursive, claim) = instance.prove(&proof);
+        let messages = instance.commit_messages(&proof);
+        assert_eq!(instance.verify(&messages, &recursive), Ok(claim.clone()));
+        assert_eq!(
+            evaluate_table(&instance.messages_table(&proof), &claim.point),
+            claim.value
+        );
+    }
+
+    #[test]
+    fn test_auxiliary_node() {
+        let text = "(x0 + 2*x1*x2) * (x1 + x2 + 3)";
+        let proof = prove_with_auxiliary_node(&parse_product(text).unwrap());
+        let instance = instance(&proof, text);
+        assert_eq!(instance.challenges, verify(&parse_product(text).unwrap(), &proof).unwrap());
+        let (recursive, _) = instance.prove(&proof);
+        assert!(instance
+            .verify(&instance.commit_messages(&proof), &recursive)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_wrong_transcript() {
+        let text = "(x0 + 2*x1) * (x1 + 3)";
+        let proof = prove(&parse_product(text).unwrap());
+        let instance = instance(&proof, text);
+        let messages = instance.commit_messages(&proof);
+
+        let mut wrong = instance.clone();
+        wrong.final_eval += F::from(1);
+        let (recursive, _) = wrong.prove(&proof);
+        assert!(wrong.verify(&messages, &recursive).is_err());
+
+        // A message which breaks its round check, with the same challenges.
+        let mut tampered = proof.clone();
+        tampered.round_messages[1][0] += F::from(1);
+        let (recursive, _) = instance.prove(&tampered);
+        assert!(instance
+            .verify(&instance.commit_messages(&tampered), &recursive)
+            .is_err());
+
+        let (recursive, _) = instance.prove(&proof);
+        assert!(instance
+            .verify(&instance.commit_messages(&tampered), &recursive)
+            .is_err());
+        let (mut recursive, _) = instance.prove(&proof);
+        recursive.evaluation += F::from(1);
+        assert_eq!(
+            instance.verify(&messages, &recursive),
+            Err(RecursiveError::FinalCheckFailed)
+        );
+
+        tampered.auxiliary_evaluations = vec![F::from(0)];
+        assert_eq!(
+            RoundCheckInstance::new(&tampered, F::from(0)),
+            Err(RecursiveError::SumCheck(SumCheckError::AuxiliaryCountMismatch {
+                expected: 2,
+                got: 1
+            }))
+        );
+        tampered.auxiliary_evaluations.clear();
+        tampered.round_messages[0].truncate(1);
+        assert_eq!(
+            RoundCheckInstance::new(&tampered, F::from(0)),
+            Err(RecursiveError::MalformedMessage { round: 0, got: 1 })
+        );
+    }
+
+    #[test]
+    fn test_messages_chosen_for_tau() {
+        let text = "(x0 + 2*x1) * (x1 + 3)";
+        let proof = prove(&parse_product(text).unwrap());
+        let instance = instance(&proof, text);
+
+        // τ as drawn before the commitment to the messages, and messages which break the round
+        // checks 0, 1 and 2 with Σ_j eq(τ, j) · c_j = 0: shifting p_1(0) by 1 and p_0(0) by ε
+        // moves the sum by ε · (eq(τ, 0) − eq(τ, 1) · L_0(r_0)) + eq(τ, 1) − eq(τ, 2) · L_0(r_1).
+        let mut transcript = instance.statement_transcript();
+        let tau: Vec<F> = (0..instance.round_vars())
+            .map(|_| F::rand(&mut transcript))
+            .collect();
+        let eq = eq_extension(&tau);
+        let [r0, r1] = [instance.challenges[0], instance.challenges[1]];
+        let l0 = |round: usize, r| lagrange_basis(instance.widths[round], 0, r);
+        let epsilon = -(eq[1] - eq[2] * l0(1, r1)) / (eq[0] - eq[1] * l0(0, r0));
+        let mut forged = proof.clone();
+        forged.round_messages[1][0] += F::from(1);
+        forged.round_messages[0][0] += epsilon;
+        let weighted: F = instance
+            .weights(&tau)
+            .iter()
+            .zip(&instance.messages_table(&forged))
+            .map(|(w, m)| *w * m)
+            .sum();
+        assert_eq!(weighted, instance.target(&tau));
+
+        // The honest prover of the instance for the forged messages and their commitment.
+        let (recursive, _) = instance.prove(&forged);
+        assert!(instance
+            .verify(&instance.commit_messages(&forged), &recursive)
+            .is_err());
+    }
+}
@@ -0,0 +1,282 @@
+//! Zero-check: proves that a product of multilinears g = f_1 · … · f_k vanishes on the whole
+//! hypercube, the usual target of constraint systems reduced to sum-check.
+//!
+//! g vanishes on the hypercube iff the multilinear extension of its evaluations is zero, which is
+//! tested at a random point r with the sum-check of
+//!
+//!   0 = Σ_b eq(b, r) · f_1(b) · … · f_k(b).
+//!
+//! The prover builds the evaluation table of eq(·, r) once, in 2^n multiplications, by doubling
+//! it one variable at a time, instead of evaluating eq at each point of the hypercube in n
+//! multiplications. The point r is drawn after the transcript absorbs the commitments to the
+//! factors, so that the prover cannot choose them to cancel out against eq(·, r). The sum-check
+//! ends at a random point ρ, where the caller has to check the claimed evaluations of the factors
+//! against these commitments.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+#[cfg(feature = "prover")]
+use ark_std::vec;
+use ark_std::{vec::Vec, UniformRand};
+use thiserror::Error;
+
+use crate::commitment::TableCommitment;
+#[cfg(feature = "prover")]
+use crate::commitment::HashCommitment;
+use crate::field::Field256 as F;
+use crate::polynomial::{eq_eval, PolynomialDescription};
+#[cfg(feature = "prover")]
+use crate::polynomial::{evaluate_table, EvalTable};
+#[cfg(feature = "prover")]
+use crate::protocol::{EqFactor, Factor, SumCheckProver};
+use crate::protocol::{SumCheckError, Transcript, Verifier};
+
+/// Domain separator of the Fiat–Shamir transcript.
+const PROTOCOL_LABEL: &[u8] = b"zerocheck";
+
+/// A non-interactive proof that a product of multilinears vanishes on the hypercube, with the
+/// evaluations of the factors at the random point of the sum-check.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct ZeroCheckProof {
+    pub round_messages: Vec<PolynomialDescription>,
+    pub evaluations: Vec<F>,
+}
+
+/// What remains to check after a successful [`verify_zero`]: the factors evaluate to
+/// 'evaluations' at 'point', e.g. by opening their commitments there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZeroCheckSubclaim {
+    pub point: Vec<F>,
+    pub evaluations: Vec<F>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ZeroCheckError {
+    #[error("expected {expected} factor evaluations, got {got}")]
+    FactorCountMismatch { expected: usize, got: usize },
+    #[error(transparent)]
+    SumCheck(#[from] SumCheckError),
+    /// The evaluations of the factors are inconsistent with the last sum-check round.
+    #[error("the evaluations of the factors do not match the sum-check")]
+    FinalCheckFailed,
+}
+
+/// Proves that the product of 'factors', given by their evaluation tables on 'num_vars'
+/// variables, is zero on the hypercube. Does not check it: the proof of a non-vanishing product
+/// is rejected by the verifier. The verifier holds the commitments to the factors, as
+/// [`HashCommitment::commit_table`] computes them.
+#[cfg(feature = "prover")]
+pub fn prove_zero(num_vars: usize, factors: &[EvalTable]) -> ZeroCheckProof {
+    assert!(
+        factors.iter().all(|table| table.len() == 1 << num_vars),
+        "the factors must have {num_vars} variables"
+    );
+    let commitments: Vec<TableCommitment> = factors.iter().map(HashCommitment::commit_table).collect();
+    let mut transcript = zerocheck_transcript(num_vars, &commitments);
+    let (round_messages, challenges) =
+        prove_zero_rounds(num_vars, vec![factors.to_vec()], &mut transcript);
+    let evaluations = factors
+        .iter()
+        .map(|table| evaluate_table(table, &challenges))
+        .collect();
+    ZeroCheckProof {
+        round_messages,
+        evaluations,
+    }
+}
+
+/// Verifies a proof produced by [`prove_zero`] for the product of the multilinears of
+/// 'commitments' on 'num_vars' variables, and returns the evaluations of the factors left to
+/// check against them.
+pub fn verify_zero(
+    num_vars: usize,
+    commitments: &[TableCommitment],
+    proof: &ZeroCheckProof,
+) -> Result<ZeroCheckSubclaim, ZeroCheckError> {
+    let num_factors = commitments.len();
+    if proof.evaluations.len() != num_factors {
+        return Err(ZeroCheckError::FactorCountMismatch {
+            expected: num_factors,
+            got: proof.evaluations.len(),
+        });
+    }
+    let mut transcript = zerocheck_transcript(num_vars, commitments);
+    let rounds = verify_zero_rounds(
+        num_vars,
+        num_factors + 1,
+        &proof.round_messages,
+        &mut transcript,
+    )?;
+    let product: F = proof.evaluations.iter().product();
+    if rounds.eq_at_point * product != rounds.running_eval {
+        return Err(ZeroCheckError::FinalCheckFailed);
+    }
+    Ok(ZeroCheckSubclaim {
+        point: rounds.point,
+        evaluations: proof.evaluations.clone(),
+    })
+}
+
+/// The end of the sum-check of a zero-check: the random point, eq(r, point) for the point r drawn
+/// before the rounds, and the running evaluation, which the products of the factors at the point
+/// must match once multiplied by eq(r, point).
+pub(crate) struct ZeroCheckRounds {
+    pub point: Vec<F>,
+    pub eq_at_point: F,
+    pub running_eval: F,
+}
+
+/// Draws r from 'transcript' and runs the sum-check of Σ_b eq(b, r) · Σ_i Π_j f_ij(b) for the
+/// factors f_ij of 'products', which is 0 if the sum of the products vanishes on the hypercube,
+/// with the round messages absorbed by 'transcript'. Returns the round messages and the random
+/// point.
+#[cfg(feature = "prover")]
+pub(crate) fn prove_zero_rounds(
+    num_vars: usize,
+    products: Vec<Vec<EvalTable>>,
+    transcript: &mut Transcript,
+) -> (Vec<PolynomialDescription>, Vec<F>) {
+    let r: Vec<F> = (0..num_vars).map(|_| F::rand(&mut *transcript)).collect();
+    let products = products
+        .into_iter()
+        .map(|tables| {
+            let mut factors = Vec::with_capacity(tables.len() + 1);
+            factors.push(Factor::Eq(EqFactor::new(&r)));
+            factors.extend(tables.into_iter().map(Factor::Table));
+            factors
+        })
+        .collect();
+
+    let mut prover = SumCheckProver::from_factors(num_vars, products);
+    let mut round_messages = Vec::with_capacity(num_vars);
+    let mut challenges = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        let message = prover.next_message(challenges.last().copied()).evaluations;
+        transcript.append_field_elements(b"round", &message);
+        challenges.push(F::rand(&mut *transcript));
+        round_messages.push(message);
+    }
+    (round_messages, challenges)
+}
+
+/// Verifies the rounds of [`prove_zero_rounds`], for products of at most 'degree' factors
+/// counting eq.
+pub(crate) fn verify_zero_rounds(
+    num_vars: usize,
+    degree: usize,
+    round_messages: &[PolynomialDescription],
+    transcript: &mut Transcript,
+) -> Result<ZeroCheckRounds, SumCheckError> {
+    let r: Vec<F> = (0..num_vars).map(|_| F::rand(&mut *transcript)).collect();
+    let mut state =
+        Verifier::initialize_committed_with_rng(num_vars, degree, F::from(0), &mut *transcript);
+    for message in round_messages {
+        state.rng.append_field_elements(b"round", message);
+        (_, state) = Verifier::round(state, message.clone())?;
+    }
+    if state.randomness.len() != num_vars {
+        return Err(SumCheckError::VariableCountMismatch {
+            expected: num_vars,
+            got: state.randomness.len(),
+        });
+    }
+    Ok(ZeroCheckRounds {
+        eq_at_point: eq_eval(&r, &state.randomness),
+        point: state.randomness,
+        running_eval: state.running_eval,
+    })
+}
+
+fn zerocheck_transcript(num_vars: usize, commitments: &[TableCommitment]) -> Transcript {
+    let mut transcript = Transcript::new(PROTOCOL_LABEL);
+    transcript.append_field_elements(
+        b"shape",
+        &[F::from(num_vars as u64), F::from(commitments.len() as u64)],
+    );
+    for commitment in commitments {
+        transcript.append_message(b"commitment", commitment);
+    }
+    transcript
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn commitments(factors: &[EvalTable]) -> Vec<TableCommitment> {
+        factors.iter().map(HashCommitment::commit_table).collect()
+    }
+
+    fn check(factors: &[EvalTable], num_vars: usize) -> Result<(), ZeroCheckError> {
+        let proof = prove_zero(num_vars, factors);
+        let subclaim = verify_zero(num_vars, &commitments(factors), &proof)?;
+        for (table, evaluation) in factors.iter().zip(&subclaim.evaluations) {
+            assert_eq!(evaluate_table(table, &subclaim.point), *evaluation);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_zerocheck() {
+        // Booleanity constraint b · (1 - b) = 0.
+        let mut rng = StdRng::seed_from_u64(0);
+        let bits: EvalTable = (0..16).map(|i| F::from((i * 7) % 3 == 0)).collect();
+        let complement = bits.iter().map(|&b| F::from(1) - b).collect();
+        assert_eq!(check(&[bits.clone(), complement], 4), Ok(()));
+
+        // A product with a factor vanishing where the others do not.
+        let a: EvalTable = (0..8).map(|_| F::rand(&mut rng)).collect();
+        let b: EvalTable = (0..8).map(|i| F::from(i % 2)).collect();
+        let c: EvalTable = (0..8).map(|i| F::from(1 - i % 2)).collect();
+        assert_eq!(check(&[a.clone(), b, c], 3), Ok(()));
+
+        // A non-vanishing product, even with a zero sum over the hypercube.
+        let mut d: EvalTable = vec![F::from(0); 8];
+        d[0] = F::from(1);
+        d[5] = -F::from(1);
+        assert!(check(&[a, d], 3).is_err());
+        assert!(check(&[bits], 4).is_err());
+    }
+
+    #[test]
+    fn test_wrong_proofs() {
+        let a: EvalTable = (0..8).map(|i| F::from(i % 2)).collect();
+        let b: EvalTable = (0..8).map(|i| F::from(1 - i % 2)).collect();
+        let commitments = commitments(&[a.clone(), b.clone()]);
+        let proof = prove_zero(3, &[a.clone(), b]);
+        assert_eq!(
+            verify_zero(3, &[commitments[0], commitments[1], commitments[0]], &proof),
+            Err(ZeroCheckError::FactorCountMismatch {
+                expected: 3,
+                got: 2
+            })
+        );
+        let mut wrong = proof.clone();
+        wrong.evaluations[0] += F::from(1);
+        assert_eq!(
+            verify_zero(3, &commitments, &wrong),
+            Err(ZeroCheckError::FinalCheckFailed)
+        );
+        // The proof for other factors.
+        assert!(verify_zero(3, &[commitments[0], commitments[0]], &proof).is_err());
+        let mut wrong = proof;
+        wrong.round_messages.pop();
+        assert!(matches!(
+            verify_zero(3, &commitments, &wrong),
+            Err(ZeroCheckError::SumCheck(_))
+        ));
+    }
+
+    #[test]
+    fn test_factor_chosen_for_r() {
+        // r as drawn before the commitments are absorbed, and a nonzero factor d with
+        // Σ_b eq(b, r) · d(b) = 0 for it, which a transcript without the commitments accepts.
+        let mut transcript = Transcript::new(PROTOCOL_LABEL);
+        transcript.append_field_elements(b"shape", &[F::from(1), F::from(1)]);
+        let r = F::rand(&mut transcript);
+        let d = vec![r, r - F::from(1)];
+        assert_eq!((F::from(1) - r) * d[0] + r * d[1], F::from(0));
+        assert!(check(&[d], 1).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! Soundness of the protocol. In each round, a cheating prover passes the check on a wrong round
+//! polynomial only if the challenge is a root of its difference with the correct one, which has
+//! degree at most d. Over n rounds, the soundness error is thus at most d·n/|F|, or d·n/|S| when
+//! the challenges are drawn from a subset S of the field by a
+//! [`ChallengeSampler`](crate::protocol::ChallengeSampler).
+
+use ark_ff::{Field, PrimeField};
+
+use crate::field::Field256 as F;
+use crate::polynomial::{get_num_vars, ProductMLPolynomial};
+use crate::protocol::{ChallengeSampler, ChallengeSpace};
+
+/// Minimum number of bits of security required by default when setting up the protocol.
+pub const DEFAULT_SECURITY_BITS: f64 = 100.0;
+
+/// Soundness error d·n/|F| of the protocol with challenges in the field 'P', for round
+/// polynomials of degree 'degree' and 'num_vars' rounds.
+pub fn soundness_error<P: Field>(degree: usize, num_vars: usize) -> f64 {
+    (-security_bits::<P>(degree, num_vars)).exp2()
+}
+
+/// Bits of security of the protocol with challenges in the field 'P', i.e. -log2 of the soundness
+/// error. 'P' may be an extension field, whose order is a power of the modulus.
+pub fn security_bits<P: Field>(degree: usize, num_vars: usize) -> f64 {
+    let rounds_times_degree = (degree * num_vars).max(1) as f64;
+    let log2_order = P::extension_degree() as f64 * log2_modulus::<P::BasePrimeField>();
+    log2_order - rounds_times_degree.log2()
+}
+
+/// Bits of security of the protocol over `Field256` for 'poly', whose round polynomials have
+/// degree the number of factors.
+pub fn instance_security_bits(poly: &ProductMLPolynomial) -> f64 {
+    security_bits::<F>(poly.len(), get_num_vars(poly).unwrap())
+}
+
+/// Bits of security of the protocol over `Field256` with the challenges drawn by 'sampler', for
+/// round polynomials of degree 'degree' and 'num_vars' rounds.
+pub fn sampler_security_bits(sampler: &dyn ChallengeSampler, degree: usize, num_vars: usize) -> f64 {
+    log2_space_size(sampler.space()) - ((degree * num_vars).max(1) as f64).log2()
+}
+
+/// log2 of the number of elements of `Field256` in 'space'.
+pub fn log2_space_size(space: ChallengeSpace) -> f64 {
+    let log2_order = log2_modulus::<F>();
+    match space {
+        ChallengeSpace::Field => log2_order,
+        ChallengeSpace::Subset(size) => (size as f64).log2(),
+        ChallengeSpace::FieldExcept(excluded) => {
+            log2_order + (1.0 - excluded as f64 * (-log2_order).exp2()).log2()
+        }
+        ChallengeSpace::Part { log2_density } => log2_order + log2_density,
+    }
+}
+
+fn log2_modulus<P: PrimeField>() -> f64 {
+    let modulus = P::MODULUS;
+    let limbs = modulus.as_ref();
+    let top = limbs.iter().rposition(|&limb| limb != 0).unwrap();
+    let mut leading = limbs[top] as f64;
+    if top > 0 {
+        leading += limbs[top - 1] as f64 / 2f64.powi(64);
+    }
+    leading.log2() + (64 * top) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::{Field31, Field31Ext4, Field64, Field64Ext2};
+    use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+    use ark_poly::DenseMVPolynomial;
+
+    #[test]
+    fn test_log2_modulus() {
+        // The modulus of Field256 is 2^255 - 19.
+        assert!((log2_modulus::<F>() - 255.0).abs() < 1e-9);
+        assert!((log2_modulus::<Field64>() - 64.0).abs() < 1e-6);
+        assert!((log2_modulus::<Field31>() - 30.907).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_small_fields() {
+        // Over BabyBear, a product of 4 multilinears on 20 variables is far from 100 bits.
+        assert!(security_bits::<Field31>(4, 20) < 25.0);
+        assert!(security_bits::<Field64>(4, 20) > 57.0);
+        // Drawing the challenges from an extension restores the security.
+        assert!(security_bits::<Field31Ext4>(4, 20) > 117.0);
+        assert!(security_bits::<Field64Ext2>(4, 20) > 121.0);
+    }
+
+    #[test]
+    fn test_security_bits() {
+        assert!((security_bits::<F>(4, 16) - 249.0).abs() < 1e-9);
+        assert!((soundness_error::<F>(4, 16) - (-249f64).exp2()).abs() < 1e-80);
+
+        let poly = vec![
+            SparsePolynomial::from_coefficients_vec(
+                2,
+                vec![(F::from(1), SparseTerm::new(vec![(0, 1)]))],
+            );
+            4
+        ];
+        assert!((instance_security_bits(&poly) - 252.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sampler_security_bits() {
+        use crate::protocol::{FullField, RejectionSampler, Subgroup};
+
+        assert_eq!(sampler_security_bits(&FullField, 4, 16), security_bits::<F>(4, 16));
+        // 12 challenges leave log2(12) - log2(1·3) = 2 bits.
+        let subgroup = Subgroup::of_order(12).unwrap();
+        assert!((sampler_security_bits(&subgroup, 1, 3) - 2.0).abs() < 1e-9);
+        let nonbinary = RejectionSampler::nonbinary();
+        assert!((sampler_security_bits(&nonbinary, 4, 16) - 249.0).abs() < 1e-9);
+        assert!((log2_space_size(ChallengeSpace::Part { log2_density: -1.0 }) - 254.0).abs() < 1e-9);
+    }
+}
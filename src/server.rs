@@ -0,0 +1,404 @@
+//! The prover as a gRPC service, with the `server` feature. The service of
+//! `proto/sum_check.proto` lets verifiers request proofs from a central prover: each verifier
+//! starts a session for an instance, then plays the rounds by fetching the round polynomials and
+//! submitting its challenges.
+//!
+//! The instances are identified by the SHA-256 hash of their canonical JSON encoding, and their
+//! evaluation tables are computed once for all the sessions of the instance. A session is closed
+//! once the polynomial of its last round is fetched. The service proves the instances over
+//! `Field256`, and rejects those which declare another field or have more than [`MAX_NUM_VARS`]
+//! variables.
+//!
+//! The service keeps at most [`MAX_INSTANCES`] instances and [`MAX_SESSIONS`] sessions, and
+//! evicts the oldest ones to make room for new ones, so that sessions which verifiers abandon do
+//! not accumulate. The evaluations and the round polynomials are computed on the blocking threads
+//! of the runtime.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+use tonic::{Request, Response, Status};
+
+use crate::field::Field256 as F;
+use crate::polynomial::{
+    evaluate_polynomial_on_hypercube, get_num_vars, parser, EvalTable, PolynomialDescription,
+};
+use crate::protocol::{bytes_to_hex, SumCheckProver};
+
+/// The types and the client and server stubs generated from `proto/sum_check.proto`.
+pub mod proto {
+    tonic::include_proto!("sumcheck");
+}
+
+use proto::prover_server::{Prover, ProverServer};
+use proto::{
+    Challenge, RoundPolynomial, RoundPolynomialRequest, StartSessionRequest, StartSessionResponse,
+    SubmitChallengeResponse,
+};
+
+/// The largest number of variables of the instances which the service proves.
+pub const MAX_NUM_VARS: usize = 24;
+
+/// The number of instances whose evaluations the service keeps.
+pub const MAX_INSTANCES: usize = 16;
+
+/// The number of sessions which the service keeps open.
+pub const MAX_SESSIONS: usize = 1024;
+
+/// An instance shared by the sessions proving it.
+struct Instance {
+    num_vars: usize,
+    tables: Vec<EvalTable>,
+    /// The order in which the instances were evaluated, for evicting the oldest one.
+    created: u64,
+}
+
+/// A run of the prover for one verifier.
+struct Session {
+    /// The order in which the sessions were started, for evicting the oldest one.
+    started: u64,
+    prover: SumCheckProver,
+    /// The polynomial of the current round, once computed.
+    message: Option<PolynomialDescription>,
+    /// The challenge of the previous round, until the polynomial of the current round is computed.
+    challenge: Option<F>,
+}
+
+/// The state of the service: the instances by hash, and the sessions by identifier.
+#[derive(Clone, Default)]
+pub struct ProvingService {
+    instances: Arc<Mutex<HashMap<[u8; 32], Arc<Instance>>>>,
+    sessions: Arc<Mutex<HashMap<String, Session>>>,
+    next_session: Arc<Mutex<u64>>,
+    next_instance: Arc<Mutex<u64>>,
+}
+
+impl ProvingService {
+    pub fn new() -> Self {
+        ProvingService::default()
+    }
+
+    /// The service, to be added to a [`tonic::transport::Server`].
+    pub fn into_server(self) -> ProverServer<Self> {
+        ProverServer::new(self)
+    }
+
+    /// The instance of 'json' and its hash, evaluated on the hypercube unless another session
+    /// already did. Fails with the reason for which the instance is invalid.
+    fn instance(&self, json: &str) -> Result<([u8; 32], Arc<Instance>), String> {
+        let poly = parser::from_json(json).map_err(|err| err.to_string())?;
+        let num_vars = get_num_vars(&poly).ok_or("the factors must have the same variables")?;
+        if num_vars == 0 {
+            return Err("the instance has no variable".to_string());
+        }
+        if num_vars > MAX_NUM_VARS {
+            return Err(format!(
+                "the instance has {} variables, more than {}",
+                num_vars, MAX_NUM_VARS
+            ));
+        }
+        let hash: [u8; 32] = Sha256::digest(parser::to_json(&poly)).into();
+        if let Some(instance) = self.instances.lock().unwrap().get(&hash) {
+            return Ok((hash, instance.clone()));
+        }
+        let instance = Arc::new(Instance {
+            num_vars,
+            tables: poly.iter().map(evaluate_polynomial_on_hypercube).collect(),
+            created: next(&self.next_instance),
+        });
+        let mut instances = self.instances.lock().unwrap();
+        if instances.len() >= MAX_INSTANCES {
+            evict_oldest(&mut instances, |instance| instance.created);
+        }
+        instances.insert(hash, instance.clone());
+        Ok((hash, instance))
+    }
+
+    /// Starts a session for the instance of 'json'.
+    fn start(&self, json: &str) -> Result<StartSessionResponse, Box<Status>> {
+        let (hash, instance) = self.instance(json).map_err(Status::invalid_argument)?;
+        let prover = SumCheckProver::from_tables(instance.num_vars, instance.tables.clone());
+        let claimed_sum = to_bytes(&prover.claimed_sum());
+        let started = next(&self.next_session);
+        let session_id = format!("{}-{}", bytes_to_hex(&hash), started);
+        let session = Session {
+            started,
+            prover,
+            message: None,
+            challenge: None,
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.len() >= MAX_SESSIONS {
+            evict_oldest(&mut sessions, |session| session.started);
+        }
+        sessions.insert(session_id.clone(), session);
+        Ok(StartSessionResponse {
+            session_id,
+            instance_hash: hash.to_vec(),
+            num_rounds: instance.num_vars as u32,
+            claimed_sum,
+        })
+    }
+
+    /// The polynomial of the current round of the session 'session_id', computed unless it was
+    /// already fetched.
+    fn round_polynomial(&self, session_id: &str) -> Result<RoundPolynomial, Box<Status>> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| Status::not_found("no such session"))?;
+        if session.message.is_none() {
+            if session.prover.round() > 0 && session.challenge.is_none() {
+                return Err(Box::new(Status::failed_precondition(
+                    "the challenge of the previous round is missing",
+                )));
+            }
+            let challenge = session.challenge.take();
+            session.message = Some(session.prover.next_message(challenge).evaluations);
+        }
+        let round = session.prover.round() - 1;
+        let evaluations = session.message.iter().flatten().map(to_bytes).collect();
+        if round + 1 == session.prover.num_rounds() {
+            sessions.remove(session_id);
+        }
+        Ok(RoundPolynomial {
+            round: round as u32,
+            evaluations,
+        })
+    }
+
+    /// Runs 'f' on the service on a blocking thread, as the evaluations and the round polynomials
+    /// take too long for the threads of the runtime.
+    async fn blocking<T, G>(&self, f: G) -> Result<T, Status>
+    where
+        T: Send + 'static,
+        G: FnOnce(&ProvingService) -> Result<T, Box<Status>> + Send + 'static,
+    {
+        let service = self.clone();
+        tokio::task::spawn_blocking(move || f(&service))
+            .await
+            .map_err(|err| Status::internal(err.to_string()))?
+            .map_err(|err| *err)
+    }
+}
+
+/// Increments 'counter' and returns its new value.
+fn next(counter: &Mutex<u64>) -> u64 {
+    let mut counter = counter.lock().unwrap();
+    *counter += 1;
+    *counter
+}
+
+/// Removes the entry of 'map' with the smallest 'order'.
+fn evict_oldest<K: Clone + Eq + std::hash::Hash, V>(
+    map: &mut HashMap<K, V>,
+    order: impl Fn(&V) -> u64,
+) {
+    if let Some(key) = map
+        .iter()
+        .min_by_key(|(_, value)| order(value))
+        .map(|(key, _)| key.clone())
+    {
+        map.remove(&key);
+    }
+}
+
+#[tonic::async_trait]
+impl Prover for ProvingService {
+    async fn start_session(
+        &self,
+        request: Request<StartSessionRequest>,
+    ) -> Result<Response<StartSessionResponse>, Status> {
+        let json = request.into_inner().instance;
+        let response = self.blocking(move |service| service.start(&json)).await?;
+        Ok(Response::new(response))
+    }
+
+    async fn get_round_polynomial(
+        &self,
+        request: Request<RoundPolynomialRequest>,
+    ) -> Result<Response<RoundPolynomial>, Status> {
+        let session_id = request.into_inner().session_id;
+        let response = self
+            .blocking(move |service| service.round_polynomial(&session_id))
+            .await?;
+        Ok(Response::new(response))
+    }
+
+    async fn submit_challenge(
+        &self,
+        request: Request<Challenge>,
+    ) -> Result<Response<SubmitChallengeResponse>, Status> {
+        let request = request.get_ref();
+        let challenge = F::deserialize_compressed(&request.challenge[..])
+            .map_err(|_| Status::invalid_argument("invalid field element"))?;
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(&request.session_id)
+            .ok_or_else(|| Status::not_found("no such session"))?;
+        if session.message.take().is_none() {
+            return Err(Status::failed_precondition(
+                "the polynomial of the round has not been fetched",
+            ));
+        }
+        session.challenge = Some(challenge);
+        Ok(Response::new(SubmitChallengeResponse {
+            next_round: session.prover.round() as u32,
+        }))
+    }
+}
+
+fn to_bytes(element: &F) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    element.serialize_compressed(&mut bytes).unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::{RoundMessage, SumCheckVerifier};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const INSTANCE: &str = r#"{"num_vars": 3, "factors": [
+        [{"coeff": 1, "vars": [[0, 1], [2, 1]]}, {"coeff": 1, "vars": [[1, 1]]}],
+        [{"coeff": 2, "vars": [[0, 1]]}, {"coeff": 5}]
+    ]}"#;
+
+    async fn start(service: &ProvingService, instance: &str) -> StartSessionResponse {
+        let request = Request::new(StartSessionRequest {
+            instance: instance.to_string(),
+        });
+        service.start_session(request).await.unwrap().into_inner()
+    }
+
+    async fn fetch(service: &ProvingService, session_id: &str) -> Result<RoundPolynomial, Status> {
+        let request = Request::new(RoundPolynomialRequest {
+            session_id: session_id.to_string(),
+        });
+        Ok(service.get_round_polynomial(request).await?.into_inner())
+    }
+
+    async fn submit(
+        service: &ProvingService,
+        session_id: &str,
+        challenge: F,
+    ) -> Result<u32, Status> {
+        let request = Request::new(Challenge {
+            session_id: session_id.to_string(),
+            challenge: to_bytes(&challenge),
+        });
+        Ok(service
+            .submit_challenge(request)
+            .await?
+            .into_inner()
+            .next_round)
+    }
+
+    #[tokio::test]
+    async fn test_remote_session() {
+        let service = ProvingService::new();
+        let poly = parser::from_json(INSTANCE).unwrap();
+        let session = start(&service, INSTANCE).await;
+        assert_eq!(session.num_rounds, 3);
+        let claimed_sum = F::deserialize_compressed(&session.claimed_sum[..]).unwrap();
+        let rng = ChaCha20Rng::from_seed([1; 32]);
+        let mut verifier = SumCheckVerifier::with_rng(&poly, claimed_sum, rng);
+        for round in 0..3 {
+            let message = fetch(&service, &session.session_id).await.unwrap();
+            assert_eq!(message.round, round);
+            let evaluations = message
+                .evaluations
+                .iter()
+                .map(|bytes| F::deserialize_compressed(&bytes[..]).unwrap())
+                .collect();
+            let challenge = verifier
+                .receive_message(RoundMessage { evaluations })
+                .unwrap();
+            if round < 2 {
+                let next_round = submit(&service, &session.session_id, challenge).await;
+                assert_eq!(next_round.unwrap(), round + 1);
+            }
+        }
+        assert!(verifier.finalize().0.is_ok());
+        // The session is closed after the last round.
+        assert!(fetch(&service, &session.session_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sessions_of_an_instance() {
+        let service = ProvingService::new();
+        let first = start(&service, INSTANCE).await;
+        // Another encoding of the same instance.
+        let second = start(
+            &service,
+            &INSTANCE.replace("\"coeff\": 5", "\"coeff\": \"5\""),
+        )
+        .await;
+        assert_eq!(first.instance_hash, second.instance_hash);
+        assert_ne!(first.session_id, second.session_id);
+        assert_eq!(service.instances.lock().unwrap().len(), 1);
+
+        // The sessions advance independently.
+        fetch(&service, &first.session_id).await.unwrap();
+        let status = submit(&service, &second.session_id, F::from(3))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(
+            submit(&service, &first.session_id, F::from(3))
+                .await
+                .unwrap(),
+            1
+        );
+        // The polynomial of a round is the same until the challenge is submitted.
+        let message = fetch(&service, &first.session_id).await.unwrap();
+        assert_eq!(fetch(&service, &first.session_id).await.unwrap(), message);
+        assert_eq!(fetch(&service, &second.session_id).await.unwrap().round, 0);
+
+        let status = fetch(&service, "unknown").await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        let request = Request::new(StartSessionRequest {
+            instance: "{".to_string(),
+        });
+        let status = service.start_session(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_limits() {
+        let service = ProvingService::new();
+        let wide = format!(
+            r#"{{"num_vars": {}, "factors": [[{{"coeff": 1}}]]}}"#,
+            MAX_NUM_VARS + 1
+        );
+        let request = Request::new(StartSessionRequest { instance: wide });
+        let status = service.start_session(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(service.instances.lock().unwrap().len(), 0);
+
+        // The oldest instance is evicted for a new one.
+        let first = start(&service, INSTANCE).await;
+        for constant in 0..MAX_INSTANCES {
+            let other = INSTANCE.replace("\"coeff\": 5", &format!("\"coeff\": {}", 6 + constant));
+            start(&service, &other).await;
+        }
+        let hash: [u8; 32] = first.instance_hash.try_into().unwrap();
+        {
+            let instances = service.instances.lock().unwrap();
+            assert_eq!(instances.len(), MAX_INSTANCES);
+            assert!(!instances.contains_key(&hash));
+        }
+
+        // The oldest session is evicted for a new one.
+        for _ in MAX_INSTANCES + 1..MAX_SESSIONS + 1 {
+            start(&service, INSTANCE).await;
+        }
+        assert_eq!(service.sessions.lock().unwrap().len(), MAX_SESSIONS);
+        let status = fetch(&service, &first.session_id).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+}
@@ -0,0 +1,6 @@
+//! Tools to exercise the protocol beyond honest runs.
+
+pub mod adversary;
+pub mod naive;
+#[cfg(feature = "parser")]
+pub mod vectors;
@@ -0,0 +1,157 @@
+//! Malicious provers, which follow the honest prover but tamper with its messages.
+//!
+//! An [`Adversary`] sees every round message before it reaches the verifier and may rewrite it.
+//! [`Tampering`] provides the usual attacks at a chosen round, and closures can be used for
+//! anything else.
+
+use ark_std::vec::Vec;
+use rand::{CryptoRng, RngCore};
+
+use crate::field::Field256 as F;
+use crate::polynomial::ProductMLPolynomial;
+use crate::protocol::{RoundMessage, SumCheckError, SumCheckProver, SumCheckVerifier};
+
+/// A hook on the prover's messages.
+pub trait Adversary {
+    /// Rewrites the message of round 'round' before it is sent to the verifier.
+    fn tamper(&mut self, round: usize, message: &mut RoundMessage);
+}
+
+impl<T: FnMut(usize, &mut RoundMessage)> Adversary for T {
+    fn tamper(&mut self, round: usize, message: &mut RoundMessage) {
+        self(round, message)
+    }
+}
+
+/// Ways of corrupting a round polynomial, given by its evaluations at 0, 1, ..., d.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TamperKind {
+    /// Adds a constant to the round polynomial.
+    WrongConstant(F),
+    /// Sends one more evaluation than expected, as for a polynomial of higher degree.
+    ExtraEvaluation(F),
+    /// Sends one evaluation less than expected.
+    MissingEvaluation,
+    /// Swaps the evaluations at two points.
+    SwapEvaluations(usize, usize),
+    /// Replaces the message altogether.
+    Replace(Vec<F>),
+}
+
+/// An adversary that tampers with the message of a single round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tampering {
+    pub round: usize,
+    pub kind: TamperKind,
+}
+
+impl Tampering {
+    pub fn new(round: usize, kind: TamperKind) -> Self {
+        Tampering { round, kind }
+    }
+}
+
+impl Adversary for Tampering {
+    fn tamper(&mut self, round: usize, message: &mut RoundMessage) {
+        if round != self.round {
+            return;
+        }
+        let evaluations = &mut message.evaluations;
+        match &self.kind {
+            TamperKind::WrongConstant(delta) => evaluations.iter_mut().for_each(|e| *e += delta),
+            TamperKind::ExtraEvaluation(value) => evaluations.push(*value),
+            TamperKind::MissingEvaluation => {
+                evaluations.pop();
+            }
+            TamperKind::SwapEvaluations(i, j) => evaluations.swap(*i, *j),
+            TamperKind::Replace(replacement) => evaluations.clone_from(replacement),
+        }
+    }
+}
+
+/// Runs the protocol on 'poly' between the honest prover, whose messages go through 'adversary',
+/// and the verifier drawing its challenges from 'rng'. The claimed sum is the honest one shifted by
+/// 'claim_offset'. Returns the random point if the verifier accepts.
+pub fn run_with_adversary<A: Adversary, R: RngCore + CryptoRng>(
+    poly: &ProductMLPolynomial,
+    claim_offset: F,
+    adversary: &mut A,
+    rng: R,
+) -> Result<Vec<F>, SumCheckError> {
+    let mut prover = SumCheckProver::new(poly);
+    let claimed_sum = prover.claimed_sum() + claim_offset;
+    let mut verifier = SumCheckVerifier::with_rng(poly, claimed_sum, rng);
+    let mut challenge = None;
+    for round in 0..prover.num_rounds() {
+        let mut message = prover.next_message(challenge);
+        adversary.tamper(round, &mut message);
+        challenge = Some(verifier.receive_message(message)?);
+    }
+    let (result, point) = verifier.finalize();
+    result.map(|()| point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::polynomial::parse_product;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn run(tampering: Tampering) -> Result<Vec<F>, SumCheckError> {
+        let poly = parse_product("(x0 + 2*x1*x2) * (x1 + x2 + 3) * (x0*x2 + 1)").unwrap();
+        let mut adversary = tampering;
+        run_with_adversary(&poly, F::from(0), &mut adversary, StdRng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn test_honest_run() {
+        let poly = parse_product("(x0 + x1) * (x1 + x2)").unwrap();
+        let mut adversary = |_: usize, _: &mut RoundMessage| {};
+        let point = run_with_adversary(&poly, F::from(0), &mut adversary, StdRng::seed_from_u64(0));
+        assert_eq!(point.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_tampering() {
+        assert!(matches!(
+            run(Tampering::new(1, TamperKind::WrongConstant(F::from(1)))),
+            Err(SumCheckError::IntermediateCheckFailed { round: 1, .. })
+        ));
+        assert_eq!(
+            run(Tampering::new(2, TamperKind::ExtraEvaluation(F::from(5)))),
+            Err(SumCheckError::DegreeMismatch {
+                round: 2,
+                expected: 4,
+                got: 5
+            })
+        );
+        // Only two factors depend on x0, so the round polynomial of round 0 is bounded by degree
+        // 2 rather than by the number of factors.
+        assert_eq!(
+            run(Tampering::new(0, TamperKind::ExtraEvaluation(F::from(5)))),
+            Err(SumCheckError::DegreeMismatch {
+                round: 0,
+                expected: 3,
+                got: 4
+            })
+        );
+        assert!(run(Tampering::new(2, TamperKind::MissingEvaluation)).is_err());
+        assert!(run(Tampering::new(2, TamperKind::SwapEvaluations(2, 3))).is_err());
+    }
+
+    #[test]
+    fn test_consistent_lie() {
+        // Claiming a wrong sum and keeping p(0) + p(1) consistent with it only delays the
+        // rejection, here to the final check.
+        let poly = parse_product("(x0 + 2) * (3*x0 + 1)").unwrap();
+        let delta = F::from(10);
+        let mut adversary = |round: usize, message: &mut RoundMessage| {
+            if round == 0 {
+                message.evaluations[0] += delta;
+            }
+        };
+        let result = run_with_adversary(&poly, delta, &mut adversary, StdRng::seed_from_u64(1));
+        assert_eq!(result, Err(SumCheckError::FinalCheckFailed));
+    }
+}
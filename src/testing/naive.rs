@@ -0,0 +1,103 @@
+//! A reference prover, as simple as possible: each round polynomial is computed by summing the
+//! product over the rest of the hypercube, evaluating the factors with
+//! [`SparsePolynomial::evaluate`] at every point, in O(k · 2^n) evaluations of the factors per
+//! node of the round, instead of folding evaluation tables. Its messages are those of the
+//! optimized [`SumCheckProver`](crate::protocol::SumCheckProver), against which it is checked.
+
+use ark_poly::Polynomial;
+use ark_std::vec::Vec;
+
+use crate::field::Field256 as F;
+use crate::polynomial::hypercube::{points, BitOrder};
+use crate::polynomial::{get_num_vars, variable_degree, ProductMLPolynomial};
+use crate::protocol::RoundMessage;
+
+/// The prover of a product of polynomials, which computes everything from the polynomials.
+#[derive(Debug, Clone)]
+pub struct Prover {
+    poly: ProductMLPolynomial,
+    num_vars: usize,
+    challenges: Vec<F>,
+    round: usize,
+}
+
+impl Prover {
+    /// The prover of 'poly', whose factors must have the same number of variables.
+    pub fn new(poly: &ProductMLPolynomial) -> Self {
+        Prover {
+            poly: poly.clone(),
+            num_vars: get_num_vars(poly).expect("the factors must have the same variables"),
+            challenges: Vec::new(),
+            round: 0,
+        }
+    }
+
+    /// The sum of the product over the whole hypercube.
+    pub fn claimed_sum(&self) -> F {
+        self.sum_with_prefix(&[])
+    }
+
+    pub fn num_rounds(&self) -> usize {
+        self.num_vars
+    }
+
+    /// The message of the next round, as [`SumCheckProver::next_message`]: the evaluations of the
+    /// round polynomial at 0, 1, ..., d for its degree d in the variable of the round, at least 1.
+    ///
+    /// [`SumCheckProver::next_message`]: crate::protocol::SumCheckProver::next_message
+    ///
+    /// # Panics
+    ///
+    /// Panics if 'challenge' is given in the first round or missing in a later one, or if all
+    /// rounds have already been played.
+    pub fn next_message(&mut self, challenge: Option<F>) -> RoundMessage {
+        assert_eq!(
+            challenge.is_some(),
+            self.round > 0,
+            "the challenge of the previous round is expected in every round but the first"
+        );
+        assert!(self.round < self.num_vars, "all rounds of the protocol have been played");
+        self.challenges.extend(challenge);
+        let var = self.round;
+        let degree: usize = self.poly.iter().map(|p| variable_degree(p, var)).sum();
+        let evaluations = (0..=degree.max(1) as u64)
+            .map(|node| {
+                let mut prefix = self.challenges.clone();
+                prefix.push(F::from(node));
+                self.sum_with_prefix(&prefix)
+            })
+            .collect();
+        self.round += 1;
+        RoundMessage { evaluations }
+    }
+
+    /// Σ_b Π_i p_i('prefix', b) over the points b of the hypercube on the remaining variables.
+    fn sum_with_prefix(&self, prefix: &[F]) -> F {
+        points::<F>(self.num_vars - prefix.len(), BitOrder::MsbFirst)
+            .map(|suffix| {
+                let point = [prefix, &suffix].concat();
+                self.poly.iter().map(|p| p.evaluate(&point)).product::<F>()
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::vec;
+
+    #[test]
+    fn test_naive_prover() {
+        let poly = crate::polynomial::parse_product("(x0 + 2*x1) * (x1 + 3)").unwrap();
+        let mut prover = Prover::new(&poly);
+        // (x0 + 2 x1)(x1 + 3) over {0, 1}^2: 0 + 8 + 3 + 12.
+        assert_eq!(prover.claimed_sum(), F::from(23));
+        // Only the first factor depends on x0: p(x0) = x0 · 3 + (x0 + 2) · 4.
+        assert_eq!(prover.next_message(None).evaluations, vec![F::from(8), F::from(15)]);
+        assert_eq!(
+            prover.next_message(Some(F::from(2))).evaluations,
+            vec![F::from(6), F::from(16), F::from(30)]
+        );
+    }
+}
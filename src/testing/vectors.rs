@@ -0,0 +1,252 @@
+//! Test vectors for other implementations of the sum-check, with the `parser` feature.
+//!
+//! [`generate`] writes the document of `test-vectors/vectors.json`, whose schema is described in
+//! `test-vectors/README.md`: runs of the interactive protocol on fixed instances, with the
+//! challenges of the verifier drawn from a seeded ChaCha20 generator, some of them against provers
+//! which claim a wrong sum or tamper with a message. [`check`] replays such a document against the
+//! prover and the verifier of this crate.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::field::Field256 as F;
+use crate::polynomial::{parse_product, parser, ProductMLPolynomial};
+use crate::protocol::{
+    bytes_to_hex, field_to_hex, hex_to_field, modulus_hex, parse_bytes, RoundMessage,
+    SumCheckError, SumCheckProver, SumCheckVerifier,
+};
+use crate::testing::adversary::{Adversary, TamperKind, Tampering};
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VectorError {
+    #[error("invalid syntax: {0}")]
+    Syntax(String),
+    #[error("the vectors are over the field of modulus {0}")]
+    FieldMismatch(String),
+    #[error("vector '{name}': invalid {field}")]
+    InvalidField { name: String, field: &'static str },
+    #[error("vector '{name}': the {field} differ from those of this implementation")]
+    Mismatch { name: String, field: &'static str },
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document {
+    modulus: String,
+    vectors: Vec<TestVector>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestVector {
+    name: String,
+    description: String,
+    instance: serde_json::Value,
+    seed: String,
+    claimed_sum: String,
+    honest: bool,
+    messages: Vec<Vec<String>>,
+    challenges: Vec<String>,
+    accept: bool,
+    error: Option<String>,
+}
+
+/// The instances of the vectors, as expressions.
+const INSTANCES: [&str; 4] = [
+    "x0*x1*x2*x3 + 7*x1 + 2",
+    "(x0*x2 + x1 + x2) * (x0 + x1 + x2)",
+    "(x0 + 2*x1) * (x1*x2 + 3) * (x0 + x2)",
+    "(x0 + 3*x5 + 2*x1*x4) * (x2*x3 + 5*x5 + 4) * (x1 + x3*x4 + 1)",
+];
+
+/// A run of the protocol: the messages sent, the challenges drawn for the accepted ones, and the
+/// decision of the verifier.
+struct Run {
+    claimed_sum: F,
+    messages: Vec<Vec<F>>,
+    challenges: Vec<F>,
+    result: Result<(), SumCheckError>,
+}
+
+fn run<A: Adversary>(
+    poly: &ProductMLPolynomial,
+    seed: [u8; 32],
+    claim_offset: F,
+    adversary: &mut A,
+) -> Run {
+    let mut prover = SumCheckProver::new(poly);
+    let claimed_sum = prover.claimed_sum() + claim_offset;
+    let rng = ChaCha20Rng::from_seed(seed);
+    let mut verifier = SumCheckVerifier::with_rng(poly, claimed_sum, rng);
+    let mut run = Run {
+        claimed_sum,
+        messages: Vec::new(),
+        challenges: Vec::new(),
+        result: Ok(()),
+    };
+    for round in 0..prover.num_rounds() {
+        let mut message = prover.next_message(run.challenges.last().copied());
+        adversary.tamper(round, &mut message);
+        run.messages.push(message.evaluations.clone());
+        match verifier.receive_message(message) {
+            Ok(r) => run.challenges.push(r),
+            Err(err) => {
+                run.result = Err(err);
+                return run;
+            }
+        }
+    }
+    run.result = verifier.finalize().0;
+    run
+}
+
+/// The document of the test vectors, in pretty-printed JSON.
+pub fn generate() -> String {
+    let mut vectors = Vec::new();
+    let mut add = |name: &str,
+                   description: &str,
+                   instance: usize,
+                   offset: u64,
+                   tampering: Option<Tampering>| {
+        let poly = parse_product(INSTANCES[instance]).unwrap();
+        let seed = [vectors.len() as u8 + 1; 32];
+        let honest = offset == 0 && tampering.is_none();
+        let run = match tampering {
+            Some(mut tampering) => run(&poly, seed, F::from(offset), &mut tampering),
+            None => run(
+                &poly,
+                seed,
+                F::from(offset),
+                &mut |_: usize, _: &mut RoundMessage| {},
+            ),
+        };
+        vectors.push(TestVector {
+            name: name.to_string(),
+            description: description.to_string(),
+            instance: serde_json::from_str(&parser::to_json(&poly)).unwrap(),
+            seed: bytes_to_hex(&seed),
+            claimed_sum: field_to_hex(run.claimed_sum),
+            honest,
+            messages: run.messages.iter().map(|m| encode(m)).collect(),
+            challenges: encode(&run.challenges),
+            accept: run.result.is_ok(),
+            error: run.result.err().map(|err| err.to_string()),
+        });
+    };
+    add("single-factor", "one factor on 4 variables", 0, 0, None);
+    add("two-factors", "two factors on 3 variables", 1, 0, None);
+    add("three-factors", "three factors on 3 variables", 2, 0, None);
+    add("six-variables", "three factors on 6 variables", 3, 0, None);
+    add("wrong-sum", "the claimed sum is off by one", 2, 1, None);
+    let tampering = Tampering::new(1, TamperKind::WrongConstant(F::from(1)));
+    add(
+        "wrong-constant",
+        "1 is added to the message of round 1",
+        2,
+        0,
+        Some(tampering),
+    );
+    let tampering = Tampering::new(2, TamperKind::SwapEvaluations(0, 1));
+    add(
+        "swapped-evaluations",
+        "p(0) and p(1) are swapped in the last round",
+        2,
+        0,
+        Some(tampering),
+    );
+    let tampering = Tampering::new(0, TamperKind::ExtraEvaluation(F::from(0)));
+    add(
+        "extra-evaluation",
+        "the message of round 0 has one evaluation too many",
+        3,
+        0,
+        Some(tampering),
+    );
+
+    let document = Document {
+        modulus: modulus_hex(),
+        vectors,
+    };
+    let mut json = serde_json::to_string_pretty(&document).unwrap();
+    json.push('\n');
+    json
+}
+
+/// Replays the vectors of 'json' and returns their number. The verifier, fed the messages of a
+/// vector with the challenges drawn from its seed, must draw the same challenges and reach the same
+/// decision; for the vectors of honest provers, the prover must also send the same claimed sum and
+/// messages given the challenges.
+pub fn check(json: &str) -> Result<usize, VectorError> {
+    let document: Document =
+        serde_json::from_str(json).map_err(|e| VectorError::Syntax(e.to_string()))?;
+    if document.modulus != modulus_hex() {
+        return Err(VectorError::FieldMismatch(document.modulus));
+    }
+    for vector in &document.vectors {
+        check_vector(vector)?;
+    }
+    Ok(document.vectors.len())
+}
+
+fn check_vector(vector: &TestVector) -> Result<(), VectorError> {
+    let invalid = |field| VectorError::InvalidField {
+        name: vector.name.clone(),
+        field,
+    };
+    let mismatch = |field| VectorError::Mismatch {
+        name: vector.name.clone(),
+        field,
+    };
+    let poly = parser::from_json(&vector.instance.to_string()).map_err(|_| invalid("instance"))?;
+    let seed = parse_bytes(&vector.seed).ok_or_else(|| invalid("seed"))?;
+    let claimed_sum = hex_to_field(&vector.claimed_sum).map_err(|_| invalid("claimed sum"))?;
+    let messages = vector
+        .messages
+        .iter()
+        .map(|message| decode(message))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| invalid("messages"))?;
+    let challenges = decode(&vector.challenges).ok_or_else(|| invalid("challenges"))?;
+
+    // The verifier replays the messages.
+    let mut replayed = messages.iter().cloned();
+    let mut adversary = |_: usize, message: &mut RoundMessage| {
+        message.evaluations = replayed.next().unwrap_or_default();
+    };
+    let honest_sum = SumCheckProver::new(&poly).claimed_sum();
+    let run = run(&poly, seed, claimed_sum - honest_sum, &mut adversary);
+    if run.messages != messages {
+        return Err(mismatch("messages"));
+    }
+    if run.challenges != challenges {
+        return Err(mismatch("challenges"));
+    }
+    if run.result.is_ok() != vector.accept
+        || run.result.err().map(|e| e.to_string()) != vector.error
+    {
+        return Err(mismatch("decision"));
+    }
+
+    // The honest prover sends the same messages.
+    if vector.honest {
+        if honest_sum != claimed_sum {
+            return Err(mismatch("claimed sum"));
+        }
+        let mut prover = SumCheckProver::new(&poly);
+        for (round, message) in messages.iter().enumerate() {
+            let challenge = round.checked_sub(1).map(|previous| challenges[previous]);
+            if &prover.next_message(challenge).evaluations != message {
+                return Err(mismatch("messages"));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn encode(elements: &[F]) -> Vec<String> {
+    elements.iter().copied().map(field_to_hex).collect()
+}
+
+fn decode(elements: &[String]) -> Option<Vec<F>> {
+    elements.iter().map(|e| hex_to_field(e).ok()).collect()
+}
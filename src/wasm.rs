@@ -0,0 +1,56 @@
+//! Bindings for JavaScript, with the `wasm` feature. Instances are given in the JSON format of
+//! [`parser::from_json`], and proofs as the compressed serialization of a [`Proof`].
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use wasm_bindgen::prelude::*;
+
+use crate::polynomial::parser;
+use crate::protocol::{self, Proof};
+
+/// Proves the sum over the hypercube of the instance, and returns the serialized proof.
+#[wasm_bindgen]
+pub fn prove(json_instance: &str) -> Result<Vec<u8>, JsError> {
+    prove_bytes(json_instance).map_err(|err| JsError::new(&err))
+}
+
+/// Verifies the serialized proof for the instance. Proofs which cannot be deserialized are
+/// rejected.
+#[wasm_bindgen]
+pub fn verify(json_instance: &str, proof_bytes: &[u8]) -> Result<bool, JsError> {
+    verify_bytes(json_instance, proof_bytes).map_err(|err| JsError::new(&err))
+}
+
+fn prove_bytes(json_instance: &str) -> Result<Vec<u8>, String> {
+    let poly = parser::from_json(json_instance).map_err(|err| err.to_string())?;
+    let mut bytes = Vec::new();
+    protocol::prove(&poly)
+        .serialize_compressed(&mut bytes)
+        .map_err(|err| err.to_string())?;
+    Ok(bytes)
+}
+
+fn verify_bytes(json_instance: &str, proof_bytes: &[u8]) -> Result<bool, String> {
+    let poly = parser::from_json(json_instance).map_err(|err| err.to_string())?;
+    Ok(match Proof::deserialize_compressed(proof_bytes) {
+        Ok(proof) => protocol::verify(&poly, &proof).is_ok(),
+        Err(_) => false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INSTANCE: &str = r#"{"num_vars": 2, "factors": [
+        [{"coeff": 1, "vars": [[0, 1]]}, {"coeff": 3}],
+        [{"coeff": 2, "vars": [[1, 1]]}, {"coeff": 1, "vars": [[0, 1]]}]
+    ]}"#;
+
+    #[test]
+    fn test_prove_verify() {
+        let proof = prove_bytes(INSTANCE).unwrap();
+        assert_eq!(verify_bytes(INSTANCE, &proof), Ok(true));
+        assert_eq!(verify_bytes(INSTANCE, &proof[1..]), Ok(false));
+        assert!(prove_bytes("{").is_err());
+    }
+}
@@ -0,0 +1,98 @@
+//! Sum-check over prime fields defined outside of the crate, of several numbers of limbs.
+
+use ark_ff::PrimeField;
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+use ark_poly::DenseMVPolynomial;
+use sum_check::define_sumcheck_field;
+use sum_check::field::Subfield;
+use sum_check::protocol::*;
+
+define_sumcheck_field!(
+    /// The scalar field of secp256k1.
+    Secp256k1Scalar,
+    secp256k1_scalar,
+    modulus = "115792089237316195423570985008687907852837564279074904382605163141518161494337",
+    generator = "7",
+    limbs = 4,
+);
+
+define_sumcheck_field!(
+    /// The base field of BLS12-381, on six limbs.
+    Bls12Base,
+    bls12_base,
+    modulus = "4002409555221667393417789825735904156556882819939007885332058136124031650490837864442687629129015664037894272559787",
+    generator = "2",
+    limbs = 6,
+);
+
+/// (x0 + 2*x1*x2) * (x1 + 3) * (x0*x2 + 5)
+fn product<G: PrimeField>() -> FieldProduct<G> {
+    let factor = |terms: &[(u64, &[usize])]| {
+        let terms = terms
+            .iter()
+            .map(|&(coeff, vars)| {
+                let vars = vars.iter().map(|&var| (var, 1)).collect();
+                (G::from(coeff), SparseTerm::new(vars))
+            })
+            .collect();
+        SparsePolynomial::from_coefficients_vec(3, terms)
+    };
+    vec![
+        factor(&[(1, &[0]), (2, &[1, 2])]),
+        factor(&[(1, &[1]), (3, &[])]),
+        factor(&[(1, &[0, 2]), (5, &[])]),
+    ]
+}
+
+fn check_field<G: PrimeField + Subfield<G>>() {
+    let poly = product::<G>();
+    let proof: FieldProof<G, G> = prove_in_field(&poly);
+    // Sum over the hypercube, from the truth table of the factors.
+    let expected: u64 = (0..8)
+        .map(|pt| {
+            let (x0, x1, x2) = (pt >> 2 & 1, pt >> 1 & 1, pt & 1);
+            (x0 + 2 * x1 * x2) * (x1 + 3) * (x0 * x2 + 5)
+        })
+        .sum();
+    assert_eq!(proof.claimed_sum, G::from(expected));
+    assert_eq!(verify_in_field(&poly, &proof).unwrap().len(), 3);
+
+    let mut tampered = proof.clone();
+    tampered.round_messages[2][1] += G::ONE;
+    assert!(matches!(
+        verify_in_field(&poly, &tampered),
+        Err(SumCheckError::IntermediateCheckFailed { round: 2, .. })
+    ));
+
+    // Factors of different numbers of variables are rejected, even under their own statement.
+    let mut mismatched = poly.clone();
+    mismatched[1].num_vars = 4;
+    let mut rebound = proof.clone();
+    rebound.instance_hash = instance_hash(&mismatched, proof.claimed_sum);
+    assert!(matches!(
+        verify_in_field(&mismatched, &rebound),
+        Err(SumCheckError::VariableCountMismatch { expected: 3, got: 4 })
+    ));
+}
+
+#[test]
+fn test_four_limbs() {
+    assert_eq!(Secp256k1Scalar::MODULUS_BIT_SIZE, 256);
+    check_field::<Secp256k1Scalar>();
+}
+
+#[test]
+fn test_six_limbs() {
+    assert_eq!(Bls12Base::MODULUS_BIT_SIZE, 381);
+    check_field::<Bls12Base>();
+}
+
+#[test]
+fn test_fields_of_the_crate() {
+    check_field::<sum_check::field::Field256>();
+    check_field::<sum_check::field::FieldBls12>();
+    // The same statement over another field has another hash.
+    let proof: FieldProof<Secp256k1Scalar, Secp256k1Scalar> = prove_in_field(&product());
+    let other: FieldProof<Bls12Base, Bls12Base> = prove_in_field(&product());
+    assert_ne!(proof.instance_hash, other.instance_hash);
+}
@@ -0,0 +1,108 @@
+//! Property-based tests of the protocol on random products of multilinear polynomials.
+
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+use ark_poly::DenseMVPolynomial;
+use ark_std::UniformRand;
+use proptest::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use sum_check::field::Field256 as F;
+use sum_check::polynomial::{MLPolynomial, ProductMLPolynomial};
+use sum_check::protocol::*;
+use sum_check::testing::naive;
+
+const MAX_VARS: usize = 5;
+const MAX_FACTORS: usize = 4;
+const MAX_TERMS: usize = 6;
+
+/// A multilinear polynomial on 'num_vars' variables with up to `MAX_TERMS` terms, each given by
+/// a coefficient and the bitmask of its variables.
+fn multilinear(num_vars: usize) -> impl Strategy<Value = MLPolynomial> {
+    prop::collection::vec((any::<u64>(), 0..1usize << num_vars), 0..=MAX_TERMS).prop_map(
+        move |terms| {
+            let terms = terms
+                .into_iter()
+                .map(|(coeff, mask)| {
+                    let vars = (0..num_vars)
+                        .filter(|var| mask & (1 << var) != 0)
+                        .map(|var| (var, 1))
+                        .collect();
+                    (F::from(coeff), SparseTerm::new(vars))
+                })
+                .collect();
+            SparsePolynomial::from_coefficients_vec(num_vars, terms)
+        },
+    )
+}
+
+fn product(min_vars: usize) -> impl Strategy<Value = ProductMLPolynomial> {
+    (min_vars..=MAX_VARS, 1..=MAX_FACTORS).prop_flat_map(|(num_vars, num_factors)| {
+        prop::collection::vec(multilinear(num_vars), num_factors)
+    })
+}
+
+/// Runs the protocol round by round, with the claimed sum shifted by 'claim_offset', and adding
+/// 'delta' to the evaluation 'index' of the message of round 'corrupted_round', if any. The index
+/// wraps around, as the messages are shorter in rounds where some factors are constant.
+fn run(
+    poly: &ProductMLPolynomial,
+    seed: u64,
+    claim_offset: F,
+    corruption: Option<(usize, usize, F)>,
+) -> Result<(), SumCheckError> {
+    let mut prover = SumCheckProver::new(poly);
+    let claimed_sum = prover.claimed_sum() + claim_offset;
+    let mut verifier = SumCheckVerifier::with_rng(poly, claimed_sum, StdRng::seed_from_u64(seed));
+    let mut challenge = None;
+    for round in 0..prover.num_rounds() {
+        let mut message = prover.next_message(challenge);
+        if let Some((corrupted_round, index, delta)) = corruption {
+            if round == corrupted_round {
+                let index = index % message.evaluations.len();
+                message.evaluations[index] += delta;
+            }
+        }
+        challenge = Some(verifier.receive_message(message)?);
+    }
+    verifier.finalize().0
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn honest_prover_is_accepted(poly in product(0), seed in any::<u64>()) {
+        prop_assert_eq!(run(&poly, seed, F::from(0), None), Ok(()));
+    }
+
+    #[test]
+    fn corrupted_message_is_rejected(
+        (poly, round, index) in product(1).prop_flat_map(|poly| {
+            let num_vars = poly[0].num_vars;
+            let num_points = poly.len() + 1;
+            (Just(poly), 0..num_vars, 0..num_points)
+        }),
+        delta in 1..u64::MAX,
+        seed in any::<u64>(),
+    ) {
+        prop_assert!(run(&poly, seed, F::from(0), Some((round, index, F::from(delta)))).is_err());
+    }
+
+    #[test]
+    fn naive_prover_matches(poly in product(1), seed in any::<u64>()) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut naive = naive::Prover::new(&poly);
+        let mut prover = SumCheckProver::new(&poly);
+        prop_assert_eq!(naive.claimed_sum(), prover.claimed_sum());
+        let mut challenge = None;
+        for _ in 0..prover.num_rounds() {
+            prop_assert_eq!(naive.next_message(challenge), prover.next_message(challenge));
+            challenge = Some(F::rand(&mut rng));
+        }
+    }
+
+    #[test]
+    fn wrong_claim_is_rejected(poly in product(0), delta in 1..u64::MAX, seed in any::<u64>()) {
+        prop_assert!(run(&poly, seed, F::from(delta), None).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+//! Replays the test vectors of `test-vectors/vectors.json`.
+
+use sum_check::testing::vectors::{check, generate, VectorError};
+
+const VECTORS: &str = include_str!("../test-vectors/vectors.json");
+
+#[test]
+fn test_vectors_replay() {
+    assert_eq!(check(VECTORS), Ok(8));
+}
+
+#[test]
+fn test_vectors_up_to_date() {
+    assert!(
+        generate() == VECTORS,
+        "regenerate with: cargo run --example test_vectors > test-vectors/vectors.json"
+    );
+}
+
+#[test]
+fn test_vectors_mismatch() {
+    // The first challenge of the first vector, changed in its last digit.
+    let index = VECTORS.find("\"challenges\": [").unwrap();
+    let digit = index + VECTORS[index..].find("\",").unwrap() - 1;
+    let mut tampered = VECTORS.to_string();
+    let replacement = if &VECTORS[digit..=digit] == "0" {
+        "1"
+    } else {
+        "0"
+    };
+    tampered.replace_range(digit..=digit, replacement);
+    assert_eq!(
+        check(&tampered),
+        Err(VectorError::Mismatch {
+            name: "single-factor".to_string(),
+            field: "challenges"
+        })
+    );
+    let tampered = VECTORS.replacen("\"accept\": true", "\"accept\": false", 1);
+    assert!(matches!(
+        check(&tampered),
+        Err(VectorError::Mismatch {
+            field: "decision",
+            ..
+        })
+    ));
+    let tampered = VECTORS.replacen("\"honest\": false", "\"honest\": true", 1);
+    assert!(matches!(
+        check(&tampered),
+        Err(VectorError::Mismatch {
+            field: "claimed sum",
+            ..
+        })
+    ));
+}